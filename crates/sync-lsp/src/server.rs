@@ -642,6 +642,8 @@ impl LsHook for () {
 
 type AsyncHandler<S, T, R> = fn(srv: &mut S, args: T) -> SchedulableResponse<R>;
 type RawHandler<S, T> = fn(srv: &mut S, args: T) -> ScheduleResult;
+#[cfg(feature = "lsp")]
+type RawIdHandler<S, T> = fn(srv: &mut S, req_id: RequestId, args: T) -> ScheduleResult;
 type BoxPureHandler<S, T> = Box<dyn Fn(&mut S, T) -> LspResult<()>>;
 type BoxHandler<S, T> = Box<dyn Fn(&mut S, RequestId, T) -> SchedulableResponse<JsonValue>>;
 type ExecuteCmdMap<S> = HashMap<&'static str, BoxHandler<S, Vec<JsonValue>>>;
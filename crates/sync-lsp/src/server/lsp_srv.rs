@@ -66,6 +66,17 @@ where
         self
     }
 
+    /// Registers an raw event handler that also receives the request ID, e.g.
+    /// to cancel the command by `$/cancelRequest`.
+    pub fn with_command_id_(
+        mut self,
+        cmd: &'static str,
+        handler: RawIdHandler<Args::S, Vec<JsonValue>>,
+    ) -> Self {
+        self.command_handlers.insert(cmd, Box::new(handler));
+        self
+    }
+
     /// Registers an async command handler.
     pub fn with_command<R: Serialize + 'static>(
         mut self,
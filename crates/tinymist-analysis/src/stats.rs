@@ -31,6 +31,18 @@ impl AllocStats {
         self.dropped.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Sums up the number of alive interned objects and their estimated size
+    /// in bytes.
+    pub fn summary() -> (usize, usize) {
+        let maps = crate::adt::interner::MAPS.lock().clone();
+        maps.iter().fold((0, 0), |(count, size), (_, sz, map)| {
+            let allocated = map.allocated.load(Ordering::Relaxed);
+            let dropped = map.dropped.load(Ordering::Relaxed);
+            let alive = allocated.saturating_sub(dropped);
+            (count + alive, size + sz * alive)
+        })
+    }
+
     /// Report the statistics of the allocation.
     pub fn report() -> String {
         let maps = crate::adt::interner::MAPS.lock().clone();
@@ -1,5 +1,7 @@
 //! Import resolution utilities.
 
+use rustc_hash::FxHashMap;
+
 use crate::prelude::*;
 
 /// Resolves a file id by its import path.
@@ -46,6 +48,106 @@ pub fn find_source_by_expr(
     }
 }
 
+/// An import or include of a file by another file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportHop {
+    /// The file containing the import.
+    pub file: TypstFileId,
+    /// The range of the import path in the file.
+    pub range: Range<usize>,
+    /// The imported file.
+    pub target: TypstFileId,
+}
+
+/// Collects the imports and includes of a source whose paths are string
+/// literals.
+pub fn source_imports(world: &dyn World, source: &Source) -> Vec<ImportHop> {
+    fn collect(world: &dyn World, node: &LinkedNode, hops: &mut Vec<ImportHop>) {
+        let import_source = match node.cast::<ast::Expr>() {
+            Some(ast::Expr::ModuleImport(import)) => Some(import.source()),
+            Some(ast::Expr::ModuleInclude(include)) => Some(include.source()),
+            _ => None,
+        };
+        if let Some(ast::Expr::Str(path)) = import_source {
+            let file = node.get().span().id();
+            let target = file.and_then(|file| resolve_id_by_path(world, file, path.get().as_str()));
+            let path_node = node.find(path.span());
+            if let (Some(file), Some(target), Some(path_node)) = (file, target, path_node) {
+                hops.push(ImportHop {
+                    file,
+                    range: path_node.range(),
+                    target,
+                });
+            }
+        }
+
+        for child in node.children() {
+            collect(world, &child, hops);
+        }
+    }
+
+    let mut hops = vec![];
+    collect(world, &LinkedNode::new(source.root()), &mut hops);
+    hops
+}
+
+/// Finds the shortest import cycle that goes back to `start`, beginning with
+/// an import of `start` overlapping the range `at` (or any import if `at` is
+/// `None`).
+///
+/// The returned hops are in import order, so the first hop is in `start` and
+/// the target of the last hop is `start` again.
+pub fn find_import_cycle(
+    world: &dyn World,
+    start: TypstFileId,
+    at: Option<Range<usize>>,
+) -> Option<Vec<ImportHop>> {
+    let imports_of = |file: TypstFileId| {
+        world
+            .source(file)
+            .map(|source| source_imports(world, &source))
+            .unwrap_or_default()
+    };
+
+    let first_hops = imports_of(start).into_iter().filter(|hop| {
+        at.as_ref()
+            .is_none_or(|at| hop.range.start <= at.end && at.start <= hop.range.end)
+    });
+
+    for first in first_hops {
+        if first.target == start {
+            return Some(vec![first]);
+        }
+
+        // Breadth-first search for the shortest way back to `start`.
+        let mut reached_by = FxHashMap::<TypstFileId, ImportHop>::default();
+        let mut queue = std::collections::VecDeque::from([first.target]);
+        while let Some(file) = queue.pop_front() {
+            for hop in imports_of(file) {
+                if hop.target == start {
+                    let mut cycle = vec![hop];
+                    let mut current = file;
+                    while current != first.target {
+                        let prev = reached_by[&current].clone();
+                        current = prev.file;
+                        cycle.push(prev);
+                    }
+                    cycle.push(first);
+                    cycle.reverse();
+                    return Some(cycle);
+                }
+
+                if hop.target != first.target && !reached_by.contains_key(&hop.target) {
+                    queue.push_back(hop.target);
+                    reached_by.insert(hop.target, hop);
+                }
+            }
+        }
+    }
+
+    None
+}
+
 /// Casts a node to a single include expression.
 pub fn cast_include_expr<'a>(name: &str, node: ast::Expr<'a>) -> Option<ast::Expr<'a>> {
     match node {
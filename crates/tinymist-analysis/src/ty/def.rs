@@ -231,6 +231,39 @@ impl Ty {
         }
     }
 
+    /// Gets the type of an item selected from an array or dictionary type, e.g.
+    /// by `at`. If the key is statically known, the type of the specific item
+    /// is selected.
+    pub fn indexed(&self, key: Option<&Value>) -> Option<Ty> {
+        let union = |tys: Vec<Ty>| (!tys.is_empty()).then(|| Ty::from_types(tys.into_iter()));
+        match (self, key) {
+            (Ty::Array(elem), _) => Some(elem.as_ref().clone()),
+            (Ty::Tuple(items), Some(Value::Int(idx))) => {
+                items.get(resolve_index(items.len(), *idx)?).cloned()
+            }
+            (Ty::Tuple(items), _) => union(items.to_vec()),
+            (Ty::Dict(record), Some(Value::Str(key))) => {
+                record.field_by_name(&key.as_str().into()).cloned()
+            }
+            (Ty::Dict(record), _) => union(record.types.to_vec()),
+            (Ty::Value(ins_ty), Some(key)) => {
+                let item = match (&ins_ty.val, key) {
+                    (Value::Array(arr), Value::Int(idx)) => {
+                        arr.as_slice().get(resolve_index(arr.len(), *idx)?)?.clone()
+                    }
+                    (Value::Dict(dict), Value::Str(key)) => dict.get(key).ok()?.clone(),
+                    _ => return None,
+                };
+                Some(Ty::Value(InsTy::new(item)))
+            }
+            (Ty::Let(bounds), _) => {
+                union(bounds.lbs.iter().filter_map(|ty| ty.indexed(key)).collect())
+            }
+            (Ty::Union(tys), _) => union(tys.iter().filter_map(|ty| ty.indexed(key)).collect()),
+            _ => None,
+        }
+    }
+
     /// Checks a type against a context.
     pub fn satisfy<T: TyCtx>(&self, ctx: &T, f: impl FnMut(&Ty, bool)) {
         self.bounds(true, &mut BoundPred::new(ctx, f));
@@ -289,6 +322,15 @@ fn is_content_builtin_type(ty: &Type) -> bool {
     *ty == Type::of::<Content>() || *ty == Type::of::<typst::foundations::Symbol>()
 }
 
+/// Resolves a possibly negative index like `array.at` does.
+fn resolve_index(len: usize, idx: i64) -> Option<usize> {
+    if idx < 0 {
+        len.checked_sub(usize::try_from(idx.unsigned_abs()).ok()?)
+    } else {
+        usize::try_from(idx).ok()
+    }
+}
+
 /// Checks if the type is a string builtin type.
 fn is_str_builtin_type(ty: &Type) -> bool {
     *ty == Type::of::<typst::foundations::Str>()
@@ -782,6 +824,8 @@ pub struct ParamAttrs {
     pub variadic: bool,
     /// Whether the parameter is settable with a set rule.
     pub settable: bool,
+    /// Whether the parameter must be given in a call.
+    pub required: bool,
 }
 
 impl ParamAttrs {
@@ -792,6 +836,7 @@ impl ParamAttrs {
             named: false,
             variadic: false,
             settable: false,
+            required: true,
         }
     }
 
@@ -802,6 +847,7 @@ impl ParamAttrs {
             named: true,
             variadic: false,
             settable: false,
+            required: false,
         }
     }
 
@@ -812,6 +858,7 @@ impl ParamAttrs {
             named: false,
             variadic: true,
             settable: false,
+            required: false,
         }
     }
 }
@@ -823,6 +870,7 @@ impl From<&ParamInfo> for ParamAttrs {
             named: param.named(),
             variadic: param.variadic(),
             settable: param.settable(),
+            required: param.required(),
         }
     }
 }
@@ -1679,6 +1727,28 @@ mod tests {
         assert_debug_snapshot!(ty_ref, @"Clause");
     }
 
+    #[test]
+    fn test_indexed() {
+        use super::*;
+        let flag = Ty::Boolean(None);
+        let yes = Ty::Boolean(Some(true));
+
+        let arr = Ty::Array(flag.clone().into());
+        assert_eq!(arr.indexed(None), Some(flag.clone()));
+
+        let tuple = Ty::Tuple(Interned::new(vec![yes.clone(), flag.clone()]));
+        assert_eq!(tuple.indexed(Some(&Value::Int(0))), Some(yes.clone()));
+        assert_eq!(tuple.indexed(Some(&Value::Int(-1))), Some(flag.clone()));
+        assert_eq!(tuple.indexed(Some(&Value::Int(2))), None);
+
+        let dict = Ty::Dict(RecordTy::new(vec![
+            ("a".into(), yes.clone()),
+            ("b".into(), flag.clone()),
+        ]));
+        assert_eq!(dict.indexed(Some(&Value::Str("b".into()))), Some(flag));
+        assert_eq!(dict.indexed(Some(&Value::Str("c".into()))), None);
+    }
+
     #[test]
     fn test_sig_matches() {
         use super::*;
@@ -2,7 +2,7 @@
 
 mod tooltip;
 
-pub use tooltip::{Tooltip, tooltip_};
+pub use tooltip::{Tooltip, TooltipFeat, tooltip_};
 
 use std::{collections::HashMap, fmt::Write, sync::LazyLock};
 
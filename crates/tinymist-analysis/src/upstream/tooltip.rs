@@ -1,11 +1,14 @@
 use std::fmt::Write;
 
 use ecow::{EcoString, eco_format};
+use serde::{Deserialize, Serialize};
 use typst::World;
 use typst::engine::Sink;
-use typst::foundations::{Capturer, Value, repr};
-use typst::layout::Length;
+use typst::foundations::{Capturer, Content, Value, repr};
+use typst::layout::{Abs, Length};
 use typst::syntax::{LinkedNode, Source, SyntaxKind, ast};
+use typst::text::FontVariant;
+use typst::visualize::Color;
 use typst_shim::eval::CapturesVisitor;
 use typst_shim::syntax::LinkedNodeExt;
 use typst_shim::utils::{Numeric, round_2};
@@ -18,17 +21,76 @@ use crate::analyze_expr;
 /// Passing a `document` (from a previous compilation) is optional, but enhances
 /// the autocompletions. Label completions, for instance, are only generated
 /// when the document is available.
-pub fn tooltip_(world: &dyn World, source: &Source, cursor: usize) -> Option<Tooltip> {
+///
+/// The producers disabled by `feat` are skipped.
+pub fn tooltip_(
+    world: &dyn World,
+    source: &Source,
+    cursor: usize,
+    feat: &TooltipFeat,
+) -> Option<Tooltip> {
     let leaf = LinkedNode::new(source.root()).leaf_at_compat(cursor)?;
     if leaf.kind().is_trivia() {
         return None;
     }
 
-    font_tooltip(world, &leaf)
+    (feat.font().then(|| font_tooltip(world, &leaf)).flatten())
+        .or_else(|| {
+            feat.font()
+                .then(|| font_feature_tooltip(world, &leaf))
+                .flatten()
+        })
         // todo: test that label_tooltip can be removed safely
         // .or_else(|| document.and_then(|doc| label_tooltip(doc, &leaf)))
-        .or_else(|| expr_tooltip(world, &leaf))
-        .or_else(|| closure_tooltip(&leaf))
+        .or_else(|| feat.expr().then(|| color_tooltip(world, &leaf)).flatten())
+        .or_else(|| {
+            let font_size = feat.base_font_size();
+            feat.expr()
+                .then(|| expr_tooltip(world, &leaf, font_size))
+                .flatten()
+        })
+        .or_else(|| feat.closure().then(|| closure_tooltip(&leaf)).flatten())
+}
+
+/// The tooltip features. All tooltip producers are enabled by default.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TooltipFeat {
+    /// Whether to show the sampled values of hovered expressions, and the
+    /// conversions of colors constructed by hovered calls.
+    pub expr: Option<bool>,
+    /// Whether to show the usage of hovered font families and OpenType
+    /// features.
+    pub font: Option<bool>,
+    /// Whether to show the captured variables of hovered closures.
+    pub closure: Option<bool>,
+    /// The font size in points to resolve font-relative lengths, e.g. `2em`,
+    /// against.
+    pub base_font_size: Option<f64>,
+}
+
+impl TooltipFeat {
+    /// Whether to enable expression tooltips.
+    pub fn expr(&self) -> bool {
+        self.expr.unwrap_or(true)
+    }
+
+    /// Whether to enable font tooltips.
+    pub fn font(&self) -> bool {
+        self.font.unwrap_or(true)
+    }
+
+    /// Whether to enable closure tooltips.
+    pub fn closure(&self) -> bool {
+        self.closure.unwrap_or(true)
+    }
+
+    /// The font size to resolve font-relative lengths against, if configured.
+    pub fn base_font_size(&self) -> Option<Abs> {
+        self.base_font_size
+            .filter(|size| size.is_finite() && *size > 0.)
+            .map(Abs::pt)
+    }
 }
 
 /// A hover tooltip.
@@ -41,12 +103,35 @@ pub enum Tooltip {
 }
 
 /// Tooltip for a hovered expression.
-pub fn expr_tooltip(world: &dyn World, leaf: &LinkedNode) -> Option<Tooltip> {
+///
+/// Font-relative lengths are resolved against `font_size` if it is given.
+pub fn expr_tooltip(
+    world: &dyn World,
+    leaf: &LinkedNode,
+    font_size: Option<Abs>,
+) -> Option<Tooltip> {
     let mut ancestor = leaf;
     while !ancestor.is::<ast::Expr>() {
         ancestor = ancestor.parent()?;
     }
 
+    // The folded value is shown above the sampled values, if any.
+    let folded = folded_tooltip(world, ancestor, font_size);
+    match (folded, values_tooltip(world, ancestor, font_size)) {
+        (Some(folded), Some(Tooltip::Code(values))) => {
+            Some(Tooltip::Code(eco_format!("{folded}\n{values}")))
+        }
+        (Some(folded), _) => Some(Tooltip::Code(folded)),
+        (None, values) => values,
+    }
+}
+
+/// Tooltip for the values sampled for a hovered expression.
+fn values_tooltip(
+    world: &dyn World,
+    ancestor: &LinkedNode,
+    font_size: Option<Abs>,
+) -> Option<Tooltip> {
     let expr = ancestor.cast::<ast::Expr>()?;
     if !expr.hash() && !matches!(expr, ast::Expr::MathIdent(_)) {
         return None;
@@ -55,7 +140,7 @@ pub fn expr_tooltip(world: &dyn World, leaf: &LinkedNode) -> Option<Tooltip> {
     let values = analyze_expr(world, ancestor);
 
     if let [(Value::Length(length), _)] = values.as_slice()
-        && let Some(tooltip) = length_tooltip(*length)
+        && let Some(tooltip) = length_tooltip(*length, font_size)
     {
         return Some(tooltip);
     }
@@ -114,11 +199,84 @@ pub fn expr_tooltip(world: &dyn World, leaf: &LinkedNode) -> Option<Tooltip> {
         pieces.push("...".into());
     }
 
-    let tooltip = repr::pretty_comma_list(&pieces, false);
+    let mut tooltip = repr::pretty_comma_list(&pieces, false);
+    if let [(Value::Content(content), _)] = values.as_slice()
+        && let Some(preview) = content_preview(content)
+    {
+        write!(tooltip, "\n// {preview}").unwrap();
+    }
     // todo: check sensible length, value highlighting
     (!tooltip.is_empty()).then(|| Tooltip::Code(tooltip.into()))
 }
 
+/// Extracts the plain text of the content for preview, which skips the
+/// layout-only elements, collapses the whitespace, and is truncated to a
+/// hundred characters.
+fn content_preview(content: &Content) -> Option<EcoString> {
+    const MAX_CHARS: usize = 100;
+
+    let text = content.plain_text();
+    let words = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut preview = words.chars().take(MAX_CHARS).collect::<EcoString>();
+    if words.chars().nth(MAX_CHARS).is_some() {
+        preview.push('…');
+    }
+
+    (!preview.is_empty()).then_some(preview)
+}
+
+/// Tooltip text for a hovered expression that is folded from constants, e.g.
+/// `2cm + 3mm`, showing the folded result.
+fn folded_tooltip(
+    world: &dyn World,
+    node: &LinkedNode,
+    font_size: Option<Abs>,
+) -> Option<EcoString> {
+    if !node.cast::<ast::Expr>().is_some_and(is_const_foldable) {
+        return None;
+    }
+
+    // Folds the outermost constant expression containing the cursor.
+    let mut folded = node.clone();
+    while let Some(parent) = folded.parent()
+        && parent.cast::<ast::Expr>().is_some_and(is_const_foldable)
+    {
+        folded = parent.clone();
+    }
+    if folded.cast::<ast::Expr>()?.is_literal() {
+        return None;
+    }
+
+    let values = analyze_expr(world, &folded);
+    let (value, _) = values.first()?;
+    if values.iter().any(|(other, _)| other != value) {
+        return None;
+    }
+
+    let mut tooltip = eco_format!("= {}", truncated_repr(value));
+    if let Value::Length(length) = value
+        && let Some(Tooltip::Code(detail)) = length_tooltip(*length, font_size)
+    {
+        tooltip.push('\n');
+        tooltip.push_str(&detail);
+    }
+
+    Some(tooltip)
+}
+
+/// Whether the expression only consists of literals and operators, which is
+/// free of side effects and evaluates to a constant.
+fn is_const_foldable(expr: ast::Expr) -> bool {
+    match expr {
+        ast::Expr::Parenthesized(paren) => is_const_foldable(paren.expr()),
+        ast::Expr::Unary(unary) => is_const_foldable(unary.expr()),
+        ast::Expr::Binary(binary) => {
+            is_const_foldable(binary.lhs()) && is_const_foldable(binary.rhs())
+        }
+        expr => expr.is_literal(),
+    }
+}
+
 /// Tooltip for a hovered closure.
 fn closure_tooltip(leaf: &LinkedNode) -> Option<Tooltip> {
     // Only show this tooltip when hovering over the equals sign or arrow of
@@ -155,16 +313,130 @@ fn closure_tooltip(leaf: &LinkedNode) -> Option<Tooltip> {
 }
 
 /// Tooltip text for a hovered length.
-fn length_tooltip(length: Length) -> Option<Tooltip> {
-    length.em.is_zero().then(|| {
-        Tooltip::Code(eco_format!(
-            "{}pt = {}mm = {}cm = {}in",
-            round_2(length.abs.to_pt()),
-            round_2(length.abs.to_mm()),
-            round_2(length.abs.to_cm()),
-            round_2(length.abs.to_inches())
-        ))
-    })
+///
+/// A length relative to the font size, e.g. `2em`, is only resolved if the
+/// font size is known, e.g. `= 24pt (at 12pt)`.
+fn length_tooltip(length: Length, font_size: Option<Abs>) -> Option<Tooltip> {
+    if !length.em.is_zero() {
+        let font_size = font_size?;
+        let resolved = length.abs + length.em.at(font_size);
+        return Some(Tooltip::Code(eco_format!(
+            "= {}pt (at {}pt)",
+            round_2(resolved.to_pt()),
+            round_2(font_size.to_pt())
+        )));
+    }
+
+    Some(Tooltip::Code(eco_format!(
+        "{}pt = {}mm = {}cm = {}in",
+        round_2(length.abs.to_pt()),
+        round_2(length.abs.to_mm()),
+        round_2(length.abs.to_cm()),
+        round_2(length.abs.to_inches())
+    )))
+}
+
+/// The color constructors whose calls get a color tooltip.
+const COLOR_CONSTRUCTORS: &[&str] = &[
+    "rgb",
+    "luma",
+    "cmyk",
+    "oklab",
+    "oklch",
+    "color.linear-rgb",
+    "color.hsl",
+    "color.hsv",
+];
+
+/// Tooltip for a hovered call to a color constructor, e.g. `oklch(..)`,
+/// showing the color converted to the other color models.
+fn color_tooltip(world: &dyn World, leaf: &LinkedNode) -> Option<Tooltip> {
+    let mut ancestor = leaf.clone();
+    let (call, constructor) = loop {
+        if let Some(call) = ancestor.cast::<ast::FuncCall>()
+            && let Some(constructor) = color_constructor(call.callee())
+        {
+            break (ancestor, constructor);
+        }
+        ancestor = ancestor.parent()?.clone();
+    };
+
+    let values = analyze_expr(world, &call);
+    let (Value::Color(color), _) = values.first()? else {
+        return None;
+    };
+    if values
+        .iter()
+        .any(|(other, _)| other != &Value::Color(*color))
+    {
+        return None;
+    }
+
+    Some(Tooltip::Code(color_conversions(*color, constructor)))
+}
+
+/// Gets the name of the color constructor called by the callee, if any.
+fn color_constructor(callee: ast::Expr) -> Option<&'static str> {
+    let name = match callee {
+        ast::Expr::Ident(ident) => ident.get().clone(),
+        ast::Expr::FieldAccess(access) => match access.target() {
+            ast::Expr::Ident(target) => eco_format!("{}.{}", target.get(), access.field().get()),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    COLOR_CONSTRUCTORS
+        .iter()
+        .copied()
+        .find(|constructor| *constructor == name)
+}
+
+/// Converts the color to the hex and the other color models, with the
+/// channels rounded to two decimal places. The model of the `constructor` is
+/// skipped, since the call already shows it.
+fn color_conversions(color: Color, constructor: &str) -> EcoString {
+    let percent = |channel: f32| round_2(f64::from(channel) * 100.);
+    let alpha = |alpha: f32| {
+        if alpha < 1. {
+            eco_format!(", {}%", percent(alpha))
+        } else {
+            EcoString::new()
+        }
+    };
+
+    let mut lines = vec![eco_format!("rgb({:?})", color.to_hex())];
+    if constructor != "rgb" {
+        let [r, g, b, a] = Color::from(color.to_rgb()).to_vec4();
+        lines.push(eco_format!(
+            "rgb({}%, {}%, {}%{})",
+            percent(r),
+            percent(g),
+            percent(b),
+            alpha(a)
+        ));
+    }
+    if constructor != "cmyk" {
+        let [c, m, y, k] = Color::from(color.to_cmyk()).to_vec4();
+        lines.push(eco_format!(
+            "cmyk({}%, {}%, {}%, {}%)",
+            percent(c),
+            percent(m),
+            percent(y),
+            percent(k)
+        ));
+    }
+    if constructor != "oklch" {
+        let [l, c, h, a] = Color::from(color.to_oklch()).to_vec4();
+        lines.push(eco_format!(
+            "oklch({}%, {}, {}deg{})",
+            percent(l),
+            round_2(f64::from(c)),
+            round_2(f64::from(h)),
+            alpha(a)
+        ));
+    }
+
+    lines.join("\n").into()
 }
 
 /// Tooltip for font.
@@ -190,3 +462,299 @@ fn font_tooltip(world: &dyn World, leaf: &LinkedNode) -> Option<Tooltip> {
 
     None
 }
+
+/// Tooltip for an OpenType feature tag in the `features` argument of the text
+/// function, i.e. `features: ("liga",)` or `features: (liga: 0)`.
+fn font_feature_tooltip(world: &dyn World, leaf: &LinkedNode) -> Option<Tooltip> {
+    let (tag, args) = feature_tag_at(leaf)?;
+    let tag_bytes: [u8; 4] = tag.as_bytes().try_into().ok()?;
+
+    let mut detail = match feature_description(&tag) {
+        Some(description) => eco_format!("`{tag}`: {description}."),
+        None => eco_format!("`{tag}`"),
+    };
+
+    // Checks the feature in the font selected by the call, or the default font.
+    let book = world.book();
+    let variant = FontVariant::default();
+    let font = font_families(&args)
+        .iter()
+        .find_map(|family| book.select(&family.to_lowercase(), variant))
+        .and_then(|index| world.font(index));
+    if let Some(font) = font {
+        let tables = font.ttf().tables();
+        let mut features = [&tables.gsub, &tables.gpos]
+            .into_iter()
+            .flatten()
+            .flat_map(|layout| layout.features);
+        let supported = features.any(|feature| feature.tag.to_bytes() == tag_bytes);
+        let family = &font.info().family;
+        if supported {
+            write!(detail, " Supported by {family}.").unwrap();
+        } else {
+            write!(detail, " Not supported by {family}.").unwrap();
+        }
+    }
+
+    Some(Tooltip::Text(detail))
+}
+
+/// Finds the feature tag under the cursor in the `features` argument of a
+/// call, along with the arguments of the call.
+fn feature_tag_at<'a>(leaf: &LinkedNode<'a>) -> Option<(EcoString, LinkedNode<'a>)> {
+    let parent = leaf.parent()?;
+    let (tag, collection) = match (leaf.cast::<ast::Str>(), leaf.cast::<ast::Ident>()) {
+        // `features: ("liga",)`
+        (Some(string), _) if parent.kind() == SyntaxKind::Array => (string.get(), parent.clone()),
+        // `features: ("liga": 0)`
+        (Some(string), _)
+            if parent
+                .cast::<ast::Keyed>()
+                .is_some_and(|keyed| keyed.key().span() == leaf.span()) =>
+        {
+            (string.get(), parent.parent()?.clone())
+        }
+        // `features: (liga: 0)`
+        (_, Some(ident))
+            if parent
+                .cast::<ast::Named>()
+                .is_some_and(|named| named.name().span() == leaf.span()) =>
+        {
+            (ident.get().clone(), parent.parent()?.clone())
+        }
+        _ => return None,
+    };
+    if !matches!(collection.kind(), SyntaxKind::Array | SyntaxKind::Dict) {
+        return None;
+    }
+
+    let named = collection.parent()?;
+    if named.cast::<ast::Named>()?.name().as_str() != "features" {
+        return None;
+    }
+    let args = named.parent()?;
+    (args.kind() == SyntaxKind::Args).then(|| (tag, args.clone()))
+}
+
+/// Gets the font families given by the `font` argument of the call, or the
+/// default font family of the text function.
+fn font_families(args: &LinkedNode) -> Vec<EcoString> {
+    let font = args.cast::<ast::Args>().and_then(|args| {
+        args.items().find_map(|arg| match arg {
+            ast::Arg::Named(named) if named.name().as_str() == "font" => Some(named.expr()),
+            _ => None,
+        })
+    });
+
+    match font {
+        Some(ast::Expr::Str(family)) => vec![family.get()],
+        Some(ast::Expr::Array(families)) => families
+            .items()
+            .filter_map(|item| match item {
+                ast::ArrayItem::Pos(ast::Expr::Str(family)) => Some(family.get()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec!["libertinus serif".into()],
+    }
+}
+
+/// Describes a registered OpenType feature tag.
+fn feature_description(tag: &str) -> Option<EcoString> {
+    if let Some(number) = tag.strip_prefix("ss")
+        && let Ok(number @ 1..=20) = number.parse::<u8>()
+    {
+        return Some(eco_format!("Stylistic set {number}"));
+    }
+    if let Some(number) = tag.strip_prefix("cv")
+        && let Ok(number @ 1..=99) = number.parse::<u8>()
+    {
+        return Some(eco_format!("Character variant {number}"));
+    }
+
+    let description = match tag {
+        "aalt" => "Access all alternates",
+        "afrc" => "Alternative fractions",
+        "c2pc" => "Petite capitals from capitals",
+        "c2sc" => "Small capitals from capitals",
+        "calt" => "Contextual alternates",
+        "case" => "Case-sensitive forms",
+        "ccmp" => "Glyph composition and decomposition",
+        "clig" => "Contextual ligatures",
+        "cpsp" => "Capital spacing",
+        "cswh" => "Contextual swash",
+        "dlig" => "Discretionary ligatures",
+        "dnom" => "Denominators",
+        "dtls" => "Dotless forms",
+        "expt" => "Expert forms",
+        "falt" => "Final glyph on line alternates",
+        "fina" => "Terminal forms",
+        "flac" => "Flattened accent forms",
+        "frac" => "Fractions",
+        "fwid" => "Full widths",
+        "half" => "Half forms",
+        "halt" => "Alternate half widths",
+        "hist" => "Historical forms",
+        "hlig" => "Historical ligatures",
+        "hwid" => "Half widths",
+        "init" => "Initial forms",
+        "isol" => "Isolated forms",
+        "ital" => "Italics",
+        "jalt" => "Justification alternates",
+        "kern" => "Kerning",
+        "liga" => "Standard ligatures",
+        "lnum" => "Lining figures",
+        "locl" => "Localized forms",
+        "mark" => "Mark positioning",
+        "medi" => "Medial forms",
+        "mgrk" => "Mathematical Greek",
+        "mkmk" => "Mark to mark positioning",
+        "nalt" => "Alternate annotation forms",
+        "numr" => "Numerators",
+        "onum" => "Oldstyle figures",
+        "opbd" => "Optical bounds",
+        "ordn" => "Ordinals",
+        "ornm" => "Ornaments",
+        "palt" => "Proportional alternate widths",
+        "pcap" => "Petite capitals",
+        "pnum" => "Proportional figures",
+        "pwid" => "Proportional widths",
+        "qwid" => "Quarter widths",
+        "rlig" => "Required ligatures",
+        "rvrn" => "Required variation alternates",
+        "ruby" => "Ruby notation forms",
+        "salt" => "Stylistic alternates",
+        "sinf" => "Scientific inferiors",
+        "size" => "Optical size",
+        "smcp" => "Small capitals",
+        "ssty" => "Math script style alternates",
+        "subs" => "Subscript",
+        "sups" => "Superscript",
+        "swsh" => "Swash",
+        "titl" => "Titling",
+        "tnum" => "Tabular figures",
+        "twid" => "Third widths",
+        "unic" => "Unicase",
+        "valt" => "Alternate vertical metrics",
+        "vert" => "Vertical writing",
+        "vkrn" => "Vertical kerning",
+        "vrt2" => "Vertical alternates and rotation",
+        "zero" => "Slashed zero",
+        _ => return None,
+    };
+    Some(description.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use typst::layout::Em;
+
+    use super::*;
+
+    fn length_text(length: Length, font_size: Option<Abs>) -> Option<EcoString> {
+        match length_tooltip(length, font_size)? {
+            Tooltip::Code(code) | Tooltip::Text(code) => Some(code),
+        }
+    }
+
+    #[test]
+    fn test_length_tooltip() {
+        let em = Length::from(Em::new(2.0));
+        assert_eq!(length_text(em, None), None);
+        assert_eq!(
+            length_text(em, Some(Abs::pt(12.0))).as_deref(),
+            Some("= 24pt (at 12pt)")
+        );
+
+        let mixed = Length {
+            abs: Abs::pt(1.0),
+            em: Em::new(0.5),
+        };
+        assert_eq!(
+            length_text(mixed, Some(Abs::pt(10.0))).as_deref(),
+            Some("= 6pt (at 10pt)")
+        );
+
+        assert_eq!(
+            length_text(Length::from(Abs::pt(72.0)), None).as_deref(),
+            Some("72pt = 25.4mm = 2.54cm = 1in")
+        );
+    }
+
+    #[test]
+    fn test_color_conversions() {
+        let orange = Color::from_u8(255, 136, 0, 255);
+        assert_eq!(
+            color_conversions(orange, "oklch").as_str(),
+            "rgb(\"#ff8800\")\nrgb(100%, 53.33%, 0%)\ncmyk(0%, 46.67%, 100%, 0%)"
+        );
+
+        let translucent = Color::from_u8(0, 0, 255, 128);
+        let conversions = color_conversions(translucent, "rgb");
+        assert!(conversions.starts_with("rgb(\"#0000ff80\")\ncmyk("));
+        assert!(conversions.ends_with(", 50.2%)"));
+    }
+
+    #[test]
+    fn test_color_constructor() {
+        let constructor = |code: &str| {
+            let source = Source::detached(code);
+            let call =
+                source
+                    .root()
+                    .cast::<ast::Markup>()?
+                    .exprs()
+                    .find_map(|expr| match expr {
+                        ast::Expr::FuncCall(call) => Some(call),
+                        _ => None,
+                    })?;
+            color_constructor(call.callee())
+        };
+
+        assert_eq!(constructor("#oklch(70%, 0.1, 30deg)"), Some("oklch"));
+        assert_eq!(
+            constructor("#color.hsl(30deg, 50%, 50%)"),
+            Some("color.hsl")
+        );
+        assert_eq!(constructor("#text(red)[]"), None);
+        assert_eq!(constructor("#calc.max(1, 2)"), None);
+    }
+
+    #[test]
+    fn test_feature_description() {
+        assert_eq!(
+            feature_description("liga").as_deref(),
+            Some("Standard ligatures")
+        );
+        assert_eq!(
+            feature_description("ss03").as_deref(),
+            Some("Stylistic set 3")
+        );
+        assert_eq!(
+            feature_description("cv11").as_deref(),
+            Some("Character variant 11")
+        );
+        assert_eq!(feature_description("ss21"), None);
+        assert_eq!(feature_description("xyzw"), None);
+    }
+
+    #[test]
+    fn test_feature_tag_at() {
+        let tag_at = |text: &str, cursor: usize| {
+            let source = Source::detached(text);
+            let leaf = LinkedNode::new(source.root()).leaf_at_compat(cursor)?;
+            feature_tag_at(&leaf).map(|(tag, _)| tag)
+        };
+
+        let array = r#"#text(features: ("liga", "smcp"))[]"#;
+        assert_eq!(tag_at(array, 19).as_deref(), Some("liga"));
+        assert_eq!(tag_at(array, 27).as_deref(), Some("smcp"));
+
+        let dict = r#"#text(features: (liga: 0, "dlig": 1))[]"#;
+        assert_eq!(tag_at(dict, 18).as_deref(), Some("liga"));
+        assert_eq!(tag_at(dict, 28).as_deref(), Some("dlig"));
+
+        assert_eq!(tag_at(r#"#text(font: ("liga",))[]"#, 15), None);
+        assert_eq!(tag_at(r#"#text(features: (liga: "kern"))[]"#, 25), None);
+    }
+}
@@ -84,8 +84,12 @@ pub async fn compile_main(args: CompileArgs) -> Result<()> {
     }
 
     // Exports the compiled project
+    if ExportTask::is_stdout(&output.task) {
+        ExportTask::do_export_to_stdout(output.task, compiled, None).await?;
+        return Ok(());
+    }
     let lock_dir = save_lock.then_some(lock_dir);
-    ExportTask::do_export(output.task, compiled, lock_dir).await?;
+    ExportTask::do_export(output.task, compiled, lock_dir, None).await?;
 
     Ok(())
 }
@@ -187,6 +187,10 @@ fn shell_build_script(shell: Shell) -> Result<String> {
                     cmd.push("--no-pdf-tags");
                 }
 
+                if task.reproducible {
+                    cmd.push("--pdf-reproducible");
+                }
+
                 if let Some(output) = &task.creation_timestamp {
                     cmd.push("--creation-timestamp");
                     cmd.push(output.to_string());
@@ -195,6 +199,9 @@ fn shell_build_script(shell: Shell) -> Result<String> {
             ProjectTask::ExportSvg(..) => {
                 cmd.push("--format=svg");
             }
+            ProjectTask::ExportAnimatedSvg(..) => {
+                cmd.push("--format=animated-svg");
+            }
             ProjectTask::ExportSvgHtml(..) => {
                 cmd.push("--format=svg_html");
             }
@@ -211,9 +218,15 @@ fn shell_build_script(shell: Shell) -> Result<String> {
             ProjectTask::ExportPng(..) => {
                 cmd.push("--format=png");
             }
+            ProjectTask::ExportGif(..) => {
+                cmd.push("--format=gif");
+            }
             ProjectTask::ExportText(..) => {
                 cmd.push("--format=txt");
             }
+            ProjectTask::ExportPptx(..) => {
+                cmd.push("--format=pptx");
+            }
             ProjectTask::ExportHtml(..) => {
                 cmd.push("--format=html");
             }
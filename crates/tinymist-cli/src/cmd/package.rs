@@ -357,6 +357,13 @@ async fn export_docs_bundle(ctx: &PackageDocsContext) -> Result<()> {
             when: TaskWhen::Never,
             output: Some(PathPattern::new(&ctx.dist.to_string_lossy())),
             transform: vec![],
+            skip_unchanged: false,
+            changed_pages: false,
+            expand_env: false,
+            manifest: false,
+            span_map: false,
+            split_by: None,
+            require: None,
         },
         pages: None,
         pdf_standards: ctx.args.compile.pdf.standard.clone(),
@@ -364,7 +371,7 @@ async fn export_docs_bundle(ctx: &PackageDocsContext) -> Result<()> {
         creation_timestamp: ctx.args.compile.creation_timestamp,
         ppi: Scalar::try_from(ctx.args.compile.png.ppi).context("cannot convert ppi")?,
     });
-    ExportTask::do_export(task, artifact, None).await?;
+    ExportTask::do_export(task, artifact, None, None).await?;
     Ok(())
 }
 
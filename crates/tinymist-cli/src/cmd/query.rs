@@ -13,6 +13,7 @@ use tinymist_query::package::PackageInfo;
 use tinymist_std::error::prelude::*;
 use tinymist_std::fs::paths::write_atomic;
 use typlite::CompileOnceArgs;
+use typst::diag::EcoString;
 
 /// The commands for language server queries.
 #[derive(Debug, Clone, clap::Subcommand)]
@@ -25,7 +26,7 @@ pub enum QueryCommands {
     /// Get the documentation for a specific package.
     PackageDocs(PackageDocsArgs),
     /// Check a specific package.
-    CheckPackage(PackageDocsArgs),
+    CheckPackage(CheckPackageArgs),
 }
 
 #[derive(Debug, Clone, clap::Parser)]
@@ -91,6 +92,28 @@ pub struct PackageDocsArgs {
     // pub format: Option<QueryDocsFormat>,
 }
 
+#[derive(Debug, Clone, clap::Parser)]
+pub struct CheckPackageArgs {
+    /// Compile a document once before querying.
+    #[clap(flatten)]
+    pub compile: CompileOnceArgs,
+
+    /// The path of the package to check.
+    #[clap(long)]
+    pub path: Option<String>,
+    /// The package of the package to check.
+    #[clap(long)]
+    pub id: String,
+    /// The output path for the diagnostics of the checked files, in JSON.
+    #[clap(short, long)]
+    pub output: Option<String>,
+    /// Additional entry files to check, relative to the package root. This
+    /// catches errors in example or test files that the entrypoint doesn't
+    /// import.
+    #[clap(long = "entry")]
+    pub entries: Vec<String>,
+}
+
 /// Creates the default analysis context for CLI query-style commands.
 pub fn default_analysis() -> Arc<Analysis> {
     let (config, _) = Config::extract_lsp_params(Default::default(), Default::default());
@@ -99,10 +122,12 @@ pub fn default_analysis() -> Arc<Analysis> {
         position_encoding: const_config.position_encoding,
         allow_overlapping_token: const_config.tokens_overlapping_token_support,
         allow_multiline_token: const_config.tokens_multiline_token_support,
+        signature_label_offsets: const_config.signature_label_offset_support,
         remove_html: !config.support_html_in_markdown,
         support_client_codelens: config.support_client_codelens,
         extended_code_action: config.extended_code_action,
         completion_feat: config.completion.clone(),
+        tooltip_feat: config.tooltip.clone(),
         color_theme: match config.color_theme.as_deref() {
             Some("dark") => tinymist_query::ColorTheme::Dark,
             _ => tinymist_query::ColorTheme::Light,
@@ -208,11 +233,23 @@ pub fn query_main(mut cmds: QueryCommands) -> Result<()> {
 
             write_output(Path::new(&args.output), res, "failed to write package docs")?;
         }
-        QueryCommands::CheckPackage(_args) => {
-            snap.run_within_package(&info, |a| {
-                tinymist_query::package::check_package(a, &info)
+        QueryCommands::CheckPackage(args) => {
+            let entries = args.entries.iter().map(|e| EcoString::from(e.as_str()));
+            let entries = entries.collect::<Vec<_>>();
+            let diagnostics = snap.run_within_package(&info, |a| {
+                tinymist_query::package::check_package(a, &info, &entries)
                     .map_err(map_string_err("failed to check package"))
             })?;
+
+            if let Some(output) = args.output {
+                let diagnostics = serde_json::to_vec_pretty(&diagnostics)
+                    .context_ut("failed to serialize diagnostics")?;
+                write_output(
+                    Path::new(&output),
+                    diagnostics,
+                    "failed to write diagnostics",
+                )?;
+            }
         }
     };
 
@@ -90,6 +90,7 @@ pub fn trace_lsp_main(args: TraceLspArgs) -> Result<()> {
             let g = snap.task(TaskInputs {
                 entry: Some(entry),
                 inputs,
+                locale: None,
             });
 
             tinymist::UserActionTask::trace_main(client, state, g, args.rpc_kind, req_id).await
@@ -2,7 +2,7 @@ use ecow::eco_format;
 use typst::diag::PackageError;
 
 use super::*;
-use crate::registry::threaded_http;
+use crate::registry::{HttpOptions, threaded_http};
 
 /// A package in the remote http.
 #[derive(Clone)]
@@ -33,7 +33,7 @@ impl<S: AsRef<str>> PackFs for HttpPack<S> {
     ) -> PackageResult<()> {
         let spec = &self.specifier;
         let url = self.url.as_ref();
-        threaded_http(url, None, |resp| {
+        threaded_http(url, &HttpOptions::default(), |resp| {
             let reader = match resp.and_then(|r| r.error_for_status()) {
                 Ok(response) => response,
                 Err(err) if matches!(err.status().map(|s| s.as_u16()), Some(404)) => {
@@ -166,6 +166,24 @@ pub struct TaskCompileArgs {
     #[arg(long = "pages", value_delimiter = ',')]
     pub pages: Option<Vec<Pages>>,
 
+    /// Also writes a span map next to the output, i.e. `<output>.spans.json`,
+    /// which maps the bounding boxes of the rendered elements on each page to
+    /// their source ranges. Only PDF, PNG, and SVG outputs support it.
+    #[arg(long = "span-map")]
+    pub span_map: bool,
+
+    /// Also records the written outputs in an `export-manifest.json` next to
+    /// them, with their formats, sizes, SHA-256 checksums, and the time of
+    /// the export.
+    #[arg(long = "manifest")]
+    pub manifest: bool,
+
+    /// Exports one file per section rather than a single file, e.g.
+    /// `heading-level-1` for a file per top-level heading. The output path
+    /// must contain the `{section}` placeholder. Only PDF outputs support it.
+    #[arg(long = "split-by")]
+    pub split_by: Option<SplitBy>,
+
     /// Specify the PDF export related arguments.
     #[clap(flatten)]
     pub pdf: PdfExportArgs,
@@ -187,7 +205,7 @@ impl TaskCompileArgs {
 
         let output_format = if let Some(specified) = self.format {
             specified
-        } else if let Some(output) = &self.output {
+        } else if let Some(output) = self.output.as_ref().filter(|output| *output != "-") {
             let output = Path::new(output);
 
             match output.extension() {
@@ -205,6 +223,11 @@ impl TaskCompileArgs {
         };
 
         let output = self.output.as_ref().map(|output| {
+            // Writes to the standard output.
+            if output == "-" {
+                return PathPattern::new(output);
+            }
+
             let output = Path::new(output);
             let output = if output.is_absolute() {
                 output.to_path_buf()
@@ -229,6 +252,13 @@ impl TaskCompileArgs {
             when,
             output,
             transform: transforms,
+            skip_unchanged: false,
+            changed_pages: false,
+            expand_env: false,
+            manifest: self.manifest,
+            span_map: self.span_map,
+            split_by: self.split_by,
+            require: None,
         };
 
         let config = match output_format {
@@ -238,20 +268,30 @@ impl TaskCompileArgs {
                 pdf_standards: self.pdf.standard.clone(),
                 no_pdf_tags: self.pdf.no_tags,
                 creation_timestamp: None,
+                reproducible: self.pdf.reproducible,
+                outline_sidecar: self.pdf.outline_sidecar,
+                icc_profile: self.pdf.icc_profile.clone(),
             }),
             OutputFormat::Png => ProjectTask::ExportPng(ExportPngTask {
                 export,
                 pages: self.pages.clone(),
                 page_number_template: None,
                 merge: None,
+                contact_sheet: None,
                 ppi: self.png.ppi.try_into().unwrap(),
                 fill: None,
+                transparent: false,
+                jobs: self.png.jobs,
+                max_height: None,
+                icc_profile: self.png.icc_profile.clone(),
+                max_bytes: self.png.max_bytes,
             }),
             OutputFormat::Svg => ProjectTask::ExportSvg(ExportSvgTask {
                 export,
                 pages: self.pages.clone(),
                 page_number_template: None,
                 merge: None,
+                text_mode: SvgTextMode::Paths,
             }),
             OutputFormat::Html => ProjectTask::ExportHtml(ExportHtmlTask { export }),
             OutputFormat::Bundle => ProjectTask::ExportBundle(ExportBundleTask {
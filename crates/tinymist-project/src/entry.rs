@@ -5,7 +5,7 @@ use tinymist_l10n::DebugL10n;
 use tinymist_std::ImmutPath;
 use tinymist_std::error::prelude::*;
 use tinymist_std::hash::FxDashMap;
-use tinymist_world::EntryState;
+use tinymist_world::{EntryState, is_workspace_manifest};
 use typst::syntax::VirtualPath;
 
 /// The kind of project resolution.
@@ -26,6 +26,24 @@ pub enum ProjectResolutionKind {
     LockDatabase,
 }
 
+/// Where the root directory of an entry comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EntryRootSource {
+    /// The root path is specified manually, e.g. by `rootPath` or `--root`.
+    RootPath,
+    /// The entry is inside one of the workspace roots.
+    Workspace,
+    /// The entry is inside a directory containing a `typst.toml` that marks a
+    /// workspace, i.e. has a `[workspace]` table.
+    TypstWorkspace,
+    /// The entry is inside a directory containing a `typst.toml`.
+    TypstToml,
+    /// The root is guessed from the parent of the entry or the first workspace
+    /// root.
+    Heuristic,
+}
+
 /// Entry resolver
 #[derive(Debug, Default, Clone)]
 pub struct EntryResolver {
@@ -43,15 +61,31 @@ pub struct EntryResolver {
 
 impl EntryResolver {
     /// Resolves the root directory for the entry file.
+    ///
+    /// The root is determined in the following order:
+    /// - the root path specified manually, e.g. by `rootPath` or `--root`,
+    /// - the workspace root containing the entry,
+    /// - the nearest ancestor whose `typst.toml` has a `[workspace]` table,
+    /// - the nearest ancestor containing a `typst.toml`,
+    /// - the parent directory of the entry, or the first workspace root.
     pub fn root(&self, entry: Option<&ImmutPath>) -> Option<ImmutPath> {
+        self.root_with_source(entry).map(|(root, _)| root)
+    }
+
+    /// Resolves the root directory for the entry file, along with where the
+    /// root comes from.
+    pub fn root_with_source(
+        &self,
+        entry: Option<&ImmutPath>,
+    ) -> Option<(ImmutPath, EntryRootSource)> {
         if let Some(root) = &self.root_path {
-            return Some(root.clone());
+            return Some((root.clone(), EntryRootSource::RootPath));
         }
 
         if let Some(entry) = entry {
             for root in self.roots.iter() {
                 if entry.starts_with(root) {
-                    return Some(root.clone());
+                    return Some((root.clone(), EntryRootSource::Workspace));
                 }
             }
 
@@ -70,9 +104,12 @@ impl EntryResolver {
                 // restart the server to refresh the cache
                 Some(None) => return None,
                 Some(Some(cached)) => {
-                    let cached = cached.clone();
-                    if cached.join("typst.toml").exists() {
-                        return Some(cached.clone());
+                    let typst_toml = cached.join("typst.toml");
+                    if is_workspace_manifest(&typst_toml) {
+                        return Some((cached, EntryRootSource::TypstWorkspace));
+                    }
+                    if typst_toml.exists() {
+                        return Some((cached, EntryRootSource::TypstToml));
                     }
                     typst_toml_cache.remove(entry);
                 }
@@ -81,28 +118,50 @@ impl EntryResolver {
 
             // cache miss, check the file system
             // todo: heavy io here?
+            // A workspace marker takes precedence over the nearest package manifest.
+            let mut package_root = None;
             for ancestor in entry.ancestors() {
                 let typst_toml = ancestor.join("typst.toml");
-                if typst_toml.exists() {
+                if is_workspace_manifest(&typst_toml) {
                     let ancestor: ImmutPath = ancestor.into();
                     typst_toml_cache.insert(entry.clone(), Some(ancestor.clone()));
-                    return Some(ancestor);
+                    return Some((ancestor, EntryRootSource::TypstWorkspace));
+                }
+                if package_root.is_none() && typst_toml.exists() {
+                    package_root = Some(ancestor);
                 }
             }
+            if let Some(package_root) = package_root {
+                let package_root: ImmutPath = package_root.into();
+                typst_toml_cache.insert(entry.clone(), Some(package_root.clone()));
+                return Some((package_root, EntryRootSource::TypstToml));
+            }
             typst_toml_cache.insert(entry.clone(), None);
 
             if let Some(parent) = entry.parent() {
-                return Some(parent.into());
+                return Some((parent.into(), EntryRootSource::Heuristic));
             }
         }
 
         if !self.roots.is_empty() {
-            return Some(self.roots[0].clone());
+            return Some((self.roots[0].clone(), EntryRootSource::Heuristic));
         }
 
         None
     }
 
+    /// Resolves the entry state, along with where its root directory comes
+    /// from.
+    pub fn resolve_with_source(
+        &self,
+        entry: Option<ImmutPath>,
+    ) -> (EntryState, Option<EntryRootSource>) {
+        let root = self.root_with_source(entry.as_ref());
+        let source = root.as_ref().map(|(_, source)| *source);
+        let state = self.resolve_with_root(root.map(|(root, _)| root), entry);
+        (state, source)
+    }
+
     /// Resolves the entry state.
     pub fn resolve(&self, entry: Option<ImmutPath>) -> EntryState {
         let root_dir = self.root(entry.as_ref());
@@ -296,4 +355,57 @@ mod entry_tests {
             assert_eq!(default_entry, Some(root_path.join("main.typ").into()));
         }
     }
+
+    #[test]
+    fn test_entry_resolution_source() {
+        let root_path = Path::new(ROOT);
+        let root2_path = Path::new(ROOT2);
+
+        let entry = EntryResolver {
+            roots: vec![ImmutPath::from(root_path)],
+            ..Default::default()
+        };
+
+        let (state, source) = entry.resolve_with_source(Some(root_path.join("main.typ").into()));
+        assert_eq!(state.root(), Some(ImmutPath::from(root_path)));
+        assert_eq!(source, Some(EntryRootSource::Workspace));
+
+        let (_, source) = entry.resolve_with_source(Some(root2_path.join("main.typ").into()));
+        assert_eq!(source, Some(EntryRootSource::Heuristic));
+
+        let entry = EntryResolver {
+            root_path: Some(ImmutPath::from(root2_path)),
+            ..Default::default()
+        };
+
+        let (_, source) = entry.resolve_with_source(Some(root2_path.join("main.typ").into()));
+        assert_eq!(source, Some(EntryRootSource::RootPath));
+    }
+
+    #[test]
+    fn test_entry_resolution_typst_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path();
+        let package = workspace.join("packages/foo");
+        std::fs::create_dir_all(&package).unwrap();
+        std::fs::write(workspace.join("typst.toml"), "[workspace]\n").unwrap();
+        std::fs::write(package.join("typst.toml"), "[package]\nname = \"foo\"\n").unwrap();
+
+        let entry = EntryResolver::default();
+        let main: ImmutPath = package.join("main.typ").into();
+
+        let (state, source) = entry.resolve_with_source(Some(main.clone()));
+        assert_eq!(state.root(), Some(ImmutPath::from(workspace)));
+        assert_eq!(source, Some(EntryRootSource::TypstWorkspace));
+
+        // The cached root keeps its source.
+        let (_, source) = entry.resolve_with_source(Some(main));
+        assert_eq!(source, Some(EntryRootSource::TypstWorkspace));
+
+        std::fs::write(workspace.join("typst.toml"), "").unwrap();
+        let entry = EntryResolver::default();
+        let (state, source) = entry.resolve_with_source(Some(package.join("main.typ").into()));
+        assert_eq!(state.root(), Some(ImmutPath::from(package.as_path())));
+        assert_eq!(source, Some(EntryRootSource::TypstToml));
+    }
 }
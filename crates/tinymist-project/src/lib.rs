@@ -11,6 +11,8 @@ pub mod mock;
 
 #[cfg(feature = "lsp")]
 mod lsp;
+#[cfg(feature = "lsp")]
+mod target;
 #[cfg(feature = "system")]
 mod watch;
 
@@ -25,6 +27,8 @@ pub use world::*;
 
 #[cfg(feature = "lsp")]
 pub use lsp::*;
+#[cfg(feature = "lsp")]
+pub use target::*;
 #[cfg(feature = "system")]
 pub use watch::*;
 
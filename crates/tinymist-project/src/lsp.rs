@@ -109,6 +109,8 @@ impl WorldProvider for CompileOnceArgs {
             } else {
                 cwd()?.join(root)
             }
+        } else if let Some(root) = tinymist_world::find_workspace_root(&main) {
+            root.to_owned()
         } else {
             main.parent()
                 .context("entry file don't have a valid parent as root")?
@@ -164,6 +166,7 @@ impl WorldProvider for (crate::ProjectInput, ImmutPath) {
                     .package_cache_path
                     .as_ref()
                     .and_then(|p| p.to_abs_path(lock_dir)),
+                ..CompilePackageArgs::default()
             }),
         );
 
@@ -325,6 +328,12 @@ impl LspUniverseBuilder {
             args.and_then(|args| Some(args.package_path.clone()?.into())),
             args.and_then(|args| Some(args.package_cache_path.clone()?.into())),
         )
+        .with_registry(args.and_then(|args| args.package_registry.clone()))
+        .with_proxy(args.and_then(|args| args.package_proxy.clone()))
+        .with_headers(
+            args.map(|args| args.package_headers.clone())
+                .unwrap_or_default(),
+        )
     }
 
     /// Resolves package registry from given options.
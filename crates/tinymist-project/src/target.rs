@@ -0,0 +1,225 @@
+//! Build targets declared in `typst.toml`.
+//!
+//! A project may declare build targets in the `[tool.tinymist.targets]` table
+//! of its `typst.toml`, each of which exports an entry file with some inputs:
+//!
+//! ```toml
+//! [tool.tinymist.targets.print]
+//! main = "main.typ"
+//! type = "export-pdf"
+//! output = "build/print/$name"
+//! inputs = { edition = "print" }
+//! ```
+//!
+//! The fields other than `main` and `inputs` specify the [`ProjectTask`] run
+//! by the target, in the same way as the tasks in the lock file.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use tinymist_std::error::prelude::*;
+use tinymist_std::{ImmutPath, bail};
+use tinymist_task::PathPattern;
+
+use crate::ProjectTask;
+
+/// A build target declared in `typst.toml`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildTarget {
+    /// The absolute path to the entry file.
+    pub main: ImmutPath,
+    /// The inputs passed to the document, i.e. `sys.inputs`.
+    pub inputs: BTreeMap<String, String>,
+    /// The task to run, whose relative output path is resolved against the
+    /// directory of the `typst.toml`.
+    pub task: ProjectTask,
+}
+
+/// The build targets declared in a `typst.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildTargets {
+    /// The directory containing the `typst.toml`.
+    pub dir: Option<ImmutPath>,
+    /// The targets by name.
+    pub targets: BTreeMap<String, BuildTarget>,
+}
+
+impl BuildTargets {
+    /// Finds the nearest `typst.toml` that declares build targets in the path
+    /// or its ancestors, and reads the targets from it.
+    pub fn find(path: &Path) -> Result<Self> {
+        for dir in path.ancestors() {
+            let manifest = dir.join("typst.toml");
+            let Ok(content) = std::fs::read_to_string(&manifest) else {
+                continue;
+            };
+            let targets = Self::parse(dir, &content)
+                .with_context("failed to read build targets", || {
+                    Some(Box::new([("path", manifest.display().to_string())]))
+                })?;
+            if !targets.targets.is_empty() {
+                return Ok(targets);
+            }
+        }
+
+        Ok(Self::default())
+    }
+
+    /// Parses the build targets from the content of a `typst.toml` in the
+    /// directory.
+    pub fn parse(dir: &Path, content: &str) -> Result<Self> {
+        let manifest: toml::Table = toml::from_str(content).context_ut("invalid typst.toml")?;
+        let targets = manifest
+            .get("tool")
+            .and_then(|tool| tool.get("tinymist"))
+            .and_then(|tinymist| tinymist.get("targets"));
+        let Some(targets) = targets else {
+            return Ok(Self::default());
+        };
+        let Some(targets) = targets.as_table() else {
+            bail!("`tool.tinymist.targets` must be a table");
+        };
+
+        let targets = targets
+            .iter()
+            .map(|(name, target)| {
+                let target = parse_target(dir, target)
+                    .with_context("invalid build target", || {
+                        Some(Box::new([("name", name.clone())]))
+                    })?;
+                Ok((name.clone(), target))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            dir: Some(dir.into()),
+            targets,
+        })
+    }
+
+    /// Gets the target by name, failing with the available targets if it is
+    /// not declared.
+    pub fn get(&self, name: &str) -> Result<&BuildTarget> {
+        if let Some(target) = self.targets.get(name) {
+            return Ok(target);
+        }
+
+        if self.targets.is_empty() {
+            bail!("unknown build target `{name}`, no targets are declared in typst.toml");
+        }
+        let available = self.targets.keys().cloned().collect::<Vec<_>>();
+        bail!(
+            "unknown build target `{name}`, available targets: {}",
+            available.join(", ")
+        )
+    }
+}
+
+/// Parses a target from its table, which holds the task along with the `main`
+/// and `inputs` fields.
+fn parse_target(dir: &Path, target: &toml::Value) -> Result<BuildTarget> {
+    let Some(target) = target.as_table() else {
+        bail!("target must be a table");
+    };
+    let mut target = target.clone();
+
+    let main = match target.remove("main") {
+        Some(toml::Value::String(main)) => dir.join(main),
+        Some(_) => bail!("`main` must be a string"),
+        None => bail!("`main` is required"),
+    };
+    let inputs = match target.remove("inputs") {
+        Some(inputs) => inputs
+            .try_into::<BTreeMap<String, String>>()
+            .context_ut("`inputs` must be a table of strings")?,
+        None => BTreeMap::new(),
+    };
+
+    // A target runs when it is built, so it doesn't have to specify the timing.
+    target
+        .entry("when")
+        .or_insert_with(|| toml::Value::String("never".to_owned()));
+    let mut task = toml::Value::Table(target)
+        .try_into::<ProjectTask>()
+        .context_ut("invalid task")?;
+
+    if let Some(export) = task.as_export_mut()
+        && let Some(output) = export.output.as_ref()
+    {
+        let output = output.to_string();
+        if !output.starts_with('$') && Path::new(&output).is_relative() {
+            let output = PathBuf::from(dir).join(output);
+            export.output = Some(PathPattern::new(&output.to_string_lossy()));
+        }
+    }
+
+    Ok(BuildTarget {
+        main: main.as_path().into(),
+        inputs,
+        task,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_build_targets() {
+        let dir = Path::new("/project");
+        let content = r#"
+[package]
+name = "book"
+version = "0.1.0"
+entrypoint = "main.typ"
+
+[tool.tinymist.targets.print]
+main = "main.typ"
+type = "export-pdf"
+output = "build/print/$name"
+inputs = { edition = "print" }
+
+[tool.tinymist.targets.web]
+main = "web.typ"
+type = "export-html"
+"#;
+
+        let targets = BuildTargets::parse(dir, content).unwrap();
+        assert_eq!(
+            targets.targets.keys().collect::<Vec<_>>(),
+            vec!["print", "web"]
+        );
+
+        let print = targets.get("print").unwrap();
+        assert_eq!(print.main.as_ref(), Path::new("/project/main.typ"));
+        assert_eq!(
+            print.inputs.get("edition").map(String::as_str),
+            Some("print")
+        );
+        let ProjectTask::ExportPdf(task) = &print.task else {
+            panic!("unexpected task: {:?}", print.task);
+        };
+        let output = task.export.output.as_ref().unwrap().to_string();
+        assert_eq!(
+            Path::new(&output),
+            Path::new("/project").join("build/print/$name")
+        );
+
+        let web = targets.get("web").unwrap();
+        assert!(matches!(web.task, ProjectTask::ExportHtml(..)));
+        assert!(web.inputs.is_empty());
+
+        let err = targets.get("epub").unwrap_err().to_string();
+        assert!(err.contains("available targets: print, web"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_no_build_targets() {
+        let content = r#"
+[package]
+name = "book"
+"#;
+        let targets = BuildTargets::parse(Path::new("/project"), content).unwrap();
+        assert!(targets.targets.is_empty());
+    }
+}
@@ -5,8 +5,8 @@ pub use tinymist_world::args::*;
 pub use tinymist_world::config::CompileFontOpts;
 pub use tinymist_world::entry::*;
 pub use tinymist_world::{
-    CompilerUniverse, CompilerWorld, DiagnosticFormat, EntryOpts, EntryState, RevisingUniverse,
-    SourceWorld, TaskInputs, WorldComputeGraph, with_main,
+    CompilerUniverse, CompilerWorld, DiagnosticFormat, EntryOpts, EntryState, Locale,
+    RevisingUniverse, SourceWorld, TaskInputs, WorldComputeGraph, with_main,
 };
 pub use tinymist_world::{diag, font, package, vfs};
 
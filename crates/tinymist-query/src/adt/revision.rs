@@ -57,6 +57,11 @@ impl<T> RevisionManager<T> {
         self.slots.clear();
     }
 
+    /// The number of alive revision slots.
+    pub fn slot_count(&self) -> usize {
+        self.slots.len()
+    }
+
     /// Lock the revision in *main thread*.
     #[must_use]
     pub fn lock(&mut self, used: NonZeroUsize) -> RevisionLock {
@@ -20,6 +20,8 @@ pub mod signature;
 pub use signature::*;
 pub mod semantic_tokens;
 pub use semantic_tokens::*;
+pub mod unused;
+pub use unused::*;
 
 mod global;
 mod post_tyck;
@@ -148,6 +150,7 @@ impl LspQuerySnapshot {
         let snap = self.task(TaskInputs {
             entry: Some(entry),
             inputs: None,
+            locale: None,
         });
 
         snap.run_analysis(f)?
@@ -244,3 +244,23 @@ pub fn analyze_call_no_cache(
 
     Some(info)
 }
+
+/// Gets the type of the item selected by an `at` call on an array or
+/// dictionary, e.g. `data.at("key")`.
+pub fn analyze_indexed_call(ctx: &mut LocalContext, call: ast::FuncCall) -> Option<Ty> {
+    let ast::Expr::FieldAccess(access) = call.callee() else {
+        return None;
+    };
+    if access.field().as_str() != "at" {
+        return None;
+    }
+
+    let key = call.args().items().find_map(|arg| match arg {
+        ast::Arg::Pos(expr) => Some(expr),
+        _ => None,
+    });
+    let key = key.and_then(super::SharedContext::const_eval);
+
+    let receiver = ctx.type_of_span(access.target().span())?;
+    receiver.indexed(key.as_ref())
+}
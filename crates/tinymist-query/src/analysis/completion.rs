@@ -148,6 +148,44 @@ pub enum SymbolCompletionWay {
     Stepless,
 }
 
+/// The kind of completions offered at a cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CompletionContextKind {
+    /// No completions are offered, e.g. on the name of a let binding.
+    None,
+    /// In a comment, where the doc comment snippets are offered.
+    Comment,
+    /// On the import list of an import statement.
+    ImportList,
+    /// On the parameter list of a closure.
+    ParamList,
+    /// On the field of a field access, e.g. `a.b`.
+    Field,
+    /// On the file path of an import or include statement.
+    Path,
+    /// On the package spec of an import statement.
+    Package,
+    /// On a reference, e.g. `@label`.
+    Reference,
+    /// On the selector of a show rule.
+    Selector,
+    /// On the transformation of a show rule.
+    ShowTransform,
+    /// On a set rule, where the settable parameters are offered.
+    SetRule,
+    /// In code.
+    Code,
+    /// In math.
+    Math,
+    /// In markup.
+    Markup,
+    /// In a raw block, where markup completions are offered.
+    Raw,
+    /// In a string, where only completions by the expected type are offered.
+    String,
+}
+
 /// The struct describing how a completion worker views the editor's cursor.
 pub struct CompletionCursor<'a> {
     /// The shared context
@@ -231,6 +269,133 @@ impl<'a> CompletionCursor<'a> {
         interpret_mode_at(Some(&self.leaf))
     }
 
+    /// Gets the start of the text being completed, which is replaced by the
+    /// completions.
+    pub fn prefix_start(&self) -> usize {
+        let start = match &self.syntax_context {
+            Some(SyntaxContext::VarAccess(
+                var @ (VarClass::FieldAccess { .. } | VarClass::DotAccess { .. }),
+            )) => var
+                .accessing_field()
+                .and_then(|field| field.offset(&self.source)),
+            Some(SyntaxContext::ImportPath(path) | SyntaxContext::IncludePath(path)) => {
+                Some(path.offset())
+            }
+            Some(SyntaxContext::Ref { node, .. } | SyntaxContext::At { node }) => {
+                Some(node.offset() + 1)
+            }
+            _ if is_ident_like(&self.leaf) => Some(self.leaf.offset()),
+            _ => self.syntax.as_ref().and_then(SyntaxClass::complete_offset),
+        };
+        start.unwrap_or(self.cursor).min(self.cursor)
+    }
+
+    /// Gets the text being completed.
+    pub fn prefix(&self) -> &str {
+        &self.text[self.prefix_start()..self.cursor]
+    }
+
+    /// Classifies the completions offered at the cursor. It decides which
+    /// completions [`CompletionPair::complete_cursor`] offers.
+    pub fn context_kind(&self) -> CompletionContextKind {
+        use CompletionContextKind as Kind;
+        use SurroundingSyntax::*;
+
+        if self.is_skipped() {
+            return Kind::None;
+        }
+        if matches!(
+            self.leaf.kind(),
+            SyntaxKind::LineComment | SyntaxKind::BlockComment
+        ) {
+            return Kind::Comment;
+        }
+        match self.surrounding_syntax {
+            ImportList => return Kind::ImportList,
+            ParamList => return Kind::ParamList,
+            _ => {}
+        }
+
+        match &self.syntax_context {
+            Some(SyntaxContext::VarAccess(
+                VarClass::FieldAccess { .. } | VarClass::DotAccess { .. },
+            )) => return Kind::Field,
+            Some(SyntaxContext::ImportPath(path) | SyntaxContext::IncludePath(path)) => {
+                return match path.cast::<ast::Expr>() {
+                    Some(ast::Expr::Str(str)) if str.get().starts_with('@') => Kind::Package,
+                    Some(ast::Expr::Str(..)) => Kind::Path,
+                    _ => Kind::None,
+                };
+            }
+            Some(SyntaxContext::Ref { .. } | SyntaxContext::At { .. }) => return Kind::Reference,
+            _ => {}
+        }
+
+        match (self.leaf_mode(), self.surrounding_syntax) {
+            (InterpretMode::Code | InterpretMode::Markup, Selector) => Kind::Selector,
+            (InterpretMode::Code | InterpretMode::Markup, ShowTransform) => Kind::ShowTransform,
+            (InterpretMode::Code | InterpretMode::Markup, SetRule) => Kind::SetRule,
+            (InterpretMode::Code, _) => Kind::Code,
+            (InterpretMode::Math, _) => Kind::Math,
+            (InterpretMode::Markup, Regular) => Kind::Markup,
+            (InterpretMode::Raw, _) => Kind::Raw,
+            (InterpretMode::Markup | InterpretMode::Comment | InterpretMode::String, _) => {
+                Kind::String
+            }
+        }
+    }
+
+    /// Whether the completions are skipped at the cursor, i.e. on the name of
+    /// a let binding or a closure parameter, or in a number with a unit.
+    fn is_skipped(&self) -> bool {
+        // Skips if is the let binding item *directly*
+        if let Some(SyntaxClass::VarAccess(var)) = &self.syntax {
+            let node = var.node();
+            let is_in_body = || -> Option<()> {
+                match node.parent_kind() {
+                    // complete the init part of the let binding
+                    Some(SyntaxKind::LetBinding) => {
+                        let parent = node.parent()?;
+                        let parent_init = parent.cast::<ast::LetBinding>()?.init()?;
+                        let parent_init = parent.find(parent_init.span())?;
+                        parent_init.find(node.span())?;
+                    }
+                    Some(SyntaxKind::Closure) => {
+                        let parent = node.parent()?;
+                        let parent_body = parent.cast::<ast::Closure>()?.body();
+                        let parent_body = parent.find(parent_body.span())?;
+                        parent_body.find(node.span())?;
+                    }
+                    _ => {}
+                }
+                Some(())
+            };
+            if is_in_body().is_none() {
+                return true;
+            }
+        }
+
+        // Skips if an error node starts with number (e.g. `1pt`)
+        if matches!(
+            self.syntax,
+            Some(SyntaxClass::Callee(..) | SyntaxClass::VarAccess(..) | SyntaxClass::Normal(..))
+        ) && self.leaf.diagnosis().errors
+        {
+            let mut chars = self.leaf.leaf_text().chars();
+            match chars.next() {
+                Some(ch) if ch.is_numeric() => return true,
+                Some('.') => {
+                    if matches!(chars.next(), Some(ch) if ch.is_numeric()) {
+                        return true;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        false
+    }
+
     /// Gets selected node under cursor.
     fn selected_node(&self) -> &Option<SelectedNode<'a>> {
         self.ident_cursor.get_or_init(|| {
@@ -524,43 +689,8 @@ impl<'a> CompletionWorker<'a> {
 
     /// Starts the completion process.
     pub(crate) fn work(&mut self, cursor: &mut Cursor) -> Option<()> {
-        // Skips if is the let binding item *directly*
-        if let Some(SyntaxClass::VarAccess(var)) = &cursor.syntax {
-            let node = var.node();
-            match node.parent_kind() {
-                // complete the init part of the let binding
-                Some(SyntaxKind::LetBinding) => {
-                    let parent = node.parent()?;
-                    let parent_init = parent.cast::<ast::LetBinding>()?.init()?;
-                    let parent_init = parent.find(parent_init.span())?;
-                    parent_init.find(node.span())?;
-                }
-                Some(SyntaxKind::Closure) => {
-                    let parent = node.parent()?;
-                    let parent_body = parent.cast::<ast::Closure>()?.body();
-                    let parent_body = parent.find(parent_body.span())?;
-                    parent_body.find(node.span())?;
-                }
-                _ => {}
-            }
-        }
-
-        // Skips if an error node starts with number (e.g. `1pt`)
-        if matches!(
-            cursor.syntax,
-            Some(SyntaxClass::Callee(..) | SyntaxClass::VarAccess(..) | SyntaxClass::Normal(..))
-        ) && cursor.leaf.diagnosis().errors
-        {
-            let mut chars = cursor.leaf.leaf_text().chars();
-            match chars.next() {
-                Some(ch) if ch.is_numeric() => return None,
-                Some('.') => {
-                    if matches!(chars.next(), Some(ch) if ch.is_numeric()) {
-                        return None;
-                    }
-                }
-                _ => {}
-            }
+        if cursor.is_skipped() {
+            return None;
         }
 
         // Excludes it self from auto completion
@@ -624,25 +754,15 @@ impl CompletionPair<'_, '_, '_> {
     pub(crate) fn complete_cursor(&mut self) -> Option<()> {
         use SurroundingSyntax::*;
 
-        // Special completions, we should remove them finally
-        if matches!(
-            self.cursor.leaf.kind(),
-            SyntaxKind::LineComment | SyntaxKind::BlockComment
-        ) {
-            return self.complete_comments().then_some(());
-        }
-
         let surrounding_syntax = self.cursor.surrounding_syntax;
-        let mode = self.cursor.leaf_mode();
-
-        // Special completions 2, we should remove them finally
-        if matches!(surrounding_syntax, ImportList) {
-            return self.complete_imports().then_some(());
-        }
+        let kind = self.cursor.context_kind();
 
-        // Special completions 3, we should remove them finally
-        if matches!(surrounding_syntax, ParamList) {
-            return self.complete_params();
+        // Special completions, we should remove them finally
+        match kind {
+            CompletionContextKind::Comment => return self.complete_comments().then_some(()),
+            CompletionContextKind::ImportList => return self.complete_imports().then_some(()),
+            CompletionContextKind::ParamList => return self.complete_params(),
+            _ => {}
         }
 
         // Checks and completes `self.cursor.syntax_context`
@@ -742,16 +862,7 @@ impl CompletionPair<'_, '_, '_> {
         // Adjusts the completion position
         // todo: syntax class seems not being considering `is_ident_like`
         // todo: merge ident_content_offset and label_content_offset
-        if is_ident_like(&self.cursor.leaf) {
-            self.cursor.from = self.cursor.leaf.offset();
-        } else if let Some(offset) = self
-            .cursor
-            .syntax
-            .as_ref()
-            .and_then(SyntaxClass::complete_offset)
-        {
-            self.cursor.from = offset;
-        }
+        self.cursor.from = self.cursor.prefix_start();
 
         // Completion by types.
         if let Some(ty) = ty {
@@ -773,27 +884,21 @@ impl CompletionPair<'_, '_, '_> {
         }
         let mut type_completions = std::mem::take(&mut self.worker.completions);
 
-        // Completion by [`crate::syntax::InterpretMode`].
-        match mode {
-            InterpretMode::Code => {
+        // Completion by [`CompletionContextKind`].
+        match kind {
+            CompletionContextKind::Code
+            | CompletionContextKind::Selector
+            | CompletionContextKind::ShowTransform
+            | CompletionContextKind::SetRule => {
                 self.complete_code();
             }
-            InterpretMode::Math => {
+            CompletionContextKind::Math => {
                 self.complete_math();
             }
-            InterpretMode::Raw => {
+            CompletionContextKind::Markup | CompletionContextKind::Raw => {
                 self.complete_markup();
             }
-            InterpretMode::Markup => match surrounding_syntax {
-                Regular => {
-                    self.complete_markup();
-                }
-                Selector | ShowTransform | SetRule => {
-                    self.complete_code();
-                }
-                StringContent | ImportList | ParamList => {}
-            },
-            InterpretMode::Comment | InterpretMode::String => {}
+            _ => {}
         };
 
         // Snippet completions associated by surrounding_syntax.
@@ -48,7 +48,7 @@ use crate::syntax::{
     classify_syntax, construct_module_dependencies, is_mark, resolve_id_by_path,
     scan_workspace_files,
 };
-use crate::upstream::{Tooltip, tooltip_};
+use crate::upstream::{Tooltip, TooltipFeat, tooltip_};
 use crate::{
     ColorTheme, CompilerQueryRequest, LspPosition, LspRange, LspWorldExt, PositionEncoding,
 };
@@ -68,6 +68,8 @@ pub struct Analysis {
     pub allow_overlapping_token: bool,
     /// Whether to allow multiline semantic tokens.
     pub allow_multiline_token: bool,
+    /// Whether to allow label offsets of parameters in signature help.
+    pub signature_label_offsets: bool,
     /// Whether to remove html from markup content in responses.
     pub remove_html: bool,
     /// Whether to add client-side code lens.
@@ -82,6 +84,8 @@ pub struct Analysis {
     pub extended_code_action: bool,
     /// Tinymist's completion features.
     pub completion_feat: CompletionFeat,
+    /// Tinymist's tooltip features.
+    pub tooltip_feat: TooltipFeat,
     /// The editor's color theme.
     pub color_theme: ColorTheme,
     /// When to trigger the lint.
@@ -181,6 +185,54 @@ impl Analysis {
         self.analysis_rev_cache.lock().clear();
     }
 
+    /// Estimates the size of the cached analysis results in bytes, which is the
+    /// size of the alive interned objects holding the bulk of them.
+    pub fn cache_size(&self) -> usize {
+        AllocStats::summary().1
+    }
+
+    /// Evicts the least recently used cached resources until the estimated
+    /// size of the caches is under `budget` bytes, returning the number of
+    /// evicted entries.
+    ///
+    /// The resources shared across modules are evicted first, by the analysis
+    /// lifetime at which they were last used, and then the module-level
+    /// results, by the revision at which they were computed. The most recently
+    /// used ones are always kept, so that the caches are not thrashed if the
+    /// working set alone exceeds the budget.
+    pub fn evict_cache_to(&self, budget: usize) -> usize {
+        let mut evicted = 0;
+        if self.cache_size() <= budget {
+            return evicted;
+        }
+
+        let caches = &self.caches;
+        for cutoff in eviction_cutoffs(caches.lifetimes()) {
+            evicted += caches.evict_before(cutoff);
+            if self.cache_size() <= budget {
+                return evicted;
+            }
+        }
+
+        let mut rev_cache = self.analysis_rev_cache.lock();
+        for cutoff in eviction_cutoffs(rev_cache.revisions()) {
+            evicted += rev_cache.evict_before(cutoff);
+            if self.cache_size() <= budget {
+                break;
+            }
+        }
+
+        evicted
+    }
+
+    /// Clear the cached module-level analysis results of the files matching
+    /// the filter, keeping the results of other files.
+    ///
+    /// The content-keyed caches, e.g. signatures and docstrings, are kept.
+    pub fn clear_cache_by(&self, filter: impl Fn(TypstFileId) -> bool) {
+        self.analysis_rev_cache.lock().clear_by(&filter);
+    }
+
     /// Report the statistics of the analysis.
     pub fn report_query_stats(&self) -> String {
         self.stats.report()
@@ -196,6 +248,28 @@ impl Analysis {
         AllocStats::report()
     }
 
+    /// Report the number of entries in the analysis caches.
+    pub fn report_cache_stats(&self) -> AnalysisCacheStats {
+        let caches = &self.caches;
+        let (interned, interned_bytes) = AllocStats::summary();
+        // Holds the lock so that the revision caches are sampled consistently.
+        let rev_cache = self.analysis_rev_cache.lock();
+        let slot = &rev_cache.default_slot;
+        AnalysisCacheStats {
+            signatures: caches.signatures.len()
+                + caches.def_signatures.len()
+                + caches.static_signatures.len(),
+            docstrings: caches.docstrings.len(),
+            terms: caches.terms.len(),
+            expr_stages: slot.expr_stage.global.lock().len(),
+            type_checks: slot.type_check.global.lock().len(),
+            lints: slot.lint.global.lock().len(),
+            revisions: rev_cache.manager.slot_count(),
+            interned,
+            interned_bytes,
+        }
+    }
+
     /// Get configured trigger suggest command.
     pub fn trigger_suggest(&self, context: bool) -> Option<Interned<str>> {
         interned_str!(INTERNED, "editor.action.triggerSuggest");
@@ -1111,7 +1185,8 @@ impl SharedContext {
     /// only generated when the document is available.
     pub fn tooltip(&self, source: &Source, cursor: usize) -> Option<Tooltip> {
         let token = &self.analysis.workers.tooltip;
-        token.enter(|| tooltip_(self.world(), source, cursor))
+        let feat = &self.analysis.tooltip_feat;
+        token.enter(|| tooltip_(self.world(), source, cursor, feat))
     }
 
     /// Get the manifest of a package by file id.
@@ -1317,6 +1392,26 @@ impl<K, V> IncrCacheMap<K, V> {
         .clone()
     }
 
+    fn retain(&self, mut f: impl FnMut(&V) -> bool) {
+        self.global.lock().retain(|_, r| f(&r.1));
+        self.prev.lock().clear();
+        self.next.lock().clear();
+    }
+
+    fn revisions(&self) -> Vec<usize> {
+        self.global.lock().iter().map(|entry| entry.0).collect()
+    }
+
+    /// Evicts the results computed at or before the revision.
+    fn evict_before(&self, revision: usize) -> usize {
+        let global = self.global.lock();
+        let len = global.len();
+        global.retain(|_, r| r.0 > revision);
+        self.prev.lock().clear();
+        self.next.lock().clear();
+        len.saturating_sub(global.len())
+    }
+
     fn crawl(&self, revision: usize) -> Self {
         Self {
             revision,
@@ -1347,6 +1442,21 @@ impl<T> CacheMap<T> {
         self.m.clear();
     }
 
+    fn lifetimes(&self) -> impl Iterator<Item = u64> + '_ {
+        self.m.iter().map(|entry| entry.0)
+    }
+
+    /// Evicts the entries last used at or before the lifetime.
+    fn evict_before(&self, lifetime: u64) -> usize {
+        let len = self.m.len();
+        self.m.retain(|_k, v| v.0 > lifetime);
+        len.saturating_sub(self.m.len())
+    }
+
+    fn len(&self) -> usize {
+        self.m.len()
+    }
+
     fn retain(&self, mut f: impl FnMut(&mut (u64, T)) -> bool) {
         self.m.retain(|_k, v| f(v));
     }
@@ -1355,11 +1465,37 @@ impl<T> CacheMap<T> {
 impl<T: Default + Clone> CacheMap<T> {
     fn entry(&self, key: u128, lifetime: u64) -> T {
         let entry = self.m.entry(key);
-        let entry = entry.or_insert_with(|| (lifetime, T::default()));
+        let mut entry = entry.or_insert_with(|| (lifetime, T::default()));
+        // Tracks the last use of the entry for the eviction.
+        entry.0 = entry.0.max(lifetime);
         entry.1.clone()
     }
 }
 
+/// The number of entries in the analysis caches.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisCacheStats {
+    /// The number of cached signatures.
+    pub signatures: usize,
+    /// The number of cached docstrings.
+    pub docstrings: usize,
+    /// The number of cached terms.
+    pub terms: usize,
+    /// The number of cached expression information of modules.
+    pub expr_stages: usize,
+    /// The number of cached type information of modules.
+    pub type_checks: usize,
+    /// The number of cached lint results of modules.
+    pub lints: usize,
+    /// The number of alive analysis revisions.
+    pub revisions: usize,
+    /// The number of alive interned objects.
+    pub interned: usize,
+    /// The estimated size of the alive interned objects, in bytes.
+    pub interned_bytes: usize,
+}
+
 /// Shared workers to limit resource usage
 #[derive(Default)]
 pub struct AnalysisGlobalWorkers {
@@ -1384,6 +1520,48 @@ pub struct AnalysisGlobalCaches {
     terms: CacheMap<(Value, Ty)>,
 }
 
+impl AnalysisGlobalCaches {
+    fn lifetimes(&self) -> Vec<u64> {
+        let mut lifetimes = Vec::new();
+        lifetimes.extend(self.def_signatures.lifetimes());
+        lifetimes.extend(self.static_signatures.lifetimes());
+        lifetimes.extend(self.signatures.lifetimes());
+        lifetimes.extend(self.docstrings.lifetimes());
+        lifetimes.extend(self.terms.lifetimes());
+        lifetimes
+    }
+
+    /// Evicts the entries last used at or before the lifetime.
+    fn evict_before(&self, lifetime: u64) -> usize {
+        self.def_signatures.evict_before(lifetime)
+            + self.static_signatures.evict_before(lifetime)
+            + self.signatures.evict_before(lifetime)
+            + self.docstrings.evict_before(lifetime)
+            + self.terms.evict_before(lifetime)
+    }
+}
+
+/// The number of steps to evict the caches in, from the oldest to the newest.
+const EVICTION_STEPS: usize = 8;
+
+/// Picks the cutoffs to evict the caches progressively from a list of the
+/// lifetimes or revisions of the cached entries. The newest one is excluded so
+/// that the entries in use are kept.
+fn eviction_cutoffs<T: Ord + Copy>(mut stamps: Vec<T>) -> Vec<T> {
+    stamps.sort_unstable();
+    stamps.dedup();
+    stamps.pop();
+
+    let mut cutoffs = (1..=EVICTION_STEPS)
+        .filter_map(|step| {
+            let idx = (stamps.len() * step / EVICTION_STEPS).checked_sub(1)?;
+            stamps.get(idx).copied()
+        })
+        .collect::<Vec<_>>();
+    cutoffs.dedup();
+    cutoffs
+}
+
 /// A local (lsp request spanned) cache for all level of analysis results of a
 /// module.
 ///
@@ -1458,6 +1636,34 @@ impl AnalysisRevCache {
         self.default_slot = Default::default();
     }
 
+    fn revisions(&self) -> Vec<usize> {
+        let slot = &self.default_slot;
+        let mut revisions = slot.expr_stage.revisions();
+        revisions.extend(slot.type_check.revisions());
+        revisions.extend(slot.lint.revisions());
+        revisions
+    }
+
+    /// Evicts the results computed at or before the revision. The revision
+    /// slots are dropped and will be recreated from the retained results.
+    fn evict_before(&mut self, revision: usize) -> usize {
+        self.manager.clear();
+        let slot = &self.default_slot;
+        slot.expr_stage.evict_before(revision)
+            + slot.type_check.evict_before(revision)
+            + slot.lint.evict_before(revision)
+    }
+
+    /// Evicts the results of the files matching the filter. The revision slots
+    /// are dropped and will be recreated from the retained global results.
+    fn clear_by(&mut self, filter: &dyn Fn(TypstFileId) -> bool) {
+        self.manager.clear();
+        let slot = &self.default_slot;
+        slot.expr_stage.retain(|ei| !filter(ei.fid));
+        slot.type_check.retain(|ti| !ti.fid.is_some_and(filter));
+        slot.lint.retain(|li| !filter(li.fid));
+    }
+
     /// Find the last revision slot by revision number.
     fn find_revision(
         &mut self,
@@ -1641,3 +1847,18 @@ impl RateLimiter {
         f()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eviction_cutoffs() {
+        // The newest lifetime is always kept.
+        assert_eq!(eviction_cutoffs(vec![3, 3, 3]), Vec::<u64>::new());
+        assert_eq!(eviction_cutoffs(vec![5, 1, 3, 1]), vec![1, 3]);
+
+        let cutoffs = eviction_cutoffs((0..=32).rev().collect::<Vec<u64>>());
+        assert_eq!(cutoffs, vec![3, 7, 11, 15, 19, 23, 27, 31]);
+    }
+}
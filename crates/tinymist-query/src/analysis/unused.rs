@@ -0,0 +1,213 @@
+//! Finds imported names and let-bindings that are never referenced in a
+//! source file.
+
+use std::collections::HashSet;
+
+use super::prelude::*;
+use crate::syntax::{Decl, Expr, ExprInfo};
+
+/// The comment that silences the unused check for a whole file.
+pub const ALLOW_UNUSED_COMMENT: &str = "// tinymist: allow(unused)";
+
+/// The kind of an unused declaration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnusedKind {
+    /// A name imported from a module, e.g. `x` in `import "a.typ": x`.
+    Import,
+    /// A name bound by a let binding, e.g. `x` in `let x = 1`.
+    Binding,
+}
+
+/// A declaration that is never referenced in its file.
+#[derive(Debug, Clone)]
+pub struct UnusedDecl {
+    /// The unused declaration.
+    pub decl: Interned<Decl>,
+    /// The kind of the declaration.
+    pub kind: UnusedKind,
+    /// The byte range of the bound identifier.
+    pub range: Range<usize>,
+}
+
+/// Finds the imported names and let-bindings that are never referenced in the
+/// file.
+///
+/// Wildcard imports bind no names and are never reported. Unless
+/// `check_exported` is set, names exported by the file, including re-exported
+/// imports, are also skipped, since they may be used by other files.
+pub fn unused_decls(ei: &ExprInfo, check_exported: bool) -> Vec<UnusedDecl> {
+    if is_unused_allowed(&ei.source) {
+        return Vec::new();
+    }
+
+    let mut used = HashSet::new();
+    for (span, r) in ei.resolves.iter() {
+        if *span != r.decl.span() {
+            used.insert(r.decl.clone());
+        }
+        for expr in [&r.root, &r.step].into_iter().flatten() {
+            if let Expr::Decl(decl) = expr {
+                used.insert(decl.clone());
+            }
+        }
+    }
+    // The original names of the renamed imports are never visible in the scope.
+    let renamed = ei
+        .module_items
+        .iter()
+        .filter(|(decl, _)| matches!(decl.as_ref(), Decl::ImportAlias(..)))
+        .map(|(_, item)| item.item_range.clone())
+        .collect::<HashSet<_>>();
+
+    let imports = ei.module_items.iter().filter_map(|(decl, item)| {
+        let is_original = matches!(decl.as_ref(), Decl::Import(..));
+        if is_original && renamed.contains(&item.item_range) {
+            return None;
+        }
+
+        Some(UnusedDecl {
+            decl: decl.clone(),
+            kind: UnusedKind::Import,
+            range: item.binding_range.clone(),
+        })
+    });
+
+    let mut idents = vec![];
+    collect_let_bindings(ei.source.root(), &mut idents);
+    let bindings = idents.into_iter().filter_map(|span| {
+        let decl = ei.resolves.get(&span)?.decl.clone();
+        let range = ei.source.range(span)?;
+        Some(UnusedDecl {
+            decl,
+            kind: UnusedKind::Binding,
+            range,
+        })
+    });
+
+    let mut unused = imports
+        .chain(bindings)
+        .filter(|it| !used.contains(&it.decl))
+        .filter(|it| check_exported || !is_exported(ei, &it.decl))
+        .collect::<Vec<_>>();
+    unused.sort_by_key(|it| it.range.start);
+    unused
+}
+
+/// Whether the declaration is exported from the file. Unlike
+/// [`ExprInfo::is_exported`], this also holds for the re-exported imports.
+fn is_exported(ei: &ExprInfo, decl: &Interned<Decl>) -> bool {
+    ei.is_exported(decl)
+        || matches!(ei.exports.get(decl.name()), Some(Expr::Ref(r)) if r.decl == *decl)
+}
+
+/// Whether the source silences the unused check by [`ALLOW_UNUSED_COMMENT`].
+fn is_unused_allowed(source: &Source) -> bool {
+    source
+        .text()
+        .lines()
+        .any(|line| line.trim() == ALLOW_UNUSED_COMMENT)
+}
+
+/// Collects the spans of the identifiers bound by the let bindings.
+fn collect_let_bindings(node: &SyntaxNode, idents: &mut Vec<Span>) {
+    if let Some(binding) = node.cast::<ast::LetBinding>() {
+        match binding.kind() {
+            ast::LetBindingKind::Closure(name) => idents.push(name.span()),
+            ast::LetBindingKind::Normal(pattern) => collect_pattern(pattern, idents),
+        }
+    }
+
+    for child in node.children() {
+        collect_let_bindings(child, idents);
+    }
+}
+
+fn collect_pattern(pattern: ast::Pattern, idents: &mut Vec<Span>) {
+    match pattern {
+        ast::Pattern::Normal(ast::Expr::Ident(ident)) => idents.push(ident.span()),
+        ast::Pattern::Normal(..) | ast::Pattern::Placeholder(..) => {}
+        ast::Pattern::Parenthesized(paren) => collect_pattern(paren.pattern(), idents),
+        ast::Pattern::Destructuring(destructuring) => {
+            for item in destructuring.items() {
+                match item {
+                    ast::DestructuringItem::Pattern(pattern) => collect_pattern(pattern, idents),
+                    ast::DestructuringItem::Named(named) => {
+                        collect_pattern(named.pattern(), idents)
+                    }
+                    ast::DestructuringItem::Spread(spread) => {
+                        if let Some(ident) = spread.sink_ident() {
+                            idents.push(ident.span());
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn test_unused_decls() {
+        let contents = r#"#import calc: pow, abs as absolute, odd
+#let used = 1
+#let unused = 2
+#let f(x) = x
+#{
+  let (a, b, ..rest) = (1, 2, 3)
+  a
+}
+#used #pow(2, 3)
+#let g() = odd(1)
+"#;
+
+        run_with_sources(contents, |verse: &mut LspUniverse, path| {
+            run_with_ctx(verse, path, &|ctx, path| {
+                let source = ctx.source_by_path(&path).unwrap();
+                let ei = ctx.expr_stage(&source);
+
+                let names = |check_exported| {
+                    unused_decls(&ei, check_exported)
+                        .into_iter()
+                        .map(|it| (it.decl.name().to_string(), it.kind))
+                        .collect::<Vec<_>>()
+                };
+
+                assert_eq!(
+                    names(true),
+                    vec![
+                        ("absolute".to_owned(), UnusedKind::Import),
+                        ("unused".to_owned(), UnusedKind::Binding),
+                        ("f".to_owned(), UnusedKind::Binding),
+                        ("b".to_owned(), UnusedKind::Binding),
+                        ("rest".to_owned(), UnusedKind::Binding),
+                        ("g".to_owned(), UnusedKind::Binding),
+                    ]
+                );
+                assert_eq!(
+                    names(false),
+                    vec![
+                        ("b".to_owned(), UnusedKind::Binding),
+                        ("rest".to_owned(), UnusedKind::Binding),
+                    ]
+                );
+            });
+        });
+    }
+
+    #[test]
+    fn test_allow_unused() {
+        let contents = "// tinymist: allow(unused)\n#let unused = 1\n";
+
+        run_with_sources(contents, |verse: &mut LspUniverse, path| {
+            run_with_ctx(verse, path, &|ctx, path| {
+                let source = ctx.source_by_path(&path).unwrap();
+                let ei = ctx.expr_stage(&source);
+                assert!(unused_decls(&ei, true).is_empty());
+            });
+        });
+    }
+}
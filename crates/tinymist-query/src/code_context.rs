@@ -242,6 +242,7 @@ fn eval_path_expr(
         let mut world = ctx.world().task(tinymist_world::TaskInputs {
             entry: None,
             inputs,
+            locale: None,
         });
         // todo: bad performance
         world.take_db();
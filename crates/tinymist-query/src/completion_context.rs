@@ -0,0 +1,85 @@
+use serde::Serialize;
+
+use crate::analysis::{CompletionContextKind, CompletionCursor};
+use crate::prelude::*;
+
+/// The context of the completions at a position.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionContextInfo {
+    /// The kind of completions offered at the position.
+    pub kind: CompletionContextKind,
+    /// The text being completed, which ends at the position.
+    pub prefix: EcoString,
+    /// The range of the text being completed, which the completions replace.
+    pub range: LspRange,
+}
+
+/// Classifies the completions at the position in the same way as the
+/// completion request, so that external completion engines can reuse the
+/// context detection.
+///
+/// Returns `None` if the file is not a source file or the position is out of
+/// the file.
+pub fn completion_context(
+    ctx: &mut LocalContext,
+    path: &Path,
+    position: LspPosition,
+) -> Option<CompletionContextInfo> {
+    let source = ctx.source_by_path(path).ok()?;
+    let offset = ctx.to_typst_pos_offset(&source, position, 0)?;
+    let cursor = CompletionCursor::new(ctx.shared_(), &source, offset)?;
+
+    Some(CompletionContextInfo {
+        kind: cursor.context_kind(),
+        prefix: cursor.prefix().into(),
+        range: ctx.to_lsp_range(cursor.prefix_start()..offset, &source),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn test_completion_context() {
+        let contents = r#"// path: /main.typ
+#import "@preview/example:0.1.0": item
+#let value = calc.po
+See @intro and $alp$ in a para
+"#;
+
+        run_with_sources(contents, |verse: &mut LspUniverse, path| {
+            run_with_ctx(verse, path, &|ctx, path| {
+                let source = ctx.source_by_path(&path).unwrap();
+                let text = source.text().to_owned();
+
+                let mut context_at = |needle: &str| {
+                    let offset = text.find(needle).unwrap() + needle.len();
+                    let position = ctx.to_lsp_pos(offset, &source);
+                    let info = completion_context(ctx, &path, position).unwrap();
+                    (info.kind, info.prefix.to_string())
+                };
+
+                assert_eq!(
+                    context_at("@preview/exa"),
+                    (CompletionContextKind::Package, "\"@preview/exa".to_owned())
+                );
+                assert_eq!(
+                    context_at("calc.po"),
+                    (CompletionContextKind::Field, "po".to_owned())
+                );
+                assert_eq!(
+                    context_at("@intr"),
+                    (CompletionContextKind::Reference, "intr".to_owned())
+                );
+                assert_eq!(
+                    context_at("$alp"),
+                    (CompletionContextKind::Math, "alp".to_owned())
+                );
+                assert_eq!(context_at("in a pa").0, CompletionContextKind::Markup);
+            });
+        });
+    }
+}
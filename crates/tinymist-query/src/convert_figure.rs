@@ -0,0 +1,207 @@
+use ecow::eco_format;
+use typst::diag::{StrResult, bail};
+
+use crate::prelude::*;
+use crate::selection_range::enclosing_node;
+use crate::workspace_label::workspace_labels;
+
+/// Wraps the image or block in the range into a `figure` with a placeholder
+/// caption and a label, which is unique in the workspace.
+///
+/// The innermost function call, content block, code block, raw block, or
+/// equation enclosing the range is wrapped. Fails if there is no such element
+/// or it is already in a figure.
+pub fn convert_to_figure(
+    ctx: &mut LocalContext,
+    path: &Path,
+    range: LspRange,
+) -> StrResult<WorkspaceEdit> {
+    let source = ctx
+        .source_by_path(path)
+        .map_err(|err| eco_format!("cannot read the file: {err}"))?;
+    let Some(range) = ctx.to_typst_range(range, &source) else {
+        bail!("the range is out of the file");
+    };
+    let range = trim_selection(source.text(), range);
+
+    let root = LinkedNode::new(source.root());
+    let target = enclosing_node(&root, range).and_then(|node| {
+        std::iter::successors(Some(node), |node| node.parent().cloned()).find(is_block)
+    });
+    let Some(target) = target else {
+        bail!("no image or block is found in the selection");
+    };
+    let in_figure = std::iter::successors(Some(&target), |node| node.parent()).any(|node| {
+        node.cast::<ast::FuncCall>().is_some_and(
+            |call| matches!(call.callee(), ast::Expr::Ident(callee) if callee.get() == "figure"),
+        )
+    });
+    if in_figure {
+        bail!("the selection is already in a figure");
+    }
+
+    let in_markup = target.parent_kind() == Some(SyntaxKind::Markup);
+    let text = target.get().clone().into_text();
+    let (start, body) = match target.kind() {
+        SyntaxKind::Raw | SyntaxKind::Equation => (target.offset(), eco_format!("[{text}]")),
+        _ if in_markup && source.text()[..target.offset()].ends_with('#') => {
+            (target.offset() - 1, text)
+        }
+        _ => (target.offset(), text),
+    };
+
+    let label = unique_label(&workspace_labels(ctx), &label_stem(&target));
+    let figure = eco_format!("#figure({body}, caption: [Caption]) <{label}>");
+    let new_text = if in_markup {
+        figure
+    } else {
+        eco_format!("[{figure}]")
+    };
+
+    let uri = ctx
+        .uri_for_id(source.id())
+        .map_err(|err| eco_format!("cannot get the uri of the file: {err}"))?;
+    let edit = TextEdit {
+        range: ctx.to_lsp_range(start..target.range().end, &source),
+        new_text: new_text.into(),
+    };
+    Ok(WorkspaceEdit {
+        changes: Some(HashMap::from_iter([(uri, vec![edit])])),
+        ..Default::default()
+    })
+}
+
+/// Trims the whitespaces and the leading `#` of an embedded expression from
+/// the selection.
+fn trim_selection(text: &str, range: Range<usize>) -> Range<usize> {
+    let selected = &text[range.clone()];
+    let start = range.start + (selected.len() - selected.trim_start().len());
+    let start = if text[start..].starts_with('#') {
+        start + 1
+    } else {
+        start
+    };
+    let end = range.end - (selected.len() - selected.trim_end().len());
+    start..end.max(start)
+}
+
+fn is_block(node: &LinkedNode) -> bool {
+    matches!(
+        node.kind(),
+        SyntaxKind::FuncCall
+            | SyntaxKind::ContentBlock
+            | SyntaxKind::CodeBlock
+            | SyntaxKind::Raw
+            | SyntaxKind::Equation
+    )
+}
+
+/// Names the label after the first image path in the element if any.
+fn label_stem(node: &LinkedNode) -> EcoString {
+    fn image_path(node: &LinkedNode) -> Option<EcoString> {
+        if let Some(call) = node.cast::<ast::FuncCall>()
+            && matches!(call.callee(), ast::Expr::Ident(callee) if callee.get() == "image")
+            && let Some(ast::Arg::Pos(ast::Expr::Str(path))) = call.args().items().next()
+        {
+            return Some(path.get());
+        }
+        node.children().find_map(|child| image_path(&child))
+    }
+
+    let stem = image_path(node).and_then(|path| {
+        let stem = Path::new(path.as_str()).file_stem()?.to_string_lossy();
+        let stem = stem
+            .chars()
+            .map(|c| match c {
+                c if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') => c,
+                _ => '-',
+            })
+            .collect::<EcoString>();
+        (!stem.is_empty()).then_some(stem)
+    });
+    eco_format!("fig:{}", stem.as_deref().unwrap_or("figure"))
+}
+
+/// Suffixes the label with a number if it is already defined.
+fn unique_label(labels: &std::collections::BTreeSet<EcoString>, stem: &str) -> EcoString {
+    let mut label = EcoString::from(stem);
+    let mut idx = 2;
+    while labels.contains(&label) {
+        label = eco_format!("{stem}-{idx}");
+        idx += 1;
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn test_convert_to_figure() {
+        let contents = r#"// path: /chapter.typ
+= Results <fig:plot>
+-----
+// path: /main.typ
+#include "chapter.typ"
+#image("assets/plot.png")
+
+#grid(image("table.svg"), [B])
+
+```rust
+fn main() {}
+```
+
+#figure(image("done.png"), caption: [Done])
+
+Plain text.
+"#;
+
+        run_with_sources(contents, |verse: &mut LspUniverse, path| {
+            run_with_ctx(verse, path, &|ctx, path| {
+                let source = ctx.source_by_path(&path).unwrap();
+                let text = source.text();
+
+                let mut convert = |needle: &str, len: usize| {
+                    let start = text.find(needle).unwrap();
+                    let range = ctx.to_lsp_range(start..start + len, &source);
+                    convert_to_figure(ctx, &path, range).map(|edit| {
+                        let edits = edit.changes.unwrap().into_values().next().unwrap();
+                        let edit = &edits[0];
+                        let replaced = ctx.to_typst_range(edit.range, &source).unwrap();
+                        (text[replaced].to_owned(), edit.new_text.clone())
+                    })
+                };
+
+                assert_eq!(
+                    convert("#image", 0).unwrap(),
+                    (
+                        r#"#image("assets/plot.png")"#.to_owned(),
+                        r#"#figure(image("assets/plot.png"), caption: [Caption]) <fig:plot-2>"#
+                            .to_owned()
+                    )
+                );
+                assert_eq!(
+                    convert("\"table.svg\"", 3).unwrap(),
+                    (
+                        r#"image("table.svg")"#.to_owned(),
+                        r#"[#figure(image("table.svg"), caption: [Caption]) <fig:table>]"#
+                            .to_owned()
+                    )
+                );
+                assert_eq!(
+                    convert("fn main", 2).unwrap().1,
+                    "#figure([```rust\nfn main() {}\n```], caption: [Caption]) <fig:figure>"
+                );
+
+                let err = convert("Plain", 5).unwrap_err();
+                assert!(err.contains("no image or block"), "{err}");
+                let err = convert("#figure", 7).unwrap_err();
+                assert!(err.contains("already in a figure"), "{err}");
+                let err = convert("\"done.png\"", 0).unwrap_err();
+                assert!(err.contains("already in a figure"), "{err}");
+            });
+        });
+    }
+}
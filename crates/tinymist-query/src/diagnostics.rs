@@ -1,10 +1,13 @@
 use std::borrow::Cow;
 
+use lsp_types::DiagnosticTag;
+use tinymist_analysis::syntax::find_import_cycle;
 use tinymist_lint::KnownIssues;
 use tinymist_world::vfs::WorkspaceResolver;
 use typst::syntax::Span;
 
-use crate::{analysis::Analysis, prelude::*};
+use crate::analysis::{Analysis, UnusedKind, unused_decls};
+use crate::prelude::*;
 
 use regex::RegexSet;
 
@@ -28,27 +31,32 @@ fn collect_lint_diagnostics_with_known(
     known_issues: &KnownIssues,
 ) -> EcoVec<TypstDiagnostic> {
     let mut diagnostics = EcoVec::new();
-    for dep in ctx.world().depended_files() {
-        if WorkspaceResolver::is_package_file(dep)
-            || dep
-                .vpath()
-                .as_rooted_path_compat()
-                .extension()
-                .is_none_or(|e| e != "typ")
-        {
-            continue;
-        }
-
-        let Ok(source) = ctx.world().source(dep) else {
-            continue;
-        };
-
+    for source in lint_sources(ctx) {
         diagnostics.extend(ctx.lint(&source, known_issues));
     }
 
     diagnostics
 }
 
+/// Gets the sources of the compilation dependencies to lint, which excludes
+/// package files.
+fn lint_sources(ctx: &LocalContext) -> Vec<Source> {
+    let world = ctx.world();
+    world
+        .depended_files()
+        .into_iter()
+        .filter(|dep| {
+            !WorkspaceResolver::is_package_file(*dep)
+                && dep
+                    .vpath()
+                    .as_rooted_path_compat()
+                    .extension()
+                    .is_some_and(|e| e == "typ")
+        })
+        .filter_map(|dep| world.source(dep).ok())
+        .collect()
+}
+
 /// Converts a list of Typst diagnostics to LSP diagnostics,
 /// with potential refinements on the error messages.
 pub fn convert_diagnostics<'a>(
@@ -90,11 +98,45 @@ impl<'w> DiagWorker<'w> {
         for diag in collect_lint_diagnostics_with_known(self.ctx, known_issues) {
             self.handle(&diag);
         }
+        for source in lint_sources(self.ctx) {
+            self.unused(&source);
+        }
         self.source = source;
 
         self
     }
 
+    /// Reports the unused imports and let-bindings in a source file as hints.
+    /// Names exported by the files other than the main file are not reported,
+    /// since they may be used elsewhere.
+    fn unused(&mut self, source: &Source) -> Option<()> {
+        let ei = self.ctx.expr_stage(source);
+        let is_main = source.id() == self.ctx.world().main();
+        let unused = unused_decls(&ei, is_main);
+        if unused.is_empty() {
+            return Some(());
+        }
+
+        let uri = self.ctx.uri_for_id(source.id()).ok()?;
+        let results = self.results.entry(uri).or_default();
+        for it in unused {
+            let kind = match it.kind {
+                UnusedKind::Import => "import",
+                UnusedKind::Binding => "variable",
+            };
+            results.push(Diagnostic {
+                range: self.ctx.to_lsp_range(it.range, source),
+                severity: Some(DiagnosticSeverity::HINT),
+                message: format!("unused {kind} `{}`", it.decl.name()),
+                source: Some(self.source.to_owned()),
+                tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                ..Default::default()
+            });
+        }
+
+        Some(())
+    }
+
     /// Converts a list of Typst diagnostics to LSP diagnostics.
     pub fn convert_all<'a>(
         mut self,
@@ -145,20 +187,26 @@ impl<'w> DiagWorker<'w> {
         let lsp_range = self.diagnostic_range(&source, span);
 
         let lsp_severity = diagnostic_severity(typst_diagnostic.severity);
-        let lsp_message = diagnostic_message(&typst_diagnostic);
+        let mut lsp_message = diagnostic_message(&typst_diagnostic);
+        let mut related_info: Vec<_> = typst_diagnostic
+            .trace
+            .iter()
+            .flat_map(|tracepoint| self.to_related_info(tracepoint))
+            .collect();
+
+        if typst_diagnostic.message.starts_with("cyclic import")
+            && let Some((message, hops)) = self.import_cycle(&source, span)
+        {
+            lsp_message = message;
+            related_info.extend(hops);
+        }
 
         let diagnostic = Diagnostic {
             range: lsp_range,
             severity: Some(lsp_severity),
             message: lsp_message,
             source: Some(self.source.to_owned()),
-            related_information: (!typst_diagnostic.trace.is_empty()).then(|| {
-                typst_diagnostic
-                    .trace
-                    .iter()
-                    .flat_map(|tracepoint| self.to_related_info(tracepoint))
-                    .collect()
-            }),
+            related_information: (!related_info.is_empty()).then_some(related_info),
             ..Default::default()
         };
 
@@ -186,6 +234,47 @@ impl<'w> DiagWorker<'w> {
         })
     }
 
+    /// Describes the import cycle closed by the import at the span, naming
+    /// every file on the cycle, e.g. `cyclic import: /a.typ → /b.typ →
+    /// /a.typ`, and returns the range of each import as related information.
+    fn import_cycle(
+        &self,
+        source: &Source,
+        span: DiagSpan,
+    ) -> Option<(String, Vec<DiagnosticRelatedInformation>)> {
+        let at = source_range(source, span);
+        let cycle = find_import_cycle(self.ctx.world(), source.id(), at)?;
+        let name = |id: TypstFileId| id.vpath().as_rooted_path_compat().display().to_string();
+
+        let path = iter::once(name(source.id()))
+            .chain(cycle.iter().map(|hop| name(hop.target)))
+            .collect::<Vec<_>>()
+            .join(" → ");
+        let closing = cycle.last()?;
+        let message = format!(
+            "cyclic import: {path}\nHint: remove one of the imports on the cycle, \
+             e.g. the import of `{}` in `{}`, to break it",
+            name(closing.target),
+            name(closing.file),
+        );
+
+        let hops = cycle
+            .iter()
+            .filter_map(|hop| {
+                let hop_source = self.ctx.source_by_id(hop.file).ok()?;
+                Some(DiagnosticRelatedInformation {
+                    location: LspLocation {
+                        uri: self.ctx.uri_for_id(hop.file).ok()?,
+                        range: self.ctx.to_lsp_range(hop.range.clone(), &hop_source),
+                    },
+                    message: format!("`{}` imports `{}`", name(hop.file), name(hop.target)),
+                })
+            })
+            .collect();
+
+        Some((message, hops))
+    }
+
     fn diagnostic_span_id(&self, typst_diagnostic: &TypstDiagnostic) -> (TypstFileId, DiagSpan) {
         iter::once(typst_diagnostic.span)
             .chain(typst_diagnostic.trace.iter().map(|trace| trace.span.into()))
@@ -271,3 +360,53 @@ impl DiagnosticRefiner for OutOfRootHintRefiner {
         raw.with_hint("Cannot read file outside of project root.")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tinymist_analysis::syntax::source_imports;
+
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn test_import_cycle() {
+        let contents = r#"/// path: b.typ
+#import "c.typ"
+-----
+/// path: c.typ
+#import "b.typ"
+-----
+#import "b.typ"
+"#;
+
+        run_with_sources(contents, |verse: &mut LspUniverse, path| {
+            run_with_ctx(verse, path, &|ctx, path| {
+                let main = ctx.source_by_path(&path).unwrap();
+                let b = source_imports(ctx.world(), &main)[0].target;
+                let cycle = find_import_cycle(ctx.world(), b, None).unwrap();
+                assert_eq!(cycle.len(), 2);
+                assert_eq!(cycle[0].file, b);
+                assert_eq!(cycle[0].target, cycle[1].file);
+                assert_eq!(cycle[1].target, b);
+                assert!(find_import_cycle(ctx.world(), main.id(), None).is_none());
+
+                let b_source = ctx.source_by_id(b).unwrap();
+                let span = LinkedNode::new(b_source.root())
+                    .leaf_at_compat(cycle[0].range.start + 1)
+                    .unwrap()
+                    .span();
+                let diag = TypstDiagnostic::error(span, "cyclic import");
+                let results = DiagWorker::new(ctx).convert_all([&diag]);
+                let diagnostic = &results.values().next().unwrap()[0];
+                assert!(
+                    diagnostic
+                        .message
+                        .starts_with("cyclic import: /b.typ → /c.typ → /b.typ\n"),
+                    "{}",
+                    diagnostic.message
+                );
+                assert_eq!(diagnostic.related_information.as_ref().unwrap().len(), 2);
+            });
+        });
+    }
+}
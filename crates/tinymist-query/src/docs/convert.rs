@@ -59,6 +59,7 @@ pub(crate) fn convert_docs(
     let mut w = ctx.world().task(TaskInputs {
         entry: Some(entry),
         inputs: None,
+        locale: None,
     });
 
     let content = prepare_docs_content(content);
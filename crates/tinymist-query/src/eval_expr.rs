@@ -0,0 +1,164 @@
+use comemo::Track;
+use ecow::eco_format;
+use tokio_util::sync::CancellationToken;
+use typst::Library;
+use typst::diag::{FileError, FileResult, SourceDiagnostic, StrResult};
+use typst::engine::Sink;
+use typst::foundations::{Bytes, Context, Datetime, Duration, Repr};
+use typst::introspection::{EmptyIntrospector, Introspector};
+use typst::routines::SpanMode;
+use typst::syntax::{Span, SyntaxMode};
+use typst::text::{Font, FontBook};
+use typst::utils::LazyHash;
+use typst_shim::eval::eval_string;
+
+use crate::prelude::*;
+
+/// Evaluates a code expression in the scope of the module of a file, i.e. with
+/// the definitions of the file, and returns the repr of the result.
+///
+/// The document is queried by the expression if it has been compiled
+/// successfully, e.g. by `query(heading)`, and it is empty otherwise.
+///
+/// Once the `cancel` token is cancelled, the evaluation fails at its next
+/// access to the world, i.e. to a file or a font. A computation without such
+/// accesses runs to the end, which is bounded by the iteration and call depth
+/// limits of the evaluator.
+pub fn eval_expr(
+    ctx: &mut LocalContext,
+    path: &Path,
+    expr: &str,
+    cancel: &CancellationToken,
+) -> StrResult<EcoString> {
+    let fid = ctx
+        .file_id_by_path(path)
+        .map_err(|err| eco_format!("cannot find the file: {err}"))?;
+    let module = ctx
+        .module_by_id(fid)
+        .map_err(|errors| describe_errors("failed to evaluate the file", &errors))?;
+
+    let empty = EmptyIntrospector;
+    let introspector = match ctx.success_doc() {
+        Some(doc) => doc.introspector(),
+        None => &empty as &dyn Introspector,
+    };
+
+    let world = CancellableWorld {
+        world: ctx.world(),
+        cancel,
+    };
+    let world: &dyn World = &world;
+    let value = eval_string(
+        world.track(),
+        world.library(),
+        Sink::new().track_mut(),
+        introspector.track(),
+        Context::none().track(),
+        expr,
+        SpanMode::Uniform(Span::detached()),
+        SyntaxMode::Code,
+        module.scope().clone(),
+    )
+    .map_err(|errors| describe_errors("failed to evaluate the expression", &errors))?;
+
+    Ok(value.repr())
+}
+
+/// A world that refuses the accesses to files and fonts once the evaluation is
+/// cancelled, which aborts the evaluation.
+struct CancellableWorld<'a> {
+    world: &'a dyn World,
+    cancel: &'a CancellationToken,
+}
+
+impl CancellableWorld<'_> {
+    fn check_cancelled(&self) -> FileResult<()> {
+        if self.cancel.is_cancelled() {
+            return Err(FileError::Other(Some("the evaluation is cancelled".into())));
+        }
+        Ok(())
+    }
+}
+
+impl World for CancellableWorld<'_> {
+    fn library(&self) -> &LazyHash<Library> {
+        self.world.library()
+    }
+
+    fn book(&self) -> &LazyHash<FontBook> {
+        self.world.book()
+    }
+
+    fn main(&self) -> TypstFileId {
+        self.world.main()
+    }
+
+    fn source(&self, id: TypstFileId) -> FileResult<Source> {
+        self.check_cancelled()?;
+        self.world.source(id)
+    }
+
+    fn file(&self, id: TypstFileId) -> FileResult<Bytes> {
+        self.check_cancelled()?;
+        self.world.file(id)
+    }
+
+    fn font(&self, index: usize) -> Option<Font> {
+        self.check_cancelled().ok()?;
+        self.world.font(index)
+    }
+
+    fn today(&self, offset: Option<Duration>) -> Option<Datetime> {
+        self.world.today(offset)
+    }
+}
+
+fn describe_errors(message: &str, errors: &[SourceDiagnostic]) -> EcoString {
+    let mut message = EcoString::from(message);
+    for (idx, error) in errors.iter().enumerate() {
+        message.push_str(if idx == 0 { ": " } else { ", " });
+        message.push_str(&error.message);
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn test_eval_expr() {
+        let contents = r#"// path: /lib.typ
+#let base = 40
+-----
+// path: /main.typ
+#import "lib.typ": base
+#let answer(x) = base + x
+= Heading
+"#;
+
+        run_with_sources(contents, |verse: &mut LspUniverse, path| {
+            run_with_ctx(verse, path, &|ctx, path| {
+                let cancel = CancellationToken::new();
+                assert_eq!(eval_expr(ctx, &path, "answer(2)", &cancel).unwrap(), "42");
+                assert_eq!(
+                    eval_expr(ctx, &path, "(base, calc.pow(2, 3))", &cancel).unwrap(),
+                    "(40, 8)"
+                );
+
+                let err = eval_expr(ctx, &path, "unknown", &cancel).unwrap_err();
+                assert!(err.contains("unknown variable"), "{err}");
+                let err = eval_expr(ctx, &path, "answer(", &cancel).unwrap_err();
+                assert!(
+                    err.starts_with("failed to evaluate the expression"),
+                    "{err}"
+                );
+
+                cancel.cancel();
+                let err = eval_expr(ctx, &path, "read(\"lib.typ\")", &cancel).unwrap_err();
+                assert!(err.contains("the evaluation is cancelled"), "{err}");
+            });
+        });
+    }
+}
@@ -0,0 +1,241 @@
+use serde::Serialize;
+
+use crate::prelude::*;
+use crate::syntax::{DeclExpr, ExprInfo};
+
+/// The kind of a definition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DefinitionKind {
+    /// A function, i.e. `let f(x) = ..` or `let f = (x) => ..`.
+    Function,
+    /// A variable, i.e. `let x = ..` or a binding in a destructuring pattern.
+    Variable,
+    /// A name imported from a module, i.e. `import "mod.typ": x`.
+    Import,
+    /// A module, i.e. `import "mod.typ"` or `import "mod.typ" as m`.
+    Module,
+}
+
+/// A definition in a file.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefinitionInfo {
+    /// The defined name.
+    pub name: EcoString,
+    /// The kind of the definition.
+    pub kind: DefinitionKind,
+    /// The range of the name, or of the `*` for a wildcard import.
+    pub range: LspRange,
+    /// Whether the definition is in the scope of the module, i.e. not in a
+    /// code block, content block, or function.
+    pub top_level: bool,
+    /// The documentation comments before the definition.
+    pub docs: Option<EcoString>,
+}
+
+/// Lists the `let` definitions and the imported names of a file, including the
+/// nested ones, in the order of their appearances. A shadowed definition is
+/// listed as well as the one shadowing it.
+///
+/// Returns `None` if the file is not a source file.
+pub fn get_definitions(ctx: &mut LocalContext, path: &Path) -> Option<Vec<DefinitionInfo>> {
+    let source = ctx.source_by_path(path).ok()?;
+    let ei = ctx.expr_stage(&source);
+
+    let mut worker = DefinitionWorker {
+        ctx,
+        source: &source,
+        ei,
+        definitions: vec![],
+    };
+    worker.collect(&LinkedNode::new(source.root()), true);
+    Some(worker.definitions)
+}
+
+struct DefinitionWorker<'a, 'w> {
+    ctx: &'a mut LocalContext<'w>,
+    source: &'a Source,
+    ei: ExprInfo,
+    definitions: Vec<DefinitionInfo>,
+}
+
+impl DefinitionWorker<'_, '_> {
+    fn collect(&mut self, node: &LinkedNode, top_level: bool) {
+        if let Some(binding) = node.cast::<ast::LetBinding>() {
+            self.let_binding(binding, top_level);
+        } else if let Some(import) = node.cast::<ast::ModuleImport>() {
+            self.module_import(node, import, top_level);
+        }
+
+        let top_level = top_level
+            && !matches!(
+                node.kind(),
+                SyntaxKind::CodeBlock | SyntaxKind::ContentBlock | SyntaxKind::Closure
+            );
+        for child in node.children() {
+            self.collect(&child, top_level);
+        }
+    }
+
+    fn let_binding(&mut self, binding: ast::LetBinding, top_level: bool) {
+        match binding.kind() {
+            ast::LetBindingKind::Closure(name) => {
+                let docs = self.docs(Decl::func(name).into());
+                self.push(name, DefinitionKind::Function, top_level, docs);
+            }
+            ast::LetBindingKind::Normal(pattern) => {
+                let docs = self.docs(Decl::pattern(pattern.span()).into());
+                let is_func = matches!(pattern, ast::Pattern::Normal(ast::Expr::Ident(..)))
+                    && matches!(binding.init(), Some(ast::Expr::Closure(..)));
+                let kind = if is_func {
+                    DefinitionKind::Function
+                } else {
+                    DefinitionKind::Variable
+                };
+                for name in pattern.bindings() {
+                    self.push(name, kind, top_level, docs.clone());
+                }
+            }
+        }
+    }
+
+    fn module_import(&mut self, node: &LinkedNode, import: ast::ModuleImport, top_level: bool) {
+        match import.imports() {
+            Some(ast::Imports::Items(items)) => {
+                for item in items.iter() {
+                    self.push(item.bound_name(), DefinitionKind::Import, top_level, None);
+                }
+            }
+            Some(ast::Imports::Wildcard) => {
+                let star = node
+                    .children()
+                    .find(|child| child.kind() == SyntaxKind::Star);
+                let module = self.ctx.module_by_syntax(import.source().to_untyped());
+                if let Some((star, scope)) = star.zip(module.as_ref().and_then(Value::scope)) {
+                    let range = self.ctx.to_lsp_range(star.range(), self.source);
+                    let names = scope.iter().map(|(name, _)| name.clone()).sorted();
+                    for name in names {
+                        self.definitions.push(DefinitionInfo {
+                            name: name.as_str().into(),
+                            kind: DefinitionKind::Import,
+                            range,
+                            top_level,
+                            docs: None,
+                        });
+                    }
+                }
+            }
+            None => {}
+        }
+
+        if let Some(new_name) = import.new_name() {
+            self.push(new_name, DefinitionKind::Module, top_level, None);
+        } else if import.imports().is_none()
+            && let ast::Expr::Str(path) = import.source()
+        {
+            // A bare import binds the stem of the path or the name of the
+            // package.
+            let path = path.get();
+            let name = match path.strip_prefix('@') {
+                Some(spec) => spec.split([':', '/']).nth(1).map(EcoString::from),
+                None => Path::new(path.as_str())
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into()),
+            };
+            if let Some(name) = name {
+                self.definitions.push(DefinitionInfo {
+                    name,
+                    kind: DefinitionKind::Module,
+                    range: self.ctx.to_lsp_range(
+                        self.source
+                            .range(import.source().span())
+                            .unwrap_or_default(),
+                        self.source,
+                    ),
+                    top_level,
+                    docs: None,
+                });
+            }
+        }
+    }
+
+    fn docs(&self, decl: DeclExpr) -> Option<EcoString> {
+        self.ei.docstrings.get(&decl)?.docs.clone()
+    }
+
+    fn push(
+        &mut self,
+        name: ast::Ident,
+        kind: DefinitionKind,
+        top_level: bool,
+        docs: Option<EcoString>,
+    ) {
+        let Some(range) = self.source.range(name.span()) else {
+            return;
+        };
+        self.definitions.push(DefinitionInfo {
+            name: name.get().clone(),
+            kind,
+            range: self.ctx.to_lsp_range(range, self.source),
+            top_level,
+            docs,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn test_get_definitions() {
+        let contents = r#"// path: /lib.typ
+#let f(x) = x
+#let g = 2
+-----
+// path: /main.typ
+#import "lib.typ": f as h
+#import "lib.typ": *
+#import "lib.typ" as l
+
+/// Adds one.
+#let add(x) = {
+  let y = x + 1
+  y
+}
+#let (a, b) = (1, 2)
+#let inc = (x) => x + 1
+#let a = 3
+"#;
+
+        run_with_sources(contents, |verse: &mut LspUniverse, path| {
+            run_with_ctx(verse, path, &|ctx, path| {
+                let definitions = get_definitions(ctx, &path).unwrap();
+                let summary = definitions
+                    .iter()
+                    .map(|def| (def.name.as_str(), def.kind, def.top_level))
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    summary,
+                    vec![
+                        ("h", DefinitionKind::Import, true),
+                        ("f", DefinitionKind::Import, true),
+                        ("g", DefinitionKind::Import, true),
+                        ("l", DefinitionKind::Module, true),
+                        ("add", DefinitionKind::Function, true),
+                        ("y", DefinitionKind::Variable, false),
+                        ("a", DefinitionKind::Variable, true),
+                        ("b", DefinitionKind::Variable, true),
+                        ("inc", DefinitionKind::Function, true),
+                        ("a", DefinitionKind::Variable, true),
+                    ]
+                );
+
+                let add = definitions.iter().find(|def| def.name == "add").unwrap();
+                assert_eq!(add.docs.as_deref().map(str::trim), Some("Adds one."));
+            });
+        });
+    }
+}
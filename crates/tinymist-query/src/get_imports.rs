@@ -0,0 +1,276 @@
+use ecow::eco_format;
+use serde::Serialize;
+use typst::syntax::package::PackageSpec;
+
+use crate::prelude::*;
+use crate::syntax::resolve_id_by_path;
+
+/// The kind of a statement that loads another module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportKind {
+    /// An `import` statement.
+    Import,
+    /// An `include` statement.
+    Include,
+}
+
+/// An import or include statement of a file with its resolved target.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportInfo {
+    /// The kind of the statement.
+    pub kind: ImportKind,
+    /// The range of the statement.
+    pub range: LspRange,
+    /// The imported path or package spec if the source is a string literal,
+    /// otherwise the text of the source expression.
+    pub source: EcoString,
+    /// The URI of the resolved file, which is the entrypoint for a package.
+    pub uri: Option<Url>,
+    /// The spec of the imported package.
+    pub package: Option<EcoString>,
+    /// The names brought into scope by the statement.
+    pub names: Vec<EcoString>,
+    /// Whether all the names of the module are imported by a wildcard.
+    pub wildcard: bool,
+    /// The reason why the target cannot be resolved.
+    pub error: Option<EcoString>,
+}
+
+/// Gets all the import and include statements of a file with their resolved
+/// targets, in the order of their appearances. Unresolved statements are kept
+/// with an error instead of being omitted.
+///
+/// Returns `None` if the file is not a source file.
+pub fn get_imports(ctx: &mut LocalContext, path: &Path) -> Option<Vec<ImportInfo>> {
+    let source = ctx.source_by_path(path).ok()?;
+
+    let mut imports = vec![];
+    collect_imports(ctx, &source, &LinkedNode::new(source.root()), &mut imports);
+    Some(imports)
+}
+
+fn collect_imports(
+    ctx: &mut LocalContext,
+    source: &Source,
+    node: &LinkedNode,
+    imports: &mut Vec<ImportInfo>,
+) {
+    if let Some(import) = node.cast::<ast::ModuleImport>() {
+        imports.push(import_info(ctx, source, node, ImportKind::Import, import));
+    } else if let Some(include) = node.cast::<ast::ModuleInclude>() {
+        let mut info = resolve_source(ctx, source, node, ImportKind::Include, include.source());
+        info.names.clear();
+        imports.push(info);
+    }
+
+    for child in node.children() {
+        collect_imports(ctx, source, &child, imports);
+    }
+}
+
+fn import_info(
+    ctx: &mut LocalContext,
+    source: &Source,
+    node: &LinkedNode,
+    kind: ImportKind,
+    import: ast::ModuleImport,
+) -> ImportInfo {
+    let mut info = resolve_source(ctx, source, node, kind, import.source());
+
+    let bare_name = info.names.pop();
+    match import.imports() {
+        Some(ast::Imports::Items(items)) => {
+            info.names
+                .extend(items.iter().map(|item| item.bound_name().get().clone()));
+        }
+        Some(ast::Imports::Wildcard) => {
+            info.wildcard = true;
+            let module = info
+                .error
+                .is_none()
+                .then(|| ctx.module_by_syntax(import.source().to_untyped()))
+                .flatten();
+            if let Some(scope) = module.as_ref().and_then(Value::scope) {
+                let names = scope.iter().map(|(name, _)| name.as_str().into());
+                info.names.extend(names.sorted());
+            }
+        }
+        None => {}
+    }
+
+    if let Some(new_name) = import.new_name() {
+        info.names.push(new_name.get().clone());
+    } else if import.imports().is_none() {
+        info.names.extend(bare_name);
+    }
+
+    info
+}
+
+/// Resolves the source of a statement. The name that a bare import binds is
+/// left in `names` if there is one.
+fn resolve_source(
+    ctx: &mut LocalContext,
+    source: &Source,
+    node: &LinkedNode,
+    kind: ImportKind,
+    src: ast::Expr,
+) -> ImportInfo {
+    let mut info = ImportInfo {
+        kind,
+        range: ctx.to_lsp_range(node.range(), source),
+        source: src.to_untyped().clone().into_text(),
+        uri: None,
+        package: None,
+        names: vec![],
+        wildcard: false,
+        error: None,
+    };
+
+    let target = if let ast::Expr::Str(path) = src {
+        let path = path.get();
+        info.source = path.clone();
+
+        if path.starts_with('@') {
+            match path.parse::<PackageSpec>() {
+                Ok(spec) => {
+                    info.package = Some(eco_format!("{spec}"));
+                    info.names.push(spec.name.clone());
+                }
+                Err(err) => {
+                    info.error = Some(eco_format!("invalid package spec: {err}"));
+                    return info;
+                }
+            }
+        } else if let Some(stem) = Path::new(path.as_str()).file_stem() {
+            info.names.push(stem.to_string_lossy().into());
+        }
+
+        match resolve_id_by_path(ctx.world(), source.id(), path.as_str()) {
+            Some(id) => Ok(Some(id)),
+            None if info.package.is_some() => Err("cannot load the package".into()),
+            None => Err("cannot resolve the path".into()),
+        }
+    } else {
+        match ctx.module_by_syntax(src.to_untyped()) {
+            Some(Value::Module(module)) => Ok(module.file_id()),
+            Some(value) if value.scope().is_some() => Ok(None),
+            _ => Err("cannot resolve the source to a module".into()),
+        }
+    };
+
+    match target {
+        Ok(Some(fid)) => {
+            info.uri = ctx.uri_for_id(fid).ok();
+            if let Err(err) = ctx.source_by_id(fid) {
+                info.error = Some(eco_format!("{err}"));
+            }
+        }
+        Ok(None) => {}
+        Err(err) => info.error = Some(err),
+    }
+
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn test_get_imports() {
+        let contents = r#"// path: /lib.typ
+#let f(x) = x + 1
+#let g = 2
+-----
+// path: /chapter.typ
+= Chapter
+-----
+// path: /main.typ
+#import "lib.typ": f, g as h
+#import "lib.typ" as l
+#import "lib.typ": *
+#import "lib.typ"
+#import "missing.typ": x
+#include "chapter.typ"
+"#;
+
+        run_with_sources(contents, |verse: &mut LspUniverse, path| {
+            run_with_ctx(verse, path, &|ctx, path| {
+                let imports = get_imports(ctx, &path).unwrap();
+                let summary = imports
+                    .iter()
+                    .map(|it| {
+                        let uri = it.uri.as_ref().map(|uri| uri.path().rsplit('/').next());
+                        (
+                            it.kind,
+                            it.source.as_str(),
+                            uri.flatten().map(str::to_owned),
+                            it.names.iter().map(EcoString::as_str).collect::<Vec<_>>(),
+                            it.wildcard,
+                            it.error.is_some(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                let lib = || Some("lib.typ".to_owned());
+                assert_eq!(
+                    summary,
+                    vec![
+                        (
+                            ImportKind::Import,
+                            "lib.typ",
+                            lib(),
+                            vec!["f", "h"],
+                            false,
+                            false
+                        ),
+                        (
+                            ImportKind::Import,
+                            "lib.typ",
+                            lib(),
+                            vec!["l"],
+                            false,
+                            false
+                        ),
+                        (
+                            ImportKind::Import,
+                            "lib.typ",
+                            lib(),
+                            vec!["f", "g"],
+                            true,
+                            false
+                        ),
+                        (
+                            ImportKind::Import,
+                            "lib.typ",
+                            lib(),
+                            vec!["lib"],
+                            false,
+                            false
+                        ),
+                        (
+                            ImportKind::Import,
+                            "missing.typ",
+                            Some("missing.typ".to_owned()),
+                            vec!["x"],
+                            false,
+                            true
+                        ),
+                        (
+                            ImportKind::Include,
+                            "chapter.typ",
+                            Some("chapter.typ".to_owned()),
+                            vec![],
+                            false,
+                            false
+                        ),
+                    ]
+                );
+            });
+        });
+    }
+}
@@ -6,7 +6,7 @@ use tinymist_world::package::{PackageSpec, PackageSpecExt};
 use typst::foundations::repr::separated_list;
 use typst_shim::syntax::LinkedNodeExt;
 
-use crate::analysis::get_link_exprs_in;
+use crate::analysis::{analyze_indexed_call, get_link_exprs_in};
 use crate::bib::{RenderedBibCitation, render_citation_string};
 use crate::jump_from_cursor;
 use crate::package::parse_package_import;
@@ -180,6 +180,7 @@ impl HoverWorker<'_> {
         let leaf = LinkedNode::new(source.root()).leaf_at_compat(self.cursor)?;
 
         self.package_import(&leaf);
+        self.indexed(&leaf);
         self.definition(&leaf)
             .or_else(|| self.star(&leaf))
             .or_else(|| self.link(&leaf))
@@ -294,6 +295,21 @@ impl HoverWorker<'_> {
         Some(())
     }
 
+    /// Item type of an `at` call on an array or dictionary
+    fn indexed(&mut self, node: &LinkedNode) -> Option<()> {
+        let access = node.parent()?;
+        if access.cast::<ast::FieldAccess>()?.field().span() != node.span() {
+            return None;
+        }
+        let call = access.parent()?.cast::<ast::FuncCall>()?;
+
+        let ty = analyze_indexed_call(self.ctx, call)?;
+        let ty = ty.describe()?;
+        self.value
+            .push(format!("### Item Type\n```typc\n{ty}\n```"));
+        Some(())
+    }
+
     fn package_import(&mut self, node: &LinkedNode) -> Option<()> {
         let package_spec = parse_package_import(node)?;
         self.def
@@ -572,10 +588,10 @@ fn push_result_ty(
     let _ = write!(type_doc, " = {short}");
 }
 
-struct ExternalDocLink;
+pub(crate) struct ExternalDocLink;
 
 impl ExternalDocLink {
-    fn get(def: &Definition) -> Option<CommandLink> {
+    pub(crate) fn get(def: &Definition) -> Option<CommandLink> {
         let value = def.value();
 
         if matches!(value, Some(Value::Func(..)))
@@ -620,7 +636,7 @@ impl ExternalDocLink {
     }
 }
 
-struct CommandLink {
+pub(crate) struct CommandLink {
     title: Option<String>,
     command_or_links: Vec<CommandOrLink>,
 }
@@ -634,7 +650,7 @@ impl fmt::Display for CommandLink {
     }
 }
 
-enum CommandOrLink {
+pub(crate) enum CommandOrLink {
     Link(String),
     Command { id: String, args: Vec<JsonValue> },
 }
@@ -684,6 +700,49 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_indexed_item_type() {
+        let hover = |code: &str| {
+            // The cursor is on the `at` of the call.
+            let contents =
+                format!("// path: /main.typ\n#let d = (a: 1, b: \"x\")\n/* loc 1, 5 */\n{code}\n");
+            run_with_sources(&contents, |verse: &mut LspUniverse, path| {
+                run_with_ctx(verse, path, &|ctx, path| {
+                    let source = ctx.source_by_path(&path).unwrap();
+                    let request = HoverRequest {
+                        path: path.clone(),
+                        position: find_test_position(&source),
+                    };
+                    HoverDisplay(request.request(ctx).as_ref()).to_string()
+                })
+            })
+        };
+
+        let content = hover("#d.at(\"b\")");
+        assert!(content.contains("### Item Type\n```typc\n"), "{content}");
+        let content = hover("#d.at(\"c\")");
+        assert!(!content.contains("### Item Type"), "{content}");
+    }
+
+    #[test]
+    fn test_folded_tooltip() {
+        let contents = "// path: /main.typ\n#(10 + 20)\n";
+
+        run_with_sources(contents, |verse: &mut LspUniverse, path| {
+            run_with_ctx(verse, path, &|ctx, path| {
+                let source = ctx.source_by_path(&path).unwrap();
+                let tooltip = |cursor| match ctx.tooltip(&source, cursor) {
+                    Some(Tooltip::Code(code)) => Some(code),
+                    _ => None,
+                };
+
+                // The folded value is shown above the sampled values.
+                assert_eq!(tooltip(2).as_deref(), Some("= 30\n30"));
+                assert_eq!(tooltip(3).as_deref(), Some("= 30"));
+            });
+        });
+    }
+
     struct HoverDisplay<'a>(Option<&'a Hover>);
 
     impl fmt::Display for HoverDisplay<'_> {
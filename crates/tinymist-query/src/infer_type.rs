@@ -0,0 +1,64 @@
+use crate::prelude::*;
+
+/// Infers the type of the expression under the cursor, which is described in
+/// the same way as the signature help. This exposes the otherwise internal
+/// inference for debugging the analyzer.
+///
+/// Returns `None` if there is no expression at the position or no type is
+/// inferred for it. If the innermost expression has no inferred type, the
+/// enclosing expressions are tried.
+pub fn infer_type(ctx: &mut LocalContext, path: &Path, position: LspPosition) -> Option<EcoString> {
+    let source = ctx.source_by_path(path).ok()?;
+    let cursor = ctx.to_typst_pos(position, &source)? + 1;
+    let leaf = LinkedNode::new(source.root()).leaf_at_compat(cursor)?;
+
+    let mut node = leaf;
+    while !node.is::<ast::Expr>() {
+        node = node.parent()?.clone();
+    }
+
+    loop {
+        if let Some(ty) = ctx.type_of_span(node.span()) {
+            return Some(ty.describe().unwrap_or_else(|| "any".into()));
+        }
+
+        node = node.parent()?.clone();
+        if !node.is::<ast::Expr>() {
+            return None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn test_infer_type() {
+        let contents = r#"// path: /main.typ
+#let x = 1
+#(x + 2)
+Some text
+"#;
+
+        run_with_sources(contents, |verse: &mut LspUniverse, path| {
+            run_with_ctx(verse, path, &|ctx, path| {
+                let source = ctx.source_by_path(&path).unwrap();
+                let text = source.text();
+
+                let ty_at = |ctx: &mut LocalContext, offset: usize| {
+                    let position = to_lsp_position(offset, PositionEncoding::Utf16, &source);
+                    infer_type(ctx, &path, position)
+                };
+
+                let x = text.find("#(x").unwrap() + 2;
+                let ty = ty_at(ctx, x);
+                assert!(ty.is_some_and(|ty| ty.contains("int") || ty == "1"));
+
+                let text_pos = text.find("Some text").unwrap() + 2;
+                assert_eq!(ty_at(ctx, text_pos), None);
+            });
+        });
+    }
+}
@@ -9,24 +9,30 @@
 
 pub use analysis::{CompletionFeat, LocalContext, LocalContextGuard, LspWorldExt};
 pub use completion::{CompletionRequest, PostfixSnippet};
+pub use completion_context::*;
 pub use typlite::ColorTheme;
-pub use upstream::with_vm;
+pub use upstream::{TooltipFeat, with_vm};
 
 pub use check::*;
 pub use code_action::*;
 pub use code_context::*;
 pub use code_lens::*;
 pub use color_presentation::*;
+pub use convert_figure::*;
 pub use diagnostics::*;
 pub use document_color::*;
 pub use document_highlight::*;
 pub use document_link::*;
 pub use document_metrics::*;
 pub use document_symbol::*;
+pub use eval_expr::*;
 pub use folding_range::*;
+pub use get_definitions::*;
+pub use get_imports::*;
 pub use goto_declaration::*;
 pub use goto_definition::*;
 pub use hover::*;
+pub use infer_type::*;
 pub use inlay_hint::*;
 pub use jump::*;
 pub use lsp_typst_boundary::*;
@@ -34,11 +40,13 @@ pub use on_enter::*;
 pub use prepare_rename::*;
 pub use references::*;
 pub use rename::*;
+pub use resolve_definition::*;
 pub use selection_range::*;
 pub use semantic_tokens_delta::*;
 pub use semantic_tokens_full::*;
 pub use signature_help::*;
 pub use symbol::*;
+pub use warm_cache::*;
 pub use will_rename_files::*;
 pub use workspace_label::*;
 
@@ -64,27 +72,35 @@ mod code_context;
 mod code_lens;
 mod color_presentation;
 mod completion;
+mod completion_context;
+mod convert_figure;
 mod diagnostics;
 mod document_color;
 mod document_highlight;
 mod document_link;
 mod document_metrics;
 mod document_symbol;
+mod eval_expr;
 mod folding_range;
+mod get_definitions;
+mod get_imports;
 mod goto_declaration;
 mod goto_definition;
 mod hover;
+mod infer_type;
 mod inlay_hint;
 mod jump;
 mod on_enter;
 mod prepare_rename;
 mod references;
 mod rename;
+mod resolve_definition;
 mod selection_range;
 mod semantic_tokens_delta;
 mod semantic_tokens_full;
 mod signature_help;
 mod symbol;
+mod warm_cache;
 mod will_rename_files;
 mod workspace_label;
 
@@ -138,6 +154,24 @@ mod polymorphic {
         pub write: bool,
         /// Whether to open the exported file(s) after the export is done.
         pub open: bool,
+        /// Whether to only resolve the output path and the timing decision
+        /// without exporting.
+        pub dry_run: bool,
+        /// The in-memory content of the document, which is exported instead
+        /// of the content on disk.
+        pub content: Option<String>,
+        /// The root directory to resolve absolute paths in the document for
+        /// this export only, instead of the root of the editing session.
+        pub root: Option<PathBuf>,
+        /// The locale overriding the default language and region of the text
+        /// for this export only.
+        pub locale: Option<tinymist_world::Locale>,
+        /// The inputs passed to the document for this export only, i.e.
+        /// `sys.inputs`, instead of the inputs of the editing session.
+        pub inputs: Option<std::sync::Arc<typst::utils::LazyHash<Dict>>>,
+        /// The token to cancel the export, which is checked between the
+        /// pages and the phases of the export.
+        pub cancel: Option<tokio_util::sync::CancellationToken>,
     }
 
     /// A request to run an export markdown task.
@@ -153,18 +187,33 @@ mod polymorphic {
         pub write: bool,
         /// Whether to open the exported file(s) after the export is done.
         pub open: bool,
+        /// The token to cancel the export, which is checked between the
+        /// pages and the phases of the export.
+        pub cancel: Option<tokio_util::sync::CancellationToken>,
     }
 
     /// The response to an export request.
     #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(untagged, rename_all = "camelCase")]
     pub enum OnExportResponse {
+        /// Nothing exported in a dry run.
+        #[serde(rename_all = "camelCase")]
+        DryRun {
+            /// The substituted output path. None if the task has no output.
+            path: Option<PathBuf>,
+            /// Whether the task would run on the current compile signal.
+            needs_run: bool,
+        },
         /// Non-page or a single page exported.
         Single {
             /// The path of the exported file. None if not written to file.
             path: Option<PathBuf>,
             /// The data of the exported file. None if written to file.
             data: Option<String>,
+            /// The PPI that the image is rendered at, if it is lowered to fit
+            /// the maximum size.
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            ppi: Option<f32>,
         },
         /// Multiple pages exported.
         Paged {
@@ -172,6 +221,15 @@ mod polymorphic {
             total_pages: usize,
             /// The exported pages.
             items: Vec<PagedExportResponse>,
+            /// The PPI that the images are rendered at, if it is lowered to
+            /// fit the maximum size.
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            ppi: Option<f32>,
+        },
+        /// Each section exported to its own file.
+        Sections {
+            /// The exported sections in the document order.
+            sections: Vec<SectionExportResponse>,
         },
     }
 
@@ -187,6 +245,18 @@ mod polymorphic {
         pub data: Option<String>,
     }
 
+    /// The response to a section export.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SectionExportResponse {
+        /// The plain text of the heading starting the section.
+        pub title: String,
+        /// The pages of the section, e.g. `3-5`.
+        pub pages: String,
+        /// The path of the exported file.
+        pub path: PathBuf,
+    }
+
     /// A request to format the document.
     #[derive(Debug, Clone)]
     pub struct FormattingRequest {
@@ -206,10 +276,68 @@ mod polymorphic {
         pub root: Option<PathBuf>,
         /// The font paths of the server.
         pub font_paths: Vec<PathBuf>,
+        /// The number of resolved font faces.
+        #[serde(default)]
+        pub font_count: usize,
+        /// Whether the system fonts are enabled.
+        #[serde(default)]
+        pub system_fonts: bool,
+        /// The active package directories, i.e. the data and cache directories.
+        #[serde(default)]
+        pub package_paths: Vec<PathBuf>,
+        /// The directory storing local packages.
+        #[serde(default)]
+        pub local_package_path: Option<PathBuf>,
         /// The inputs of the server.
         pub inputs: Dict,
         /// The statistics of the server.
         pub stats: HashMap<String, String>,
+        /// The build information of the server.
+        #[serde(default)]
+        pub build: Option<BuildInfo>,
+    }
+
+    /// The build information of the server, e.g. the linked typst version.
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BuildInfo {
+        /// The version of tinymist.
+        pub version: String,
+        /// The git describe of the tinymist build, e.g. `v0.15.0-3-gabcdef0`.
+        pub git_describe: Option<String>,
+        /// The commit hash of the tinymist build.
+        pub commit_sha: Option<String>,
+        /// The version of the linked typst.
+        pub typst_version: String,
+        /// The source of the linked typst, e.g. a git repository or registry.
+        pub typst_source: String,
+        /// The target triple of the build.
+        pub target: String,
+        /// The enabled cargo features of the build.
+        pub features: Vec<String>,
+        /// The information formatted to paste into issue reports.
+        pub report: String,
+    }
+
+    impl BuildInfo {
+        /// Formats the information as a markdown list for issue reports.
+        pub fn format_report(&self) -> String {
+            let features = if self.features.is_empty() {
+                "none".to_owned()
+            } else {
+                self.features.join(", ")
+            };
+            format!(
+                "- tinymist: {} ({}, {})\n- typst: {} ({})\n- target: {}\n- features: {}\n",
+                self.version,
+                self.git_describe.as_deref().unwrap_or("unknown"),
+                self.commit_sha.as_deref().unwrap_or("unknown"),
+                self.typst_version,
+                self.typst_source,
+                self.target,
+                features,
+            )
+        }
     }
 
     /// The feature of the fold request.
@@ -242,6 +370,8 @@ mod polymorphic {
         GotoDefinitionSymbol(String),
         /// A request to go to the declaration.
         GotoDeclaration(GotoDeclarationRequest),
+        /// A request to resolve the definition.
+        ResolveDefinition(ResolveDefinitionRequest),
         /// A request to get the references.
         References(ReferencesRequest),
         /// A request to get the inlay hints.
@@ -308,6 +438,7 @@ mod polymorphic {
                 Self::GotoDefinition(..) => PinnedFirst,
                 Self::GotoDefinitionSymbol(..) => PinnedFirst,
                 Self::GotoDeclaration(..) => PinnedFirst,
+                Self::ResolveDefinition(..) => PinnedFirst,
                 Self::References(..) => PinnedFirst,
                 Self::InlayHint(..) => Unique,
                 Self::DocumentColor(..) => PinnedFirst,
@@ -348,6 +479,7 @@ mod polymorphic {
                 Self::GotoDefinition(req) => &req.path,
                 Self::GotoDefinitionSymbol(..) => return None,
                 Self::GotoDeclaration(req) => &req.path,
+                Self::ResolveDefinition(req) => &req.path,
                 Self::References(req) => &req.path,
                 Self::InlayHint(req) => &req.path,
                 Self::DocumentColor(req) => &req.path,
@@ -391,6 +523,8 @@ mod polymorphic {
         GotoDefinition(Option<GotoDefinitionResponse>),
         /// The response to the goto declaration request.
         GotoDeclaration(Option<GotoDeclarationResponse>),
+        /// The response to the resolve definition request.
+        ResolveDefinition(Option<ResolvedDefinition>),
         /// The response to the references request.
         References(Option<Vec<LspLocation>>),
         /// The response to the inlay hint request.
@@ -1,13 +1,15 @@
 //! Package management tools.
 
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::path::PathBuf;
 
-use ecow::eco_format;
+use ecow::{EcoVec, eco_format};
 #[cfg(feature = "local-registry")]
-use ecow::{EcoVec, eco_vec};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 // use reflexo_typst::typst::prelude::*;
 use serde::{Deserialize, Serialize};
+use tinymist_lint::KnownIssues;
 use tinymist_world::package::registry::PackageIndexEntry;
 use tinymist_world::package::{PackageSpec, PackageSpecExt};
 use typst::World;
@@ -16,8 +18,11 @@ use typst::syntax::package::PackageManifest;
 use typst::syntax::{FileId, LinkedNode, RootedPath, SyntaxKind, VirtualPath, VirtualRoot, ast};
 use typst_shim::syntax::resolve_path_from_id;
 
-use crate::LocalContext;
 use crate::analysis::SharedContext;
+use crate::{DiagWorker, DiagnosticsMap, LocalContext};
+
+#[cfg(feature = "local-registry")]
+mod ignore;
 
 /// Information about a package.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +49,45 @@ impl From<PackageIndexEntry> for PackageInfo {
     }
 }
 
+/// The versions of a package.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageGroup {
+    /// The namespace the package lives in.
+    pub namespace: EcoString,
+    /// The name of the package within its namespace.
+    pub name: EcoString,
+    /// The versions of the package, sorted in descending order.
+    pub versions: Vec<PackageInfo>,
+    /// The latest version of the package.
+    pub latest: String,
+}
+
+/// Groups packages by their namespace and name. The groups are sorted by
+/// name, and the versions in each group are sorted in descending order.
+pub fn group_packages(packages: impl IntoIterator<Item = PackageIndexEntry>) -> Vec<PackageGroup> {
+    let mut groups = std::collections::BTreeMap::<_, Vec<PackageIndexEntry>>::new();
+    for entry in packages {
+        let key = (entry.namespace.clone(), entry.package.name.clone());
+        groups.entry(key).or_default().push(entry);
+    }
+
+    groups
+        .into_iter()
+        .map(|((namespace, name), mut entries)| {
+            // The sort is stable to keep the packages in the earlier package paths
+            // first.
+            entries.sort_by(|a, b| b.package.version.cmp(&a.package.version));
+            let latest = entries[0].package.version.to_string();
+            PackageGroup {
+                namespace,
+                name,
+                versions: entries.into_iter().map(PackageInfo::from).collect(),
+                latest,
+            }
+        })
+        .collect()
+}
+
 /// Parses a package import from a string literal node in an import statement.
 /// Returns the PackageSpec if it's a valid package import.
 pub fn parse_package_import(node: &LinkedNode) -> Option<PackageSpec> {
@@ -135,14 +179,57 @@ pub(crate) fn package_entrypoint_id(manifest_id: FileId, entrypoint: &str) -> Fi
 }
 
 /// Check Package.
-pub fn check_package(ctx: &mut LocalContext, spec: &PackageInfo) -> StrResult<()> {
+///
+/// The package's entrypoint is always checked. The `extra_entries` are
+/// additional entry files (relative to the package root), e.g. examples or
+/// tests, which are not necessarily imported by the entrypoint. The lint
+/// diagnostics of all files reached from the entries are aggregated per file.
+/// The check fails if an entry cannot be read.
+pub fn check_package(
+    ctx: &mut LocalContext,
+    spec: &PackageInfo,
+    extra_entries: &[EcoString],
+) -> StrResult<DiagnosticsMap> {
     let toml_id = get_manifest_id(spec)?;
     let manifest = ctx.get_manifest(toml_id)?;
 
-    let entry_point = package_entrypoint_id(toml_id, &manifest.package.entrypoint);
+    let mut entry_points = vec![package_entrypoint_id(toml_id, &manifest.package.entrypoint)];
+    for entry in extra_entries {
+        let entry_point = resolve_path_from_id(toml_id, entry)
+            .map_err(|err| eco_format!("invalid package entry {entry:?} ({})", err.message()))?
+            .intern();
+        entry_points.push(entry_point);
+    }
+
+    for entry_point in entry_points.iter() {
+        ctx.source_by_id(*entry_point).map_err(|err| {
+            let path = entry_point.vpath().get_with_slash();
+            eco_format!("cannot read package entry {path:?} ({err})")
+        })?;
+        ctx.preload_package(*entry_point);
+    }
 
-    ctx.preload_package(entry_point);
-    Ok(())
+    let known_issues = KnownIssues::default();
+    let mut diagnostics = EcoVec::new();
+    let mut checked = HashSet::new();
+    let mut pending = entry_points;
+    while let Some(fid) = pending.pop() {
+        // Only checks the files that belong to the package.
+        if fid.root() != toml_id.root() || !checked.insert(fid) {
+            continue;
+        }
+        let Ok(source) = ctx.source_by_id(fid) else {
+            continue;
+        };
+
+        let exprs = ctx.expr_stage(&source);
+        pending.extend(exprs.imports.keys().copied());
+        diagnostics.extend(ctx.lint(&source, &known_issues));
+    }
+
+    let mut worker = DiagWorker::new(ctx);
+    worker.source = "tinymist-lint";
+    Ok(worker.convert_all(diagnostics.iter()))
 }
 
 /// A filter for packages.
@@ -181,56 +268,64 @@ pub fn list_package(
     // search packages locally. We only search in the data
     // directory and not the cache directory, because the latter is not
     // intended for storage of local packages.
-    let mut packages = eco_vec![];
+    let mut packages = vec![];
 
     let paths = registry.paths();
     log::info!("searching for packages in paths {paths:?}");
 
-    let mut search_in_dir = |local_path: PathBuf, ns: EcoString| {
+    let search_in_dir = |local_path: PathBuf, ns: EcoString| -> Vec<PackageIndexEntry> {
         if !local_path.exists() || !local_path.is_dir_follow_links() {
-            return;
+            return Vec::new();
         }
+        // The `.gitignore` or `.tinymistignore` in the namespace directory may
+        // exclude packages or versions, e.g. `my-pkg/0.1.0`.
+        let ns_ignore = ignore::ignore_rules(&local_path);
         // namespace/package_name/version
         // 2. package_name
         let Some(package_names) = once_log(std::fs::read_dir(local_path), "read local package")
         else {
-            return;
+            return Vec::new();
         };
-        for package in package_names {
-            let Some(package) = once_log(package, "read package name") else {
-                continue;
-            };
+        let packages = package_names.filter_map(|package| {
+            let package = once_log(package, "read package name")?;
             let package_name = EcoString::from(package.file_name().to_string_lossy());
-            if package_name.starts_with('.') {
-                continue;
+            if package_name.starts_with('.') || ns_ignore.is_ignored(&package_name) {
+                return None;
             }
+            Some((package_name, package.path()))
+        });
 
-            let package_path = package.path();
+        // The directories are read in parallel, which is much faster on network
+        // filesystems.
+        let search_package = |(package_name, package_path): (EcoString, PathBuf)| {
             if !package_path.is_dir_follow_links() {
-                continue;
+                return Vec::new();
             }
+            let package_ignore = ignore::ignore_rules(&package_path);
             // 3. version
             let Some(versions) = once_log(std::fs::read_dir(package_path), "read package versions")
             else {
-                continue;
+                return Vec::new();
             };
-            for version in versions {
-                let Some(version_entry) = once_log(version, "read package version") else {
-                    continue;
-                };
-                if version_entry.file_name().to_string_lossy().starts_with('.') {
-                    continue;
+            let versions = versions.filter_map(|version| {
+                let version_entry = once_log(version, "read package version")?;
+                let version_name = version_entry.file_name();
+                let version_name = version_name.to_string_lossy();
+                if version_name.starts_with('.')
+                    || package_ignore.is_ignored(&version_name)
+                    || ns_ignore.is_ignored(&format!("{package_name}/{version_name}"))
+                {
+                    return None;
                 }
-                let package_version_path = version_entry.path();
+                let version = version_name.parse::<typst::syntax::package::PackageVersion>();
+                let version = once_log(version, "parse package version")?;
+                Some((version, version_entry.path()))
+            });
+
+            let search_version = |(version, package_version_path): (_, PathBuf)| {
                 if !package_version_path.is_dir_follow_links() {
-                    continue;
+                    return None;
                 }
-                let Some(version) = once_log(
-                    version_entry.file_name().to_string_lossy().parse(),
-                    "parse package version",
-                ) else {
-                    continue;
-                };
                 let spec = PackageSpec {
                     namespace: ns.clone(),
                     name: package_name.clone(),
@@ -240,27 +335,37 @@ pub fn list_package(
                     typst::syntax::VirtualRoot::Package(spec.clone()),
                     typst::syntax::VirtualPath::new("typst.toml").expect("valid manifest path"),
                 ));
-                let Some(manifest) =
-                    once_log(get_manifest(world, manifest_id), "read package manifest")
-                else {
-                    continue;
-                };
-                packages.push(PackageIndexEntry {
+                let manifest = once_log(get_manifest(world, manifest_id), "read package manifest")?;
+                Some(PackageIndexEntry {
                     namespace: ns.clone(),
                     package: manifest.package,
                     template: manifest.template,
                     updated_at: None,
                     path: Some(package_version_path),
-                });
-            }
-        }
+                })
+            };
+
+            let versions = versions.collect::<Vec<_>>();
+            versions
+                .into_par_iter()
+                .filter_map(search_version)
+                .collect::<Vec<_>>()
+        };
+
+        let packages = packages.collect::<Vec<_>>();
+        in_scan_pool(|| {
+            packages
+                .into_par_iter()
+                .flat_map_iter(search_package)
+                .collect()
+        })
     };
 
     for dir in paths {
         let matching_ns = match &filter {
             PackageFilter::For(ns) => {
                 let local_path = dir.join(ns.as_str());
-                search_in_dir(local_path, ns.clone());
+                packages.extend(search_in_dir(local_path, ns.clone()));
 
                 continue;
             }
@@ -283,11 +388,49 @@ pub fn list_package(
                 continue;
             }
             let local_path = dir.path();
-            search_in_dir(local_path, ns.into());
+            packages.extend(search_in_dir(local_path, ns.into()));
         }
     }
 
-    packages
+    // The order of the packages doesn't depend on the order of reading the
+    // directories. The sort is stable to keep the packages in the earlier
+    // package paths first.
+    packages.sort_by(|a, b| {
+        (
+            a.namespace.as_str(),
+            a.package.name.as_str(),
+            &a.package.version,
+        )
+            .cmp(&(
+                b.namespace.as_str(),
+                b.package.name.as_str(),
+                &b.package.version,
+            ))
+    });
+    packages.into_iter().collect()
+}
+
+/// Runs the scan of local packages in a pool with a bounded number of threads,
+/// since the scan mostly waits for the filesystem.
+#[cfg(feature = "local-registry")]
+fn in_scan_pool<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    use std::sync::LazyLock;
+
+    /// The maximum number of directories read at the same time.
+    const MAX_SCAN_THREADS: usize = 8;
+
+    static SCAN_POOL: LazyLock<Option<rayon::ThreadPool>> = LazyLock::new(|| {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(MAX_SCAN_THREADS)
+            .thread_name(|idx| format!("package-scan-{idx}"))
+            .build();
+        once_log(pool, "build package scan pool")
+    });
+
+    match SCAN_POOL.as_ref() {
+        Some(pool) => pool.install(f),
+        None => f(),
+    }
 }
 
 #[cfg(feature = "local-registry")]
@@ -315,9 +458,12 @@ fn once_log<T, E: std::fmt::Display>(result: Result<T, E>, site: &'static str) -
 mod tests {
     use std::str::FromStr;
 
+    use tinymist_world::ShadowApi;
+    use typst::foundations::Bytes;
     use typst::syntax::package::PackageSpec;
 
     use super::*;
+    use crate::tests::*;
 
     fn manifest_id() -> FileId {
         FileId::new(RootedPath::new(
@@ -328,6 +474,61 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn group_packages_sorts_versions_descending() {
+        let entry = |spec: &str| {
+            let spec = PackageSpec::from_str(spec).expect("valid package spec");
+            PackageIndexEntry {
+                namespace: spec.namespace,
+                package: typst::syntax::package::PackageInfo {
+                    name: spec.name,
+                    version: spec.version,
+                    entrypoint: Default::default(),
+                    authors: Default::default(),
+                    license: Default::default(),
+                    description: Default::default(),
+                    homepage: Default::default(),
+                    repository: Default::default(),
+                    keywords: Default::default(),
+                    categories: Default::default(),
+                    disciplines: Default::default(),
+                    compiler: Default::default(),
+                    exclude: Default::default(),
+                    unknown_fields: Default::default(),
+                },
+                template: None,
+                updated_at: None,
+                path: None,
+            }
+        };
+
+        let groups = group_packages([
+            entry("@local/b:0.1.0"),
+            entry("@local/a:0.2.0"),
+            entry("@local/b:0.10.0"),
+            entry("@local/b:0.2.0"),
+        ]);
+        let groups = groups
+            .iter()
+            .map(|group| {
+                let versions = group.versions.iter().map(|it| it.version.as_str());
+                (
+                    group.name.as_str(),
+                    group.latest.as_str(),
+                    versions.collect(),
+                )
+            })
+            .collect::<Vec<(_, _, Vec<_>)>>();
+
+        assert_eq!(
+            groups,
+            vec![
+                ("a", "0.2.0", vec!["0.2.0"]),
+                ("b", "0.10.0", vec!["0.10.0", "0.2.0", "0.1.0"]),
+            ]
+        );
+    }
+
     #[test]
     fn package_entrypoint_id_resolves_relative_to_manifest_parent() {
         let manifest_id = manifest_id();
@@ -345,4 +546,73 @@ mod tests {
         assert_eq!(entrypoint.root(), manifest_id.root());
         assert_eq!(entrypoint.vpath().get_with_slash(), "/lib.typ");
     }
+
+    /// Checks a package made of the files, returning the number of files with
+    /// diagnostics.
+    fn check(files: &[(&str, &str)], extra_entries: &[EcoString]) -> StrResult<usize> {
+        let spec = PackageInfo {
+            path: PathBuf::new(),
+            namespace: "local".into(),
+            name: "example".into(),
+            version: "0.1.0".into(),
+        };
+        let toml_id = get_manifest_id(&spec).expect("valid package spec");
+
+        run_with_sources("// path: /main.typ\n", |verse: &mut LspUniverse, path| {
+            for (path, content) in files {
+                let fid = resolve_path_from_id(toml_id, path).expect("valid package path");
+                verse
+                    .map_shadow_by_id(fid.intern(), Bytes::from_string(content.to_string()))
+                    .expect("shadow package file");
+            }
+            run_with_ctx(verse, path, &|ctx, _| {
+                check_package(ctx, &spec, extra_entries).map(|diags| diags.len())
+            })
+        })
+    }
+
+    const MANIFEST: &str = r#"[package]
+name = "example"
+version = "0.1.0"
+entrypoint = "lib.typ"
+"#;
+
+    #[test]
+    fn check_valid_package() {
+        let files = [
+            ("typst.toml", MANIFEST),
+            (
+                "lib.typ",
+                "#import \"utils.typ\": double\n#let quad(x) = double(double(x))\n",
+            ),
+            ("utils.typ", "#let double(x) = x * 2\n"),
+            (
+                "examples/demo.typ",
+                "#import \"../lib.typ\": quad\n#quad(1)\n",
+            ),
+        ];
+
+        assert!(check(&files, &[]).is_ok());
+        assert!(check(&files, &["examples/demo.typ".into()]).is_ok());
+    }
+
+    #[test]
+    fn check_package_missing_entrypoint() {
+        let err = check(&[("typst.toml", MANIFEST)], &[]).unwrap_err();
+        assert!(
+            err.contains("cannot read package entry \"/lib.typ\""),
+            "{err}"
+        );
+
+        let files = [("typst.toml", MANIFEST), ("lib.typ", "#let x = 1\n")];
+        let err = check(&files, &["tests/missing.typ".into()]).unwrap_err();
+        assert!(err.contains("/tests/missing.typ"), "{err}");
+    }
+
+    #[test]
+    fn check_package_invalid_manifest() {
+        let files = [("typst.toml", "[package\nname = "), ("lib.typ", "")];
+        let err = check(&files, &[]).unwrap_err();
+        assert!(err.contains("package manifest is malformed"), "{err}");
+    }
 }
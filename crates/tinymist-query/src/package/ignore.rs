@@ -0,0 +1,208 @@
+//! Ignore files of local package directories.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+use std::time::SystemTime;
+
+use parking_lot::Mutex;
+
+/// The ignore files read in a package directory, where the rules of the later
+/// file take precedence.
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".tinymistignore"];
+
+/// The ignore rules of the directories, with the modification time of the
+/// ignore files they were read from.
+static IGNORE_CACHE: LazyLock<Mutex<HashMap<PathBuf, CachedRules>>> =
+    LazyLock::new(Default::default);
+
+struct CachedRules {
+    stamps: [Option<SystemTime>; 2],
+    rules: Arc<IgnoreRules>,
+}
+
+/// Gets the ignore rules of the directory, which are read again only if the
+/// ignore files are changed.
+pub(super) fn ignore_rules(dir: &Path) -> Arc<IgnoreRules> {
+    let stamps = IGNORE_FILES.map(|name| {
+        let meta = std::fs::metadata(dir.join(name)).ok()?;
+        meta.modified().ok()
+    });
+
+    let mut cache = IGNORE_CACHE.lock();
+    if let Some(cached) = cache.get(dir)
+        && cached.stamps == stamps
+    {
+        return cached.rules.clone();
+    }
+
+    let mut rules = IgnoreRules::default();
+    for (name, stamp) in IGNORE_FILES.iter().zip(&stamps) {
+        if stamp.is_none() {
+            continue;
+        }
+        match std::fs::read_to_string(dir.join(name)) {
+            Ok(content) => rules.extend(&content),
+            Err(err) => log::warn!("failed to read ignore file {name} in {dir:?}: {err}"),
+        }
+    }
+
+    let rules = Arc::new(rules);
+    cache.insert(
+        dir.to_owned(),
+        CachedRules {
+            stamps,
+            rules: rules.clone(),
+        },
+    );
+    rules
+}
+
+/// A subset of the gitignore rules, which supports comments, negations (`!`),
+/// anchored patterns (containing `/`), and the wildcards `*` and `?`.
+#[derive(Debug, Default)]
+pub(super) struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug)]
+struct IgnoreRule {
+    /// The pattern split by `/`.
+    pattern: Vec<String>,
+    /// Whether the pattern matches the whole path rather than its last
+    /// component.
+    anchored: bool,
+    /// Whether the rule re-includes the matched paths.
+    negated: bool,
+}
+
+impl IgnoreRules {
+    /// Adds the rules written in an ignore file.
+    fn extend(&mut self, content: &str) {
+        for line in content.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(line) => (true, line),
+                None => (false, line.strip_prefix('\\').unwrap_or(line)),
+            };
+            // All the matched paths are directories, so the directory-only
+            // suffix doesn't make a difference.
+            let line = line.strip_suffix('/').unwrap_or(line);
+            let anchored = line.contains('/');
+            let line = line.strip_prefix('/').unwrap_or(line);
+            if line.is_empty() {
+                continue;
+            }
+
+            self.rules.push(IgnoreRule {
+                pattern: line.split('/').map(str::to_owned).collect(),
+                anchored,
+                negated,
+            });
+        }
+    }
+
+    /// Whether the path relative to the directory of the ignore files is
+    /// ignored. The last matching rule decides.
+    pub fn is_ignored(&self, path: &str) -> bool {
+        let components = path.split('/').collect::<Vec<_>>();
+        let name = components.last().copied().unwrap_or_default();
+
+        let matched = self.rules.iter().rev().find(|rule| {
+            if rule.anchored {
+                rule.pattern.len() == components.len()
+                    && rule
+                        .pattern
+                        .iter()
+                        .zip(&components)
+                        .all(|(pattern, component)| wildcard_match(pattern, component))
+            } else {
+                wildcard_match(&rule.pattern[0], name)
+            }
+        });
+        matched.is_some_and(|rule| !rule.negated)
+    }
+}
+
+/// Matches the text against the pattern with the wildcards `*` and `?`.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+
+    let (mut pi, mut ti) = (0, 0);
+    // The position of the last star and the text it matches up to.
+    let mut star = None;
+    while ti < text.len() {
+        match pattern.get(pi) {
+            Some('*') => {
+                star = Some((pi, ti));
+                pi += 1;
+            }
+            Some(&c) if c == '?' || c == text[ti] => {
+                pi += 1;
+                ti += 1;
+            }
+            _ => match star {
+                Some((star_pi, star_ti)) => {
+                    star = Some((star_pi, star_ti + 1));
+                    pi = star_pi + 1;
+                    ti = star_ti + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[pi..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(content: &str) -> IgnoreRules {
+        let mut rules = IgnoreRules::default();
+        rules.extend(content);
+        rules
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("build", "build"));
+        assert!(wildcard_match("*", "anything"));
+        assert!(wildcard_match("0.1.*", "0.1.0"));
+        assert!(wildcard_match("*-dev", "0.2.0-dev"));
+        assert!(wildcard_match("0.?.0", "0.3.0"));
+        assert!(wildcard_match("a*b*c", "aXbYbZc"));
+        assert!(!wildcard_match("0.1.*", "0.2.0"));
+        assert!(!wildcard_match("build", "builds"));
+        assert!(!wildcard_match("a*b", "ac"));
+    }
+
+    #[test]
+    fn test_ignore_rules() {
+        let rules = rules(
+            "# build artifacts\n\
+             target/\n\
+             *-dev\n\
+             !keep-dev\n\
+             /draft/0.1.0\n\
+             \\!bang\n",
+        );
+
+        assert!(rules.is_ignored("target"));
+        assert!(rules.is_ignored("0.2.0-dev"));
+        assert!(rules.is_ignored("pkg/0.2.0-dev"));
+        assert!(rules.is_ignored("draft/0.1.0"));
+        assert!(rules.is_ignored("!bang"));
+        assert!(!rules.is_ignored("keep-dev"));
+        assert!(!rules.is_ignored("draft"));
+        assert!(!rules.is_ignored("other/0.1.0"));
+        assert!(!rules.is_ignored("0.1.0"));
+        assert!(!rules.is_ignored("# build artifacts"));
+    }
+}
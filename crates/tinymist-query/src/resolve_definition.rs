@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// A request to resolve the definition of the symbol at the given position,
+/// which serves the scripts that navigate across files without a full LSP
+/// client.
+#[derive(Debug, Clone)]
+pub struct ResolveDefinitionRequest {
+    /// The path of the document to request for.
+    pub path: PathBuf,
+    /// The source code position to request for.
+    pub position: LspPosition,
+}
+
+/// The resolved definition of a symbol.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedDefinition {
+    /// The uri of the file containing the definition.
+    pub uri: Url,
+    /// The range of the definition's name.
+    pub range: LspRange,
+    /// The name of the definition.
+    pub name: String,
+    /// The kind of the definition.
+    pub kind: DefKind,
+}
+
+impl SemanticRequest for ResolveDefinitionRequest {
+    type Response = ResolvedDefinition;
+
+    fn request(self, ctx: &mut LocalContext) -> Option<Self::Response> {
+        let source = ctx.source_by_path(&self.path).ok()?;
+        let syntax = ctx.classify_for_decl(&source, self.position)?;
+        let def = ctx.def_of_syntax_or_dyn(&source, syntax)?;
+
+        let fid = def.file_id()?;
+        let name_range = def.name_range(ctx.shared()).unwrap_or_default();
+
+        Some(ResolvedDefinition {
+            uri: ctx.uri_for_id(fid).ok()?,
+            range: ctx.to_lsp_range_(name_range, fid)?,
+            name: def.name().to_string(),
+            kind: def.decl.kind(),
+        })
+    }
+}
@@ -57,3 +57,16 @@ fn range_for_node(
             .map(|node| Box::new(range_for_node(source, position_encoding, node))),
     }
 }
+
+/// Finds the innermost node that encloses the range, starting from the leaf at
+/// its start as the selection ranges do.
+pub(crate) fn enclosing_node<'a>(
+    root: &'a LinkedNode<'a>,
+    range: Range<usize>,
+) -> Option<LinkedNode<'a>> {
+    let mut node = root.leaf_at_compat(range.start + 1)?;
+    while node.range().end < range.end {
+        node = node.parent()?.clone();
+    }
+    Some(node)
+}
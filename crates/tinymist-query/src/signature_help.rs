@@ -1,10 +1,15 @@
+use std::collections::HashSet;
+
 use typst_shim::syntax::LinkedNodeExt;
 
 use crate::{
-    SemanticRequest,
+    SemanticRequest, StrRef,
     adt::interner::Interned,
+    analysis::{Signature, analyze_indexed_call},
+    hover::ExternalDocLink,
     prelude::*,
     syntax::{ArgClass, SyntaxContext, classify_context, classify_syntax},
+    ty::Ty,
 };
 
 /// The [`textDocument/signatureHelp`] request is sent from the client to the
@@ -29,14 +34,18 @@ impl SemanticRequest for SignatureHelpRequest {
         let ast_node = LinkedNode::new(source.root()).leaf_at_compat(cursor)?;
         let SyntaxContext::Arg {
             callee,
+            args,
             target,
             is_set,
-            ..
         } = classify_context(ast_node, Some(cursor))?
         else {
             return None;
         };
 
+        // Selectors and partial applications don't need the required arguments.
+        let is_partial = callee
+            .cast::<ast::FieldAccess>()
+            .is_some_and(|access| matches!(access.field().as_str(), "where" | "with"));
         let syntax = classify_syntax(callee, cursor)?;
         let def = ctx.def_of_syntax_or_dyn(&source, syntax)?;
         let sig = ctx.sig_of_def(def.clone())?;
@@ -50,6 +59,9 @@ impl SemanticRequest for SignatureHelpRequest {
 
         label.push('(');
 
+        let call_site = CallSiteTypes::infer(ctx, &args);
+        let pos_size = sig.primary().pos_size();
+
         let mut real_offset = 0;
         let focus_name = OnceLock::new();
         for (idx, (param, ty)) in sig.params().enumerate() {
@@ -79,27 +91,43 @@ impl SemanticRequest for SignatureHelpRequest {
                 label.push_str(", ");
             }
 
+            let inferred = if idx < pos_size {
+                idx.checked_sub(param_shift)
+                    .and_then(|pos| call_site.positional.get(pos)?.as_ref())
+            } else {
+                call_site.named.get(&param.name)
+            };
+            let start = label.len();
             label.push_str(&format!(
                 "{}: {}",
                 param.name,
-                ty.unwrap_or(&param.ty)
-                    .describe()
-                    .as_deref()
-                    .unwrap_or("any")
+                describe_ty(ty.unwrap_or(&param.ty), inferred)
             ));
 
             let documentation = param.docs.as_ref().map(|docs| markdown_docs(ctx, docs));
 
+            let param_label = if ctx.analysis.signature_label_offsets {
+                let encoding = ctx.position_encoding();
+                lsp_types::ParameterLabel::LabelOffsets([
+                    label_offset(&label, start, encoding),
+                    label_offset(&label, label.len(), encoding),
+                ])
+            } else {
+                lsp_types::ParameterLabel::Simple(format!("{}:", param.name))
+            };
             params.push(ParameterInformation {
-                label: lsp_types::ParameterLabel::Simple(format!("{}:", param.name)),
+                label: param_label,
                 documentation,
             });
         }
         label.push(')');
-        let ret = sig.type_sig().body.clone();
+        let call = args.parent().and_then(|call| call.cast::<ast::FuncCall>());
+        let indexed = call.and_then(|call| analyze_indexed_call(ctx, call));
+        let ret = indexed.or_else(|| sig.type_sig().body.clone());
         if let Some(ret_ty) = ret {
+            let inferred = call.and_then(|call| ctx.type_of_span(call.span()));
             label.push_str(" -> ");
-            label.push_str(ret_ty.describe().as_deref().unwrap_or("any"));
+            label.push_str(&describe_ty(&ret_ty, inferred.as_ref()));
         }
 
         if matches!(target, ArgClass::Positional { .. }) {
@@ -109,14 +137,46 @@ impl SemanticRequest for SignatureHelpRequest {
 
         crate::log_debug_ct!("got signature info {label} {params:?}");
 
+        let missing = if is_set || is_partial {
+            vec![]
+        } else {
+            missing_params(&sig, &args)
+        };
+        let mut documentation = sig
+            .primary()
+            .docs
+            .as_ref()
+            .map(|docs| markdown_docs(ctx, docs));
+        if !missing.is_empty() {
+            let mut value = format!("Missing: {}", missing.iter().join(", "));
+            if let Some(Documentation::MarkupContent(docs)) = &documentation
+                && !docs.value.is_empty()
+            {
+                value.push_str("\n\n");
+                value.push_str(&docs.value);
+            }
+            documentation = Some(Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }));
+        }
+        if let Some(link) = def_link(ctx, &def) {
+            let value = match &documentation {
+                Some(Documentation::MarkupContent(docs)) if !docs.value.is_empty() => {
+                    format!("{}\n\n{link}", docs.value)
+                }
+                _ => link,
+            };
+            documentation = Some(Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }));
+        }
+
         Some(SignatureHelp {
             signatures: vec![SignatureInformation {
                 label: label.to_string(),
-                documentation: sig
-                    .primary()
-                    .docs
-                    .as_ref()
-                    .map(|docs| markdown_docs(ctx, docs)),
+                documentation,
                 parameters: Some(params),
                 active_parameter: active_parameter.map(|x| x as u32),
             }],
@@ -126,6 +186,122 @@ impl SemanticRequest for SignatureHelpRequest {
     }
 }
 
+/// The types of the arguments inferred at the call site.
+struct CallSiteTypes {
+    /// The types of the positional arguments, which are unknown after a
+    /// spread argument.
+    positional: Vec<Option<Ty>>,
+    /// The types of the named arguments.
+    named: HashMap<StrRef, Ty>,
+}
+
+impl CallSiteTypes {
+    fn infer(ctx: &mut LocalContext, args: &LinkedNode) -> Self {
+        let mut positional = vec![];
+        let mut named = HashMap::new();
+        let mut spread = false;
+        for node in args.children() {
+            match node.cast::<ast::Arg>() {
+                Some(ast::Arg::Pos(expr)) if !spread => {
+                    positional.push(ctx.type_of_span(expr.span()));
+                }
+                Some(ast::Arg::Named(arg)) => {
+                    if let Some(ty) = ctx.type_of_span(arg.expr().span()) {
+                        named.insert(Interned::new_str(arg.name().get()), ty);
+                    }
+                }
+                Some(ast::Arg::Spread(..)) => spread = true,
+                _ => {}
+            }
+        }
+
+        Self { positional, named }
+    }
+}
+
+/// Describes the declared type, or the type inferred at the call site if the
+/// declared one is unknown, falling back to `any`.
+fn describe_ty(declared: &Ty, inferred: Option<&Ty>) -> EcoString {
+    declared
+        .describe()
+        .filter(|desc| desc.as_str() != "any")
+        .or_else(|| inferred?.describe())
+        .unwrap_or_else(|| "any".into())
+}
+
+/// Collects the required parameters that are not yet supplied by the
+/// arguments, including the ones bound by `fn.with(..)`.
+fn missing_params(sig: &Signature, args: &LinkedNode) -> Vec<StrRef> {
+    let mut positional = 0;
+    let mut named = HashSet::new();
+    for arg in sig.bindings().iter().flat_map(|args| args.items.iter()) {
+        match &arg.name {
+            Some(name) => {
+                named.insert(name.clone());
+            }
+            None => positional += 1,
+        }
+    }
+
+    for node in args.children() {
+        let Some(arg) = node.cast::<ast::Arg>() else {
+            continue;
+        };
+        match arg {
+            ast::Arg::Pos(..) => positional += 1,
+            ast::Arg::Named(arg) => {
+                named.insert(Interned::new_str(arg.name().get()));
+            }
+            // A spread may supply any of the parameters.
+            ast::Arg::Spread(..) => return vec![],
+        }
+    }
+
+    let primary = sig.primary();
+    let missing_pos = primary.pos().iter().skip(positional);
+    let missing_named = primary
+        .named()
+        .iter()
+        .filter(|param| !param.attrs.positional && !named.contains(&param.name));
+    missing_pos
+        .chain(missing_named)
+        .filter(|param| param.attrs.required)
+        .map(|param| param.name.clone())
+        .collect()
+}
+
+/// Converts a byte offset in the label to the offset in the position
+/// encoding, which is in UTF-16 code units by default.
+fn label_offset(label: &str, offset: usize, encoding: PositionEncoding) -> u32 {
+    let prefix = &label[..offset];
+    let offset = match encoding {
+        PositionEncoding::Utf8 => prefix.len(),
+        PositionEncoding::Utf16 => prefix.encode_utf16().count(),
+    };
+    offset as u32
+}
+
+/// Links to the source of the definition, or to the online documentation if
+/// the definition is a builtin one.
+fn def_link(ctx: &LocalContext, def: &Definition) -> Option<String> {
+    if let Some(fid) = def.file_id()
+        && let Some(range) = def.name_range(ctx.shared())
+        && let Some(range) = ctx.to_lsp_range_(range, fid)
+        && let Ok(uri) = ctx.uri_for_id(fid)
+    {
+        let (start, end) = (range.start, range.end);
+        return Some(format!(
+            "[Open source]({uri}#L{},{}-L{},{})",
+            start.line + 1,
+            start.character + 1,
+            end.line + 1,
+            end.character + 1,
+        ));
+    }
+
+    ExternalDocLink::get(def).map(|link| link.to_string())
+}
+
 fn markdown_docs(ctx: &mut LocalContext, docs: &crate::docs::DocText) -> Documentation {
     let docs = crate::docs::resolve_doc_text(ctx.shared(), docs);
     Documentation::MarkupContent(MarkupContent {
@@ -139,6 +315,18 @@ mod tests {
     use super::*;
     use crate::tests::*;
 
+    #[test]
+    fn test_label_offset() {
+        let label = "text(lang: str, émoji😀: str)";
+        let start = label.find("émoji").unwrap();
+        let end = label.len() - 1;
+
+        assert_eq!(label_offset(label, start, PositionEncoding::Utf8), 16);
+        assert_eq!(label_offset(label, start, PositionEncoding::Utf16), 16);
+        assert_eq!(label_offset(label, end, PositionEncoding::Utf8), 31);
+        assert_eq!(label_offset(label, end, PositionEncoding::Utf16), 28);
+    }
+
     #[test]
     fn test() {
         snapshot_testing("signature_help", &|ctx, path| {
@@ -151,7 +339,9 @@ mod tests {
             with_settings!({
                 description => format!("signature help on {anno}"),
             }, {
-                assert_snapshot!(JsonRepr::new_redacted(result, &REDACT_LOC));
+                // Normalizes the source links on Windows.
+                let result = JsonRepr::new_redacted(result, &REDACT_LOC).to_string();
+                assert_snapshot!(result.replace("file:///C:/dummy-root/", "file:///dummy-root/"));
             })
         });
     }
@@ -0,0 +1,37 @@
+use crate::prelude::*;
+
+/// Runs the common analyses of the file, i.e. the expression analysis and the
+/// type checking of it and the modules it imports, to populate the caches used
+/// by hovers, completions, and the like. The results are discarded.
+///
+/// Returns `None` if the file is not a source file.
+pub fn warm_cache(ctx: &mut LocalContext, path: &Path) -> Option<()> {
+    let source = ctx.source_by_path(path).ok()?;
+    ctx.expr_stage(&source);
+    ctx.type_check(&source);
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn test_warm_cache() {
+        let contents = r#"// path: /lib.typ
+#let f(x) = x + 1
+-----
+// path: /main.typ
+#import "lib.typ": f
+#f(1)
+"#;
+
+        run_with_sources(contents, |verse: &mut LspUniverse, path| {
+            run_with_ctx(verse, path, &|ctx, path| {
+                assert_eq!(warm_cache(ctx, &path), Some(()));
+                assert_eq!(warm_cache(ctx, &path.with_file_name("missing.typ")), None);
+            });
+        });
+    }
+}
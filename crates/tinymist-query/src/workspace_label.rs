@@ -1,3 +1,10 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use ecow::eco_format;
+use serde::Serialize;
+use typst::diag::{StrResult, bail};
+use typst::syntax::is_valid_label_literal_id;
+
 use crate::{
     SemanticRequest,
     prelude::*,
@@ -41,13 +48,309 @@ impl SemanticRequest for WorkspaceLabelRequest {
     }
 }
 
-#[allow(deprecated)]
-fn filter_document_labels(
-    hierarchy: &[LexicalHierarchy],
-    source: &Source,
-    uri: &Url,
-    position_encoding: PositionEncoding,
-) -> Vec<SymbolInformation> {
+/// Renames a label across the workspace, rewriting its definitions and all the
+/// references to it, i.e. `<label>` and `@label`.
+///
+/// Fails if `new_name` cannot be written both as `<new_name>` and
+/// `@new_name`, or if a label named `new_name` already exists in the
+/// workspace, listing the locations of the colliding labels.
+pub fn rename_label(
+    ctx: &mut LocalContext,
+    label: &str,
+    new_name: &str,
+) -> StrResult<WorkspaceEdit> {
+    // A trailing dot or colon isn't part of a reference, e.g. `@intro.`.
+    if !is_valid_label_literal_id(new_name) || new_name.ends_with(['.', ':']) {
+        bail!("`{new_name}` is not a valid label name");
+    }
+    if label == new_name {
+        return Ok(WorkspaceEdit::default());
+    }
+
+    let mut collisions = vec![];
+    let mut edits = HashMap::new();
+
+    for fid in ctx.source_files().clone() {
+        let Ok(source) = ctx.source_by_id(fid) else {
+            continue;
+        };
+        let Ok(uri) = ctx.uri_for_id(fid) else {
+            continue;
+        };
+
+        // The label names, excluding the angle brackets.
+        let mut ranges = BTreeSet::new();
+        let hierarchy = get_lexical_hierarchy(&source, LexicalScopeKind::Symbol);
+        for label_def in document_labels(hierarchy.as_deref().unwrap_or_default()) {
+            let range = label_def.info.range.clone();
+            if label_def.info.name == new_name {
+                let pos = ctx.to_lsp_range(range, &source).start;
+                let line = pos.line + 1;
+                let column = pos.character + 1;
+                collisions.push(eco_format!("{uri}:{line}:{column}"));
+            } else if label_def.info.name == label {
+                ranges.insert((range.start + 1, range.end - 1));
+            }
+        }
+
+        let ei = ctx.expr_stage(&source);
+        for (span, r) in ei.resolves.iter() {
+            let (start, end) = match r.decl.as_ref() {
+                Decl::Label(..) => (1, 1),
+                Decl::ContentRef(..) => (1, 0),
+                _ => continue,
+            };
+            if r.decl.name() != label {
+                continue;
+            }
+            if let Some(range) = source_range(&source, *span) {
+                ranges.insert((range.start + start, range.end - end));
+            }
+        }
+
+        if ranges.is_empty() {
+            continue;
+        }
+        let file_edits = ranges
+            .into_iter()
+            .map(|(start, end)| TextEdit {
+                range: ctx.to_lsp_range(start..end, &source),
+                new_text: new_name.to_owned(),
+            })
+            .collect::<Vec<_>>();
+        edits.insert(uri, file_edits);
+    }
+
+    if !collisions.is_empty() {
+        bail!(
+            "label `<{new_name}>` already exists at {}",
+            collisions.join(", ")
+        );
+    }
+    if edits.is_empty() {
+        bail!("label `<{label}>` is not found in the workspace");
+    }
+
+    Ok(WorkspaceEdit {
+        changes: Some(edits),
+        ..Default::default()
+    })
+}
+
+/// A label defined in the workspace, i.e. a node of the [`LabelGraph`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelNode {
+    /// The name of the label, excluding the angle brackets.
+    pub name: EcoString,
+    /// The location of the label definition.
+    pub location: LspLocation,
+}
+
+/// The kind of a reference in the [`LabelGraph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LabelEdgeKind {
+    /// The reference points to a label defined in the workspace.
+    Reference,
+    /// The reference points to an entry of the bibliography.
+    Citation,
+    /// The reference points to a label that doesn't exist, which is an error.
+    Dangling,
+}
+
+/// A reference to a label, i.e. an edge of the [`LabelGraph`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelEdge {
+    /// The name of the referenced label.
+    pub label: EcoString,
+    /// The location of the reference.
+    pub location: LspLocation,
+    /// The kind of the reference.
+    pub kind: LabelEdgeKind,
+}
+
+/// The graph of the labels in the workspace and the references pointing to
+/// them, by `@label` or `<label>`.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelGraph {
+    /// The label definitions, sorted by location.
+    pub nodes: Vec<LabelNode>,
+    /// The references, sorted by location.
+    pub edges: Vec<LabelEdge>,
+}
+
+/// Builds the graph of the labels and the references across the workspace.
+///
+/// A reference to a bibliography entry is only recognized if the document has
+/// been compiled successfully, and is reported as dangling otherwise.
+pub fn label_graph(ctx: &mut LocalContext) -> LabelGraph {
+    let mut graph = LabelGraph::default();
+    let mut refs = vec![];
+
+    for fid in ctx.source_files().clone() {
+        let Ok(source) = ctx.source_by_id(fid) else {
+            continue;
+        };
+        let Ok(uri) = ctx.uri_for_id(fid) else {
+            continue;
+        };
+
+        let hierarchy = get_lexical_hierarchy(&source, LexicalScopeKind::Symbol);
+        for label_def in document_labels(hierarchy.as_deref().unwrap_or_default()) {
+            let range = label_def.info.range.clone();
+            graph.nodes.push(LabelNode {
+                name: label_def.info.name.clone(),
+                location: LspLocation {
+                    uri: uri.clone(),
+                    range: ctx.to_lsp_range(range, &source),
+                },
+            });
+        }
+
+        let mut ranges = BTreeSet::new();
+        let root = LinkedNode::new(source.root());
+        let ei = ctx.expr_stage(&source);
+        for (span, r) in ei.resolves.iter() {
+            match r.decl.as_ref() {
+                // A label in markup is a definition, which is attached to the
+                // preceding element.
+                Decl::Label(..) => {
+                    let in_markup = root
+                        .find(*span)
+                        .and_then(|node| Some(node.parent()?.kind()))
+                        .is_some_and(|kind| kind == SyntaxKind::Markup);
+                    if in_markup {
+                        continue;
+                    }
+                }
+                Decl::ContentRef(..) => {}
+                _ => continue,
+            }
+            let Some(range) = source_range(&source, *span) else {
+                continue;
+            };
+            let name: &str = r.decl.name();
+            ranges.insert((range.start, range.end, EcoString::from(name)));
+        }
+
+        for (start, end, label) in ranges {
+            let location = LspLocation {
+                uri: uri.clone(),
+                range: ctx.to_lsp_range(start..end, &source),
+            };
+            refs.push((label, location));
+        }
+    }
+
+    let bib_info = ctx
+        .success_doc()
+        .and_then(|doc| ctx.analyze_bib(doc.introspector()));
+    for (label, location) in refs {
+        let kind = if graph.nodes.iter().any(|node| node.name == label) {
+            LabelEdgeKind::Reference
+        } else if bib_info
+            .as_ref()
+            .is_some_and(|bib| bib.entries.contains_key(label.as_str()))
+        {
+            LabelEdgeKind::Citation
+        } else {
+            LabelEdgeKind::Dangling
+        };
+        graph.edges.push(LabelEdge {
+            label,
+            location,
+            kind,
+        });
+    }
+
+    graph
+        .nodes
+        .sort_by(|a, b| location_key(&a.location).cmp(&location_key(&b.location)));
+    graph
+        .edges
+        .sort_by(|a, b| location_key(&a.location).cmp(&location_key(&b.location)));
+    graph
+}
+
+/// A label defined more than once in the workspace.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateLabel {
+    /// The name of the label, excluding the angle brackets.
+    pub name: EcoString,
+    /// The locations of the label definitions, sorted by location.
+    pub locations: Vec<LspLocation>,
+}
+
+/// Finds the labels defined more than once across the workspace, sorted by
+/// name.
+///
+/// The labels are scanned from the syntax of the files, so the document is
+/// not compiled. A label defined once but attached to multiple elements, e.g.
+/// in a loop, is not reported.
+pub fn find_duplicate_labels(ctx: &mut LocalContext) -> Vec<DuplicateLabel> {
+    let mut labels = BTreeMap::<EcoString, Vec<LspLocation>>::new();
+
+    for fid in ctx.source_files().clone() {
+        let Ok(source) = ctx.source_by_id(fid) else {
+            continue;
+        };
+        let Ok(uri) = ctx.uri_for_id(fid) else {
+            continue;
+        };
+
+        let hierarchy = get_lexical_hierarchy(&source, LexicalScopeKind::Symbol);
+        for label_def in document_labels(hierarchy.as_deref().unwrap_or_default()) {
+            let range = label_def.info.range.clone();
+            let location = LspLocation {
+                uri: uri.clone(),
+                range: ctx.to_lsp_range(range, &source),
+            };
+            let name = label_def.info.name.clone();
+            labels.entry(name).or_default().push(location);
+        }
+    }
+
+    labels
+        .into_iter()
+        .filter(|(_, locations)| locations.len() > 1)
+        .map(|(name, mut locations)| {
+            locations.sort_by(|a, b| location_key(a).cmp(&location_key(b)));
+            DuplicateLabel { name, locations }
+        })
+        .collect()
+}
+
+/// Orders the locations by file and position.
+fn location_key(loc: &LspLocation) -> (&str, u32, u32) {
+    (
+        loc.uri.as_str(),
+        loc.range.start.line,
+        loc.range.start.character,
+    )
+}
+
+/// Collects the names of the labels defined in the workspace.
+pub(crate) fn workspace_labels(ctx: &mut LocalContext) -> BTreeSet<EcoString> {
+    let mut labels = BTreeSet::new();
+    for fid in ctx.source_files().clone() {
+        let Ok(source) = ctx.source_by_id(fid) else {
+            continue;
+        };
+        let hierarchy = get_lexical_hierarchy(&source, LexicalScopeKind::Symbol);
+        labels.extend(
+            document_labels(hierarchy.as_deref().unwrap_or_default())
+                .map(|label_def| label_def.info.name.clone()),
+        );
+    }
+    labels
+}
+
+/// Iterates the labels in the top two levels of the lexical hierarchy.
+fn document_labels(hierarchy: &[LexicalHierarchy]) -> impl Iterator<Item = &LexicalHierarchy> {
     hierarchy
         .iter()
         .flat_map(|hierarchy| {
@@ -55,14 +358,21 @@ fn filter_document_labels(
                 .into_iter()
                 .chain(hierarchy.children.as_deref().into_iter().flatten())
         })
-        .flat_map(|hierarchy| {
-            if !matches!(hierarchy.info.kind, LexicalKind::Var(LexicalVarKind::Label)) {
-                return None;
-            }
+        .filter(|hierarchy| matches!(hierarchy.info.kind, LexicalKind::Var(LexicalVarKind::Label)))
+}
 
+#[allow(deprecated)]
+fn filter_document_labels(
+    hierarchy: &[LexicalHierarchy],
+    source: &Source,
+    uri: &Url,
+    position_encoding: PositionEncoding,
+) -> Vec<SymbolInformation> {
+    document_labels(hierarchy)
+        .map(|hierarchy| {
             let rng = to_lsp_range(hierarchy.info.range.clone(), source, position_encoding);
 
-            Some(SymbolInformation {
+            SymbolInformation {
                 name: hierarchy.info.name.to_string(),
                 kind: hierarchy.info.kind.clone().into(),
                 tags: None,
@@ -72,7 +382,151 @@ fn filter_document_labels(
                     range: rng,
                 },
                 container_name: None,
-            })
+            }
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn test_rename_label() {
+        let contents = r#"// path: /chapter.typ
+= Intro <intro>
+See @intro.
+-----
+// path: /main.typ
+#include "chapter.typ"
+= Setup <setup>
+As in @intro[the intro] and #ref(<intro>).
+"#;
+
+        run_with_sources(contents, |verse: &mut LspUniverse, path| {
+            run_with_ctx(verse, path, &|ctx, _path| {
+                let edit = rename_label(ctx, "intro", "overview").unwrap();
+                let changes = edit.changes.unwrap();
+                assert_eq!(changes.len(), 2);
+                for (uri, edits) in changes {
+                    let source = ctx.source_by_path(&crate::url_to_path(&uri)).unwrap();
+                    let mut edits = edits
+                        .iter()
+                        .map(|edit| {
+                            let range = ctx.to_typst_range(edit.range, &source).unwrap();
+                            (range, edit.new_text.as_str())
+                        })
+                        .collect::<Vec<_>>();
+                    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+
+                    let mut text = source.text().to_owned();
+                    for (range, new_text) in edits {
+                        text.replace_range(range, new_text);
+                    }
+                    let expected = source
+                        .text()
+                        .replace("<intro>", "<overview>")
+                        .replace("@intro", "@overview");
+                    assert_eq!(text, expected);
+                }
+
+                for invalid in ["", "has space", "intro.", "a>b"] {
+                    let err = rename_label(ctx, "intro", invalid).unwrap_err();
+                    assert!(err.contains("is not a valid label name"), "{err}");
+                }
+
+                let err = rename_label(ctx, "intro", "setup").unwrap_err();
+                assert!(err.contains("`<setup>` already exists"), "{err}");
+                assert!(err.contains("main.typ:2:9"), "{err}");
+            });
+        });
+    }
+
+    #[test]
+    fn test_label_graph() {
+        let contents = r#"// path: /chapter.typ
+= Intro <intro>
+See @intro and @missing.
+-----
+// path: /main.typ
+#include "chapter.typ"
+As in @intro and #ref(<intro>).
+"#;
+
+        run_with_sources(contents, |verse: &mut LspUniverse, path| {
+            run_with_ctx(verse, path, &|ctx, _path| {
+                let graph = label_graph(ctx);
+                let nodes = graph
+                    .nodes
+                    .iter()
+                    .map(|node| node.name.as_str())
+                    .collect::<Vec<_>>();
+                assert_eq!(nodes, vec!["intro"]);
+
+                let edges = graph
+                    .edges
+                    .iter()
+                    .map(|edge| (edge.label.as_str(), edge.kind))
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    edges,
+                    vec![
+                        ("intro", LabelEdgeKind::Reference),
+                        ("missing", LabelEdgeKind::Dangling),
+                        ("intro", LabelEdgeKind::Reference),
+                        ("intro", LabelEdgeKind::Reference),
+                    ]
+                );
+            });
+        });
+    }
+
+    #[test]
+    fn test_find_duplicate_labels() {
+        let contents = r#"// path: /chapter.typ
+= Intro <intro>
+= Setup <setup>
+-----
+// path: /main.typ
+#include "chapter.typ"
+= Overview <intro>
+#figure[] <fig>
+#figure[] <fig>
+= Usage <usage>
+"#;
+
+        run_with_sources(contents, |verse: &mut LspUniverse, path| {
+            run_with_ctx(verse, path, &|ctx, _path| {
+                let duplicates = find_duplicate_labels(ctx);
+                let summary = duplicates
+                    .iter()
+                    .map(|label| {
+                        let files = label
+                            .locations
+                            .iter()
+                            .map(|loc| {
+                                let file = loc.uri.path().rsplit('/').next().unwrap();
+                                format!("{file}:{}", loc.range.start.line + 1)
+                            })
+                            .collect::<Vec<_>>();
+                        (label.name.as_str(), files)
+                    })
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    summary,
+                    vec![
+                        (
+                            "fig",
+                            vec!["main.typ:3".to_owned(), "main.typ:4".to_owned()]
+                        ),
+                        (
+                            "intro",
+                            vec!["chapter.typ:1".to_owned(), "main.typ:2".to_owned()]
+                        ),
+                    ]
+                );
+            });
+        });
+    }
+}
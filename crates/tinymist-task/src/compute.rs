@@ -4,23 +4,37 @@ use std::str::FromStr;
 use std::sync::Arc;
 
 use tinymist_std::error::prelude::*;
-use tinymist_std::typst::TypstPagedDocument;
+use tinymist_std::typst::{TypstDocument, TypstPagedDocument};
 use tinymist_world::{CompileSnapshot, CompilerFeat, ExportComputation, WorldComputeGraph};
+use tokio_util::sync::CancellationToken;
 use typst::foundations::Bytes;
-use typst::layout::Abs;
+use typst::foundations::{Label, Selector, Smart, Value};
+use typst::introspection::MetadataElem;
+use typst::layout::{Abs, Frame, Point, Size};
 use typst::model::Document;
 use typst::syntax::{SyntaxNode, ast};
-use typst::visualize::Color;
+use typst::utils::PicoStr;
+use typst::visualize::{Color, Paint};
 use typst_layout::Page;
 
 use crate::{Pages, TaskWhen, exported_page_ranges};
 
+mod gif;
+pub use gif::*;
 mod html;
 pub use html::*;
+mod html_query;
+pub use html_query::*;
+mod icc;
+pub use icc::*;
 mod png;
 pub use png::*;
+mod pptx;
+pub use pptx::*;
 mod query;
 pub use query::*;
+mod speaker_notes;
+pub use speaker_notes::*;
 mod svg;
 pub use svg::*;
 #[cfg(feature = "pdf")]
@@ -31,6 +45,7 @@ pub use pdf::*;
 pub mod text;
 #[cfg(feature = "text")]
 pub use text::*;
+mod woff2;
 
 /// The flag indicating that the svg export is needed.
 pub struct SvgFlag;
@@ -44,13 +59,62 @@ pub struct ExportTimings;
 
 impl ExportTimings {
     /// Checks if the export is needed.
+    ///
+    /// If `require` is given, the export additionally requires the document to
+    /// set the metadata with the label to a truthy value, e.g.
+    /// `#metadata(true) <export-pdf>`.
     pub fn needs_run<F: CompilerFeat, D: Document>(
         snap: &CompileSnapshot<F>,
         timing: Option<&TaskWhen>,
+        require: Option<&str>,
         docs: Option<&D>,
     ) -> Option<bool> {
-        snap.signal
-            .should_run_task(timing.unwrap_or(&TaskWhen::Never), docs)
+        let needs_run = snap
+            .signal
+            .should_run_task(timing.unwrap_or(&TaskWhen::Never), docs)?;
+        match require {
+            Some(label) if needs_run => docs.map(|doc| Self::is_required_by(doc, label)),
+            _ => Some(needs_run),
+        }
+    }
+
+    /// Checks if the document sets the metadata with the label to a truthy
+    /// value. If there are multiple such metadata, the last one wins.
+    pub fn is_required_by<D: Document>(doc: &D, label: &str) -> bool {
+        let Some(label) = Label::new(PicoStr::intern(label)) else {
+            return false;
+        };
+
+        let elements = doc.introspector().query(&Selector::Label(label));
+        let value = elements
+            .iter()
+            .rev()
+            .find_map(|elem| elem.to_packed::<MetadataElem>());
+        value.is_some_and(|metadata| is_truthy(&metadata.value))
+    }
+
+    /// Checks if the document sets the metadata with the label to a truthy
+    /// value.
+    pub fn is_required_by_dyn(doc: &TypstDocument, label: &str) -> bool {
+        match doc {
+            TypstDocument::Paged(doc) => Self::is_required_by(doc.as_ref(), label),
+            TypstDocument::Html(doc) => Self::is_required_by(doc.as_ref(), label),
+        }
+    }
+}
+
+/// Whether a metadata value opts a document in, where `none`, `false`, zeros,
+/// and empty strings or collections are falsy.
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::None => false,
+        Value::Bool(value) => *value,
+        Value::Int(value) => *value != 0,
+        Value::Float(value) => *value != 0.,
+        Value::Str(value) => !value.is_empty(),
+        Value::Array(value) => !value.is_empty(),
+        Value::Dict(value) => !value.is_empty(),
+        _ => true,
     }
 }
 
@@ -70,7 +134,37 @@ pub struct PagedOutput<T> {
     pub value: T,
 }
 
-fn select_pages<'a>(
+/// Checks whether the export is canceled by the token. Exports check it
+/// between their pages and phases, so a canceled export stops early.
+pub(crate) fn check_cancelled(cancel: &CancellationToken) -> Result<()> {
+    if cancel.is_cancelled() {
+        bail!("export is cancelled");
+    }
+    Ok(())
+}
+
+/// Escapes the text to be written in XML, e.g. in svg and pptx exports. The
+/// control characters that are not allowed in XML 1.0 are dropped.
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Selects the pages to export with their 0-based indices. When `pages` is
+/// unspecified, all pages are selected.
+pub fn select_pages<'a>(
     document: &'a TypstPagedDocument,
     pages: &Option<Vec<Pages>>,
 ) -> Vec<(usize, &'a Page)> {
@@ -87,6 +181,45 @@ fn select_pages<'a>(
         .collect::<Vec<_>>()
 }
 
+/// Merges the pages into a single page by placing them from left to right with
+/// the gap between them. Each page keeps its own fill, resolved by `fill_of`.
+fn merge_pages_horizontally(
+    pages: &[(usize, &Page)],
+    gap: Abs,
+    fill_of: impl Fn(&Page) -> Option<Paint>,
+) -> Result<Page> {
+    let Some((_, first)) = pages.first() else {
+        bail!("no pages to merge");
+    };
+
+    let width = pages
+        .iter()
+        .map(|(_, page)| page.frame.width())
+        .sum::<Abs>()
+        + gap * pages.len().saturating_sub(1) as f64;
+    let height = pages
+        .iter()
+        .map(|(_, page)| page.frame.height())
+        .fold(Abs::zero(), Abs::max);
+
+    let mut frame = Frame::soft(Size::new(width, height));
+    let mut x = Abs::zero();
+    for (_, page) in pages {
+        let mut page_frame = page.frame.clone();
+        if let Some(fill) = fill_of(page) {
+            page_frame.fill(fill);
+        }
+        let page_width = page_frame.width();
+        frame.push_frame(Point::with_x(x), page_frame);
+        x += page_width + gap;
+    }
+
+    let mut merged = (*first).clone();
+    merged.frame = frame;
+    merged.fill = Smart::Custom(None);
+    Ok(merged)
+}
+
 fn parse_length(gap: &str) -> Result<Abs> {
     let length = typst::syntax::parse_code(gap);
     if length.diagnosis().errors {
@@ -155,6 +288,15 @@ mod tests {
         assert!(parse_color("invalid").is_err());
     }
 
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(
+            escape_xml(r#"a & <b> "c" 'd'"#),
+            "a &amp; &lt;b&gt; &quot;c&quot; &apos;d&apos;"
+        );
+        assert_eq!(escape_xml("a\tb\nc\u{1}\u{7f}"), "a\tb\nc");
+    }
+
     #[test]
     fn test_parse_length() {
         assert_eq!(parse_length("1pt").unwrap(), Abs::pt(1.));
@@ -164,4 +306,19 @@ mod tests {
         assert!(parse_length("1").is_err());
         assert!(parse_length("1px").is_err());
     }
+
+    #[test]
+    fn test_is_truthy() {
+        assert!(is_truthy(&Value::Bool(true)));
+        assert!(is_truthy(&Value::Int(1)));
+        assert!(is_truthy(&Value::Str("yes".into())));
+        assert!(is_truthy(&Value::Auto));
+        assert!(!is_truthy(&Value::None));
+        assert!(!is_truthy(&Value::Bool(false)));
+        assert!(!is_truthy(&Value::Int(0)));
+        assert!(!is_truthy(&Value::Float(0.)));
+        assert!(!is_truthy(&Value::Str("".into())));
+        assert!(!is_truthy(&Value::Array(Default::default())));
+        assert!(!is_truthy(&Value::Dict(Default::default())));
+    }
 }
@@ -0,0 +1,196 @@
+//! The computation for gif export.
+
+use std::sync::Arc;
+
+use rayon::prelude::*;
+use tinymist_std::error::prelude::*;
+use tinymist_std::typst::TypstPagedDocument;
+use tinymist_world::{CompilerFeat, ExportComputation, WorldComputeGraph};
+use tokio_util::sync::CancellationToken;
+use typst::foundations::Bytes;
+
+use crate::compute::{check_cancelled, select_pages};
+use crate::model::{ExportGifTask, GifPalette};
+
+/// The maximum number of pixels sampled to build a global palette.
+const PALETTE_SAMPLES: usize = 1 << 20;
+
+/// The computation for gif export.
+pub struct GifExport;
+
+impl<F: CompilerFeat> ExportComputation<F, TypstPagedDocument> for GifExport {
+    type Output = Bytes;
+    type Config = ExportGifTask;
+
+    fn run(
+        _graph: &Arc<WorldComputeGraph<F>>,
+        doc: &Arc<TypstPagedDocument>,
+        config: &ExportGifTask,
+    ) -> Result<Bytes> {
+        Self::run_cancellable(doc, config, &CancellationToken::new())
+    }
+}
+
+impl GifExport {
+    /// Exports the gif, checking the `cancel` token between the frames.
+    pub fn run_cancellable(
+        doc: &Arc<TypstPagedDocument>,
+        config: &ExportGifTask,
+        cancel: &CancellationToken,
+    ) -> Result<Bytes> {
+        let ppi = config.ppi.to_f32();
+        if ppi <= 1e-6 {
+            bail!("invalid ppi: {ppi}");
+        }
+
+        let exported_pages = select_pages(doc, &config.pages);
+        if exported_pages.is_empty() {
+            bail!("no pages to export");
+        }
+
+        let render_options = typst_render::RenderOptions {
+            pixel_per_pt: f64::from(ppi / 72.).into(),
+            ..Default::default()
+        };
+        let frames = exported_pages
+            .par_iter()
+            .map(|(_, page)| {
+                check_cancelled(cancel)?;
+                let pixmap = typst_render::render(page, &render_options);
+                let (Ok(width), Ok(height)) = (
+                    u16::try_from(pixmap.width()),
+                    u16::try_from(pixmap.height()),
+                ) else {
+                    bail!(
+                        "the frame is too large for gif ({}x{} pixels), try a lower ppi",
+                        pixmap.width(),
+                        pixmap.height()
+                    );
+                };
+                Ok(GifFrame {
+                    width,
+                    height,
+                    rgba: flatten_on_white(pixmap.data()),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        check_cancelled(cancel)?;
+        encode_gif(&frames, config).map(Bytes::new)
+    }
+}
+
+/// A rendered frame of the gif.
+struct GifFrame {
+    width: u16,
+    height: u16,
+    /// The opaque pixels in RGBA.
+    rgba: Vec<u8>,
+}
+
+/// Composites the premultiplied pixels on a white background, since a gif
+/// cannot have semi-transparent pixels.
+fn flatten_on_white(premultiplied: &[u8]) -> Vec<u8> {
+    let mut rgba = premultiplied.to_vec();
+    for pixel in rgba.chunks_exact_mut(4) {
+        let background = 255 - pixel[3];
+        for channel in &mut pixel[..3] {
+            *channel = channel.saturating_add(background);
+        }
+        pixel[3] = 255;
+    }
+    rgba
+}
+
+/// Encodes the frames into a gif, each shown for the frame delay of the
+/// config. The canvas is as large as the largest frame, and the frames are
+/// placed at its top-left.
+fn encode_gif(frames: &[GifFrame], config: &ExportGifTask) -> Result<Vec<u8>> {
+    let width = frames.iter().map(|frame| frame.width).max().unwrap_or(1);
+    let height = frames.iter().map(|frame| frame.height).max().unwrap_or(1);
+    // The delay of a gif frame is in units of 10ms.
+    let delay = u16::try_from(config.frame_delay.div_ceil(10)).unwrap_or(u16::MAX);
+
+    let global_palette = match config.palette {
+        GifPalette::Global => Some(color_quant::NeuQuant::new(
+            10,
+            256,
+            &palette_samples(frames),
+        )),
+        GifPalette::PerFrame => None,
+    };
+    let palette = global_palette
+        .as_ref()
+        .map(|quant| quant.color_map_rgb())
+        .unwrap_or_default();
+
+    let mut gif = Vec::new();
+    {
+        let mut encoder = gif::Encoder::new(&mut gif, width, height, &palette)
+            .context_ut("failed to create the gif encoder")?;
+        let repeat = match config.loop_count {
+            Some(count) => gif::Repeat::Finite(count),
+            None => gif::Repeat::Infinite,
+        };
+        encoder
+            .set_repeat(repeat)
+            .context_ut("failed to set the loop count")?;
+
+        for frame in frames {
+            let mut gif_frame = match &global_palette {
+                Some(quant) => {
+                    let indices = frame
+                        .rgba
+                        .chunks_exact(4)
+                        .map(|pixel| quant.index_of(pixel) as u8)
+                        .collect::<Vec<_>>();
+                    gif::Frame::from_indexed_pixels(frame.width, frame.height, indices, None)
+                }
+                None => {
+                    let mut rgba = frame.rgba.clone();
+                    gif::Frame::from_rgba_speed(frame.width, frame.height, &mut rgba, 10)
+                }
+            };
+            gif_frame.delay = delay;
+            // Clears the canvas so that a smaller frame doesn't show the
+            // previous one around it.
+            gif_frame.dispose = gif::DisposalMethod::Background;
+            encoder
+                .write_frame(&gif_frame)
+                .context_ut("failed to encode the gif frame")?;
+        }
+    }
+
+    Ok(gif)
+}
+
+/// Samples the pixels of all the frames evenly to build a global palette.
+fn palette_samples(frames: &[GifFrame]) -> Vec<u8> {
+    let total = frames
+        .iter()
+        .map(|frame| frame.rgba.len() / 4)
+        .sum::<usize>();
+    let step = total.div_ceil(PALETTE_SAMPLES).max(1);
+    frames
+        .iter()
+        .flat_map(|frame| frame.rgba.chunks_exact(4))
+        .step_by(step)
+        .flatten()
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flatten_on_white() {
+        // Transparent, opaque red, and half-transparent black pixels.
+        let premultiplied = [0, 0, 0, 0, 255, 0, 0, 255, 0, 0, 0, 128];
+        assert_eq!(
+            flatten_on_white(&premultiplied),
+            vec![255, 255, 255, 255, 255, 0, 0, 255, 127, 127, 127, 255]
+        );
+    }
+}
@@ -0,0 +1,459 @@
+//! The computation for querying the html output of a document.
+
+use std::sync::Arc;
+
+use ecow::{EcoString, eco_format};
+use serde_json::{Map, Value};
+use tinymist_std::error::prelude::*;
+use tinymist_std::typst::TypstHtmlDocument;
+use tinymist_world::{CompilerFeat, ExportComputation, WorldComputeGraph};
+use typst::diag::StrResult;
+use typst_html::{HtmlElement, HtmlNode};
+
+use crate::QueryTask;
+use crate::compute::query::{field_path, serialize};
+
+/// The computation for querying the html output of a document with CSS-like
+/// selectors.
+///
+/// A selector is a comma-separated list of compound selectors chained by
+/// descendant (whitespace) or child (`>`) combinators. A compound selector
+/// matches a tag name or `*`, followed by any number of `.class`, `#id`,
+/// `[attr]`, and `[attr=value]` conditions, e.g. `article > h2.title`.
+pub struct HtmlQuery;
+
+impl HtmlQuery {
+    /// Retrieves the matches for the selector in the document order. Each
+    /// match is an object with the `tag`, `attrs`, and `text` of the element.
+    pub fn retrieve(doc: &TypstHtmlDocument, selector: &str) -> StrResult<Vec<Value>> {
+        let selectors = parse_selector_list(selector)?;
+
+        let mut matches = vec![];
+        let mut ancestors = vec![];
+        collect_matches(doc.root(), &selectors, &mut ancestors, &mut matches);
+        Ok(matches)
+    }
+
+    fn retrieve_values(
+        doc: &TypstHtmlDocument,
+        selector: &str,
+        field: Option<&str>,
+        one: bool,
+    ) -> Result<Value> {
+        let elements = Self::retrieve(doc, selector)
+            .map_err(|e| anyhow::anyhow!("failed to retrieve: {e}"))?;
+        if one && elements.len() != 1 {
+            bail!("expected exactly one element, found {}", elements.len());
+        }
+
+        let path = field.map(field_path);
+        let mapped: Vec<Value> = elements
+            .into_iter()
+            .filter_map(|elem| match &path {
+                Some(path) => path.iter().try_fold(elem, |value, key| match value {
+                    Value::Object(mut map) => map.remove(key),
+                    _ => None,
+                }),
+                None => Some(elem),
+            })
+            .collect();
+
+        if one {
+            let Some(value) = mapped.into_iter().next() else {
+                bail!("no such field found for element");
+            };
+            Ok(value)
+        } else {
+            Ok(Value::Array(mapped))
+        }
+    }
+
+    /// Queries the document and returns the result as a value.
+    pub fn get_as_value(doc: &TypstHtmlDocument, config: &QueryTask) -> Result<Value> {
+        if config.page.is_some() {
+            bail!("cannot query the html output by page");
+        }
+
+        // All the selectors are queried on the same compiled document.
+        let mut results = config
+            .selectors()
+            .map(|(selector, field, one)| {
+                let value = Self::retrieve_values(doc, selector, field, one)?;
+                Ok((selector.to_owned(), value))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if config.selector.is_many() {
+            Ok(Value::Object(results.into_iter().collect()))
+        } else {
+            Ok(results.pop().map(|(_, value)| value).unwrap_or_default())
+        }
+    }
+}
+
+impl<F: CompilerFeat> ExportComputation<F, TypstHtmlDocument> for HtmlQuery {
+    type Output = String;
+    type Config = QueryTask;
+
+    fn run(
+        _graph: &Arc<WorldComputeGraph<F>>,
+        doc: &Arc<TypstHtmlDocument>,
+        config: &QueryTask,
+    ) -> Result<String> {
+        let pretty = false;
+        let value = Self::get_as_value(doc, config)?;
+
+        serialize(&value, &config.format, pretty)
+    }
+}
+
+/// Collects the elements matching any of the selectors, which are converted
+/// to JSON objects.
+fn collect_matches<'a>(
+    elem: &'a HtmlElement,
+    selectors: &[ComplexSelector],
+    ancestors: &mut Vec<&'a HtmlElement>,
+    matches: &mut Vec<Value>,
+) {
+    if selectors.iter().any(|sel| sel.matches(elem, ancestors)) {
+        matches.push(element_to_json(elem));
+    }
+
+    ancestors.push(elem);
+    for child in elem.children.iter() {
+        if let HtmlNode::Element(child) = child {
+            collect_matches(child, selectors, ancestors, matches);
+        }
+    }
+    ancestors.pop();
+}
+
+fn element_to_json(elem: &HtmlElement) -> Value {
+    let attrs = elem
+        .attrs
+        .0
+        .iter()
+        .map(|(name, value)| (name.resolve().to_string(), Value::from(value.as_str())))
+        .collect::<Map<_, _>>();
+
+    let mut text = String::new();
+    element_text(elem, &mut text);
+
+    let mut obj = Map::new();
+    obj.insert("tag".into(), elem.tag.resolve().to_string().into());
+    obj.insert("attrs".into(), Value::Object(attrs));
+    obj.insert("text".into(), text.into());
+    Value::Object(obj)
+}
+
+/// Gets the text of the element and its descendants. Frames are skipped.
+fn element_text(elem: &HtmlElement, text: &mut String) {
+    for child in elem.children.iter() {
+        match child {
+            HtmlNode::Text(t, _) => text.push_str(t),
+            HtmlNode::Element(child) => element_text(child, text),
+            HtmlNode::Tag(..) | HtmlNode::Frame(..) => {}
+        }
+    }
+}
+
+/// How a compound selector relates to the one before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    /// The element is a descendant of the one matched before.
+    Descendant,
+    /// The element is a child of the one matched before.
+    Child,
+}
+
+/// A selector matching a single element, e.g. `h2.title[id]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct CompoundSelector {
+    /// The tag name, or `None` for any tag.
+    tag: Option<EcoString>,
+    /// The attribute conditions, where `None` only requires the attribute to
+    /// exist.
+    attrs: Vec<(EcoString, Option<EcoString>)>,
+    /// The classes the element must have.
+    classes: Vec<EcoString>,
+}
+
+impl CompoundSelector {
+    fn matches(&self, elem: &HtmlElement) -> bool {
+        let attr = |name: &str| {
+            elem.attrs
+                .0
+                .iter()
+                .find(|(attr, _)| attr.resolve().as_str() == name)
+                .map(|(_, value)| value)
+        };
+
+        self.tag
+            .as_ref()
+            .is_none_or(|tag| elem.tag.resolve().as_str().eq_ignore_ascii_case(tag))
+            && self.attrs.iter().all(|(name, expected)| match attr(name) {
+                Some(value) => expected.as_ref().is_none_or(|expected| value == expected),
+                None => false,
+            })
+            && self.classes.iter().all(|class| {
+                attr("class").is_some_and(|classes| classes.split_whitespace().any(|c| c == class))
+            })
+    }
+}
+
+/// A chain of compound selectors, each with the combinator before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ComplexSelector(Vec<(Combinator, CompoundSelector)>);
+
+impl ComplexSelector {
+    fn matches(&self, elem: &HtmlElement, ancestors: &[&HtmlElement]) -> bool {
+        Self::matches_at(&self.0, elem, ancestors)
+    }
+
+    fn matches_at(
+        parts: &[(Combinator, CompoundSelector)],
+        elem: &HtmlElement,
+        ancestors: &[&HtmlElement],
+    ) -> bool {
+        let Some(((combinator, last), rest)) = parts.split_last() else {
+            return true;
+        };
+        if !last.matches(elem) {
+            return false;
+        }
+        if rest.is_empty() {
+            return true;
+        }
+
+        match combinator {
+            Combinator::Child => ancestors
+                .split_last()
+                .is_some_and(|(parent, up)| Self::matches_at(rest, parent, up)),
+            Combinator::Descendant => (0..ancestors.len())
+                .rev()
+                .any(|i| Self::matches_at(rest, ancestors[i], &ancestors[..i])),
+        }
+    }
+}
+
+/// Parses a comma-separated list of selectors.
+fn parse_selector_list(input: &str) -> StrResult<Vec<ComplexSelector>> {
+    let mut parser = SelectorParser {
+        input,
+        chars: input.char_indices().peekable(),
+    };
+
+    let mut selectors = vec![parser.complex()?];
+    while parser.eat(',') {
+        selectors.push(parser.complex()?);
+    }
+    parser.skip_whitespace();
+    if let Some((pos, c)) = parser.chars.peek() {
+        return Err(eco_format!(
+            "unexpected `{c}` at {pos} in selector `{input}`"
+        ));
+    }
+
+    Ok(selectors)
+}
+
+struct SelectorParser<'a> {
+    input: &'a str,
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+}
+
+impl SelectorParser<'_> {
+    fn complex(&mut self) -> StrResult<ComplexSelector> {
+        self.skip_whitespace();
+        let mut parts = vec![(Combinator::Descendant, self.compound()?)];
+        loop {
+            let spaced = self.skip_whitespace();
+            let combinator = if self.eat('>') {
+                self.skip_whitespace();
+                Combinator::Child
+            } else if spaced && self.peek().is_some_and(|c| c != ',') {
+                Combinator::Descendant
+            } else {
+                break;
+            };
+            parts.push((combinator, self.compound()?));
+        }
+
+        Ok(ComplexSelector(parts))
+    }
+
+    fn compound(&mut self) -> StrResult<CompoundSelector> {
+        let mut compound = CompoundSelector::default();
+        let mut empty = true;
+        if self.eat('*') {
+            empty = false;
+        } else if self.peek().is_some_and(is_ident_char) {
+            compound.tag = Some(self.ident()?);
+            empty = false;
+        }
+
+        loop {
+            if self.eat('.') {
+                compound.classes.push(self.ident()?);
+            } else if self.eat('#') {
+                compound.attrs.push(("id".into(), Some(self.ident()?)));
+            } else if self.eat('[') {
+                self.skip_whitespace();
+                let name = self.ident()?;
+                self.skip_whitespace();
+                let value = if self.eat('=') {
+                    self.skip_whitespace();
+                    Some(self.value()?)
+                } else {
+                    None
+                };
+                self.skip_whitespace();
+                self.expect(']')?;
+                compound.attrs.push((name, value));
+            } else {
+                break;
+            }
+            empty = false;
+        }
+
+        if empty {
+            return Err(self.error("expected a selector"));
+        }
+        Ok(compound)
+    }
+
+    fn ident(&mut self) -> StrResult<EcoString> {
+        let mut ident = EcoString::new();
+        while let Some(c) = self.peek().filter(|&c| is_ident_char(c)) {
+            ident.push(c);
+            self.chars.next();
+        }
+        if ident.is_empty() {
+            return Err(self.error("expected a name"));
+        }
+        Ok(ident)
+    }
+
+    fn value(&mut self) -> StrResult<EcoString> {
+        let Some(quote) = self.peek().filter(|&c| c == '"' || c == '\'') else {
+            return self.ident();
+        };
+        self.chars.next();
+
+        let mut value = EcoString::new();
+        loop {
+            match self.chars.next() {
+                Some((_, c)) if c == quote => return Ok(value),
+                Some((_, '\\')) => match self.chars.next() {
+                    Some((_, c)) => value.push(c),
+                    None => break,
+                },
+                Some((_, c)) => value.push(c),
+                None => break,
+            }
+        }
+        Err(eco_format!("unclosed string in selector `{}`", self.input))
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|(_, c)| *c)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.chars.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> StrResult<()> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{expected}`")))
+        }
+    }
+
+    /// Skips whitespace and returns whether any was skipped.
+    fn skip_whitespace(&mut self) -> bool {
+        let mut skipped = false;
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.chars.next();
+            skipped = true;
+        }
+        skipped
+    }
+
+    fn error(&mut self, message: &str) -> EcoString {
+        let pos = self.chars.peek().map_or(self.input.len(), |(pos, _)| *pos);
+        eco_format!("{message} at {pos} in selector `{}`", self.input)
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compound(tag: Option<&str>) -> CompoundSelector {
+        CompoundSelector {
+            tag: tag.map(EcoString::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_selector() {
+        let selectors = parse_selector_list("article > h2.title, a[href='#top'] *").unwrap();
+        assert_eq!(
+            selectors,
+            vec![
+                ComplexSelector(vec![
+                    (Combinator::Descendant, compound(Some("article"))),
+                    (
+                        Combinator::Child,
+                        CompoundSelector {
+                            classes: vec!["title".into()],
+                            ..compound(Some("h2"))
+                        }
+                    ),
+                ]),
+                ComplexSelector(vec![
+                    (
+                        Combinator::Descendant,
+                        CompoundSelector {
+                            attrs: vec![("href".into(), Some("#top".into()))],
+                            ..compound(Some("a"))
+                        }
+                    ),
+                    (Combinator::Descendant, compound(None)),
+                ]),
+            ]
+        );
+
+        assert_eq!(
+            parse_selector_list("#intro").unwrap(),
+            vec![ComplexSelector(vec![(
+                Combinator::Descendant,
+                CompoundSelector {
+                    attrs: vec![("id".into(), Some("intro".into()))],
+                    ..compound(None)
+                }
+            )])]
+        );
+    }
+
+    #[test]
+    fn test_parse_bad_selector() {
+        assert!(parse_selector_list("").is_err());
+        assert!(parse_selector_list("h2 >").is_err());
+        assert!(parse_selector_list("a[href").is_err());
+        assert!(parse_selector_list("a[href='x]").is_err());
+        assert!(parse_selector_list("h2 )").is_err());
+    }
+}
@@ -0,0 +1,322 @@
+//! Embeds ICC color profiles into the exported images and documents.
+
+use std::io::Write;
+use std::path::Path;
+
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use lopdf::{Document, Object, Stream, dictionary};
+use tinymist_std::error::prelude::*;
+use tinymist_world::{CompilerFeat, WorldComputeGraph};
+
+/// A validated ICC color profile.
+#[derive(Debug, Clone)]
+pub struct IccProfile {
+    /// The raw data of the profile.
+    data: Vec<u8>,
+    /// The number of color components of the profile's color space.
+    components: u8,
+}
+
+impl IccProfile {
+    /// Loads the profile at the path. A relative path is resolved against the
+    /// root of the project.
+    pub fn load<F: CompilerFeat>(graph: &WorldComputeGraph<F>, path: &Path) -> Result<Self> {
+        let root = graph.world().entry_state().root();
+        let path = match root {
+            Some(root) if path.is_relative() => root.join(path),
+            _ => path.to_owned(),
+        };
+
+        let data = std::fs::read(&path).with_context("failed to read ICC profile", || {
+            Some(Box::new([("path", format!("{}", path.display()))]))
+        })?;
+        Self::new(data)
+    }
+
+    /// Validates the header of the profile data.
+    pub fn new(data: Vec<u8>) -> Result<Self> {
+        const HEADER_SIZE: usize = 128;
+        if data.len() < HEADER_SIZE {
+            bail!("invalid ICC profile: too short ({} bytes)", data.len());
+        }
+
+        let size = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
+        if size != data.len() {
+            bail!(
+                "invalid ICC profile: declares {size} bytes but has {} bytes",
+                data.len()
+            );
+        }
+        if &data[36..40] != b"acsp" {
+            bail!("invalid ICC profile: no `acsp` signature");
+        }
+
+        let components = match &data[16..20] {
+            b"GRAY" => 1,
+            b"RGB " => 3,
+            b"CMYK" => 4,
+            space => bail!(
+                "unsupported color space of the ICC profile: {:?}",
+                String::from_utf8_lossy(space).trim_end()
+            ),
+        };
+
+        Ok(Self { data, components })
+    }
+
+    /// Embeds the profile into the PNG image as an `iCCP` chunk.
+    pub fn embed_in_png(&self, png: &[u8]) -> Result<Vec<u8>> {
+        const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+        // The signature, the length, type, data, and CRC of the `IHDR` chunk.
+        const IHDR_END: usize = 8 + 4 + 4 + 13 + 4;
+        if png.len() < IHDR_END || !png.starts_with(SIGNATURE) || &png[12..16] != b"IHDR" {
+            bail!("not a PNG image");
+        }
+
+        // Grayscale images take gray profiles and color images take RGB ones.
+        let color_type = png[8 + 4 + 4 + 9];
+        let expected = if color_type & 2 == 0 { 1 } else { 3 };
+        if self.components != expected {
+            bail!(
+                "a profile with {} color components cannot be embedded into a PNG image with {expected}",
+                self.components
+            );
+        }
+
+        let mut data = b"ICC profile\0\0".to_vec();
+        data.extend(self.compressed()?);
+
+        let mut out = Vec::with_capacity(png.len() + data.len() + 12);
+        out.extend_from_slice(&png[..IHDR_END]);
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let mut crc = flate2::Crc::new();
+        crc.update(b"iCCP");
+        crc.update(&data);
+        out.extend_from_slice(b"iCCP");
+        out.extend_from_slice(&data);
+        out.extend_from_slice(&crc.sum().to_be_bytes());
+        out.extend_from_slice(&png[IHDR_END..]);
+        Ok(out)
+    }
+
+    /// Embeds the profile into the PDF document as its output intent, replacing
+    /// the existing ones.
+    pub fn embed_in_pdf(&self, pdf: &[u8]) -> Result<Vec<u8>> {
+        let mut doc = Document::load_mem(pdf).context("failed to parse the PDF document")?;
+
+        let profile = doc.add_object(Stream::new(
+            dictionary! {
+                "N" => i64::from(self.components),
+                "Filter" => "FlateDecode",
+            },
+            self.compressed()?,
+        ));
+        let intent = doc.add_object(dictionary! {
+            "Type" => "OutputIntent",
+            "S" => "GTS_PDFA1",
+            "OutputConditionIdentifier" => Object::string_literal("Custom"),
+            "DestOutputProfile" => profile,
+        });
+        doc.catalog_mut()
+            .context("cannot find the catalog")?
+            .set("OutputIntents", vec![Object::Reference(intent)]);
+
+        let mut out = Vec::with_capacity(pdf.len() + self.data.len());
+        doc.save_to(&mut out)
+            .context("failed to write the PDF document")?;
+        Ok(out)
+    }
+
+    /// The zlib-compressed profile data.
+    fn compressed(&self) -> Result<Vec<u8>> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&self.data)
+            .context("failed to compress ICC profile")?;
+        encoder.finish().context("failed to compress ICC profile")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use lopdf::Dictionary;
+    use tinymist_std::typst::TypstPagedDocument;
+    use tinymist_world::ExportComputation;
+    use tinymist_world::args::PdfStandard;
+    use tinymist_world::mock::MockWorkspaceWorldExt;
+    use tinymist_world::vfs::mock::MockWorkspace;
+    use typst_pdf::PdfOptions;
+
+    use super::*;
+    use crate::PdfExport;
+    use crate::model::ExportPdfTask;
+
+    fn profile(space: &[u8; 4]) -> Vec<u8> {
+        let mut data = vec![0; 132];
+        data[..4].copy_from_slice(&132u32.to_be_bytes());
+        data[16..20].copy_from_slice(space);
+        data[36..40].copy_from_slice(b"acsp");
+        data
+    }
+
+    /// Builds a PDF document with the objects, whose first one is the catalog.
+    fn pdf(objects: &[&str]) -> Vec<u8> {
+        let mut out = b"%PDF-1.7\n".to_vec();
+        let mut offsets = vec![];
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            write!(out, "{} 0 obj\n{object}\nendobj\n", i + 1).unwrap();
+        }
+        let xref = out.len();
+        write!(out, "xref\n0 {}\n0000000000 65535 f\r\n", objects.len() + 1).unwrap();
+        for offset in offsets {
+            write!(out, "{offset:010} 00000 n\r\n").unwrap();
+        }
+        write!(
+            out,
+            "trailer\n<< /Size {} /Root 1 0 R /ID [<00ff> <00ff>] >>\nstartxref\n{xref}\n%%EOF",
+            objects.len() + 1
+        )
+        .unwrap();
+        out
+    }
+
+    #[test]
+    fn test_validate_profile() {
+        assert_eq!(IccProfile::new(profile(b"RGB ")).unwrap().components, 3);
+        assert_eq!(IccProfile::new(profile(b"CMYK")).unwrap().components, 4);
+        assert!(IccProfile::new(profile(b"Lab ")).is_err());
+        assert!(IccProfile::new(vec![0; 16]).is_err());
+
+        let mut truncated = profile(b"RGB ");
+        truncated.pop();
+        assert!(IccProfile::new(truncated).is_err());
+
+        let mut unsigned = profile(b"RGB ");
+        unsigned[36..40].copy_from_slice(b"xxxx");
+        assert!(IccProfile::new(unsigned).is_err());
+    }
+
+    #[test]
+    fn test_embed_in_png() {
+        let pixmap = tiny_skia::Pixmap::new(1, 1).unwrap();
+        let png = pixmap.encode_png().unwrap();
+
+        let rgb = IccProfile::new(profile(b"RGB ")).unwrap();
+        let embedded = rgb.embed_in_png(&png).unwrap();
+        assert_eq!(&embedded[33 + 4..33 + 8], b"iCCP");
+        let len = u32::from_be_bytes(embedded[33..37].try_into().unwrap()) as usize;
+        assert_eq!(&embedded[33 + 12 + len..], &png[33..]);
+
+        let cmyk = IccProfile::new(profile(b"CMYK")).unwrap();
+        assert!(cmyk.embed_in_png(&png).is_err());
+    }
+
+    /// Reads the output intent of the document and the number of color
+    /// components of its profile.
+    fn output_intent(doc: &Document) -> (&Dictionary, i64) {
+        let intents = doc.catalog().unwrap().get(b"OutputIntents").unwrap();
+        let intents = intents.as_array().unwrap();
+        assert_eq!(intents.len(), 1);
+        let intent = doc
+            .get_dictionary(intents[0].as_reference().unwrap())
+            .unwrap();
+
+        let dest = intent.get(b"DestOutputProfile").unwrap();
+        let dest = doc.get_object(dest.as_reference().unwrap()).unwrap();
+        let components = dest.as_stream().unwrap().dict.get(b"N").unwrap();
+        (intent, components.as_i64().unwrap())
+    }
+
+    #[test]
+    fn test_embed_in_pdf() {
+        let doc = pdf(&[
+            "<< /Type /Catalog /Pages 2 0 R /OutputIntents [3 0 R] >>",
+            "<< /Type /Pages /Kids [] /Count 0 >>",
+            "<< /Type /OutputIntent /S /GTS_PDFA1 >>",
+        ]);
+
+        let cmyk = IccProfile::new(profile(b"CMYK")).unwrap();
+        let embedded = cmyk.embed_in_pdf(&doc).unwrap();
+        let embedded = Document::load_mem(&embedded).unwrap();
+
+        let catalog = embedded.catalog().unwrap();
+        assert_eq!(
+            catalog.get(b"Pages").unwrap().as_reference().unwrap(),
+            (2, 0)
+        );
+        assert!(embedded.trailer.get(b"ID").is_ok());
+
+        let (intent, components) = output_intent(&embedded);
+        assert_eq!(
+            intent.get(b"Type").unwrap().as_name().unwrap(),
+            b"OutputIntent"
+        );
+        assert_eq!(components, 4);
+
+        let dest = intent.get(b"DestOutputProfile").unwrap();
+        let dest = embedded.get_object(dest.as_reference().unwrap()).unwrap();
+        let data = dest.as_stream().unwrap().decompressed_content().unwrap();
+        assert_eq!(data, profile(b"CMYK"));
+    }
+
+    /// Compiles the source and exports it by `typst_pdf`.
+    fn typst_pdf(source: &str, options: &PdfOptions) -> Vec<u8> {
+        let workspace = MockWorkspace::default_builder()
+            .file("main.typ", source)
+            .build();
+        let world = workspace.world("main.typ").build_world().unwrap();
+        let doc = typst::compile::<TypstPagedDocument>(&world)
+            .output
+            .expect("failed to compile");
+        typst_pdf::pdf(&doc, options).expect("failed to export")
+    }
+
+    #[test]
+    fn test_embed_in_typst_pdf() {
+        let doc = typst_pdf(
+            "#set document(title: [Notes])\n= Heading\nHello",
+            &PdfOptions::default(),
+        );
+
+        let rgb = IccProfile::new(profile(b"RGB ")).unwrap();
+        let embedded = rgb.embed_in_pdf(&doc).unwrap();
+        let embedded = Document::load_mem(&embedded).unwrap();
+
+        // The catalog keeps its entries besides the output intents.
+        let original = Document::load_mem(&doc).unwrap();
+        let updated = embedded.catalog().unwrap();
+        for (key, value) in original.catalog().unwrap().iter() {
+            if key != b"OutputIntents" {
+                assert_eq!(updated.get(key).unwrap(), value);
+            }
+        }
+        assert_eq!(embedded.get_pages().len(), original.get_pages().len());
+
+        let (_, components) = output_intent(&embedded);
+        assert_eq!(components, 3);
+    }
+
+    #[test]
+    fn test_reject_pdf_a() {
+        let workspace = MockWorkspace::default_builder()
+            .file("main.typ", "Hello")
+            .build();
+        let world = workspace.world("main.typ").build_world().unwrap();
+        let graph = WorldComputeGraph::from_world(world);
+        let doc = typst::compile::<TypstPagedDocument>(graph.world())
+            .output
+            .expect("failed to compile");
+
+        let config = ExportPdfTask {
+            pdf_standards: vec![PdfStandard::A_2b],
+            icc_profile: Some("profile.icc".into()),
+            ..ExportPdfTask::default()
+        };
+        let err = PdfExport::run(&graph, &Arc::new(doc), &config).unwrap_err();
+        assert!(err.to_string().contains("PDF/A"), "{err}");
+    }
+}
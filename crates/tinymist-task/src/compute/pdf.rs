@@ -1,10 +1,12 @@
 //! The computation for pdf export.
 
+use ecow::EcoVec;
 use tinymist_std::time::ToUtcDateTime;
 use tinymist_world::args::PdfStandard;
 pub use typst_pdf::PdfStandard as TypstPdfStandard;
 pub use typst_pdf::pdf;
 
+use typst::diag::SourceDiagnostic;
 use typst_pdf::{PdfOptions, PdfStandards, Timestamp};
 
 use super::*;
@@ -18,39 +20,112 @@ impl<F: CompilerFeat> ExportComputation<F, TypstPagedDocument> for PdfExport {
     type Config = ExportPdfTask;
 
     fn run(
-        _graph: &Arc<WorldComputeGraph<F>>,
+        graph: &Arc<WorldComputeGraph<F>>,
         doc: &Arc<TypstPagedDocument>,
         config: &ExportPdfTask,
     ) -> Result<Bytes> {
+        Self::run_cancellable(graph, doc, config, &CancellationToken::new())
+    }
+}
+
+impl PdfExport {
+    /// Exports the pdf, checking the `cancel` token between the phases of the
+    /// export.
+    pub fn run_cancellable<F: CompilerFeat>(
+        graph: &Arc<WorldComputeGraph<F>>,
+        doc: &Arc<TypstPagedDocument>,
+        config: &ExportPdfTask,
+        cancel: &CancellationToken,
+    ) -> Result<Bytes> {
+        let icc_profile = match &config.icc_profile {
+            // The output intent of a PDF/A document is written by `typst_pdf`
+            // and must be kept for the conformance.
+            Some(..) if config.pdf_standards.iter().any(is_pdf_a) => {
+                bail!("cannot embed an ICC profile into a PDF/A document")
+            }
+            Some(path) => Some(IccProfile::load(graph, path)?),
+            None => None,
+        };
+
         let options = pdf_options(
             config.pages.as_deref(),
             &config.pdf_standards,
             config.no_pdf_tags,
             config.creation_timestamp,
+            config.reproducible,
         )?;
 
         // log::info!("used options for pdf export: {options:?}");
 
         // todo: Some(pdf_uri.as_str())
         // todo: ident option
-        Ok(Bytes::new(typst_pdf::pdf(doc, &options)?))
+        check_cancelled(cancel)?;
+        let pdf = typst_pdf::pdf(doc, &options)?;
+        check_cancelled(cancel)?;
+        match icc_profile {
+            Some(profile) => Ok(Bytes::new(profile.embed_in_pdf(&pdf)?)),
+            None => Ok(Bytes::new(pdf)),
+        }
+    }
+
+    /// Checks whether the document conforms to the PDF standards, e.g. that
+    /// all fonts are embeddable and no transparency is used for PDF/A-1. The
+    /// PDF is generated in memory and discarded, so nothing is written. Returns
+    /// the issues blocking the conformance, which are empty if it conforms.
+    pub fn check_standards(
+        doc: &TypstPagedDocument,
+        pdf_standards: &[PdfStandard],
+    ) -> Result<EcoVec<SourceDiagnostic>> {
+        let options = pdf_options(None, pdf_standards, false, None, false)?;
+        Ok(match typst_pdf::pdf(doc, &options) {
+            Ok(_) => EcoVec::new(),
+            Err(issues) => issues,
+        })
     }
 }
 
+/// Whether the standard is a PDF/A standard.
+fn is_pdf_a(standard: &PdfStandard) -> bool {
+    matches!(
+        standard,
+        PdfStandard::A_1b
+            | PdfStandard::A_1a
+            | PdfStandard::A_2b
+            | PdfStandard::A_2u
+            | PdfStandard::A_2a
+            | PdfStandard::A_3b
+            | PdfStandard::A_3u
+            | PdfStandard::A_3a
+            | PdfStandard::A_4
+            | PdfStandard::A_4f
+            | PdfStandard::A_4e
+    )
+}
+
 /// Creates PDF options from shared project export arguments.
+///
+/// A `reproducible` export omits the timestamp if `creation_timestamp` is not
+/// given. The identifiers of the PDF are derived from the document by
+/// `typst_pdf`, so the output only depends on the document then.
 pub fn pdf_options(
     pages: Option<&[Pages]>,
     pdf_standards: &[PdfStandard],
     no_pdf_tags: bool,
     creation_timestamp: Option<i64>,
+    reproducible: bool,
 ) -> Result<PdfOptions> {
     let creation_timestamp = creation_timestamp
         .map(|ts| ts.to_utc_datetime().context("timestamp is out of range"))
-        .transpose()?
-        .unwrap_or_else(tinymist_std::time::utc_now);
+        .transpose()?;
+    let creation_timestamp = match creation_timestamp {
+        Some(ts) => Some(ts),
+        None if reproducible => None,
+        None => Some(tinymist_std::time::utc_now()),
+    };
     // todo: this seems different from `Timestamp::new_local` which also embeds the
     // timezone information.
-    let timestamp = Timestamp::new_utc(tinymist_std::time::to_typst_time(creation_timestamp));
+    let timestamp =
+        creation_timestamp.map(|ts| Timestamp::new_utc(tinymist_std::time::to_typst_time(ts)));
 
     let standards = PdfStandards::new(
         &pdf_standards
@@ -109,7 +184,7 @@ pub fn pdf_options(
 
     Ok(PdfOptions {
         page_ranges: pages.map(exported_page_ranges),
-        timestamp: Some(timestamp),
+        timestamp,
         standards,
         tagged,
         ..Default::default()
@@ -1,16 +1,28 @@
 //! The computation for png export.
 
+use std::borrow::Cow;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 
+use rayon::prelude::*;
 use tinymist_std::error::prelude::*;
 use tinymist_std::typst::TypstPagedDocument;
 use tinymist_world::{CompilerFeat, ExportComputation, WorldComputeGraph};
-use typst::foundations::Bytes;
+use tokio_util::sync::CancellationToken;
+use typst::foundations::{Bytes, Smart};
+use typst::layout::Abs;
 use typst::model::Document;
+use typst::visualize::{Color, Paint};
+use typst_layout::Page;
 
-use crate::compute::{parse_color, parse_length, select_pages};
-use crate::model::ExportPngTask;
-use crate::{ImageOutput, PageMerge, PagedOutput};
+use crate::compute::{
+    IccProfile, check_cancelled, merge_pages_horizontally, parse_color, parse_length, select_pages,
+};
+use crate::model::{ExportPngTask, PageFill};
+use crate::primitives::Scalar;
+use crate::{
+    ContactSheet, ImageOutput, PageMerge, PageMergeDirection, PagedOutput, exported_page_ranges,
+};
 
 /// The computation for png export.
 pub struct PngExport;
@@ -20,19 +32,62 @@ impl<F: CompilerFeat> ExportComputation<F, TypstPagedDocument> for PngExport {
     type Config = ExportPngTask;
 
     fn run(
-        _graph: &Arc<WorldComputeGraph<F>>,
+        graph: &Arc<WorldComputeGraph<F>>,
         doc: &Arc<TypstPagedDocument>,
         config: &ExportPngTask,
     ) -> Result<Self::Output> {
+        Self::run_cancellable(graph, doc, config, &CancellationToken::new())
+    }
+}
+
+impl PngExport {
+    /// Exports the images, checking the `cancel` token between the pages.
+    pub fn run_cancellable<F: CompilerFeat>(
+        graph: &Arc<WorldComputeGraph<F>>,
+        doc: &Arc<TypstPagedDocument>,
+        config: &ExportPngTask,
+        cancel: &CancellationToken,
+    ) -> Result<ImageOutput<Bytes>> {
+        if config.max_bytes.is_some() {
+            return Self::run_fitted(graph, doc, config, cancel).map(|(output, _)| output);
+        }
+
         let ppi = config.ppi.to_f32();
         if ppi <= 1e-6 {
             bail!("invalid ppi: {ppi}");
         }
 
-        let fill = if let Some(fill) = &config.fill {
-            Some(parse_color(fill).map_err(|err| anyhow::anyhow!("invalid fill ({err})"))?)
-        } else {
-            None
+        if config.transparent && config.fill.is_some() {
+            bail!("cannot export with both a transparent background and a fill");
+        }
+
+        let parse_fill = |fill: &str| -> Result<Color> {
+            Ok(parse_color(fill).map_err(|err| anyhow::anyhow!("invalid fill ({err})"))?)
+        };
+        let (fill, page_fills) = match &config.fill {
+            Some(PageFill::All(fill)) => (Some(parse_fill(fill)?), vec![]),
+            Some(page_fill @ PageFill::Ranges(fills)) => {
+                page_fill.check()?;
+                let page_fills = fills
+                    .iter()
+                    .map(|fill| Ok((exported_page_ranges(&fill.ranges), parse_fill(&fill.fill)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                (None, page_fills)
+            }
+            None => (None, vec![]),
+        };
+
+        // Loads the profile before rendering so that an invalid profile fails
+        // the export early.
+        let icc_profile = match &config.icc_profile {
+            Some(path) => Some(IccProfile::load(graph, path)?),
+            None => None,
+        };
+        let embed_profile = |png: Bytes| -> Result<Bytes> {
+            match &icc_profile {
+                Some(profile) => Ok(Bytes::new(profile.embed_in_png(&png)?)),
+                None => Ok(png),
+            }
         };
 
         let ppp = ppi / 72.;
@@ -42,42 +97,392 @@ impl<F: CompilerFeat> ExportComputation<F, TypstPagedDocument> for PngExport {
         };
 
         let exported_pages = select_pages(doc, &config.pages);
-        if let Some(PageMerge { ref gap }) = config.merge {
-            let dummy_doc = TypstPagedDocument::new(
-                exported_pages
-                    .into_iter()
-                    .map(|(_, page)| page.clone())
-                    .collect(),
-                doc.info().clone(),
-            );
+        // Overrides the page fills, which are removed so that the background is
+        // left transparent, or replaced by the fills of the page ranges.
+        let page_fill = |idx: usize| -> Option<Option<Paint>> {
+            if config.transparent {
+                return Some(None);
+            }
+            page_fills
+                .iter()
+                .find(|(ranges, _)| ranges.includes_page_index(idx))
+                .map(|(_, fill)| Some(Paint::from(*fill)))
+        };
+        let filled_pages = exported_pages
+            .iter()
+            .map(|(i, page)| match page_fill(*i) {
+                Some(fill) => {
+                    let mut page = (*page).clone();
+                    page.fill = Smart::Custom(fill);
+                    (*i, Cow::Owned(page))
+                }
+                None => (*i, Cow::Borrowed(*page)),
+            })
+            .collect::<Vec<_>>();
+        let exported_pages = filled_pages
+            .iter()
+            .map(|(i, page)| (*i, page.as_ref()))
+            .collect::<Vec<_>>();
+        if let Some(sheet) = &config.contact_sheet {
+            if config.merge.is_some() {
+                bail!("cannot export a contact sheet with merged pages");
+            }
+
+            let background = match fill {
+                Some(fill) => fill.to_vec4_u8(),
+                None if config.transparent => [0, 0, 0, 0],
+                None => [255, 255, 255, 255],
+            };
+            let png = render_contact_sheet(
+                &exported_pages,
+                sheet,
+                ppp,
+                &render_options,
+                background,
+                config.jobs,
+                cancel,
+            )?;
+            Ok(ImageOutput::Merged(embed_profile(png)?))
+        } else if let Some(PageMerge { ref gap, direction }) = config.merge {
             let gap = gap
                 .as_ref()
                 .and_then(|gap| parse_length(gap).ok())
                 .unwrap_or_default();
-            let pixmap = typst_render::render_merged(&dummy_doc, &render_options, gap, fill);
+            if let Some(max_height) = config.max_height {
+                let heights = exported_pages
+                    .iter()
+                    .map(|(_, page)| page.frame.height())
+                    .collect::<Vec<_>>();
+                let height = merged_height(&heights, gap, direction, ppp);
+                if height > u64::from(max_height.get()) {
+                    bail!(
+                        "the merged image would be {height} pixels tall, which exceeds the \
+                         maximum height of {max_height} pixels; lower the ppi or export fewer pages"
+                    );
+                }
+            }
+            let pixmap = match direction {
+                PageMergeDirection::Vertical => {
+                    let dummy_doc = TypstPagedDocument::new(
+                        exported_pages
+                            .into_iter()
+                            .map(|(_, page)| page.clone())
+                            .collect(),
+                        doc.info().clone(),
+                    );
+                    typst_render::render_merged(&dummy_doc, &render_options, gap, fill)
+                }
+                PageMergeDirection::Horizontal => {
+                    let mut merged =
+                        merge_pages_horizontally(&exported_pages, gap, Page::fill_or_white)?;
+                    // The gaps are filled like `render_merged` does.
+                    merged.fill = Smart::Custom(fill.map(Paint::from));
+                    typst_render::render(&merged, &render_options)
+                }
+            };
+            check_cancelled(cancel)?;
             let png = pixmap
                 .encode_png()
                 .map(Bytes::new)
                 .context_ut("failed to encode PNG")?;
-            Ok(ImageOutput::Merged(png))
+            Ok(ImageOutput::Merged(embed_profile(png)?))
         } else {
-            let exported = exported_pages
-                .into_iter()
-                .map(|(i, page)| {
-                    let pixmap = typst_render::render(page, &render_options);
-                    let png = pixmap
-                        .encode_png()
-                        .map(Bytes::new)
-                        .context_ut("failed to encode PNG")?;
-                    Ok(PagedOutput {
-                        page: i,
-                        value: png,
-                    })
+            let render = |(i, page): (usize, &Page)| -> Result<PagedOutput<Bytes>> {
+                check_cancelled(cancel)?;
+                let pixmap = typst_render::render(page, &render_options);
+                let png = pixmap
+                    .encode_png()
+                    .map(Bytes::new)
+                    .context_ut("failed to encode PNG")?;
+                Ok(PagedOutput {
+                    page: i,
+                    value: embed_profile(png)?,
                 })
-                .collect::<Result<Vec<_>>>()?;
+            };
+
+            // Pages are independent after layout, so they are rendered in
+            // parallel. The results are collected in page order.
+            let exported = match config.jobs.map(NonZeroUsize::get) {
+                Some(1) => exported_pages
+                    .into_iter()
+                    .map(render)
+                    .collect::<Result<Vec<_>>>()?,
+                Some(jobs) => rayon::ThreadPoolBuilder::new()
+                    .num_threads(jobs)
+                    .build()
+                    .context("failed to build thread pool for PNG export")?
+                    .install(|| {
+                        exported_pages
+                            .into_par_iter()
+                            .map(render)
+                            .collect::<Result<Vec<_>>>()
+                    })?,
+                None => exported_pages
+                    .into_par_iter()
+                    .map(render)
+                    .collect::<Result<Vec<_>>>()?,
+            };
             Ok(ImageOutput::Paged(exported))
         }
     }
+
+    /// Exports the images, lowering the PPI from the configured one until every
+    /// image fits in `max_bytes`, and returns the images with the PPI that they
+    /// are rendered at.
+    ///
+    /// Fails with the smallest achievable size if the images don't fit even at
+    /// the lowest PPI.
+    pub fn run_fitted<F: CompilerFeat>(
+        graph: &Arc<WorldComputeGraph<F>>,
+        doc: &Arc<TypstPagedDocument>,
+        config: &ExportPngTask,
+        cancel: &CancellationToken,
+    ) -> Result<(ImageOutput<Bytes>, Scalar)> {
+        let mut config = config.clone();
+        let Some(max_bytes) = config.max_bytes.take() else {
+            let output = Self::run_cancellable(graph, doc, &config, cancel)?;
+            return Ok((output, config.ppi));
+        };
+        let max_bytes = max_bytes.get();
+
+        loop {
+            let output = Self::run_cancellable(graph, doc, &config, cancel)?;
+            let size = largest_image(&output);
+            if size <= max_bytes {
+                return Ok((output, config.ppi));
+            }
+
+            let ppi = config.ppi.to_f32();
+            if ppi <= MIN_FIT_PPI {
+                bail!(
+                    "cannot fit the images in {max_bytes} bytes, the smallest achievable size is \
+                     {size} bytes at {ppi} ppi"
+                );
+            }
+            config.ppi = next_fit_ppi(ppi, size, max_bytes)
+                .try_into()
+                .map_err(|err| anyhow::anyhow!("invalid ppi ({err})"))?;
+        }
+    }
+}
+
+/// The lowest PPI that the export lowers the PPI to for `max_bytes`.
+const MIN_FIT_PPI: f32 = 18.;
+
+/// The size (in bytes) of the largest image in the output.
+fn largest_image(output: &ImageOutput<Bytes>) -> usize {
+    match output {
+        ImageOutput::Merged(image) => image.len(),
+        ImageOutput::Paged(pages) => pages
+            .iter()
+            .map(|page| page.value.len())
+            .max()
+            .unwrap_or_default(),
+    }
+}
+
+/// Estimates the PPI at which an image of `size` bytes at `ppi` fits in
+/// `max_bytes`. The size of a PNG grows roughly with the number of pixels, i.e.
+/// with the square of the PPI, so the PPI is scaled by the square root of the
+/// ratio, with some slack for the estimate being off.
+fn next_fit_ppi(ppi: f32, size: usize, max_bytes: usize) -> f32 {
+    let scale = (max_bytes as f32 / size as f32).sqrt() * 0.95;
+    (ppi * scale.min(0.95)).max(MIN_FIT_PPI)
+}
+
+/// The height (in pixels) of the image merging pages of the heights.
+fn merged_height(
+    heights: &[Abs],
+    gap: Abs,
+    direction: PageMergeDirection,
+    pixel_per_pt: f32,
+) -> u64 {
+    let height = match direction {
+        PageMergeDirection::Vertical => {
+            let gaps = gap * heights.len().saturating_sub(1) as f64;
+            heights.iter().fold(gaps, |acc, height| acc + *height)
+        }
+        PageMergeDirection::Horizontal => heights
+            .iter()
+            .fold(Abs::zero(), |acc, height| acc.max(*height)),
+    };
+    (f64::from(pixel_per_pt) * height.to_pt()).ceil() as u64
+}
+
+/// The margin around and between the thumbnails of a contact sheet (in
+/// pixels).
+const SHEET_GAP: u32 = 16;
+/// The size of a dot of the page number digits (in pixels).
+const DIGIT_DOT: u32 = 3;
+/// The 3x5 dot patterns of the digits, from the top row to the bottom row.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// The layout of a contact sheet, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SheetLayout {
+    /// The number of thumbnails in each row.
+    columns: u32,
+    /// The size of a thumbnail cell, which fits the largest page.
+    cell: (u32, u32),
+    /// The height of the page number label below each thumbnail.
+    label_height: u32,
+    /// The size of the whole sheet.
+    size: (u32, u32),
+}
+
+impl SheetLayout {
+    fn new(thumbs: impl Iterator<Item = (u32, u32)>, count: usize, columns: usize) -> Self {
+        let (cell_width, cell_height) =
+            thumbs.fold((1, 1), |(w, h), (tw, th)| (w.max(tw), h.max(th)));
+        let columns = columns.min(count).max(1) as u32;
+        let rows = count.div_ceil(columns as usize).max(1) as u32;
+        let label_height = 5 * DIGIT_DOT + SHEET_GAP / 2;
+
+        let width = columns * cell_width + (columns + 1) * SHEET_GAP;
+        let height = rows * (cell_height + label_height) + (rows + 1) * SHEET_GAP;
+        Self {
+            columns,
+            cell: (cell_width, cell_height),
+            label_height,
+            size: (width, height),
+        }
+    }
+
+    /// The top-left corner of the cell at the index.
+    fn cell_origin(&self, index: usize) -> (u32, u32) {
+        let (column, row) = (index as u32 % self.columns, index as u32 / self.columns);
+        let x = SHEET_GAP + column * (self.cell.0 + SHEET_GAP);
+        let y = SHEET_GAP + row * (self.cell.1 + self.label_height + SHEET_GAP);
+        (x, y)
+    }
+}
+
+/// The size of the pixmap that `typst_render` renders the page to.
+fn thumb_size(page: &Page, pixel_per_pt: f32) -> (u32, u32) {
+    let size = page.frame.size();
+    let pixels = |pt: f64| ((pixel_per_pt as f64 * pt).round() as u32).max(1);
+    (pixels(size.x.to_pt()), pixels(size.y.to_pt()))
+}
+
+/// Renders the pages as thumbnails tiled in a grid, each labeled with its page
+/// number. The pages are rendered row by row, so at most a row of thumbnails is
+/// kept in memory besides the sheet.
+fn render_contact_sheet(
+    pages: &[(usize, &Page)],
+    sheet: &ContactSheet,
+    pixel_per_pt: f32,
+    render_options: &typst_render::RenderOptions,
+    background: [u8; 4],
+    jobs: Option<NonZeroUsize>,
+    cancel: &CancellationToken,
+) -> Result<Bytes> {
+    if pages.is_empty() {
+        bail!("no pages to put on the contact sheet");
+    }
+
+    let columns = sheet.columns().get();
+    let layout = SheetLayout::new(
+        pages.iter().map(|(_, page)| thumb_size(page, pixel_per_pt)),
+        pages.len(),
+        columns,
+    );
+
+    let mut pixmap = tiny_skia::Pixmap::new(layout.size.0, layout.size.1)
+        .context_ut("the contact sheet is too large")?;
+    let [r, g, b, a] = background;
+    pixmap.fill(tiny_skia::Color::from_rgba8(r, g, b, a));
+
+    let pool = jobs
+        .map(|jobs| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(jobs.get())
+                .build()
+                .context("failed to build thread pool for PNG export")
+        })
+        .transpose()?;
+
+    let mut label_paint = tiny_skia::Paint::default();
+    label_paint.set_color_rgba8(64, 64, 64, 255);
+
+    for (row, row_pages) in pages.chunks(columns).enumerate() {
+        check_cancelled(cancel)?;
+        let render_row = || {
+            row_pages
+                .par_iter()
+                .map(|(_, page)| typst_render::render(page, render_options))
+                .collect::<Vec<_>>()
+        };
+        let thumbs = match &pool {
+            Some(pool) => pool.install(render_row),
+            None => render_row(),
+        };
+
+        for (column, ((i, _), thumb)) in row_pages.iter().zip(thumbs).enumerate() {
+            let (x, y) = layout.cell_origin(row * columns + column);
+            let thumb_x = x + layout.cell.0.saturating_sub(thumb.width()) / 2;
+            pixmap.draw_pixmap(
+                thumb_x as i32,
+                y as i32,
+                thumb.as_ref(),
+                &tiny_skia::PixmapPaint::default(),
+                tiny_skia::Transform::identity(),
+                None,
+            );
+
+            let label = (i + 1).to_string();
+            let label_width = label.len() as u32 * 4 * DIGIT_DOT - DIGIT_DOT;
+            let label_x = x + layout.cell.0.saturating_sub(label_width) / 2;
+            let label_y = y + layout.cell.1 + SHEET_GAP / 4;
+            draw_number(&mut pixmap, &label, (label_x, label_y), &label_paint);
+        }
+    }
+
+    pixmap
+        .encode_png()
+        .map(Bytes::new)
+        .context_ut("failed to encode PNG")
+}
+
+/// Draws the decimal digits with the dot patterns in [`DIGITS`].
+fn draw_number(
+    pixmap: &mut tiny_skia::Pixmap,
+    digits: &str,
+    (x, y): (u32, u32),
+    paint: &tiny_skia::Paint,
+) {
+    let digits = digits
+        .bytes()
+        .filter_map(|b| DIGITS.get(b.wrapping_sub(b'0') as usize));
+    for (nth, pattern) in digits.enumerate() {
+        let digit_x = x + nth as u32 * 4 * DIGIT_DOT;
+        for (dy, bits) in pattern.iter().enumerate() {
+            for dx in 0..3 {
+                if bits & (0b100 >> dx) == 0 {
+                    continue;
+                }
+                let dot = tiny_skia::Rect::from_xywh(
+                    (digit_x + dx * DIGIT_DOT) as f32,
+                    (y + dy as u32 * DIGIT_DOT) as f32,
+                    DIGIT_DOT as f32,
+                    DIGIT_DOT as f32,
+                );
+                if let Some(dot) = dot {
+                    pixmap.fill_rect(dot, paint, tiny_skia::Transform::identity(), None);
+                }
+            }
+        }
+    }
 }
 
 // impl<F: CompilerFeat> WorldComputable<F> for PngExport {
@@ -87,3 +492,61 @@ impl<F: CompilerFeat> ExportComputation<F, TypstPagedDocument> for PngExport {
 //         OptionDocumentTask::run_export::<F, Self>(graph)
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sheet_layout() {
+        let thumbs = [(40, 60), (40, 60), (60, 40), (40, 60), (40, 60)];
+        let layout = SheetLayout::new(thumbs.into_iter(), thumbs.len(), 2);
+        let label_height = 5 * DIGIT_DOT + SHEET_GAP / 2;
+
+        assert_eq!(layout.columns, 2);
+        assert_eq!(layout.cell, (60, 60));
+        assert_eq!(
+            layout.size,
+            (
+                2 * 60 + 3 * SHEET_GAP,
+                3 * (60 + label_height) + 4 * SHEET_GAP
+            )
+        );
+        assert_eq!(layout.cell_origin(0), (SHEET_GAP, SHEET_GAP));
+        assert_eq!(
+            layout.cell_origin(3),
+            (2 * SHEET_GAP + 60, 2 * SHEET_GAP + 60 + label_height)
+        );
+    }
+
+    #[test]
+    fn test_merged_height() {
+        let heights = [Abs::pt(100.), Abs::pt(50.), Abs::pt(100.)];
+        let gap = Abs::pt(10.);
+        assert_eq!(
+            merged_height(&heights, gap, PageMergeDirection::Vertical, 2.),
+            540
+        );
+        assert_eq!(
+            merged_height(&heights, gap, PageMergeDirection::Horizontal, 2.),
+            200
+        );
+        assert_eq!(merged_height(&[], gap, PageMergeDirection::Vertical, 2.), 0);
+    }
+
+    #[test]
+    fn test_next_fit_ppi() {
+        // A quarter of the size needs half of the PPI.
+        assert!((next_fit_ppi(144., 400, 100) - 144. * 0.5 * 0.95).abs() < 1e-3);
+        // A slightly large image still lowers the PPI.
+        assert!(next_fit_ppi(144., 101, 100) <= 144. * 0.95);
+        assert_eq!(next_fit_ppi(144., 1_000_000, 1), MIN_FIT_PPI);
+    }
+
+    #[test]
+    fn test_sheet_layout_few_pages() {
+        let layout = SheetLayout::new([(10, 20)].into_iter(), 1, 4);
+        assert_eq!(layout.columns, 1);
+        assert_eq!(layout.size.0, 10 + 2 * SHEET_GAP);
+    }
+}
@@ -0,0 +1,412 @@
+//! The computation for pptx export.
+
+use super::*;
+use crate::model::ExportPptxTask;
+
+/// A task that exports the slides, i.e. pages, of the document to a PPTX
+/// presentation. Each page becomes a slide showing the rendered page as a
+/// full-bleed image, and the speaker notes queried by the `pdfpc-notes` label
+/// become the notes of the slide.
+pub struct PptxExport;
+
+impl<F: CompilerFeat> ExportComputation<F, TypstPagedDocument> for PptxExport {
+    type Output = Bytes;
+    type Config = ExportPptxTask;
+
+    fn run(
+        _graph: &Arc<WorldComputeGraph<F>>,
+        doc: &Arc<TypstPagedDocument>,
+        config: &ExportPptxTask,
+    ) -> Result<Bytes> {
+        Self::run_cancellable(doc, config, &CancellationToken::new())
+    }
+}
+
+impl PptxExport {
+    /// Exports the presentation, checking the `cancel` token between the
+    /// slides.
+    pub fn run_cancellable(
+        doc: &Arc<TypstPagedDocument>,
+        config: &ExportPptxTask,
+        cancel: &CancellationToken,
+    ) -> Result<Bytes> {
+        use rayon::prelude::*;
+
+        let ppi = config.ppi.to_f32();
+        if ppi <= 1e-6 {
+            bail!("invalid ppi: {ppi}");
+        }
+        let render_options = typst_render::RenderOptions {
+            pixel_per_pt: f64::from(ppi / 72.).into(),
+            ..Default::default()
+        };
+
+        let pages = doc.pages();
+        let images = pages
+            .par_iter()
+            .map(|page| {
+                check_cancelled(cancel)?;
+                typst_render::render(page, &render_options)
+                    .encode_png()
+                    .context_ut("failed to encode PNG")
+            })
+            .collect::<Result<Vec<_>>>()?;
+        check_cancelled(cancel)?;
+        let notes = speaker_notes(doc);
+
+        let slides = pages
+            .iter()
+            .zip(images)
+            .zip(notes)
+            .map(|((page, image), notes)| PptxSlide {
+                size: page.frame.size(),
+                image,
+                notes,
+            })
+            .collect::<Vec<_>>();
+        pptx_package(&slides).map(Bytes::new)
+    }
+}
+
+/// A slide of the PPTX presentation.
+struct PptxSlide {
+    /// The size of the page.
+    size: Size,
+    /// The rendered page in PNG.
+    image: Vec<u8>,
+    /// The speaker notes, of which lines become paragraphs.
+    notes: String,
+}
+
+/// The namespaces declared by the presentation parts.
+const PPTX_NS: &str = concat!(
+    r#"xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" "#,
+    r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" "#,
+    r#"xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main""#
+);
+const PPTX_XML_DECL: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#;
+const PPTX_REL_TYPE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const PPTX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument";
+const PPTX_EMPTY_TREE: &str = concat!(
+    r#"<p:nvGrpSpPr><p:cNvPr id="1" name=""/><p:cNvGrpSpPr/><p:nvPr/></p:nvGrpSpPr>"#,
+    "<p:grpSpPr/>"
+);
+const PPTX_CLR_MAP: &str = concat!(
+    r#"<p:clrMap bg1="lt1" tx1="dk1" bg2="lt2" tx2="dk2" accent1="accent1" "#,
+    r#"accent2="accent2" accent3="accent3" accent4="accent4" accent5="accent5" "#,
+    r#"accent6="accent6" hlink="hlink" folHlink="folHlink"/>"#
+);
+const PPTX_THEME: &str = concat!(
+    r#"<a:theme xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" name="Tinymist">"#,
+    r#"<a:themeElements><a:clrScheme name="Tinymist">"#,
+    r#"<a:dk1><a:srgbClr val="000000"/></a:dk1><a:lt1><a:srgbClr val="FFFFFF"/></a:lt1>"#,
+    r#"<a:dk2><a:srgbClr val="44546A"/></a:dk2><a:lt2><a:srgbClr val="E7E6E6"/></a:lt2>"#,
+    r#"<a:accent1><a:srgbClr val="4472C4"/></a:accent1><a:accent2><a:srgbClr val="ED7D31"/></a:accent2>"#,
+    r#"<a:accent3><a:srgbClr val="A5A5A5"/></a:accent3><a:accent4><a:srgbClr val="FFC000"/></a:accent4>"#,
+    r#"<a:accent5><a:srgbClr val="5B9BD5"/></a:accent5><a:accent6><a:srgbClr val="70AD47"/></a:accent6>"#,
+    r#"<a:hlink><a:srgbClr val="0563C1"/></a:hlink><a:folHlink><a:srgbClr val="954F72"/></a:folHlink>"#,
+    r#"</a:clrScheme><a:fontScheme name="Tinymist">"#,
+    r#"<a:majorFont><a:latin typeface="Calibri"/><a:ea typeface=""/><a:cs typeface=""/></a:majorFont>"#,
+    r#"<a:minorFont><a:latin typeface="Calibri"/><a:ea typeface=""/><a:cs typeface=""/></a:minorFont>"#,
+    r#"</a:fontScheme><a:fmtScheme name="Tinymist"><a:fillStyleLst>"#,
+    r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill>"#,
+    r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill>"#,
+    r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill>"#,
+    r#"</a:fillStyleLst><a:lnStyleLst>"#,
+    r#"<a:ln w="6350"><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:ln>"#,
+    r#"<a:ln w="12700"><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:ln>"#,
+    r#"<a:ln w="19050"><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:ln>"#,
+    r#"</a:lnStyleLst><a:effectStyleLst>"#,
+    r#"<a:effectStyle><a:effectLst/></a:effectStyle>"#,
+    r#"<a:effectStyle><a:effectLst/></a:effectStyle>"#,
+    r#"<a:effectStyle><a:effectLst/></a:effectStyle>"#,
+    r#"</a:effectStyleLst><a:bgFillStyleLst>"#,
+    r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill>"#,
+    r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill>"#,
+    r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill>"#,
+    r#"</a:bgFillStyleLst></a:fmtScheme></a:themeElements></a:theme>"#
+);
+
+/// Packs the slides into a minimal OOXML presentation. The slide size of the
+/// presentation follows the first slide, and the images of the slides in other
+/// sizes are stretched to fill it.
+fn pptx_package(slides: &[PptxSlide]) -> Result<Vec<u8>> {
+    use std::fmt::Write;
+    use std::io::Write as _;
+    use zip::write::FileOptions;
+
+    /// The EMUs (English Metric Units) per point.
+    const EMU_PER_PT: f64 = 12700.;
+    /// The bounds of the slide size accepted by PowerPoint, in EMUs.
+    const SLIDE_EMU: (i64, i64) = (914400, 51206400);
+
+    let Some(first) = slides.first() else {
+        bail!("no slides to export");
+    };
+    let emu =
+        |abs: Abs| ((abs.to_pt() * EMU_PER_PT).round() as i64).clamp(SLIDE_EMU.0, SLIDE_EMU.1);
+    let (cx, cy) = (emu(first.size.x), emu(first.size.y));
+
+    let rel = |id: &str, kind: &str, target: &str| {
+        format!(r#"<Relationship Id="{id}" Type="{PPTX_REL_TYPE}/{kind}" Target="{target}"/>"#)
+    };
+    let rels = |items: &[String]| {
+        format!(
+            r#"{PPTX_XML_DECL}<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{}</Relationships>"#,
+            items.concat()
+        )
+    };
+    let part = |path: &str, kind: &str| {
+        format!(r#"<Override PartName="/{path}" ContentType="{PPTX_CONTENT_TYPE}.{kind}+xml"/>"#)
+    };
+
+    let mut parts: Vec<(String, Vec<u8>)> = vec![];
+    let mut content_types = vec![
+        part("ppt/presentation.xml", "presentationml.presentation.main"),
+        part(
+            "ppt/slideMasters/slideMaster1.xml",
+            "presentationml.slideMaster",
+        ),
+        part(
+            "ppt/slideLayouts/slideLayout1.xml",
+            "presentationml.slideLayout",
+        ),
+        part(
+            "ppt/notesMasters/notesMaster1.xml",
+            "presentationml.notesMaster",
+        ),
+        part("ppt/theme/theme1.xml", "theme"),
+        part("ppt/theme/theme2.xml", "theme"),
+    ];
+
+    let mut slide_ids = String::new();
+    let mut presentation_rels = vec![
+        rel("rId1", "slideMaster", "slideMasters/slideMaster1.xml"),
+        rel("rId2", "notesMaster", "notesMasters/notesMaster1.xml"),
+        rel("rId3", "theme", "theme/theme1.xml"),
+    ];
+    for (idx, slide) in slides.iter().enumerate() {
+        let n = idx + 1;
+        let rid = format!("rId{}", n + 3);
+        let _ = write!(slide_ids, r#"<p:sldId id="{}" r:id="{rid}"/>"#, 255 + n);
+        presentation_rels.push(rel(&rid, "slide", &format!("slides/slide{n}.xml")));
+
+        let slide_path = format!("ppt/slides/slide{n}.xml");
+        content_types.push(part(&slide_path, "presentationml.slide"));
+        let picture = format!(
+            concat!(
+                r#"<p:pic><p:nvPicPr><p:cNvPr id="2" name="Page {n}"/>"#,
+                r#"<p:cNvPicPr><a:picLocks noChangeAspect="1"/></p:cNvPicPr><p:nvPr/></p:nvPicPr>"#,
+                r#"<p:blipFill><a:blip r:embed="rId2"/><a:stretch><a:fillRect/></a:stretch></p:blipFill>"#,
+                r#"<p:spPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm>"#,
+                r#"<a:prstGeom prst="rect"><a:avLst/></a:prstGeom></p:spPr></p:pic>"#
+            ),
+            n = n,
+            cx = cx,
+            cy = cy
+        );
+        parts.push((
+            slide_path,
+            format!(
+                r#"{PPTX_XML_DECL}<p:sld {PPTX_NS}><p:cSld><p:spTree>{PPTX_EMPTY_TREE}{picture}</p:spTree></p:cSld><p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sld>"#
+            )
+            .into_bytes(),
+        ));
+        parts.push((format!("ppt/media/image{n}.png"), slide.image.clone()));
+
+        let mut slide_rels = vec![
+            rel("rId1", "slideLayout", "../slideLayouts/slideLayout1.xml"),
+            rel("rId2", "image", &format!("../media/image{n}.png")),
+        ];
+        if !slide.notes.is_empty() {
+            let notes_path = format!("ppt/notesSlides/notesSlide{n}.xml");
+            content_types.push(part(&notes_path, "presentationml.notesSlide"));
+            slide_rels.push(rel(
+                "rId3",
+                "notesSlide",
+                &format!("../notesSlides/notesSlide{n}.xml"),
+            ));
+
+            let paragraphs = slide
+                .notes
+                .lines()
+                .map(|line| match line {
+                    "" => "<a:p/>".to_owned(),
+                    line => format!("<a:p><a:r><a:t>{}</a:t></a:r></a:p>", escape_xml(line)),
+                })
+                .collect::<String>();
+            let body = format!(
+                concat!(
+                    r#"<p:sp><p:nvSpPr><p:cNvPr id="2" name="Notes"/>"#,
+                    r#"<p:cNvSpPr><a:spLocks noGrp="1"/></p:cNvSpPr>"#,
+                    r#"<p:nvPr><p:ph type="body" idx="1"/></p:nvPr></p:nvSpPr><p:spPr/>"#,
+                    r#"<p:txBody><a:bodyPr/><a:lstStyle/>{}</p:txBody></p:sp>"#
+                ),
+                paragraphs
+            );
+            parts.push((
+                notes_path,
+                format!(
+                    r#"{PPTX_XML_DECL}<p:notes {PPTX_NS}><p:cSld><p:spTree>{PPTX_EMPTY_TREE}{body}</p:spTree></p:cSld><p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:notes>"#
+                )
+                .into_bytes(),
+            ));
+            parts.push((
+                format!("ppt/notesSlides/_rels/notesSlide{n}.xml.rels"),
+                rels(&[
+                    rel("rId1", "notesMaster", "../notesMasters/notesMaster1.xml"),
+                    rel("rId2", "slide", &format!("../slides/slide{n}.xml")),
+                ])
+                .into_bytes(),
+            ));
+        }
+        parts.push((
+            format!("ppt/slides/_rels/slide{n}.xml.rels"),
+            rels(&slide_rels).into_bytes(),
+        ));
+    }
+
+    let fixed_parts = [
+        (
+            "[Content_Types].xml".to_owned(),
+            format!(
+                concat!(
+                    r#"{}<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+                    r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+                    r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+                    r#"<Default Extension="png" ContentType="image/png"/>{}</Types>"#
+                ),
+                PPTX_XML_DECL,
+                content_types.concat()
+            ),
+        ),
+        (
+            "_rels/.rels".to_owned(),
+            rels(&[rel("rId1", "officeDocument", "ppt/presentation.xml")]),
+        ),
+        (
+            "ppt/presentation.xml".to_owned(),
+            format!(
+                concat!(
+                    r#"{PPTX_XML_DECL}<p:presentation {PPTX_NS}>"#,
+                    r#"<p:sldMasterIdLst><p:sldMasterId id="2147483648" r:id="rId1"/></p:sldMasterIdLst>"#,
+                    r#"<p:notesMasterIdLst><p:notesMasterId r:id="rId2"/></p:notesMasterIdLst>"#,
+                    r#"<p:sldIdLst>{slide_ids}</p:sldIdLst><p:sldSz cx="{cx}" cy="{cy}"/>"#,
+                    r#"<p:notesSz cx="6858000" cy="9144000"/></p:presentation>"#
+                ),
+                PPTX_XML_DECL = PPTX_XML_DECL,
+                PPTX_NS = PPTX_NS,
+                slide_ids = slide_ids,
+                cx = cx,
+                cy = cy
+            ),
+        ),
+        (
+            "ppt/_rels/presentation.xml.rels".to_owned(),
+            rels(&presentation_rels),
+        ),
+        (
+            "ppt/slideMasters/slideMaster1.xml".to_owned(),
+            format!(
+                concat!(
+                    r#"{}<p:sldMaster {}><p:cSld><p:spTree>{}</p:spTree></p:cSld>{}"#,
+                    r#"<p:sldLayoutIdLst><p:sldLayoutId id="2147483649" r:id="rId1"/></p:sldLayoutIdLst>"#,
+                    r#"</p:sldMaster>"#
+                ),
+                PPTX_XML_DECL, PPTX_NS, PPTX_EMPTY_TREE, PPTX_CLR_MAP
+            ),
+        ),
+        (
+            "ppt/slideMasters/_rels/slideMaster1.xml.rels".to_owned(),
+            rels(&[
+                rel("rId1", "slideLayout", "../slideLayouts/slideLayout1.xml"),
+                rel("rId2", "theme", "../theme/theme1.xml"),
+            ]),
+        ),
+        (
+            "ppt/slideLayouts/slideLayout1.xml".to_owned(),
+            format!(
+                r#"{PPTX_XML_DECL}<p:sldLayout {PPTX_NS} type="blank" preserve="1"><p:cSld name="Blank"><p:spTree>{PPTX_EMPTY_TREE}</p:spTree></p:cSld><p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sldLayout>"#
+            ),
+        ),
+        (
+            "ppt/slideLayouts/_rels/slideLayout1.xml.rels".to_owned(),
+            rels(&[rel(
+                "rId1",
+                "slideMaster",
+                "../slideMasters/slideMaster1.xml",
+            )]),
+        ),
+        (
+            "ppt/notesMasters/notesMaster1.xml".to_owned(),
+            format!(
+                r#"{PPTX_XML_DECL}<p:notesMaster {PPTX_NS}><p:cSld><p:spTree>{PPTX_EMPTY_TREE}</p:spTree></p:cSld>{PPTX_CLR_MAP}</p:notesMaster>"#
+            ),
+        ),
+        (
+            "ppt/notesMasters/_rels/notesMaster1.xml.rels".to_owned(),
+            rels(&[rel("rId1", "theme", "../theme/theme2.xml")]),
+        ),
+        (
+            "ppt/theme/theme1.xml".to_owned(),
+            format!("{PPTX_XML_DECL}{PPTX_THEME}"),
+        ),
+        (
+            "ppt/theme/theme2.xml".to_owned(),
+            format!("{PPTX_XML_DECL}{PPTX_THEME}"),
+        ),
+    ];
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let deflated = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    // The images are already compressed.
+    let stored = FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    let fixed_parts = fixed_parts.into_iter().map(|(p, c)| (p, c.into_bytes()));
+    for (path, content) in fixed_parts.chain(parts) {
+        let options = if path.ends_with(".png") {
+            stored
+        } else {
+            deflated
+        };
+        zip.start_file(path, options)
+            .context("failed to write PPTX")?;
+        zip.write_all(&content).context("failed to write PPTX")?;
+    }
+    let cursor = zip.finish().context("failed to write PPTX")?;
+    Ok(cursor.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pptx_package() {
+        let slide = |notes: &str| PptxSlide {
+            size: Size::new(Abs::pt(720.), Abs::pt(405.)),
+            image: vec![0x89, b'P', b'N', b'G'],
+            notes: notes.to_owned(),
+        };
+        let package = pptx_package(&[slide("First & <only>\nline two"), slide("")]).unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(package)).unwrap();
+        let mut read = |path: &str| {
+            use std::io::Read;
+            let mut file = archive.by_name(path).ok()?;
+            let mut content = String::new();
+            file.read_to_string(&mut content).ok()?;
+            Some(content)
+        };
+
+        let presentation = read("ppt/presentation.xml").unwrap();
+        assert!(presentation.contains(r#"<p:sldSz cx="9144000" cy="5143500"/>"#));
+        assert!(presentation.contains(r#"<p:sldId id="257" r:id="rId5"/>"#));
+
+        let notes = read("ppt/notesSlides/notesSlide1.xml").unwrap();
+        assert!(notes.contains("<a:t>First &amp; &lt;only&gt;</a:t>"));
+        assert!(notes.contains("<a:t>line two</a:t>"));
+        assert!(read("ppt/notesSlides/notesSlide2.xml").is_none());
+        let slide_rels = read("ppt/slides/_rels/slide2.xml.rels").unwrap();
+        assert!(!slide_rels.contains("notesSlide"));
+    }
+}
@@ -1,5 +1,6 @@
 //! The computation for document query.
 
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 
 use comemo::Track;
@@ -11,6 +12,7 @@ use typst::World;
 use typst::diag::{SourceResult, StrResult};
 use typst::engine::Sink;
 use typst::foundations::{Content, Context, IntoValue, LocatableSelector, Output, Scope, Value};
+use typst::introspection::Introspector;
 use typst::model::Document;
 use typst::routines::SpanMode;
 use typst::syntax::Span;
@@ -59,25 +61,77 @@ impl DocumentQuery {
             .collect::<Vec<_>>())
     }
 
-    fn run_inner<F: CompilerFeat, D: Document + Output>(
+    fn retrieve_values<F: CompilerFeat, D: Document + Output>(
         g: &Arc<WorldComputeGraph<F>>,
         doc: &Arc<D>,
-        config: &QueryTask,
-    ) -> Result<Vec<Value>> {
-        let selector = &config.selector;
-        let elements = Self::retrieve(&g.snap.world, selector, doc.as_ref())
+        selector: &str,
+        field: Option<&str>,
+        one: bool,
+        page: Option<NonZeroUsize>,
+    ) -> Result<serde_json::Value> {
+        let mut elements = Self::retrieve(&g.snap.world, selector, doc.as_ref())
             .map_err(|e| anyhow::anyhow!("failed to retrieve: {e}"))?;
-        if config.one && elements.len() != 1 {
+        if let Some(page) = page {
+            let introspector = doc.introspector();
+            elements.retain(|elem| {
+                let pos = elem.location().and_then(|loc| introspector.position(loc));
+                pos.is_some_and(|pos| pos.as_paged_or_default().page == page)
+            });
+
+            // No elements on the page, e.g. the page is out of range, results
+            // in an empty result rather than an error.
+            if elements.is_empty() {
+                return Ok(if one {
+                    serde_json::Value::Null
+                } else {
+                    serde_json::Value::Array(vec![])
+                });
+            }
+        }
+        if one && elements.len() != 1 {
             bail!("expected exactly one element, found {}", elements.len());
         }
 
-        Ok(elements
+        let path = field.map(field_path);
+        let mapped: Vec<Value> = elements
             .into_iter()
-            .filter_map(|c| match &config.field {
-                Some(field) => c.get_by_name(field).ok(),
+            .filter_map(|c| match &path {
+                Some(path) => get_by_path(&c, path),
                 _ => Some(c.into_value()),
             })
-            .collect())
+            .collect();
+
+        let res = if one {
+            let Some(value) = mapped.first() else {
+                bail!("no such field found for element");
+            };
+            serde_json::to_value(value)
+        } else {
+            serde_json::to_value(&mapped)
+        };
+
+        res.context("failed to serialize")
+    }
+
+    fn run_inner<F: CompilerFeat, D: Document + Output>(
+        g: &Arc<WorldComputeGraph<F>>,
+        doc: &Arc<D>,
+        config: &QueryTask,
+    ) -> Result<serde_json::Value> {
+        // All the selectors are queried on the same compiled document.
+        let mut results = config
+            .selectors()
+            .map(|(selector, field, one)| {
+                let value = Self::retrieve_values(g, doc, selector, field, one, config.page)?;
+                Ok((selector.to_owned(), value))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if config.selector.is_many() {
+            Ok(serde_json::Value::Object(results.into_iter().collect()))
+        } else {
+            Ok(results.pop().map(|(_, value)| value).unwrap_or_default())
+        }
     }
 
     /// Queries the document and returns the result as a value.
@@ -98,18 +152,7 @@ impl DocumentQuery {
         doc: &Arc<D>,
         config: &QueryTask,
     ) -> Result<serde_json::Value> {
-        let mapped = Self::run_inner(g, doc, config)?;
-
-        let res = if config.one {
-            let Some(value) = mapped.first() else {
-                bail!("no such field found for element");
-            };
-            serde_json::to_value(value)
-        } else {
-            serde_json::to_value(&mapped)
-        };
-
-        res.context("failed to serialize")
+        Self::run_inner(g, doc, config)
     }
 }
 
@@ -123,23 +166,58 @@ impl<F: CompilerFeat, D: Document + Output> ExportComputation<F, D> for Document
         config: &QueryTask,
     ) -> Result<SourceResult<String>> {
         let pretty = false;
-        let mapped = Self::run_inner(g, doc, config)?;
+        let value = Self::run_inner(g, doc, config)?;
 
-        let res = if config.one {
-            let Some(value) = mapped.first() else {
-                bail!("no such field found for element");
-            };
-            serialize(value, &config.format, pretty)
-        } else {
-            serialize(&mapped, &config.format, pretty)
-        };
+        serialize(&value, &config.format, pretty).map(Ok)
+    }
+}
 
-        res.map(Ok)
+/// Splits a dotted field path into keys, where `\.` and `\\` escape a dot and
+/// a backslash in a key.
+pub(crate) fn field_path(field: &str) -> Vec<String> {
+    let mut keys = vec![String::new()];
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(c @ ('.' | '\\')) => keys.last_mut().unwrap().push(c),
+                Some(c) => keys.last_mut().unwrap().extend(['\\', c]),
+                None => keys.last_mut().unwrap().push('\\'),
+            },
+            '.' => keys.push(String::new()),
+            c => keys.last_mut().unwrap().push(c),
+        }
+    }
+    keys
+}
+
+/// Gets the field of the element by the path. The element is skipped if it
+/// doesn't have the first field, while a missing intermediate field results in
+/// `none`.
+fn get_by_path(elem: &Content, path: &[String]) -> Option<Value> {
+    let (first, rest) = path.split_first()?;
+    let value = elem.get_by_name(first).ok()?;
+    Some(rest.iter().fold(value, |value, key| {
+        field_of(&value, key).unwrap_or(Value::None)
+    }))
+}
+
+/// Gets the field of a content or dictionary, or the item of an array by index.
+fn field_of(value: &Value, key: &str) -> Option<Value> {
+    match value {
+        Value::Content(content) => content.get_by_name(key).ok(),
+        Value::Dict(dict) => dict.get(key).ok().cloned(),
+        Value::Array(array) => array.iter().nth(key.parse().ok()?).cloned(),
+        _ => None,
     }
 }
 
 /// Serialize data to the output format.
-fn serialize(data: &impl serde::Serialize, format: &str, pretty: bool) -> Result<String> {
+pub(crate) fn serialize(
+    data: &impl serde::Serialize,
+    format: &str,
+    pretty: bool,
+) -> Result<String> {
     Ok(match format {
         "json" if pretty => serde_json::to_string_pretty(data).context("serialize query")?,
         "json" => serde_json::to_string(data).context("serialize query")?,
@@ -165,3 +243,21 @@ fn serialize(data: &impl serde::Serialize, format: &str, pretty: bool) -> Result
         _ => bail!("unsupported format for query: {format}"),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_path() {
+        assert_eq!(field_path("value"), vec!["value"]);
+        assert_eq!(
+            field_path("value.caption.body"),
+            vec!["value", "caption", "body"]
+        );
+        assert_eq!(field_path(r"value.a\.b"), vec!["value", "a.b"]);
+        assert_eq!(field_path(r"value.a\\.b"), vec!["value", r"a\", "b"]);
+        assert_eq!(field_path(r"value.a\b"), vec!["value", r"a\b"]);
+        assert_eq!(field_path("value..b"), vec!["value", "", "b"]);
+    }
+}
@@ -0,0 +1,96 @@
+//! Extracts the speaker notes of a compiled presentation.
+
+use tinymist_std::typst::TypstPagedDocument;
+use typst::foundations::{Label, Repr, Selector, Value};
+use typst::introspection::{Introspector, MetadataElem};
+use typst::utils::PicoStr;
+
+/// The label of the metadata holding the speaker notes, which is also queried
+/// to create pdfpc files.
+const NOTES_LABEL: &str = "pdfpc-notes";
+
+/// Collects the speaker notes of each slide, i.e. page, of the document. The
+/// notes on the same slide are joined by newlines, and a slide without notes
+/// gets an empty string, so that the indices of the notes match the slides.
+pub fn speaker_notes(doc: &TypstPagedDocument) -> Vec<String> {
+    let mut notes = vec![String::new(); doc.pages().len()];
+    let Some(label) = Label::new(PicoStr::intern(NOTES_LABEL)) else {
+        return notes;
+    };
+
+    let introspector: &dyn Introspector = doc.introspector().as_ref();
+    for elem in introspector.query(&Selector::Label(label)).iter() {
+        let Some(metadata) = elem.to_packed::<MetadataElem>() else {
+            continue;
+        };
+        let Some(pos) = elem.location().and_then(|loc| introspector.position(loc)) else {
+            continue;
+        };
+        let page = pos.as_paged_or_default().page.get();
+        let Some(slide) = notes.get_mut(page - 1) else {
+            continue;
+        };
+
+        let text = note_text(&metadata.value);
+        if text.is_empty() {
+            continue;
+        }
+        if !slide.is_empty() {
+            slide.push('\n');
+        }
+        slide.push_str(&text);
+    }
+
+    notes
+}
+
+/// Converts the value of the notes to text.
+fn note_text(value: &Value) -> String {
+    match value {
+        Value::None => String::new(),
+        Value::Str(text) => text.to_string(),
+        Value::Content(content) => content.plain_text().to_string(),
+        value => value.repr().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tinymist_world::mock::MockWorkspaceWorldExt;
+    use tinymist_world::vfs::mock::MockWorkspace;
+
+    use super::*;
+
+    fn notes_of(source: &str) -> Vec<String> {
+        let workspace = MockWorkspace::default_builder()
+            .file("main.typ", source)
+            .build();
+        let world = workspace.world("main.typ").build_world().unwrap();
+        let doc = typst::compile::<TypstPagedDocument>(&world)
+            .output
+            .expect("failed to compile");
+        speaker_notes(&doc)
+    }
+
+    #[test]
+    fn test_speaker_notes() {
+        let notes = notes_of(
+            r#"First
+#metadata("Intro") <pdfpc-notes>
+#metadata([*Key* point]) <pdfpc-notes>
+#pagebreak()
+Second
+#pagebreak()
+Third
+#metadata(none) <pdfpc-notes>
+#metadata(3) <pdfpc-notes>"#,
+        );
+        assert_eq!(notes, ["Intro\nKey point", "", "3"]);
+    }
+
+    #[test]
+    fn test_no_speaker_notes() {
+        let notes = notes_of("First\n#pagebreak()\nSecond");
+        assert_eq!(notes, ["", ""]);
+    }
+}
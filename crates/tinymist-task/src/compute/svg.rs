@@ -1,15 +1,45 @@
 //! The computation for svg export.
 
-use std::sync::Arc;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+use std::ops::Range;
+use std::sync::{Arc, LazyLock};
 
+use base64::Engine;
+use parking_lot::Mutex;
 use tinymist_std::error::prelude::*;
+use tinymist_std::hash::hash128;
 use tinymist_std::typst::TypstPagedDocument;
 use tinymist_world::{CompilerFeat, ExportComputation, WorldComputeGraph};
+use tokio_util::sync::CancellationToken;
+use typst::foundations::Label;
+use typst::layout::{Abs, Frame, FrameItem, GroupItem, Size};
 use typst::model::Document;
+use typst::text::{Font, FontStyle, TextItem};
+use typst::utils::PicoStr;
+use typst::visualize::Paint;
+use typst_layout::Page;
+use typst_svg::SvgOptions;
 
-use crate::compute::{parse_length, select_pages};
-use crate::model::ExportSvgTask;
-use crate::{ImageOutput, PageMerge, PagedOutput};
+use super::woff2::woff2_subset;
+use crate::compute::{
+    check_cancelled, escape_xml, merge_pages_horizontally, parse_length, select_pages,
+};
+use crate::model::{ExportAnimatedSvgTask, ExportSvgTask, SvgTextMode};
+use crate::{ImageOutput, PageMerge, PageMergeDirection, PagedOutput};
+
+/// The number of exports after which an unused page is evicted from the cache.
+const PAGE_CACHE_MAX_AGE: usize = 10;
+
+/// The svg of the pages rendered by previous exports, keyed by the hash of the
+/// page, with the number of exports since the page was last used.
+static PAGE_CACHE: LazyLock<Mutex<HashMap<u128, (usize, String)>>> =
+    LazyLock::new(Default::default);
+
+/// Clears the svg of the pages cached by previous exports.
+pub fn clear_svg_page_cache() {
+    PAGE_CACHE.lock().clear();
+}
 
 /// The computation for svg export.
 pub struct SvgExport;
@@ -23,38 +53,520 @@ impl<F: CompilerFeat> ExportComputation<F, TypstPagedDocument> for SvgExport {
         doc: &Arc<TypstPagedDocument>,
         config: &ExportSvgTask,
     ) -> Result<Self::Output> {
-        let svg_options = typst_svg::SvgOptions::default();
+        Self::run_cancellable(doc, config, &CancellationToken::new())
+    }
+}
+
+impl SvgExport {
+    /// Exports the svg, checking the `cancel` token between the pages.
+    pub fn run_cancellable(
+        doc: &Arc<TypstPagedDocument>,
+        config: &ExportSvgTask,
+        cancel: &CancellationToken,
+    ) -> Result<ImageOutput<String>> {
+        let svg_options = SvgOptions::default();
+        let text_mode = config.text_mode;
         let exported_pages = select_pages(doc, &config.pages);
-        if let Some(PageMerge { ref gap }) = config.merge {
-            // Typst does not expose svg-merging API.
-            // Therefore, we have to create a dummy document here.
-            let dummy_doc = TypstPagedDocument::new(
-                exported_pages
-                    .into_iter()
-                    .map(|(_, page)| page.clone())
-                    .collect(),
-                doc.info().clone(),
-            );
+        if let Some(PageMerge { ref gap, direction }) = config.merge {
             let gap = gap
                 .as_ref()
                 .and_then(|gap| parse_length(gap).ok())
                 .unwrap_or_default();
-            let svg = typst_svg::svg_merged(&dummy_doc, &svg_options, gap);
+            let svg = match direction {
+                PageMergeDirection::Vertical => {
+                    let mut layer = TextLayer::new(text_mode);
+                    let pages = exported_pages
+                        .into_iter()
+                        .map(|(_, page)| {
+                            check_cancelled(cancel)?;
+                            Ok(layer.strip_page(page))
+                        })
+                        .collect::<Result<_>>()?;
+
+                    // Typst does not expose svg-merging API.
+                    // Therefore, we have to create a dummy document here.
+                    let dummy_doc = TypstPagedDocument::new(pages, doc.info().clone());
+                    layer.finish(typst_svg::svg_merged(&dummy_doc, &svg_options, gap))
+                }
+                PageMergeDirection::Horizontal => {
+                    let merged =
+                        merge_pages_horizontally(&exported_pages, gap, Page::fill_or_transparent)?;
+                    check_cancelled(cancel)?;
+                    svg_page(&merged, &svg_options, text_mode)
+                }
+            };
             Ok(ImageOutput::Merged(svg))
         } else {
-            let exported = exported_pages
-                .into_iter()
-                .map(|(i, page)| {
-                    let svg = typst_svg::svg(page, &svg_options);
-                    Ok(PagedOutput {
-                        page: i,
-                        value: svg,
-                    })
-                })
-                .collect::<Result<Vec<_>>>()?;
+            let mut exported = vec![];
+            Self::run_paged(doc, config, cancel, |page, svg| {
+                exported.push(PagedOutput {
+                    page,
+                    value: svg.to_owned(),
+                });
+                Ok(())
+            })?;
             Ok(ImageOutput::Paged(exported))
         }
     }
+
+    /// Exports the selected pages one by one, passing each page with its index
+    /// to the `sink` as soon as it is rendered, so that the exported pages are
+    /// not collected in memory, except for the svg cached for the next
+    /// exports. The pages are never merged.
+    pub fn run_paged(
+        doc: &Arc<TypstPagedDocument>,
+        config: &ExportSvgTask,
+        cancel: &CancellationToken,
+        mut sink: impl FnMut(usize, &str) -> Result<()>,
+    ) -> Result<()> {
+        let svg_options = SvgOptions::default();
+        let text_mode = config.text_mode;
+        for (age, _) in PAGE_CACHE.lock().values_mut() {
+            *age += 1;
+        }
+
+        let res = select_pages(doc, &config.pages)
+            .into_iter()
+            .try_for_each(|(i, page)| {
+                check_cancelled(cancel)?;
+                // The hash of a page covers its frame, so an unchanged page
+                // reuses the svg rendered by a previous export.
+                let svg = {
+                    let mut cache = PAGE_CACHE.lock();
+                    let (age, svg) = cache
+                        .entry(hash128(&(page, text_mode)))
+                        .or_insert_with(|| (0, svg_page(page, &svg_options, text_mode)));
+                    *age = 0;
+                    svg.clone()
+                };
+                sink(i, &svg)
+            });
+
+        PAGE_CACHE
+            .lock()
+            .retain(|_, (age, _)| *age <= PAGE_CACHE_MAX_AGE);
+        res
+    }
+}
+
+/// The computation for animated svg export.
+pub struct AnimatedSvgExport;
+
+impl<F: CompilerFeat> ExportComputation<F, TypstPagedDocument> for AnimatedSvgExport {
+    type Output = String;
+    type Config = ExportAnimatedSvgTask;
+
+    fn run(
+        _graph: &Arc<WorldComputeGraph<F>>,
+        doc: &Arc<TypstPagedDocument>,
+        config: &ExportAnimatedSvgTask,
+    ) -> Result<Self::Output> {
+        Self::run_cancellable(doc, config, &CancellationToken::new())
+    }
+}
+
+impl AnimatedSvgExport {
+    /// Exports the animated svg, checking the `cancel` token between the
+    /// frames.
+    pub fn run_cancellable(
+        doc: &Arc<TypstPagedDocument>,
+        config: &ExportAnimatedSvgTask,
+        cancel: &CancellationToken,
+    ) -> Result<String> {
+        let duration = config.frame_duration.to_f32();
+        if duration <= 1e-6 {
+            bail!("invalid frame duration: {duration}");
+        }
+
+        let svg_options = SvgOptions::default();
+        let exported_pages = select_pages(doc, &config.pages);
+        if exported_pages.is_empty() {
+            bail!("no pages to export");
+        }
+        animated_svg(
+            &exported_pages,
+            &svg_options,
+            duration,
+            config.repeat,
+            cancel,
+        )
+    }
+}
+
+/// Stacks the pages as frames of an svg, each shown for `duration` seconds
+/// by switching the visibility with SMIL animations. The first page is shown
+/// by viewers that don't play the animations.
+///
+/// The pages are nested as svg elements in the same canvas, which is as large
+/// as the largest page.
+fn animated_svg(
+    pages: &[(usize, &Page)],
+    options: &SvgOptions,
+    duration: f32,
+    repeat: bool,
+    cancel: &CancellationToken,
+) -> Result<String> {
+    let width = pages
+        .iter()
+        .map(|(_, page)| page.frame.width())
+        .fold(Abs::zero(), Abs::max);
+    let height = pages
+        .iter()
+        .map(|(_, page)| page.frame.height())
+        .fold(Abs::zero(), Abs::max);
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<svg class="typst-doc" viewBox="0 0 {w} {h}" width="{w}pt" height="{h}pt" xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">"#,
+        w = width.to_pt(),
+        h = height.to_pt(),
+    );
+
+    let n = pages.len();
+    let total = duration * n as f32;
+    for (idx, (_, page)) in pages.iter().enumerate() {
+        check_cancelled(cancel)?;
+        let visibility = if idx == 0 { "visible" } else { "hidden" };
+        let _ = write!(svg, r#"<g visibility="{visibility}">"#);
+
+        if n > 1 {
+            let start = idx as f32 / n as f32;
+            let end = (idx + 1) as f32 / n as f32;
+            // A discrete animation holds each value until the next key time.
+            let (values, key_times) = match idx {
+                0 => ("visible;hidden".to_owned(), format!("0;{end}")),
+                // The last page stays if the animation doesn't repeat.
+                _ if idx == n - 1 && !repeat => ("hidden;visible".to_owned(), format!("0;{start}")),
+                _ => (
+                    "hidden;visible;hidden".to_owned(),
+                    format!("0;{start};{end}"),
+                ),
+            };
+            let repeat_count = if repeat { "indefinite" } else { "1" };
+            let _ = write!(
+                svg,
+                r#"<animate attributeName="visibility" values="{values}" keyTimes="{key_times}" calcMode="discrete" dur="{total}s" repeatCount="{repeat_count}" fill="freeze"/>"#,
+            );
+        }
+
+        let page_svg = svg_page(page, options, SvgTextMode::Paths);
+        // Drops the xml declaration if any, which is not allowed in the middle
+        // of a document.
+        let page_svg = page_svg
+            .find("<svg")
+            .map_or(page_svg.as_str(), |start| &page_svg[start..]);
+        svg.push_str(page_svg);
+        svg.push_str("</g>");
+    }
+
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+/// Renders the page to svg, writing the text as specified by `text_mode`.
+pub fn svg_page(page: &Page, options: &SvgOptions, text_mode: SvgTextMode) -> String {
+    if text_mode.is_paths() {
+        return typst_svg::svg(page, options);
+    }
+
+    let mut layer = TextLayer::new(text_mode);
+    let page = layer.strip_page(page);
+    layer.finish(typst_svg::svg(&page, options))
+}
+
+/// The prefix of the labels marking the places of the text runs taken out of
+/// the pages.
+const TEXT_MARKER: &str = "tinymist-svg-text-";
+
+/// The selectable text of an svg. The text runs are taken out of the pages,
+/// leaving labelled empty groups as markers, and are written in place of the
+/// markers, so that the painting order is kept.
+struct TextLayer {
+    /// How to write the text.
+    mode: SvgTextMode,
+    /// The fonts used by the text, whose indices name the inlined font faces,
+    /// with the used glyphs and characters.
+    fonts: Vec<(Font, BTreeSet<u16>, BTreeSet<char>)>,
+    /// The text elements, whose indices name the markers.
+    runs: Vec<String>,
+}
+
+impl TextLayer {
+    fn new(mode: SvgTextMode) -> Self {
+        Self {
+            mode,
+            fonts: Vec::new(),
+            runs: Vec::new(),
+        }
+    }
+
+    /// Takes the text out of the page.
+    fn strip_page(&mut self, page: &Page) -> Page {
+        let mut page = page.clone();
+        if !self.mode.is_paths() {
+            page.frame = self.strip_frame(&page.frame);
+        }
+        page
+    }
+
+    fn strip_frame(&mut self, frame: &Frame) -> Frame {
+        let mut stripped = frame.clone();
+        stripped.clear();
+
+        for (pos, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => {
+                    let group = GroupItem {
+                        frame: self.strip_frame(&group.frame),
+                        ..group.clone()
+                    };
+                    stripped.push(*pos, FrameItem::Group(group));
+                }
+                FrameItem::Text(text) => match self.push_text(text) {
+                    Some(marker) => stripped.push(*pos, FrameItem::Group(marker)),
+                    None => stripped.push(*pos, item.clone()),
+                },
+                _ => stripped.push(*pos, item.clone()),
+            }
+        }
+
+        stripped
+    }
+
+    /// Writes the text as a text element, returning the marker to place at the
+    /// position of the text. The text that cannot be written as a text
+    /// element, e.g. filled with a gradient, is left as paths.
+    fn push_text(&mut self, text: &TextItem) -> Option<GroupItem> {
+        let Paint::Solid(color) = &text.fill else {
+            return None;
+        };
+        if text.stroke.is_some() {
+            return None;
+        }
+
+        let label = format!("{TEXT_MARKER}{}", self.runs.len());
+        let mut marker = GroupItem::new(Frame::soft(Size::zero()));
+        marker.label = Some(Label::new(PicoStr::intern(&label))?);
+
+        let font = text.font.font();
+        let family = escape_xml(&font.info().family);
+        let family = match self.mode {
+            SvgTextMode::Embed => {
+                let idx = match self.fonts.iter().position(|(f, ..)| f == font) {
+                    Some(idx) => idx,
+                    None => {
+                        self.fonts
+                            .push((font.clone(), BTreeSet::new(), BTreeSet::new()));
+                        self.fonts.len() - 1
+                    }
+                };
+                let (_, glyphs, chars) = &mut self.fonts[idx];
+                glyphs.extend(text.glyphs.iter().map(|glyph| glyph.id));
+                chars.extend(text.text.chars());
+                // Falls back to the family if the font cannot be inlined.
+                format!("'typst-font-{idx}', '{family}'")
+            }
+            _ => format!("'{family}'"),
+        };
+        let variant = font.info().variant;
+        let style = match variant.style {
+            FontStyle::Normal => "normal",
+            FontStyle::Italic => "italic",
+            FontStyle::Oblique => "oblique",
+        };
+
+        // The marker is placed at the baseline of the text.
+        let mut run = String::new();
+        let _ = write!(
+            run,
+            r#"<text class="tinymist-text" xml:space="preserve" font-family="{family}" font-size="{}" font-weight="{}" font-style="{style}" fill="{}">"#,
+            text.size.to_pt(),
+            variant.weight.to_number(),
+            color.to_hex(),
+        );
+
+        let mut x = 0.;
+        let mut last_range = None;
+        for glyph in &text.glyphs {
+            let range = glyph.range();
+            // A cluster shaped into multiple glyphs is written once.
+            if last_range.as_ref() != Some(&range) {
+                let dx = glyph.x_offset.at(text.size).to_pt();
+                let dy = glyph.y_offset.at(text.size).to_pt();
+                let _ = write!(
+                    run,
+                    r#"<tspan x="{}" y="{}">{}</tspan>"#,
+                    x + dx,
+                    -dy,
+                    escape_xml(&text.text[range.clone()]),
+                );
+                last_range = Some(range);
+            }
+            x += glyph.x_advance.at(text.size).to_pt();
+        }
+
+        run.push_str("</text>");
+        self.runs.push(run);
+        Some(marker)
+    }
+
+    /// Writes the text into the svg rendered from the stripped pages, replacing
+    /// the markers.
+    fn finish(self, svg: String) -> String {
+        if self.runs.is_empty() {
+            return svg;
+        }
+
+        let mut out = String::with_capacity(svg.len());
+        let mut cursor = 0;
+        let pattern = format!(r#"data-typst-label="{TEXT_MARKER}"#);
+        for (at, _) in svg.match_indices(&pattern) {
+            let Some((range, idx)) = marker_range(&svg, at, pattern.len()) else {
+                continue;
+            };
+            let Some(run) = self.runs.get(idx).filter(|_| range.start >= cursor) else {
+                continue;
+            };
+            out.push_str(&svg[cursor..range.start]);
+            out.push_str(run);
+            cursor = range.end;
+        }
+        out.push_str(&svg[cursor..]);
+
+        if !self.fonts.is_empty() {
+            let mut style = String::from("<style>");
+            for (idx, (font, glyphs, chars)) in self.fonts.iter().enumerate() {
+                let woff2 = match woff2_subset(font, glyphs, chars) {
+                    Ok(woff2) => woff2,
+                    Err(err) => {
+                        log::warn!("cannot inline font {}: {err}", font.info().family);
+                        continue;
+                    }
+                };
+                let data = base64::engine::general_purpose::STANDARD.encode(woff2);
+                let _ = write!(
+                    style,
+                    "@font-face{{font-family:'typst-font-{idx}';src:url(data:font/woff2;base64,{data}) format('woff2')}}"
+                );
+            }
+            style.push_str("</style>");
+
+            match out.rfind("</svg>") {
+                Some(end) => out.insert_str(end, &style),
+                None => out.push_str(&style),
+            }
+        }
+        out
+    }
+}
+
+/// Finds the range of the marker element whose label attribute starts at `at`
+/// with the prefix of `prefix_len` bytes, and the index of the text run.
+fn marker_range(svg: &str, at: usize, prefix_len: usize) -> Option<(Range<usize>, usize)> {
+    let digits = &svg[at + prefix_len..];
+    let digits = &digits[..digits.find('"')?];
+    let idx = digits.parse().ok()?;
+
+    let start = svg[..at].rfind("<g")?;
+    let open_end = at + svg[at..].find('>')?;
+    let end = if svg[..open_end].ends_with('/') {
+        open_end + 1
+    } else {
+        let close = open_end + svg[open_end..].find("</g>")?;
+        close + "</g>".len()
+    };
+
+    Some((start..end, idx))
+}
+
+#[cfg(test)]
+mod tests {
+    use tinymist_world::mock::MockWorkspaceWorldExt;
+    use tinymist_world::vfs::mock::MockWorkspace;
+
+    use super::*;
+
+    fn compile(source: &str) -> Arc<TypstPagedDocument> {
+        let workspace = MockWorkspace::default_builder()
+            .file("main.typ", source)
+            .build();
+        let world = workspace.world("main.typ").build_world().unwrap();
+        let doc = typst::compile::<TypstPagedDocument>(&world)
+            .output
+            .expect("failed to compile");
+        Arc::new(doc)
+    }
+
+    fn render(source: &str, text_mode: SvgTextMode) -> String {
+        let doc = compile(source);
+        svg_page(&doc.pages()[0], &SvgOptions::default(), text_mode)
+    }
+
+    #[test]
+    fn test_svg_cancelled() {
+        let doc = compile("A\n#pagebreak()\nB");
+        let config = ExportSvgTask::default();
+
+        let cancel = CancellationToken::new();
+        let output = SvgExport::run_cancellable(&doc, &config, &cancel).unwrap();
+        assert!(matches!(output, ImageOutput::Paged(pages) if pages.len() == 2));
+
+        cancel.cancel();
+        assert!(SvgExport::run_cancellable(&doc, &config, &cancel).is_err());
+    }
+
+    #[test]
+    fn test_svg_text_stripped() {
+        let svg = render("Hello", SvgTextMode::Reference);
+        assert!(svg.contains(r#"<text class="tinymist-text""#));
+        assert!(svg.contains(">H</tspan>"));
+        assert!(svg.contains("font-family=\"'Libertinus Serif'\""));
+        assert!(!svg.contains(TEXT_MARKER));
+        assert!(!svg.contains("@font-face"));
+
+        let svg = render("Hello", SvgTextMode::Paths);
+        assert!(!svg.contains("<tspan"));
+    }
+
+    #[test]
+    fn test_svg_text_painting_order() {
+        let source = r##"#set page(width: 100pt, height: 50pt, margin: 0pt)
+#place(rect(width: 10pt, height: 10pt, fill: rgb("#00ff00")))
+Hello
+#place(rect(width: 10pt, height: 10pt, fill: rgb("#ff0000")))"##;
+        let svg = render(source, SvgTextMode::Reference);
+
+        let below = svg.find("#00ff00").expect("the shape below the text");
+        let text = svg.find(">H</tspan>").expect("the text");
+        let above = svg.find("#ff0000").expect("the shape above the text");
+        assert!(below < text && text < above, "{svg}");
+    }
+
+    #[test]
+    fn test_svg_text_embed() {
+        let source = "#set text(font: \"DejaVu Sans Mono\")\nHello";
+        let svg = render(source, SvgTextMode::Embed);
+        assert!(svg.contains("font-family=\"'typst-font-0', 'DejaVu Sans Mono'\""));
+
+        let prefix = "data:font/woff2;base64,";
+        let start = svg.find(prefix).expect("an inlined font") + prefix.len();
+        let end = start + svg[start..].find(')').unwrap();
+        let woff2 = base64::engine::general_purpose::STANDARD
+            .decode(&svg[start..end])
+            .unwrap();
+        assert_eq!(&woff2[0..4], b"wOF2");
+
+        // Only the outlines of the used glyphs are kept.
+        let font = typst_assets::fonts()
+            .filter_map(|data| Font::new(typst::foundations::Bytes::new(data), 0))
+            .find(|font| {
+                font.info().family == "DejaVu Sans Mono"
+                    && font.info().variant == Default::default()
+            })
+            .unwrap();
+        assert!(woff2.len() * 4 < font.data().len(), "{} bytes", woff2.len());
+    }
 }
 
 // impl<F: CompilerFeat> WorldComputable<F> for SvgExport {
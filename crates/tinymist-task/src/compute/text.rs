@@ -1,7 +1,9 @@
 //! The computation for text export.
 
 use core::fmt;
+use std::cell::Cell;
 use std::sync::Arc;
+use typst::introspection::Location;
 use typst_html::{HtmlNode::*, tag};
 
 use crate::ExportTextTask;
@@ -17,6 +19,23 @@ impl TextExport {
     pub fn run_on_doc(doc: &TypstDocument) -> Result<String> {
         Ok(format!("{}", FullTextDigest(doc)))
     }
+
+    /// Runs the computation on the part of a paged document that starts at the
+    /// element at `start` and ends before the element at `end`. A missing
+    /// bound extends the part to the start or the end of the document.
+    pub fn run_on_region(
+        doc: &TypstPagedDocument,
+        start: Option<Location>,
+        end: Option<Location>,
+    ) -> Result<String> {
+        let digest = RegionTextDigest {
+            doc,
+            start,
+            end,
+            active: Cell::new(start.is_none()),
+        };
+        Ok(format!("{digest}"))
+    }
 }
 
 impl<F: CompilerFeat> ExportComputation<F, TypstPagedDocument> for TextExport {
@@ -101,3 +120,55 @@ impl fmt::Display for FullTextDigest<'_> {
         }
     }
 }
+
+/// A text digest of the part of a paged document between two elements.
+struct RegionTextDigest<'a> {
+    doc: &'a TypstPagedDocument,
+    start: Option<Location>,
+    end: Option<Location>,
+    /// Whether the walk is inside the part.
+    active: Cell<bool>,
+}
+
+impl RegionTextDigest<'_> {
+    fn export_frame(&self, f: &mut fmt::Formatter<'_>, doc: &typst::layout::Frame) -> fmt::Result {
+        use typst::introspection::Tag;
+        use typst::layout::FrameItem;
+
+        for (_, item) in doc.items() {
+            match item {
+                FrameItem::Group(g) => self.export_frame(f, &g.frame)?,
+                FrameItem::Tag(Tag::Start(elem, ..)) => {
+                    let loc = elem.location();
+                    if loc.is_some() && loc == self.start {
+                        self.active.set(true);
+                    }
+                    if loc.is_some() && loc == self.end {
+                        self.active.set(false);
+                    }
+                }
+                FrameItem::Text(t) if self.active.get() => f.write_str(t.text.as_str())?,
+                _ => {}
+            }
+        }
+        #[cfg(not(feature = "no-content-hint"))]
+        if self.active.get() {
+            use std::fmt::Write;
+            let c = doc.content_hint();
+            if c != '\0' {
+                f.write_char(c)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for RegionTextDigest<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for page in self.doc.pages() {
+            self.export_frame(f, &page.frame)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,306 @@
+//! Encoding subsets of fonts as WOFF2, which are inlined into svg exports.
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::io::Write;
+
+use tinymist_std::error::prelude::*;
+use typst::text::Font;
+
+/// The glyph flag indicating that the arguments of a component are words.
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+/// The glyph flag indicating that a component has a simple scale.
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+/// The glyph flag indicating that more components follow.
+const MORE_COMPONENTS: u16 = 0x0020;
+/// The glyph flag indicating that a component has an x and y scale.
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+/// The glyph flag indicating that a component has a two by two transform.
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+/// Encodes a subset of the font as WOFF2, which keeps the outlines of the
+/// `glyphs` and of the glyphs mapped from the `chars`. The outlines of the
+/// other glyphs are emptied, so that the glyph ids and the character map are
+/// kept for the text shaping of viewers.
+///
+/// Fonts with CFF outlines are not subsetted, but still compressed.
+pub(crate) fn woff2_subset(
+    font: &Font,
+    glyphs: &BTreeSet<u16>,
+    chars: &BTreeSet<char>,
+) -> Result<Vec<u8>> {
+    let (flavor, mut tables) =
+        read_sfnt(font.data(), font.index()).context("cannot read the tables of the font")?;
+
+    let mut used = glyphs.clone();
+    let ttf = font.ttf();
+    used.extend(
+        chars
+            .iter()
+            .filter_map(|&c| ttf.glyph_index(c))
+            .map(|g| g.0),
+    );
+    if let Some((mut glyf, mut loca)) = subset_glyf(&tables, &used) {
+        for (tag, data) in tables.iter_mut() {
+            match &*tag {
+                b"glyf" => *data = Cow::Owned(std::mem::take(&mut glyf)),
+                b"loca" => *data = Cow::Owned(std::mem::take(&mut loca)),
+                _ => {}
+            }
+        }
+    }
+
+    encode_woff2(flavor, &tables)
+}
+
+/// The tables of an sfnt font, with their tags.
+type Tables<'a> = Vec<([u8; 4], Cow<'a, [u8]>)>;
+
+/// Reads the version and the tables of the font at `index` in the sfnt data,
+/// which is possibly a font collection.
+fn read_sfnt(data: &[u8], index: u32) -> Option<(u32, Tables<'_>)> {
+    let mut offset = 0;
+    if data.get(0..4)? == b"ttcf" {
+        let num_fonts = read_u32(data, 8)?;
+        if index >= num_fonts {
+            return None;
+        }
+        offset = read_u32(data, 12 + 4 * index as usize)? as usize;
+    }
+
+    let flavor = read_u32(data, offset)?;
+    let num_tables = read_u16(data, offset + 4)? as usize;
+    let mut tables = Vec::with_capacity(num_tables);
+    for idx in 0..num_tables {
+        let record = offset + 12 + 16 * idx;
+        let tag = data.get(record..record + 4)?.try_into().ok()?;
+        let start = read_u32(data, record + 8)? as usize;
+        let len = read_u32(data, record + 12)? as usize;
+        tables.push((
+            tag,
+            Cow::Borrowed(data.get(start..start.checked_add(len)?)?),
+        ));
+    }
+
+    Some((flavor, tables))
+}
+
+/// Builds the `glyf` and `loca` tables keeping the outlines of the used
+/// glyphs, the glyphs referenced by them, and the `.notdef` glyph. Returns
+/// `None` if the font has no TrueType outlines.
+fn subset_glyf(tables: &Tables, used: &BTreeSet<u16>) -> Option<(Vec<u8>, Vec<u8>)> {
+    let table = |name: &[u8; 4]| {
+        tables
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, data)| data.as_ref())
+    };
+    let glyf = table(b"glyf")?;
+    let loca = table(b"loca")?;
+    let long_loca = read_u16(table(b"head")?, 50)? != 0;
+    let num_glyphs = read_u16(table(b"maxp")?, 4)?;
+
+    let glyph_range = |gid: u16| {
+        let gid = gid as usize;
+        let (start, end) = if long_loca {
+            (read_u32(loca, gid * 4)?, read_u32(loca, gid * 4 + 4)?)
+        } else {
+            let start = read_u16(loca, gid * 2)? as u32 * 2;
+            (start, read_u16(loca, gid * 2 + 2)? as u32 * 2)
+        };
+        let range = start as usize..end as usize;
+        glyf.get(range.clone()).map(|_| range)
+    };
+
+    // Keeps the components of the composite glyphs as well.
+    let mut kept = BTreeSet::new();
+    let mut stack = vec![0];
+    stack.extend(used.iter().copied().filter(|&gid| gid < num_glyphs));
+    while let Some(gid) = stack.pop() {
+        if !kept.insert(gid) {
+            continue;
+        }
+        let glyph = &glyf[glyph_range(gid)?];
+        stack.extend(composite_components(glyph).filter(|&gid| gid < num_glyphs));
+    }
+
+    let mut new_glyf = Vec::new();
+    let mut new_loca = Vec::new();
+    let push_offset = |loca: &mut Vec<u8>, offset: usize| {
+        if long_loca {
+            loca.extend((offset as u32).to_be_bytes());
+        } else {
+            loca.extend(((offset / 2) as u16).to_be_bytes());
+        }
+    };
+    for gid in 0..num_glyphs {
+        push_offset(&mut new_loca, new_glyf.len());
+        if kept.contains(&gid) {
+            new_glyf.extend_from_slice(&glyf[glyph_range(gid)?]);
+        }
+    }
+    push_offset(&mut new_loca, new_glyf.len());
+
+    Some((new_glyf, new_loca))
+}
+
+/// Iterates over the glyph ids of the components of a composite glyph, which
+/// is empty for simple glyphs.
+fn composite_components(glyph: &[u8]) -> impl Iterator<Item = u16> + '_ {
+    let is_composite = glyph.len() >= 10 && i16::from_be_bytes([glyph[0], glyph[1]]) < 0;
+    let mut offset = is_composite.then_some(10);
+    std::iter::from_fn(move || {
+        let at = offset?;
+        let flags = read_u16(glyph, at)?;
+        let gid = read_u16(glyph, at + 2)?;
+
+        let mut next = at + 4;
+        next += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            4
+        } else {
+            2
+        };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            next += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            next += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            next += 8;
+        }
+        offset = (flags & MORE_COMPONENTS != 0).then_some(next);
+
+        Some(gid)
+    })
+}
+
+/// Encodes the tables as a WOFF2 font without table transforms.
+fn encode_woff2(flavor: u32, tables: &Tables) -> Result<Vec<u8>> {
+    // The `loca` table must follow the `glyf` table in the directory.
+    let mut tables = tables.iter().collect::<Vec<_>>();
+    if let Some(loca) = tables.iter().position(|(tag, _)| tag == b"loca") {
+        let loca = tables.remove(loca);
+        match tables.iter().position(|(tag, _)| tag == b"glyf") {
+            Some(glyf) => tables.insert(glyf + 1, loca),
+            None => tables.push(loca),
+        }
+    }
+
+    let mut directory = Vec::new();
+    let mut stream = Vec::new();
+    let mut sfnt_size = 12 + 16 * tables.len();
+    for (tag, data) in &tables {
+        // The tag is written explicitly. The null transform of `glyf` and
+        // `loca` is the version 3, and the version 0 for the other tables.
+        let transform = if matches!(tag, b"glyf" | b"loca") {
+            0xC0
+        } else {
+            0x00
+        };
+        directory.push(0x3F | transform);
+        directory.extend_from_slice(tag);
+        write_base128(&mut directory, data.len() as u32);
+
+        stream.extend_from_slice(data);
+        sfnt_size += data.len().next_multiple_of(4);
+    }
+
+    let mut compressor = brotli::CompressorWriter::new(Vec::new(), 4096, 11, 22);
+    compressor
+        .write_all(&stream)
+        .context("cannot compress the font")?;
+    let compressed = compressor.into_inner();
+
+    let length = (48 + directory.len() + compressed.len()).next_multiple_of(4);
+    let mut woff2 = Vec::with_capacity(length);
+    woff2.extend_from_slice(b"wOF2");
+    woff2.extend(flavor.to_be_bytes());
+    woff2.extend((length as u32).to_be_bytes());
+    woff2.extend((tables.len() as u16).to_be_bytes());
+    woff2.extend(0u16.to_be_bytes());
+    woff2.extend((sfnt_size as u32).to_be_bytes());
+    woff2.extend((compressed.len() as u32).to_be_bytes());
+    // The version is 1.0, and there are no metadata or private data.
+    woff2.extend(1u16.to_be_bytes());
+    woff2.extend(0u16.to_be_bytes());
+    woff2.extend([0; 20]);
+    woff2.extend(directory);
+    woff2.extend(compressed);
+    woff2.resize(length, 0);
+
+    Ok(woff2)
+}
+
+/// Writes a `UIntBase128` number, which is big-endian with 7 bits per byte.
+fn write_base128(out: &mut Vec<u8>, value: u32) {
+    let len = (1..5).find(|len| value >> (7 * len) == 0).unwrap_or(5);
+    for idx in (0..len).rev() {
+        let byte = ((value >> (7 * idx)) & 0x7F) as u8;
+        out.push(if idx == 0 { byte } else { byte | 0x80 });
+    }
+}
+
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_base128() {
+        let encode = |value| {
+            let mut out = Vec::new();
+            write_base128(&mut out, value);
+            out
+        };
+        assert_eq!(encode(0), vec![0x00]);
+        assert_eq!(encode(0x7F), vec![0x7F]);
+        assert_eq!(encode(0x80), vec![0x81, 0x00]);
+        assert_eq!(encode(63), vec![63]);
+        assert_eq!(encode(u32::MAX), vec![0x8F, 0xFF, 0xFF, 0xFF, 0x7F]);
+    }
+
+    #[test]
+    fn test_composite_components() {
+        let mut glyph = vec![0xFF, 0xFF];
+        glyph.extend([0; 8]);
+        // A component with word arguments and a scale, followed by one with
+        // byte arguments.
+        glyph.extend((ARG_1_AND_2_ARE_WORDS | WE_HAVE_A_SCALE | MORE_COMPONENTS).to_be_bytes());
+        glyph.extend(3u16.to_be_bytes());
+        glyph.extend([0; 6]);
+        glyph.extend(0u16.to_be_bytes());
+        glyph.extend(7u16.to_be_bytes());
+        glyph.extend([0; 2]);
+
+        assert_eq!(composite_components(&glyph).collect::<Vec<_>>(), vec![3, 7]);
+        assert_eq!(composite_components(&[0, 1, 0, 0]).count(), 0);
+    }
+
+    #[test]
+    fn test_woff2_subset() {
+        let font = typst_assets::fonts()
+            .filter_map(|data| Font::new(typst::foundations::Bytes::new(data), 0))
+            .find(|font| font.ttf().tables().glyf.is_some())
+            .expect("an embedded font with TrueType outlines");
+        let chars = "Hello".chars().collect();
+
+        let woff2 = woff2_subset(&font, &BTreeSet::new(), &chars).unwrap();
+        assert_eq!(&woff2[0..4], b"wOF2");
+        assert_eq!(read_u32(&woff2, 8), Some(woff2.len() as u32));
+        assert_eq!(woff2.len() % 4, 0);
+        assert!(woff2.len() < font.data().len());
+
+        // The subset keeps the glyph ids of the font.
+        let (_, tables) = read_sfnt(font.data(), 0).unwrap();
+        let (_, loca) = tables.iter().find(|(tag, _)| tag == b"loca").unwrap();
+        let (glyf, new_loca) = subset_glyf(&tables, &BTreeSet::from([1])).unwrap();
+        assert_eq!(new_loca.len(), loca.len());
+        assert!(!glyf.is_empty());
+    }
+}
@@ -1,10 +1,11 @@
 //! Project task models.
 
+use std::num::{NonZeroU32, NonZeroUsize};
 use std::{hash::Hash, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 
-use super::{Id, Pages, PathPattern, PdfStandard, Scalar, TaskWhen};
+use super::{Id, OneOrMany, Pages, PathPattern, PdfStandard, Scalar, SplitBy, TaskWhen};
 
 /// A project task application specifier. This is used for specifying tasks to
 /// run in a project. When the language service notifies an update event of the
@@ -66,6 +67,10 @@ pub enum ProjectTask {
     ExportPng(ExportPngTask),
     /// An export SVG task.
     ExportSvg(ExportSvgTask),
+    /// An export animated SVG task.
+    ExportAnimatedSvg(ExportAnimatedSvgTask),
+    /// An export GIF task.
+    ExportGif(ExportGifTask),
     /// An export HTML task.
     ExportHtml(ExportHtmlTask),
     /// An export bundle task.
@@ -78,6 +83,8 @@ pub enum ProjectTask {
     ExportTeX(ExportTeXTask),
     /// An export Text task.
     ExportText(ExportTextTask),
+    /// An export PPTX task.
+    ExportPptx(ExportPptxTask),
     /// An query task.
     Query(QueryTask),
     // todo: compatibility
@@ -93,12 +100,15 @@ impl ProjectTask {
             Self::ExportPdf(..)
             | Self::ExportPng(..)
             | Self::ExportSvg(..)
+            | Self::ExportAnimatedSvg(..)
+            | Self::ExportGif(..)
             | Self::ExportHtml(..)
             | Self::ExportBundle(..)
             | Self::ExportSvgHtml(..)
             | Self::ExportMd(..)
             | Self::ExportTeX(..)
             | Self::ExportText(..)
+            | Self::ExportPptx(..)
             | Self::Query(..) => &self.as_export()?.when,
         })
     }
@@ -110,12 +120,15 @@ impl ProjectTask {
             Self::ExportPdf(task) => &task.export,
             Self::ExportPng(task) => &task.export,
             Self::ExportSvg(task) => &task.export,
+            Self::ExportAnimatedSvg(task) => &task.export,
+            Self::ExportGif(task) => &task.export,
             Self::ExportHtml(task) => &task.export,
             Self::ExportBundle(task) => &task.export,
             Self::ExportSvgHtml(task) => &task.export,
             Self::ExportTeX(task) => &task.export,
             Self::ExportMd(task) => &task.export,
             Self::ExportText(task) => &task.export,
+            Self::ExportPptx(task) => &task.export,
             Self::Query(task) => &task.export,
         })
     }
@@ -127,12 +140,15 @@ impl ProjectTask {
             Self::ExportPdf(task) => &mut task.export,
             Self::ExportPng(task) => &mut task.export,
             Self::ExportSvg(task) => &mut task.export,
+            Self::ExportAnimatedSvg(task) => &mut task.export,
+            Self::ExportGif(task) => &mut task.export,
             Self::ExportHtml(task) => &mut task.export,
             Self::ExportBundle(task) => &mut task.export,
             Self::ExportSvgHtml(task) => &mut task.export,
             Self::ExportTeX(task) => &mut task.export,
             Self::ExportMd(task) => &mut task.export,
             Self::ExportText(task) => &mut task.export,
+            Self::ExportPptx(task) => &mut task.export,
             Self::Query(task) => &mut task.export,
         })
     }
@@ -146,8 +162,10 @@ impl ProjectTask {
             Self::ExportMd { .. } => "md",
             Self::ExportTeX { .. } => "tex",
             Self::ExportText { .. } => "txt",
-            Self::ExportSvg { .. } => "svg",
+            Self::ExportPptx { .. } => "pptx",
+            Self::ExportSvg { .. } | Self::ExportAnimatedSvg { .. } => "svg",
             Self::ExportPng { .. } => "png",
+            Self::ExportGif { .. } => "gif",
             Self::Query(QueryTask {
                 format,
                 output_extension,
@@ -177,6 +195,45 @@ pub struct ExportTask {
     /// The task's transforms.
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub transform: Vec<ExportTransform>,
+    /// Whether to skip writing the output if it is identical to the existing
+    /// file, which keeps the modification time of the file unchanged.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub skip_unchanged: bool,
+    /// Whether to export only the pages changed since the last export to the
+    /// same output, into a separate diff set next to it, e.g. `main.diff.pdf`.
+    /// The changes are detected by the content hashes of the pages, which are
+    /// kept in memory by the process running the export, so they are not
+    /// detected across restarts and the first export writes all the pages.
+    /// Only PDF and PNG exports support it.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub changed_pages: bool,
+    /// Whether to expand the environment variables (`$VAR` or `${VAR}`) in the
+    /// output path. It is disabled by default because the output path is
+    /// allowed to read the environment of the process only if it is trusted.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub expand_env: bool,
+    /// Whether to record the written outputs in an `export-manifest.json` next
+    /// to them, with their sizes and checksums.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub manifest: bool,
+    /// Whether to write a span map next to the output, i.e.
+    /// `<output>.spans.json`, which maps the bounding boxes of the rendered
+    /// elements on each page to their source ranges. Only PDF, PNG, and SVG
+    /// exports support it.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub span_map: bool,
+    /// Whether to export one file per section rather than a single file, e.g.
+    /// `heading-level-1` for a file per top-level heading. The output path
+    /// must contain the `{section}` placeholder, which is replaced with the
+    /// slug of the heading. Only PDF exports support it.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub split_by: Option<SplitBy>,
+    /// The label of a metadata element that the document must set to a truthy
+    /// value for the task to run, e.g. `export-pdf` for
+    /// `#metadata(true) <export-pdf>`. This lets documents opt in to a shared
+    /// export configuration.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub require: Option<String>,
 }
 
 impl ExportTask {
@@ -186,6 +243,22 @@ impl ExportTask {
             when,
             output: None,
             transform: Vec::new(),
+            skip_unchanged: false,
+            changed_pages: false,
+            expand_env: false,
+            manifest: false,
+            span_map: false,
+            split_by: None,
+            require: None,
+        }
+    }
+
+    /// Gets the output path pattern, with the environment variables expanded
+    /// if [`Self::expand_env`] is enabled.
+    pub fn output_pattern(&self) -> tinymist_std::Result<Option<PathPattern>> {
+        match &self.output {
+            Some(output) if self.expand_env => output.expand_env().map(Some),
+            output => Ok(output.clone()),
         }
     }
 
@@ -202,6 +275,84 @@ impl ExportTask {
 pub struct PageMerge {
     /// The gap between pages (in pt).
     pub gap: Option<String>,
+    /// The direction to place the pages along.
+    pub direction: PageMergeDirection,
+}
+
+/// The direction to place the pages along when merging them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PageMergeDirection {
+    /// Places the pages from top to bottom.
+    #[default]
+    Vertical,
+    /// Places the pages from left to right.
+    Horizontal,
+}
+
+impl PageMergeDirection {
+    /// Whether the pages are placed from top to bottom.
+    pub fn is_vertical(&self) -> bool {
+        matches!(self, Self::Vertical)
+    }
+}
+
+/// A contact sheet specifier, which tiles the thumbnails of the pages in a
+/// grid, each labeled with its page number.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContactSheet {
+    /// The number of thumbnails in each row, which is 4 by default.
+    pub columns: Option<NonZeroUsize>,
+}
+
+impl ContactSheet {
+    /// The number of thumbnails in each row.
+    pub fn columns(&self) -> NonZeroUsize {
+        self.columns.unwrap_or(NonZeroUsize::new(4).unwrap())
+    }
+}
+
+/// A background fill specifier, which fills either all pages or the pages in
+/// the given ranges.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PageFill {
+    /// The expression constructing the fill (in typst script), e.g. `#ffffff`.
+    All(String),
+    /// The fills of the pages in the ranges. The pages not covered keep the
+    /// fill of the document.
+    Ranges(Vec<PageRangeFill>),
+}
+
+impl PageFill {
+    /// Checks that no page is covered by more than one range.
+    pub fn check(&self) -> tinymist_std::Result<()> {
+        let Self::Ranges(fills) = self else {
+            return Ok(());
+        };
+
+        let ranges = fills
+            .iter()
+            .flat_map(|fill| fill.ranges.iter())
+            .collect::<Vec<_>>();
+        for (idx, range) in ranges.iter().enumerate() {
+            if let Some(other) = ranges[..idx].iter().find(|other| other.overlaps(range)) {
+                tinymist_std::bail!("the page ranges of the fills overlap: {other} and {range}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The fill of the pages in some ranges.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PageRangeFill {
+    /// The page ranges to fill, e.g. `["1", "5-7"]`.
+    pub ranges: Vec<Pages>,
+    /// The expression constructing the fill (in typst script), e.g. `#ffffff`.
+    pub fill: String,
 }
 
 /// A project export transform specifier.
@@ -217,6 +368,10 @@ pub enum ExportTransform {
     Merge {
         /// The gap between pages (typst code expression, e.g. `1pt`).
         gap: Option<String>,
+        /// The direction to place the pages along, which is vertical by
+        /// default.
+        #[serde(skip_serializing_if = "PageMergeDirection::is_vertical", default)]
+        direction: PageMergeDirection,
     },
     /// Execute a transform script.
     Script {
@@ -260,6 +415,21 @@ pub struct ExportPdfTask {
     /// For more information, see <https://reproducible-builds.org/specs/source-date-epoch/>.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub creation_timestamp: Option<i64>,
+    /// Whether to make the PDF bit-reproducible. The creation and modification
+    /// dates are omitted unless `creation_timestamp` is given, instead of
+    /// using the time of export, so that exporting the same source twice
+    /// yields identical bytes.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub reproducible: bool,
+    /// Whether to write the outline (bookmark tree) embedded in the PDF to a
+    /// sidecar JSON file, i.e. `<output>.pdf.outline.json`.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub outline_sidecar: bool,
+    /// The ICC profile to embed as the output intent of the PDF. A relative
+    /// path is resolved against the root of the project. It cannot be used
+    /// with PDF/A standards, whose output intent is kept.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub icc_profile: Option<PathBuf>,
 }
 
 /// An export png task specifier.
@@ -278,15 +448,43 @@ pub struct ExportPngTask {
     /// The page merge specifier.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub merge: Option<PageMerge>,
+    /// Tiles the pages into a contact sheet rather than exporting them
+    /// separately. The pages are rendered at `ppi` as thumbnails. It cannot be
+    /// combined with `merge`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub contact_sheet: Option<ContactSheet>,
     /// The PPI (pixels per inch) to use for PNG export.
     pub ppi: Scalar,
     /// The expression constructing background fill color (in typst script).
-    /// e.g. `#ffffff`, `#000000`, `rgba(255, 255, 255, 0.5)`.
+    /// e.g. `#ffffff`, `#000000`, `rgba(255, 255, 255, 0.5)`, or a list of
+    /// fills of page ranges, e.g. `[{ "ranges": ["1", "5"], "fill": "red" }]`,
+    /// which must not overlap.
     ///
     /// If not provided, the default background color specified in the document
     /// will be used.
     #[serde(skip_serializing_if = "Option::is_none", default)]
-    pub fill: Option<String>,
+    pub fill: Option<PageFill>,
+    /// Whether to export with a transparent background, ignoring the page fill
+    /// of the document. It cannot be combined with `fill`.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub transparent: bool,
+    /// The number of threads to render pages in parallel. When unspecified,
+    /// the number of CPUs is used. Set it to 1 to render the pages one by one.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub jobs: Option<NonZeroUsize>,
+    /// The maximum height (in pixels) of the image merged by `merge`. The
+    /// export fails rather than rendering a taller image.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub max_height: Option<NonZeroU32>,
+    /// The ICC profile to embed into the images as an `iCCP` chunk. A relative
+    /// path is resolved against the root of the project.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub icc_profile: Option<PathBuf>,
+    /// The maximum size (in bytes) of each image. The PPI is lowered from
+    /// `ppi` until every image fits, and the export fails if they don't fit
+    /// even at the lowest PPI.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub max_bytes: Option<NonZeroUsize>,
 }
 
 /// An export svg task specifier.
@@ -305,6 +503,89 @@ pub struct ExportSvgTask {
     /// The page merge specifier.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub merge: Option<PageMerge>,
+    /// How to write the text, which is converted to paths by default.
+    #[serde(skip_serializing_if = "SvgTextMode::is_paths", default)]
+    pub text_mode: SvgTextMode,
+}
+
+/// An export animated svg task specifier. Each page becomes a frame of a
+/// single svg that shows the pages one after another.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExportAnimatedSvgTask {
+    /// The shared export arguments.
+    #[serde(flatten)]
+    pub export: ExportTask,
+    /// Which pages to export. When unspecified, all pages are exported.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub pages: Option<Vec<Pages>>,
+    /// How long each page is shown (in seconds).
+    pub frame_duration: Scalar,
+    /// Whether the animation starts over after the last page. Otherwise, the
+    /// last page stays.
+    #[serde(rename = "loop")]
+    pub repeat: bool,
+}
+
+/// An export gif task specifier. Each page becomes a frame of a single
+/// animated gif.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExportGifTask {
+    /// The shared export arguments.
+    #[serde(flatten)]
+    pub export: ExportTask,
+    /// Which pages to export. When unspecified, all pages are exported.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub pages: Option<Vec<Pages>>,
+    /// The PPI (pixels per inch) to use for the frames.
+    pub ppi: Scalar,
+    /// How long each frame is shown (in milliseconds).
+    pub frame_delay: u32,
+    /// How many times the animation repeats after it is played once. When
+    /// unspecified, it repeats forever.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub loop_count: Option<u16>,
+    /// How to quantize the colors of the frames.
+    #[serde(default)]
+    pub palette: GifPalette,
+}
+
+/// How to quantize the colors of the frames in gif exports, which can have at
+/// most 256 colors in a palette.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GifPalette {
+    /// A palette shared by all the frames, which makes smaller files and
+    /// avoids the colors flickering between frames.
+    Global,
+    /// A palette for each frame, which preserves the colors of the frames
+    /// better.
+    #[default]
+    PerFrame,
+}
+
+/// How to write the text in svg exports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SvgTextMode {
+    /// Converts the text to paths, which looks the same without the fonts but
+    /// cannot be selected.
+    #[default]
+    Paths,
+    /// Writes selectable text and inlines subsets of the used fonts into the
+    /// svg as WOFF2.
+    Embed,
+    /// Writes selectable text that references the used fonts by their family
+    /// names, which must be available to the viewer.
+    Reference,
+}
+
+impl SvgTextMode {
+    /// Whether the text is converted to paths.
+    pub fn is_paths(&self) -> bool {
+        matches!(self, Self::Paths)
+    }
 }
 
 /// An export html task specifier.
@@ -376,6 +657,17 @@ pub struct ExportTextTask {
     pub export: ExportTask,
 }
 
+/// An export PPTX task specifier.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExportPptxTask {
+    /// The shared export arguments.
+    #[serde(flatten)]
+    pub export: ExportTask,
+    /// The PPI (pixels per inch) to render the slides at.
+    pub ppi: Scalar,
+}
+
 /// An export query task specifier.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -388,10 +680,46 @@ pub struct QueryTask {
     /// Uses a different output extension from the one inferring from the
     /// [`Self::format`].
     pub output_extension: Option<String>,
-    /// Defines which elements to retrieve.
-    pub selector: String,
-    /// Extracts just one field from all retrieved elements.
-    pub field: Option<String>,
-    /// Expects and retrieves exactly one element.
-    pub one: bool,
+    /// Defines which elements to retrieve. If multiple selectors are given,
+    /// the results are keyed by the selectors.
+    pub selector: OneOrMany<String>,
+    /// Extracts just one field from all retrieved elements. If given as an
+    /// array, each field applies to the selector at the same index.
+    ///
+    /// The field can be a dotted path like `value.caption.body` to access
+    /// nested fields of contents and dictionaries, or items of arrays by
+    /// index. A missing intermediate field results in `none`. A dot or
+    /// backslash in a key is escaped by a backslash, e.g. `value.a\.b`
+    /// accesses the key `a.b`.
+    pub field: Option<OneOrMany<Option<String>>>,
+    /// Expects and retrieves exactly one element. If given as an array, each
+    /// flag applies to the selector at the same index.
+    pub one: OneOrMany<bool>,
+    /// Retrieves only the elements that are laid out on the page (1-based).
+    /// Pages out of range retrieve no elements.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub page: Option<NonZeroUsize>,
+    /// Queries the HTML output rather than the elements of the document, with
+    /// CSS-like selectors such as `article > h2.title`. Each retrieved element
+    /// is an object with its `tag`, `attrs`, and `text`.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub html: bool,
+}
+
+impl QueryTask {
+    /// Iterates over the selectors along with their `field` and `one` options.
+    pub fn selectors(&self) -> impl Iterator<Item = (&str, Option<&str>, bool)> {
+        self.selector
+            .as_slice()
+            .iter()
+            .enumerate()
+            .map(|(idx, selector)| {
+                let field = self
+                    .field
+                    .as_ref()
+                    .and_then(|field| field.get(idx)?.as_deref());
+                let one = self.one.get(idx).copied().unwrap_or(false);
+                (selector.as_str(), field, one)
+            })
+    }
 }
@@ -67,6 +67,45 @@ impl Ord for Scalar {
     }
 }
 
+/// A value that is either a single item or an array of items.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    /// A single item.
+    One(T),
+    /// An array of items.
+    Many(Vec<T>),
+}
+
+impl<T: Default> Default for OneOrMany<T> {
+    fn default() -> Self {
+        Self::One(T::default())
+    }
+}
+
+impl<T> OneOrMany<T> {
+    /// Whether the value is given as an array.
+    pub fn is_many(&self) -> bool {
+        matches!(self, Self::Many(..))
+    }
+
+    /// Gets the items as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        match self {
+            Self::One(item) => std::slice::from_ref(item),
+            Self::Many(items) => items,
+        }
+    }
+
+    /// Gets the item at the index. A single item applies to all indices.
+    pub fn get(&self, idx: usize) -> Option<&T> {
+        match self {
+            Self::One(item) => Some(item),
+            Self::Many(items) => items.get(idx),
+        }
+    }
+}
+
 /// A project ID.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -143,6 +182,62 @@ impl PathPattern {
         }
     }
 
+    /// Whether the pattern refers to the standard output, i.e. `-`.
+    pub fn is_stdout(&self) -> bool {
+        self.0 == "-"
+    }
+
+    /// Expands the environment variables in the pattern, written as `$VAR` or
+    /// `${VAR}`. The placeholders `$root`, `$dir`, and `$name` are kept for
+    /// [`Self::substitute`], also when followed by other characters, e.g.
+    /// `$name_v2`.
+    pub fn expand_env(&self) -> Result<Self> {
+        self.expand_env_with(|name| std::env::var(name).ok())
+    }
+
+    fn expand_env_with(&self, lookup: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        const PLACEHOLDERS: [&str; 3] = ["root", "dir", "name"];
+
+        let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+        let mut expanded = EcoString::new();
+        let mut rest = self.0.as_str();
+        while let Some(idx) = rest.find('$') {
+            expanded.push_str(&rest[..idx]);
+            rest = &rest[idx + 1..];
+
+            let (name, braced) = if let Some(braced) = rest.strip_prefix('{') {
+                let Some(end) = braced.find('}') else {
+                    bail!("unclosed `${{` in output path: {self}");
+                };
+                (&braced[..end], true)
+            } else {
+                let end = rest.find(|c| !is_ident(c)).unwrap_or(rest.len());
+                (&rest[..end], false)
+            };
+
+            if name.is_empty() || !name.chars().all(is_ident) {
+                bail!("invalid environment variable `{name}` in output path: {self}");
+            }
+
+            rest = &rest[name.len() + if braced { 2 } else { 0 }..];
+            // The placeholders are substituted as prefixes, e.g. `$name_v2`.
+            if !braced && PLACEHOLDERS.iter().any(|p| name.starts_with(p)) {
+                expanded.push('$');
+                expanded.push_str(name);
+                continue;
+            }
+
+            let Some(value) = lookup(name) else {
+                bail!("undefined environment variable `{name}` in output path: {self}");
+            };
+            expanded.push_str(&value);
+        }
+        expanded.push_str(rest);
+
+        Ok(Self(expanded))
+    }
+
     /// Substitutes the path pattern with `$root`, and `$dir/$name`.
     pub fn substitute(&self, entry: &EntryState) -> Option<ImmutPath> {
         self.substitute_impl(entry.root(), entry.main())
@@ -158,6 +253,10 @@ impl PathPattern {
         if WorkspaceResolver::is_package_file(main) {
             return None;
         }
+        // The standard output is not substituted
+        if self.is_stdout() {
+            return Some(Path::new("-").into());
+        }
         // Files without a path are not exported
         let path = main.vpath().realize(&root).ok()?;
 
@@ -207,6 +306,17 @@ pub struct Pages(pub RangeInclusive<Option<NonZeroUsize>>);
 impl Pages {
     /// Selects the first page.
     pub const FIRST: Pages = Pages(NonZeroUsize::new(1)..=NonZeroUsize::new(1));
+
+    /// Whether the range shares any page with the other range.
+    pub fn overlaps(&self, other: &Pages) -> bool {
+        let starts_before_end =
+            |start: &Option<NonZeroUsize>, end: &Option<NonZeroUsize>| match (start, end) {
+                (Some(start), Some(end)) => start <= end,
+                _ => true,
+            };
+        starts_before_end(self.0.start(), other.0.end())
+            && starts_before_end(other.0.start(), self.0.end())
+    }
 }
 
 impl FromStr for Pages {
@@ -288,6 +398,55 @@ fn parse_page_number(value: &str) -> Result<NonZeroUsize, &'static str> {
     }
 }
 
+/// How to split an export into one file per section, e.g. `heading-level-1`
+/// for a file per top-level heading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SplitBy {
+    /// Starts a new section at each heading of the level.
+    HeadingLevel(NonZeroUsize),
+}
+
+impl FromStr for SplitBy {
+    type Err = &'static str;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let Some(level) = value.trim().strip_prefix("heading-level-") else {
+            return Err("split specifier must be of the form `heading-level-N`");
+        };
+        match NonZeroUsize::from_str(level) {
+            Ok(level) => Ok(SplitBy::HeadingLevel(level)),
+            Err(_) => Err("heading level must be a positive integer"),
+        }
+    }
+}
+
+impl fmt::Display for SplitBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplitBy::HeadingLevel(level) => write!(f, "heading-level-{level}"),
+        }
+    }
+}
+
+impl serde::Serialize for SplitBy {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for SplitBy {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// A resource path.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ResourcePath(EcoString, String);
@@ -472,6 +631,56 @@ mod tests {
         EntryState::new_rooted(root.into(), Some(VirtualPath::new(path).unwrap()))
     }
 
+    #[test]
+    fn test_one_or_many() {
+        let one: OneOrMany<String> = serde_json::from_str(r#""heading""#).unwrap();
+        assert!(!one.is_many());
+        assert_eq!(one.get(2).map(String::as_str), Some("heading"));
+
+        let many: OneOrMany<bool> = serde_json::from_str("[true, false]").unwrap();
+        assert!(many.is_many());
+        assert_eq!(many.as_slice(), &[true, false]);
+        assert_eq!(many.get(2), None);
+    }
+
+    #[test]
+    fn test_pages_overlap() {
+        let pages = |s: &str| s.parse::<Pages>().unwrap();
+
+        assert!(pages("2-5").overlaps(&pages("5-8")));
+        assert!(pages("-3").overlaps(&pages("2")));
+        assert!(pages("4-").overlaps(&pages("-9")));
+        assert!(pages("4-").overlaps(&pages("10-")));
+        assert!(!pages("2-5").overlaps(&pages("6-8")));
+        assert!(!pages("-3").overlaps(&pages("4-")));
+        assert!(!pages("7").overlaps(&pages("1-6")));
+    }
+
+    #[test]
+    fn test_expand_env() {
+        let lookup = |name: &str| (name == "BUILD_DIR").then(|| "/build".to_owned());
+        let expand = |pattern: &str| PathPattern::new(pattern).expand_env_with(lookup);
+
+        assert_eq!(
+            expand("$BUILD_DIR/$dir/$name").unwrap(),
+            PathPattern::new("/build/$dir/$name")
+        );
+        assert_eq!(
+            expand("${BUILD_DIR}_out/$root").unwrap(),
+            PathPattern::new("/build_out/$root")
+        );
+        assert_eq!(expand("out/$name").unwrap(), PathPattern::new("out/$name"));
+        assert_eq!(
+            expand("$dir_out/$name_suffix").unwrap(),
+            PathPattern::new("$dir_out/$name_suffix")
+        );
+
+        let err = expand("$OUT_DIR/$name").unwrap_err().to_string();
+        assert!(err.contains("OUT_DIR"), "{err}");
+        assert!(expand("${BUILD_DIR/$name").is_err());
+        assert!(expand("$/out").is_err());
+    }
+
     #[test]
     fn test_substitute_path() {
         let root = Path::new("/dummy-root");
@@ -492,6 +701,10 @@ mod tests {
             PathPattern::new("/substitute/$name").substitute(&entry),
             Some(PathBuf::from("/substitute/file.txt").into())
         );
+        assert_eq!(
+            PathPattern::new("/substitute/$name_suffix").substitute(&entry),
+            Some(PathBuf::from("/substitute/file.txt_suffix").into())
+        );
         assert_eq!(
             PathPattern::new("/substitute/target/$dir/$name").substitute(&entry),
             Some(PathBuf::from("/substitute/target/dir1/dir2/file.txt").into())
@@ -540,4 +753,25 @@ mod tests {
             Some(root.join("README").into())
         );
     }
+
+    #[test]
+    fn test_substitute_path_keeps_section_placeholder() {
+        let root = test_root();
+        let entry = test_entry("/main.typ");
+
+        assert_eq!(
+            PathPattern::new("$root/build/{section}").substitute(&entry),
+            Some(root.join("build/{section}").into())
+        );
+    }
+
+    #[test]
+    fn test_split_by() {
+        let split_by: SplitBy = "heading-level-1".parse().unwrap();
+        assert_eq!(split_by, SplitBy::HeadingLevel(NonZeroUsize::MIN));
+        assert_eq!(split_by.to_string(), "heading-level-1");
+
+        assert!("heading-level-0".parse::<SplitBy>().is_err());
+        assert!("page".parse::<SplitBy>().is_err());
+    }
 }
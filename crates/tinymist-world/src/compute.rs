@@ -16,6 +16,9 @@ use crate::{CompilerFeat, CompilerWorld, EntryReader, TaskInputs};
 
 type AnyArc = Arc<dyn std::any::Any + Send + Sync>;
 
+/// The name of the timing scope around each compilation.
+pub const COMPILE_SCOPE: &str = "compile";
+
 /// A world compute entry.
 #[derive(Debug, Clone, Default)]
 struct WorldComputeEntry {
@@ -308,7 +311,10 @@ impl<D: Output + Send + Sync + 'static> CompilationTask<D> {
         };
 
         world.to_mut().set_is_compiling(true);
+        // The scope marks a recompile cycle in the server trace.
+        let scope = typst_timing::TimingScope::new(COMPILE_SCOPE);
         let compiled = ::typst_shim::compile_opt::<D>(world.as_ref());
+        drop(scope);
         world.to_mut().set_is_compiling(false);
 
         let exclude_html_warnings = if !is_html_compilation {
@@ -26,6 +26,26 @@ pub trait EntryManager: EntryReader {
     fn mutate_entry(&mut self, state: EntryState) -> SourceResult<EntryState>;
 }
 
+/// Checks whether the `typst.toml` at the given path marks a workspace, i.e.
+/// it contains a `[workspace]` table.
+pub fn is_workspace_manifest(path: &Path) -> bool {
+    std::fs::read_to_string(path).is_ok_and(|content| has_workspace_table(&content))
+}
+
+/// Checks whether the manifest contains a `workspace` table, which is also
+/// declared by dotted keys, e.g. `workspace.members = [..]`.
+fn has_workspace_table(manifest: &str) -> bool {
+    toml::from_str::<toml::Table>(manifest)
+        .is_ok_and(|manifest| manifest.get("workspace").is_some_and(toml::Value::is_table))
+}
+
+/// Finds the nearest ancestor directory of the path that contains a
+/// `typst.toml` marking a workspace.
+pub fn find_workspace_root(path: &Path) -> Option<&Path> {
+    path.ancestors()
+        .find(|dir| is_workspace_manifest(&dir.join("typst.toml")))
+}
+
 /// The state of the entry.
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Default)]
 pub struct EntryState {
@@ -275,6 +295,21 @@ mod tests {
         assert!(matches!(resolution, WorkspaceResolution::Workspace(id) if id.path() == *root));
     }
 
+    #[test]
+    fn workspace_manifest_is_parsed() {
+        assert!(has_workspace_table("[workspace]\nmembers = [\"docs\"]"));
+        assert!(has_workspace_table("[workspace.targets]\n"));
+        assert!(has_workspace_table("workspace.members = [\"docs\"]"));
+        assert!(has_workspace_table("workspace = { members = [] }"));
+
+        assert!(!has_workspace_table("[package]\nname = \"pkg\""));
+        assert!(!has_workspace_table(
+            "description = \"\"\"\n[workspace]\n\"\"\""
+        ));
+        assert!(!has_workspace_table("workspace = true"));
+        assert!(!has_workspace_table("[workspace"));
+    }
+
     #[test]
     fn try_select_path_in_workspace_selects_absolute_workspace_path() {
         let root = ImmutPath::from(Path::new(ROOT));
@@ -17,12 +17,14 @@ pub mod mock;
 
 pub use compute::*;
 pub use entry::*;
+pub use locale::*;
 pub use snapshot::*;
 pub use world::*;
 
 pub use tinymist_vfs as vfs;
 
 mod compute;
+mod locale;
 mod snapshot;
 
 /// Run the compiler in the system environment.
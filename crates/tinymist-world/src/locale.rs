@@ -0,0 +1,79 @@
+use std::fmt;
+use std::str::FromStr;
+
+use typst::text::{Lang, Region};
+
+/// A locale overriding the default language and region of the text, e.g.
+/// `fr-FR` or `en`. The rules in the document still take precedence over it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Locale {
+    /// The language of the text.
+    pub lang: Lang,
+    /// The region of the text.
+    pub region: Option<Region>,
+}
+
+impl FromStr for Locale {
+    type Err = &'static str;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts = value.trim().split(['-', '_']);
+        let lang = parts.next().unwrap_or_default().parse()?;
+        let region = parts.next().map(Region::from_str).transpose()?;
+        if parts.next().is_some() {
+            return Err("locale must consist of a language and an optional region");
+        }
+
+        Ok(Locale { lang, region })
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.lang.as_str())?;
+        if let Some(region) = self.region {
+            write!(f, "-{}", region.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+impl serde::Serialize for Locale {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Locale {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_locale() {
+        let locale: Locale = "fr-FR".parse().unwrap();
+        assert_eq!(locale.lang, Lang::FRENCH);
+        assert_eq!(locale.to_string(), "fr-FR");
+
+        let locale: Locale = "en_us".parse().unwrap();
+        assert_eq!(locale.to_string(), "en-US");
+
+        let locale: Locale = "de".parse().unwrap();
+        assert_eq!(locale.region, None);
+
+        assert!("".parse::<Locale>().is_err());
+        assert!("fr-FR-x".parse::<Locale>().is_err());
+    }
+}
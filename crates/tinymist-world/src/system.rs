@@ -113,6 +113,12 @@ impl SystemUniverseBuilder {
             args.and_then(|args| Some(args.package_path.clone()?.into())),
             args.and_then(|args| Some(args.package_cache_path.clone()?.into())),
         )
+        .with_registry(args.and_then(|args| args.package_registry.clone()))
+        .with_proxy(args.and_then(|args| args.package_proxy.clone()))
+        .with_headers(
+            args.map(|args| args.package_headers.clone())
+                .unwrap_or_default(),
+        )
     }
 }
 
@@ -32,11 +32,13 @@ use typst::{
     diag::{At, FileError, FileResult, SourceResult, eco_format},
     foundations::{Bytes, Datetime, Dict, Duration},
     syntax::{Source, Span, VirtualPath},
-    text::{Font, FontBook},
+    text::{Font, FontBook, TextElem},
     utils::LazyHash,
 };
 
-use crate::{CompileSnapshot, MEMORY_MAIN_ENTRY, package::PackageRegistry, source::SourceDb};
+use crate::{
+    CompileSnapshot, Locale, MEMORY_MAIN_ENTRY, package::PackageRegistry, source::SourceDb,
+};
 use crate::{
     WorldComputeGraph,
     parser::{
@@ -164,12 +166,14 @@ impl<F: CompilerFeat> CompilerUniverse<F> {
         let mut world = if self.main_id().is_some() {
             self.snapshot_with(inputs)
         } else {
+            let inputs = inputs.unwrap_or_default();
             self.snapshot_with(Some(TaskInputs {
                 entry: Some(
                     self.entry_state()
                         .select_in_workspace(MEMORY_MAIN_ENTRY.vpath().as_rooted_path_compat()),
                 ),
-                inputs: inputs.and_then(|i| i.inputs),
+                inputs: inputs.inputs,
+                locale: inputs.locale,
             }))
         };
 
@@ -185,7 +189,7 @@ impl<F: CompilerFeat> CompilerUniverse<F> {
             entry: self.entry.clone(),
             features: self.features.clone(),
             inputs: self.inputs.clone(),
-            library: create_library(self.inputs.clone(), self.features.clone()),
+            library: create_library(self.inputs.clone(), self.features.clone(), None),
             font_resolver: self.font_resolver.clone(),
             registry: self.registry.clone(),
             vfs: self.vfs.snapshot(),
@@ -196,6 +200,7 @@ impl<F: CompilerFeat> CompilerUniverse<F> {
             },
             now: OnceLock::new(),
             creation_timestamp: self.creation_timestamp,
+            locale: None,
         };
 
         mutant.map(|m| w.task(m)).unwrap_or(w)
@@ -292,6 +297,7 @@ impl<F: CompilerFeat> CompilerUniverse<F> {
                 self.snapshot_with(Some(TaskInputs {
                     entry: Some(s),
                     inputs: None,
+                    locale: None,
                 }))
             }
             None => self.snapshot(),
@@ -538,6 +544,8 @@ pub struct CompilerWorld<F: CompilerFeat> {
     now: OnceLock<NowStorage>,
     /// The creation timestamp for reproducible builds.
     creation_timestamp: Option<i64>,
+    /// The locale overriding the default language and region of the text.
+    locale: Option<Locale>,
 }
 
 impl<F: CompilerFeat> Clone for CompilerWorld<F> {
@@ -553,6 +561,8 @@ pub struct TaskInputs {
     pub entry: Option<EntryState>,
     /// The inputs.
     pub inputs: Option<Arc<LazyHash<Dict>>>,
+    /// The locale overriding the default language and region of the text.
+    pub locale: Option<Locale>,
 }
 
 impl<F: CompilerFeat> CompilerWorld<F> {
@@ -561,10 +571,11 @@ impl<F: CompilerFeat> CompilerWorld<F> {
         // Fetch to avoid inconsistent state.
         let _ = self.today(None);
 
-        let library = mutant
-            .inputs
-            .clone()
-            .map(|inputs| create_library(inputs, self.features.clone()));
+        let locale = mutant.locale.or(self.locale);
+        let library = (mutant.inputs.is_some() || mutant.locale.is_some()).then(|| {
+            let inputs = mutant.inputs.clone().unwrap_or_else(|| self.inputs.clone());
+            create_library(inputs, self.features.clone(), locale)
+        });
 
         let root_changed = if let Some(e) = mutant.entry.as_ref() {
             self.entry.workspace_root() != e.workspace_root()
@@ -584,6 +595,7 @@ impl<F: CompilerFeat> CompilerWorld<F> {
             source_db: self.source_db.clone(),
             now: self.now.clone(),
             creation_timestamp: self.creation_timestamp,
+            locale,
         };
 
         if root_changed {
@@ -718,7 +730,7 @@ impl<F: CompilerFeat> CompilerWorld<F> {
         }
 
         let mut world = self.clone();
-        world.library = create_library(world.inputs.clone(), self.features.clone());
+        world.library = create_library(world.inputs.clone(), self.features.clone(), self.locale);
 
         Cow::Owned(world)
     }
@@ -736,7 +748,7 @@ impl<F: CompilerFeat> CompilerWorld<F> {
         let features = typst::Features::from_iter([typst::Feature::Html]);
 
         let mut world = self.clone();
-        world.library = create_library(world.inputs.clone(), features);
+        world.library = create_library(world.inputs.clone(), features, self.locale);
 
         Cow::Owned(world)
     }
@@ -1110,11 +1122,20 @@ impl<'a, F: CompilerFeat> codespan_reporting::files::Files<'a> for CompilerWorld
 }
 
 #[comemo::memoize]
-fn create_library(inputs: Arc<LazyHash<Dict>>, features: Features) -> Arc<LazyHash<Library>> {
-    let lib = typst::Library::builder()
+fn create_library(
+    inputs: Arc<LazyHash<Dict>>,
+    features: Features,
+    locale: Option<Locale>,
+) -> Arc<LazyHash<Library>> {
+    let mut lib = typst::Library::builder()
         .with_inputs(inputs.deref().deref().clone())
         .with_features(features)
         .build();
 
+    if let Some(locale) = locale {
+        lib.styles.set(TextElem::lang, locale.lang);
+        lib.styles.set(TextElem::region, locale.region);
+    }
+
     Arc::new(LazyHash::new(lib))
 }
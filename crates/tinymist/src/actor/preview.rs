@@ -4,7 +4,9 @@ use lsp_types::notification::Notification;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use sync_ls::{internal_error, LspClient, LspResult};
-use tinymist_preview::{ControlPlaneMessage, Previewer};
+use tinymist_preview::{
+    ControlPlaneMessage, PreviewStatus, PreviewTheme, Previewer, SetThemeRequest,
+};
 use tinymist_std::error::IgnoreLogging;
 use tokio::sync::{mpsc, oneshot};
 
@@ -26,6 +28,10 @@ pub struct PreviewTab {
     pub is_primary: bool,
     /// Whether this tab is background
     pub is_background: bool,
+    /// The active theme of the previewer
+    pub theme: PreviewTheme,
+    /// The latest status reported by the previewer
+    pub status: Arc<parking_lot::Mutex<PreviewStatus>>,
 }
 
 pub enum PreviewRequest {
@@ -34,6 +40,9 @@ pub enum PreviewRequest {
     KillAll(oneshot::Sender<LspResult<JsonValue>>),
     Scroll(String, ControlPlaneMessage),
     ScrollAll(ControlPlaneMessage),
+    SetTheme(String, PreviewTheme, oneshot::Sender<LspResult<JsonValue>>),
+    ListThemes(oneshot::Sender<LspResult<JsonValue>>),
+    Status(String, oneshot::Sender<LspResult<JsonValue>>),
 }
 
 pub struct PreviewActor {
@@ -69,6 +78,23 @@ impl PreviewActor {
                         self.scroll(task_id, req.clone()).await;
                     }
                 }
+                PreviewRequest::SetTheme(task_id, theme, tx) => {
+                    let _ = tx.send(self.set_theme(task_id, theme));
+                }
+                PreviewRequest::ListThemes(tx) => {
+                    let active = self
+                        .tabs
+                        .iter()
+                        .map(|(task_id, tab)| (task_id.clone(), tab.theme.clone()))
+                        .collect::<HashMap<_, _>>();
+                    let _ = tx.send(Ok(serde_json::json!({
+                        "kinds": PreviewTheme::KINDS,
+                        "active": active,
+                    })));
+                }
+                PreviewRequest::Status(task_id, tx) => {
+                    let _ = tx.send(self.status(task_id));
+                }
             }
         }
     }
@@ -125,6 +151,33 @@ impl PreviewActor {
     async fn scroll(&mut self, task_id: String, req: ControlPlaneMessage) -> Option<()> {
         self.tabs.get(&task_id)?.ctl_tx.send(req).ok()
     }
+
+    fn set_theme(&mut self, task_id: String, theme: PreviewTheme) -> LspResult<JsonValue> {
+        let tab = self
+            .tabs
+            .get_mut(&task_id)
+            .ok_or_else(|| internal_error("task not found"))?;
+
+        let req = ControlPlaneMessage::SetTheme(SetThemeRequest {
+            theme: theme.clone(),
+        });
+        tab.ctl_tx
+            .send(req)
+            .map_err(|_| internal_error("failed to send theme request"))?;
+        tab.theme = theme;
+
+        Ok(JsonValue::Null)
+    }
+
+    fn status(&mut self, task_id: String) -> LspResult<JsonValue> {
+        let tab = self
+            .tabs
+            .get(&task_id)
+            .ok_or_else(|| internal_error("task not found"))?;
+
+        let status: PreviewStatus = *tab.status.lock();
+        serde_json::to_value(status).map_err(internal_error)
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -12,21 +12,23 @@ use serde_json::Value as JsonValue;
 use task::TraceParams;
 use tinymist_assets::TYPST_PREVIEW_HTML;
 use tinymist_query::package::PackageInfo;
-use tinymist_query::{LocalContextGuard, LspRange};
+use tinymist_query::{DiagnosticsMap, LocalContextGuard, LspRange};
 use tinymist_std::error::prelude::*;
+use tinymist_std::ImmutPath;
 use typst::syntax::{LinkedNode, Source};
 
 use super::*;
 use crate::lsp::query::run_query;
 use crate::tool::ast::AstRepr;
 
+use typst::diag::EcoString;
 #[cfg(feature = "system")]
-use typst::diag::{EcoString, StrResult};
+use typst::diag::StrResult;
 #[cfg(feature = "system")]
 use typst::syntax::package::{PackageSpec, VersionlessPackageSpec};
 
 #[cfg(feature = "system")]
-use crate::tool::package::InitTask;
+use crate::tool::package::{InitTask, TemplateSource};
 
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -34,6 +36,152 @@ struct ExportSyntaxRangeOpts {
     range: Option<LspRange>,
 }
 
+/// The options to compile all the documents in the workspace.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompileWorkspaceOpts {
+    /// The entries to compile. If empty, the configured entry or the discovered
+    /// entrypoints in the workspace roots are compiled.
+    #[serde(default)]
+    entries: Vec<PathBuf>,
+    /// The glob patterns of the entries to include, relative to the root.
+    #[serde(default)]
+    include: Vec<String>,
+    /// The glob patterns of the entries to exclude, relative to the root.
+    #[serde(default)]
+    exclude: Vec<String>,
+    /// The number of documents compiled concurrently. Defaults to the number
+    /// of available CPUs.
+    jobs: Option<std::num::NonZeroUsize>,
+}
+
+/// The options to warm the caches for a set of files.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WarmCacheOpts {
+    /// The number of files warmed concurrently. Defaults to half of the
+    /// available CPUs, leaving the rest to the interactive requests.
+    jobs: Option<std::num::NonZeroUsize>,
+}
+
+/// The options to evaluate an expression.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EvalOpts {
+    /// The time after which the evaluation is cancelled in milliseconds, which
+    /// is 5 seconds by default.
+    timeout: Option<u64>,
+}
+
+/// The options to export the layout of a document.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportLayoutOpts {
+    /// Which pages to export. When unspecified, all pages are exported.
+    pages: Option<Vec<tinymist_task::Pages>>,
+}
+
+/// The scope of the cache to clear.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum ClearCacheScope {
+    /// Clears the analysis results of a file.
+    File(PathBuf),
+    /// Clears the analysis results of the files in a package namespace, e.g.
+    /// `@preview`, or in a package, e.g. `@preview/cetz`.
+    Package(EcoString),
+}
+
+/// An error of the export commands. Besides the message, the error carries a
+/// stable code in the `data` field of the response, so that clients can react
+/// to the failure without parsing the message.
+#[derive(Debug, Clone)]
+pub(crate) enum ExportError {
+    /// The arguments of the command are invalid.
+    InvalidArgs(String),
+    /// The entry or the output path of the document cannot be resolved.
+    RootUnresolved(String),
+    /// The document failed to compile.
+    CompileFailed(String),
+    /// The document compiled, but the artifact failed to be generated.
+    ExportFailed(String),
+    /// The artifact failed to be written to the file system.
+    WriteFailed(String),
+    /// The export was canceled by the client before it finished.
+    Cancelled(String),
+}
+
+impl ExportError {
+    /// The stable code of the error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidArgs(..) => "INVALID_ARGS",
+            Self::RootUnresolved(..) => "ROOT_UNRESOLVED",
+            Self::CompileFailed(..) => "COMPILE_FAILED",
+            Self::ExportFailed(..) => "EXPORT_FAILED",
+            Self::WriteFailed(..) => "WRITE_FAILED",
+            Self::Cancelled(..) => "CANCELLED",
+        }
+    }
+
+    /// The message of the error.
+    pub fn message(&self) -> &str {
+        match self {
+            Self::InvalidArgs(msg)
+            | Self::RootUnresolved(msg)
+            | Self::CompileFailed(msg)
+            | Self::ExportFailed(msg)
+            | Self::WriteFailed(msg)
+            | Self::Cancelled(msg) => msg,
+        }
+    }
+}
+
+impl From<ExportError> for ResponseError {
+    fn from(err: ExportError) -> Self {
+        let mut resp = match &err {
+            ExportError::InvalidArgs(msg) => invalid_params(msg),
+            ExportError::Cancelled(msg) => ResponseError {
+                code: ErrorCode::RequestCanceled as i32,
+                message: msg.clone(),
+                data: None,
+            },
+            _ => internal_error(err.message()),
+        };
+        resp.data = Some(serde_json::json!({ "code": err.code() }));
+        resp
+    }
+}
+
+/// The cancellation tokens of the running exports, by the requests running
+/// them, so that `$/cancelRequest` can abort the exports.
+pub(crate) type ExportCancels = std::sync::Arc<
+    parking_lot::Mutex<std::collections::HashMap<RequestId, tokio_util::sync::CancellationToken>>,
+>;
+
+/// Parses the template source. The package specification takes precedence. If
+/// the user didn't specify the version, we try to figure it out automatically by
+/// downloading the package index or searching the disk. Otherwise, git URLs
+/// (`git+…`) and local directories are accepted.
+#[cfg(feature = "system")]
+fn parse_template_source(
+    snap: &crate::project::LspComputeGraph,
+    from_source: &str,
+) -> StrResult<TemplateSource> {
+    let spec = from_source.parse::<PackageSpec>().or_else(|err| {
+        // Try to parse without version, but prefer the error message of the
+        // normal package spec parsing if it fails.
+        let spec: VersionlessPackageSpec = from_source.parse().map_err(|_| err)?;
+        let version = snap.registry().determine_latest_version(&spec)?;
+        StrResult::Ok(spec.at(version))
+    });
+
+    match spec {
+        Ok(spec) => Ok(TemplateSource::Package(spec)),
+        Err(err) => TemplateSource::from_non_package(from_source).ok_or(err),
+    }
+}
+
 /// Here are implemented the handlers for each command.
 impl ServerState {
     /// Export a range of the current document as Ansi highlighted text.
@@ -72,6 +220,22 @@ impl ServerState {
         just_ok(JsonValue::String(output))
     }
 
+    /// Formats the syntax nodes selected by a range of the current file. Returns
+    /// the range that is reformatted and its formatted text.
+    pub fn format_range(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let path = get_arg!(args[0] as PathBuf);
+        let range = get_arg!(args[1] as LspRange);
+
+        let formatter = self.formatter.clone();
+        let (range, text) = self.select_range(path, Some(range), |source, range| {
+            formatter
+                .format_range(source, range.unwrap_or_default())
+                .ok_or_else(|| internal_error("cannot format the range"))
+        })?;
+
+        just_ok(serde_json::json!({ "range": range, "text": text }))
+    }
+
     fn select_range<T>(
         &mut self,
         path: PathBuf,
@@ -93,13 +257,725 @@ impl ServerState {
         f(s, range)
     }
 
-    /// Clear all cached resources.
-    pub fn clear_cache(&mut self, _arguments: Vec<JsonValue>) -> AnySchedulableResponse {
-        comemo::evict(0);
-        self.project.analysis.clear_cache();
+    /// Clear cached resources. If a scope is given, only the analysis results
+    /// related to the file or the package namespace are cleared, along with
+    /// the memoized results of the compiler, which cannot be cleared by scope.
+    /// Otherwise, all cached resources are cleared, including the svg of
+    /// exported pages.
+    pub fn clear_cache(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        // Editors may pass the uri of the active document, which is not a scope
+        // and clears all the cached resources as before.
+        let scope = match args.first() {
+            Some(JsonValue::Object(..)) => get_arg!(args[0] as Option<ClearCacheScope>),
+            _ => None,
+        };
+
+        match scope {
+            None => {
+                comemo::evict(0);
+                tinymist_task::clear_svg_page_cache();
+                self.project.analysis.clear_cache();
+            }
+            Some(ClearCacheScope::File(path)) => {
+                let snap = self.snapshot().map_err(internal_error)?;
+                let Some(fid) = snap.world().id_for_path(&path) else {
+                    return Err(invalid_params(format!(
+                        "file not found in workspace: {path:?}"
+                    )));
+                };
+                log::info!("clearing cache of file: {fid:?}");
+                comemo::evict(0);
+                self.project.analysis.clear_cache_by(|id| id == fid);
+            }
+            Some(ClearCacheScope::Package(namespace)) => {
+                log::info!("clearing cache of package namespace: {namespace}");
+                let namespace = namespace.trim_start_matches('@');
+                let (namespace, name) = match namespace.split_once('/') {
+                    Some((namespace, name)) => (namespace, Some(name)),
+                    None => (namespace, None),
+                };
+                comemo::evict(0);
+                self.project.analysis.clear_cache_by(|id| {
+                    id.package().is_some_and(|spec| {
+                        spec.namespace == namespace && name.is_none_or(|name| spec.name == name)
+                    })
+                });
+            }
+        }
+
+        just_ok(JsonValue::Null)
+    }
+
+    /// Get the outline of the document, i.e. the headings with the pages they
+    /// start on.
+    pub fn get_outline(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        use crate::world::base::{FlagTask, OptionDocumentTask, PagedCompilationTask};
+        use tinymist_std::typst::TypstPagedDocument;
+
+        let path = get_arg!(args[0] as PathBuf);
+        let entry = self.entry_resolver().resolve(Some(path.as_path().into()));
+        let snap = self.snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let snap = snap.task(crate::world::TaskInputs {
+                entry: Some(entry),
+                ..Default::default()
+            });
+
+            let _ = snap.provide::<FlagTask<PagedCompilationTask>>(Ok(FlagTask::flag(true)));
+            let doc = snap
+                .compute::<OptionDocumentTask<TypstPagedDocument>>()
+                .map_err(internal_error)?;
+            let Some(doc) = doc.as_ref() else {
+                return Ok(JsonValue::Null);
+            };
+
+            let outline = crate::tool::outline::document_outline(doc);
+            serde_json::to_value(outline).map_err(internal_error)
+        })
+    }
+
+    /// Compile all the documents in the workspace, returning the diagnostics
+    /// aggregated by file and the error and warning counts of each entry.
+    pub fn compile_workspace(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        use rayon::prelude::*;
+
+        use crate::project::CompiledArtifact;
+        use crate::tool::workspace::{discover_entries, WorkspaceFilter};
+
+        let opts = get_arg_or_default!(args[0] as CompileWorkspaceOpts);
+        let resolver = self.entry_resolver().clone();
+        let enc = self.const_config().position_encoding;
+        let snap = self.snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            // Discovering the entries walks the workspace, so it is done in the
+            // blocking task as well.
+            let compile = move || -> Result<JsonValue> {
+                let filter = WorkspaceFilter {
+                    include: opts.include,
+                    exclude: opts.exclude,
+                };
+                let roots = match resolver.root_path.as_ref() {
+                    Some(root) => vec![root.to_path_buf()],
+                    None => resolver
+                        .roots
+                        .iter()
+                        .map(|root| root.to_path_buf())
+                        .collect(),
+                };
+                // The explicit entries and the configured entry are filtered
+                // like the discovered ones.
+                let entries: Vec<PathBuf> = if !opts.entries.is_empty() {
+                    opts.entries
+                        .into_iter()
+                        .filter(|path| filter.is_path_selected(&roots, path))
+                        .collect()
+                } else if let Some(entry) = resolver.entry.as_ref() {
+                    Some(entry.to_path_buf())
+                        .filter(|path| filter.is_path_selected(&roots, path))
+                        .into_iter()
+                        .collect()
+                } else {
+                    roots
+                        .iter()
+                        .flat_map(|root| discover_entries(root, &filter))
+                        .collect()
+                };
+                let jobs = opts.jobs.map_or_else(
+                    || std::thread::available_parallelism().map_or(1, |n| n.get()),
+                    |jobs| jobs.get(),
+                );
+                log::info!(
+                    "compiling {} workspace entries with {jobs} jobs",
+                    entries.len()
+                );
+
+                let compile_entry = |path: &PathBuf| {
+                    let entry = resolver.resolve(Some(path.as_path().into()));
+                    let graph = snap.task(crate::world::TaskInputs {
+                        entry: Some(entry),
+                        ..Default::default()
+                    });
+                    let art = CompiledArtifact::from_graph(graph, false);
+                    let diagnostics = tinymist_query::convert_diagnostics(
+                        art.graph.clone(),
+                        art.diagnostics(),
+                        enc,
+                    );
+                    let status = serde_json::json!({
+                        "path": path,
+                        "errors": art.error_cnt(),
+                        "warnings": art.warning_cnt(),
+                    });
+                    (status, diagnostics)
+                };
+
+                let results = rayon::ThreadPoolBuilder::new()
+                    .num_threads(jobs)
+                    .build()
+                    .context("failed to build thread pool for compiling workspace")?
+                    .install(|| entries.par_iter().map(compile_entry).collect::<Vec<_>>());
+
+                // A file shared by several entries reports the same diagnostics
+                // for each of them, which are deduplicated.
+                let mut statuses = vec![];
+                let mut aggregated = DiagnosticsMap::default();
+                for (status, diagnostics) in results {
+                    statuses.push(status);
+                    for (uri, diags) in diagnostics {
+                        let merged = aggregated.entry(uri).or_default();
+                        for diag in diags {
+                            if !merged.contains(&diag) {
+                                merged.push(diag);
+                            }
+                        }
+                    }
+                }
+
+                Ok(serde_json::json!({
+                    "entries": statuses,
+                    "diagnostics": aggregated,
+                }))
+            };
+
+            tokio::task::spawn_blocking(compile)
+                .await
+                .map_err(internal_error)?
+                .map_err(internal_error)
+        })
+    }
+
+    /// Warm the caches for the files in the background by compiling them and
+    /// running the common analyses on them, so that the first requests on them
+    /// are fast. The results are discarded. A new warm-up cancels the running
+    /// one, and so does `tinymist.cancelWarmCache`.
+    pub fn warm_cache(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        use rayon::prelude::*;
+
+        use crate::project::CompiledArtifact;
+
+        let paths = get_arg!(args[0] as Vec<PathBuf>);
+        let opts = get_arg_or_default!(args[1] as WarmCacheOpts);
+        let jobs = opts.jobs.map_or_else(
+            || std::thread::available_parallelism().map_or(1, |n| n.get().div_ceil(2)),
+            |jobs| jobs.get(),
+        );
+
+        self.warm_cache.cancel();
+        let cancel = tokio_util::sync::CancellationToken::new();
+        self.warm_cache = cancel.clone();
+
+        let resolver = self.entry_resolver().clone();
+        let snaps = paths
+            .into_iter()
+            .map(|path| {
+                let entry = resolver.resolve(Some(path.as_path().into()));
+                let snap = self.query_snapshot()?.task(crate::world::TaskInputs {
+                    entry: Some(entry),
+                    ..Default::default()
+                });
+                Ok((path, snap))
+            })
+            .collect::<Result<Vec<_>>>()
+            .map_err(internal_error)?;
+
+        just_future(async move {
+            log::info!("warming caches for {} files with {jobs} jobs", snaps.len());
+
+            let warm = move || -> Result<()> {
+                let warm_file =
+                    |(path, snap): (PathBuf, tinymist_query::analysis::LspQuerySnapshot)| {
+                        if cancel.is_cancelled() {
+                            return;
+                        }
+                        // Compiles the document to populate the comemo caches.
+                        CompiledArtifact::from_graph(snap.snap.clone(), false);
+                        if cancel.is_cancelled() {
+                            return;
+                        }
+                        let res = snap.run_analysis(|ctx| tinymist_query::warm_cache(ctx, &path));
+                        if let Err(err) = res {
+                            log::warn!("failed to warm caches for {path:?}: {err}");
+                        }
+                    };
+
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(jobs)
+                    .build()
+                    .context("failed to build thread pool for warming caches")?
+                    .install(|| snaps.into_par_iter().for_each(warm_file));
+
+                if cancel.is_cancelled() {
+                    log::info!("warming caches is cancelled");
+                }
+                Ok(())
+            };
+
+            tokio::task::spawn_blocking(warm)
+                .await
+                .map_err(internal_error)?
+                .map_err(internal_error)?;
+
+            Ok(JsonValue::Null)
+        })
+    }
+
+    /// Cancel the running cache warm-up, if any.
+    pub fn cancel_warm_cache(&mut self, _args: Vec<JsonValue>) -> AnySchedulableResponse {
+        self.warm_cache.cancel();
         just_ok(JsonValue::Null)
     }
 
+    /// Get the type inferred for the expression at the position, which is
+    /// `null` if there is no expression.
+    pub fn infer_type(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let path = get_arg!(args[0] as PathBuf);
+        let position = get_arg!(args[1] as lsp_types::Position);
+        let snap = self.query_snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let ty = snap
+                .run_analysis(|a| tinymist_query::infer_type(a, &path, position))
+                .map_err(internal_error)?;
+
+            serde_json::to_value(ty).map_err(internal_error)
+        })
+    }
+
+    /// Get the kind of completions offered at a position and the text being
+    /// completed, which is `null` if the file is not a source file.
+    pub fn get_completion_context(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let path = get_arg!(args[0] as PathBuf);
+        let position = get_arg!(args[1] as lsp_types::Position);
+        let snap = self.query_snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let context = snap
+                .run_analysis(|a| tinymist_query::completion_context(a, &path, position))
+                .map_err(internal_error)?;
+
+            serde_json::to_value(context).map_err(internal_error)
+        })
+    }
+
+    /// Get the import and include statements of a file with their resolved
+    /// targets, which is `null` if the file is not a source file.
+    pub fn get_imports(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let path = get_arg!(args[0] as PathBuf);
+        let snap = self.query_snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let imports = snap
+                .run_analysis(|a| tinymist_query::get_imports(a, &path))
+                .map_err(internal_error)?;
+
+            serde_json::to_value(imports).map_err(internal_error)
+        })
+    }
+
+    /// Get the `let` definitions and the imported names of a file, including
+    /// the nested ones, which is `null` if the file is not a source file.
+    pub fn get_definitions(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let path = get_arg!(args[0] as PathBuf);
+        let snap = self.query_snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let definitions = snap
+                .run_analysis(|a| tinymist_query::get_definitions(a, &path))
+                .map_err(internal_error)?;
+
+            serde_json::to_value(definitions).map_err(internal_error)
+        })
+    }
+
+    /// Evaluate a code expression in the scope of a file, returning the repr of
+    /// the result. The evaluation is cancelled when the timeout elapses, which
+    /// aborts it at its next access to a file or a font.
+    pub fn eval_expr(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let path = get_arg!(args[0] as PathBuf);
+        let expr = get_arg!(args[1] as String);
+        let opts = get_arg_or_default!(args[2] as EvalOpts);
+        let timeout = std::time::Duration::from_millis(opts.timeout.unwrap_or(5000));
+        let snap = self.query_snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let cancel = tokio_util::sync::CancellationToken::new();
+            // Cancels the evaluation if it times out or the request is dropped.
+            let _cancel_guard = cancel.clone().drop_guard();
+            let eval = tokio::task::spawn_blocking(move || {
+                snap.run_analysis(|a| tinymist_query::eval_expr(a, &path, &expr, &cancel))
+            });
+            let repr = tokio::time::timeout(timeout, eval)
+                .await
+                .map_err(|_| internal_error(format!("evaluation timed out after {timeout:?}")))?
+                .map_err(internal_error)?
+                .map_err(internal_error)?
+                .map_err(invalid_params)?;
+
+            Ok(JsonValue::String(repr.into()))
+        })
+    }
+
+    /// Rename a label across the workspace, returning the workspace edit that
+    /// rewrites its definitions and references.
+    pub fn rename_label(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let label = get_arg!(args[0] as String);
+        let new_name = get_arg!(args[1] as String);
+        let snap = self.query_snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let edit = snap
+                .run_analysis(|a| tinymist_query::rename_label(a, &label, &new_name))
+                .map_err(internal_error)?
+                .map_err(invalid_params)?;
+
+            serde_json::to_value(edit).map_err(internal_error)
+        })
+    }
+
+    /// Wrap the image or block in the range into a figure with a unique label,
+    /// returning the workspace edit that does it.
+    pub fn convert_to_figure(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let path = get_arg!(args[0] as PathBuf);
+        let range = get_arg!(args[1] as lsp_types::Range);
+        let snap = self.query_snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let edit = snap
+                .run_analysis(|a| tinymist_query::convert_to_figure(a, &path, range))
+                .map_err(internal_error)?
+                .map_err(invalid_params)?;
+
+            serde_json::to_value(edit).map_err(internal_error)
+        })
+    }
+
+    /// Get the graph of the labels and the references pointing to them across
+    /// the workspace. References to nonexistent labels are reported as
+    /// dangling edges.
+    pub fn export_label_graph(&mut self, _args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let snap = self.query_snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let graph = snap
+                .run_analysis(|a| tinymist_query::label_graph(a))
+                .map_err(internal_error)?;
+
+            serde_json::to_value(graph).map_err(internal_error)
+        })
+    }
+
+    /// Find the labels defined more than once across the workspace, with the
+    /// locations of their definitions. The labels are scanned from the syntax
+    /// of the files without compiling the document.
+    pub fn find_duplicate_labels(&mut self, _args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let snap = self.query_snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let duplicates = snap
+                .run_analysis(|a| tinymist_query::find_duplicate_labels(a))
+                .map_err(internal_error)?;
+
+            serde_json::to_value(duplicates).map_err(internal_error)
+        })
+    }
+
+    /// Get the speaker notes of each slide of a presentation, which are
+    /// attached by `<pdfpc-notes>` metadata.
+    pub fn export_speaker_notes(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        use crate::world::base::{FlagTask, OptionDocumentTask, PagedCompilationTask};
+        use tinymist_std::typst::TypstPagedDocument;
+
+        let path = get_arg!(args[0] as PathBuf);
+        let entry = self.entry_resolver().resolve(Some(path.as_path().into()));
+        let snap = self.snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let snap = snap.task(crate::world::TaskInputs {
+                entry: Some(entry),
+                ..Default::default()
+            });
+
+            let _ = snap.provide::<FlagTask<PagedCompilationTask>>(Ok(FlagTask::flag(true)));
+            let doc = snap
+                .compute::<OptionDocumentTask<TypstPagedDocument>>()
+                .map_err(internal_error)?;
+            let Some(doc) = doc.as_ref() else {
+                return Ok(JsonValue::Null);
+            };
+
+            let notes = tinymist_task::speaker_notes(doc);
+            serde_json::to_value(notes).map_err(internal_error)
+        })
+    }
+
+    /// Compares the rendered pages of the saved version of a document with
+    /// the version containing the unsaved edits.
+    pub fn diff_render(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        use crate::tool::diff_render::DiffRenderOpts;
+        use crate::world::base::ShadowApi;
+        use tinymist_std::typst::TypstPagedDocument;
+
+        let path = get_arg!(args[0] as PathBuf);
+        let opts = get_arg_or_default!(args[1] as DiffRenderOpts);
+        let entry = self.entry_resolver().resolve(Some(path.as_path().into()));
+        let snap = self.snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let edited = snap.world().task(crate::world::TaskInputs {
+                entry: Some(entry),
+                ..Default::default()
+            });
+            // Drops the unsaved edits so that the files are read from the disk.
+            let mut saved = edited.clone();
+            saved.reset_shadow();
+
+            let compile = |world: &crate::project::LspWorld, version: &str| {
+                let doc = typst_shim::compile_opt::<TypstPagedDocument>(world).output;
+                doc.map_err(|errors| {
+                    let message = errors.first().map_or("", |err| err.message.as_str());
+                    invalid_params(format!("cannot compile the {version} version: {message}"))
+                })
+            };
+            let saved = compile(&saved, "saved")?;
+            let edited = compile(&edited, "edited")?;
+
+            let pages = crate::tool::diff_render::diff_render(&saved, &edited, &opts)
+                .map_err(invalid_params)?;
+            serde_json::to_value(pages).map_err(internal_error)
+        })
+    }
+
+    /// Get the word, character, and page counts of a document, optionally
+    /// scoped to a region delimited by labels.
+    pub fn get_counts(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        use crate::tool::word_count::CountRegion;
+        use crate::world::base::{FlagTask, OptionDocumentTask, PagedCompilationTask};
+        use tinymist_std::typst::TypstPagedDocument;
+
+        let path = get_arg!(args[0] as PathBuf);
+        let region = get_arg_or_default!(args[1] as CountRegion);
+        let entry = self.entry_resolver().resolve(Some(path.as_path().into()));
+        let snap = self.snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let snap = snap.task(crate::world::TaskInputs {
+                entry: Some(entry),
+                ..Default::default()
+            });
+
+            let _ = snap.provide::<FlagTask<PagedCompilationTask>>(Ok(FlagTask::flag(true)));
+            let doc = snap
+                .compute::<OptionDocumentTask<TypstPagedDocument>>()
+                .map_err(internal_error)?;
+            let Some(doc) = doc.as_ref() else {
+                return Ok(JsonValue::Null);
+            };
+
+            let counts =
+                crate::tool::word_count::document_counts(doc, &region).map_err(invalid_params)?;
+            serde_json::to_value(counts).map_err(internal_error)
+        })
+    }
+
+    /// Extract the sections of a document rendered on the pages into a new
+    /// document, which is written to the output path if there is one.
+    pub fn extract_pages(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        use crate::project::EntryReader;
+        use crate::world::base::{FlagTask, OptionDocumentTask, PagedCompilationTask};
+        use tinymist_std::typst::TypstPagedDocument;
+        use tinymist_task::Pages;
+        use typst::World;
+
+        let path = get_arg!(args[0] as PathBuf);
+        let ranges = get_arg!(args[1] as Vec<Pages>);
+        let out_path = get_arg_or_default!(args[2] as Option<PathBuf>);
+        if out_path.as_ref().is_some_and(|path| path.is_relative()) {
+            return Err(invalid_params("the output path must be absolute"));
+        }
+        let entry = self.entry_resolver().resolve(Some(path.as_path().into()));
+        let snap = self.snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let snap = snap.task(crate::world::TaskInputs {
+                entry: Some(entry),
+                ..Default::default()
+            });
+
+            let _ = snap.provide::<FlagTask<PagedCompilationTask>>(Ok(FlagTask::flag(true)));
+            let doc = snap
+                .compute::<OptionDocumentTask<TypstPagedDocument>>()
+                .map_err(internal_error)?;
+            let Some(doc) = doc.as_ref() else {
+                return Err(invalid_params("cannot compile the document"));
+            };
+            let world = snap.world();
+            let main = world
+                .main_id()
+                .ok_or_else(|| invalid_params("no main file found"))?;
+            let source = world.source(main).map_err(invalid_params)?;
+
+            let extracted = crate::tool::extract_pages::extract_pages(doc, &source, &ranges)
+                .map_err(invalid_params)?;
+            if let Some(out_path) = &out_path {
+                tinymist_std::fs::paths::write_atomic(out_path, &extracted.text)
+                    .map_err(internal_error)?;
+            }
+
+            Ok(serde_json::json!({
+                "path": out_path,
+                "text": extracted.text,
+                "sections": extracted.sections,
+            }))
+        })
+    }
+
+    /// Check whether the document conforms to the PDF standards without
+    /// writing a PDF, returning the issues blocking the conformance by file.
+    /// The standards default to the ones configured for the PDF export.
+    pub fn check_pdf_standards(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        use crate::world::base::{FlagTask, OptionDocumentTask, PagedCompilationTask};
+        use tinymist_std::typst::TypstPagedDocument;
+        use tinymist_task::PdfExport;
+
+        let path = get_arg!(args[0] as PathBuf);
+        let standards = get_arg_or_default!(args[1] as Option<Vec<PdfStandard>>);
+        let standards = standards
+            .or_else(|| self.config.pdf_standards())
+            .unwrap_or_default();
+        if standards.is_empty() {
+            return Err(invalid_params("no PDF standard to check"));
+        }
+
+        let enc = self.const_config().position_encoding;
+        let entry = self.entry_resolver().resolve(Some(path.as_path().into()));
+        let snap = self.snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let graph = snap.task(crate::world::TaskInputs {
+                entry: Some(entry),
+                ..Default::default()
+            });
+
+            let _ = graph.provide::<FlagTask<PagedCompilationTask>>(Ok(FlagTask::flag(true)));
+            let doc = graph
+                .compute::<OptionDocumentTask<TypstPagedDocument>>()
+                .map_err(internal_error)?;
+            let Some(doc) = doc.as_ref() else {
+                return Ok(JsonValue::Null);
+            };
+
+            let issues = PdfExport::check_standards(doc, &standards).map_err(internal_error)?;
+            let issues = tinymist_query::convert_diagnostics(graph, issues.iter(), enc);
+            serde_json::to_value(issues).map_err(internal_error)
+        })
+    }
+
+    /// Compile a document and export its diagnostics, as a SARIF log by
+    /// default or as the LSP diagnostics grouped by file.
+    pub fn export_diagnostics(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        use crate::project::CompiledArtifact;
+        use crate::tool::sarif::{to_sarif, DiagnosticsFormat};
+        use tinymist_query::PositionEncoding;
+
+        let path = get_arg!(args[0] as PathBuf);
+        let format = get_arg_or_default!(args[1] as DiagnosticsFormat);
+
+        // SARIF counts the columns in UTF-16 code units.
+        let enc = match format {
+            DiagnosticsFormat::Sarif => PositionEncoding::Utf16,
+            DiagnosticsFormat::Json => self.const_config().position_encoding,
+        };
+        let entry = self.entry_resolver().resolve(Some(path.as_path().into()));
+        let snap = self.snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let graph = snap.task(crate::world::TaskInputs {
+                entry: Some(entry),
+                ..Default::default()
+            });
+            let root = graph.world().entry_state().root();
+
+            let art = CompiledArtifact::from_graph(graph.clone(), false);
+            let diagnostics = tinymist_query::convert_diagnostics(graph, art.diagnostics(), enc);
+            match format {
+                DiagnosticsFormat::Sarif => Ok(to_sarif(&diagnostics, root.as_deref())),
+                DiagnosticsFormat::Json => {
+                    serde_json::to_value(diagnostics).map_err(internal_error)
+                }
+            }
+        })
+    }
+
+    /// Export the equations of the document as standalone svgs, each cropped
+    /// to the bounding box of the equation.
+    pub fn export_equations(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        use crate::world::base::{FlagTask, OptionDocumentTask, PagedCompilationTask};
+        use tinymist_std::typst::TypstPagedDocument;
+
+        let path = get_arg!(args[0] as PathBuf);
+        let entry = self.entry_resolver().resolve(Some(path.as_path().into()));
+        let snap = self.snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let snap = snap.task(crate::world::TaskInputs {
+                entry: Some(entry),
+                ..Default::default()
+            });
+
+            let _ = snap.provide::<FlagTask<PagedCompilationTask>>(Ok(FlagTask::flag(true)));
+            let doc = snap
+                .compute::<OptionDocumentTask<TypstPagedDocument>>()
+                .map_err(internal_error)?;
+            let Some(doc) = doc.as_ref() else {
+                return Ok(JsonValue::Null);
+            };
+
+            let equations = crate::tool::equations::document_equations(doc);
+            serde_json::to_value(equations).map_err(internal_error)
+        })
+    }
+
+    /// Export the frame tree of the document, with the kind, the bounding box,
+    /// and the source of each item on the pages.
+    pub fn export_layout(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        use crate::world::base::{FlagTask, OptionDocumentTask, PagedCompilationTask};
+        use tinymist_std::typst::TypstPagedDocument;
+
+        let path = get_arg!(args[0] as PathBuf);
+        let opts = get_arg_or_default!(args[1] as ExportLayoutOpts);
+        let entry = self.entry_resolver().resolve(Some(path.as_path().into()));
+        let snap = self.snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let snap = snap.task(crate::world::TaskInputs {
+                entry: Some(entry),
+                ..Default::default()
+            });
+
+            let _ = snap.provide::<FlagTask<PagedCompilationTask>>(Ok(FlagTask::flag(true)));
+            let doc = snap
+                .compute::<OptionDocumentTask<TypstPagedDocument>>()
+                .map_err(internal_error)?;
+            let Some(doc) = doc.as_ref() else {
+                return Ok(JsonValue::Null);
+            };
+
+            let pages = opts.pages.as_deref();
+            let layout = crate::tool::layout::document_layout(snap.world(), doc, pages);
+            serde_json::to_value(layout).map_err(internal_error)
+        })
+    }
+
+    /// Get the statistics of the analysis caches, keyed by `analysis`. The
+    /// memoized results of the compiler are not counted, since `comemo` doesn't
+    /// expose them, but they are cleared by `tinymist.doClearCache`.
+    pub fn get_cache_stats(&mut self, _arguments: Vec<JsonValue>) -> AnySchedulableResponse {
+        let analysis = self.project.analysis.report_cache_stats();
+        just_ok(serde_json::json!({ "analysis": analysis }))
+    }
+
     /// Pin main file to some path.
     pub fn pin_document(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
         let entry = get_arg!(args[0] as Option<PathBuf>).map(From::from);
@@ -111,6 +987,79 @@ impl ServerState {
         just_ok(JsonValue::Null)
     }
 
+    /// Resolves the entry that the server would use for the given file.
+    pub fn resolve_entry(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let path: ImmutPath = get_arg!(args[0] as PathBuf).into();
+
+        let (entry, root_source) = if self.pinning_by_user {
+            let primary_verse = &self.project.compiler.primary.verse;
+            (primary_verse.entry_state().clone(), None)
+        } else {
+            self.entry_resolver().resolve_with_source(Some(path))
+        };
+
+        let root = entry.root();
+        let main = match (entry.main(), &root) {
+            (Some(main), Some(root)) => main.vpath().realize(root).ok(),
+            _ => None,
+        };
+
+        just_ok(serde_json::json!({
+            "root": root,
+            "main": main,
+            "rootSource": root_source,
+            "pinned": self.pinning_by_user,
+        }))
+    }
+
+    /// Resolves the path of an asset, e.g. an image or a data file, that is
+    /// referenced by a file, in the same way as the compiler does.
+    pub fn resolve_asset(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let path = get_arg!(args[0] as PathBuf);
+        let relative = get_arg!(args[1] as String);
+
+        let snap = self.snapshot().map_err(internal_error)?;
+        just_future(async move {
+            use typst::World;
+
+            let world = snap.world();
+            let Some(current) = world.id_for_path(&path) else {
+                return Err(invalid_params(format!(
+                    "file not found in workspace: {path:?}"
+                )));
+            };
+
+            let id = typst_shim::syntax::resolve_path_from_id(current, &relative)
+                .map_err(|err| invalid_params(err.message().as_str()))?
+                .intern();
+            let resolved = world.path_for_id(id).ok();
+            let (exists, size, error) = match world.file(id) {
+                Ok(bytes) => (true, Some(bytes.len()), None),
+                Err(err) => (false, None, Some(err.to_string())),
+            };
+
+            Ok(serde_json::json!({
+                "path": resolved.as_ref().map(|p| p.as_path()),
+                "exists": exists,
+                "size": size,
+                "error": error,
+            }))
+        })
+    }
+
+    /// Resolves the symbol that a math shorthand becomes, e.g. `→` for `->`,
+    /// along with its name in the `sym` module.
+    pub fn resolve_math_shorthand(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let input = get_arg!(args[0] as String);
+
+        let snap = self.snapshot().map_err(internal_error)?;
+        just_future(async move {
+            let resolved =
+                crate::tool::math_shorthand::resolve_math_shorthand(snap.library(), &input);
+            serde_json::to_value(resolved).map_err(internal_error)
+        })
+    }
+
     /// Focus main file to some path.
     pub fn focus_document(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
         let entry = get_arg!(args[0] as Option<PathBuf>).map(From::from);
@@ -204,84 +1153,82 @@ impl ServerState {
         self.preview.scroll(task_id, req)
     }
 
+    /// Switches the rendering theme of a preview instance.
+    #[cfg(feature = "preview")]
+    pub fn set_preview_theme(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        use tinymist_preview::PreviewTheme;
+
+        let task_id = get_arg!(args[0] as String);
+        let theme = get_arg!(args[1] as PreviewTheme);
+
+        self.preview.set_theme(task_id, theme)
+    }
+
+    /// Lists the preview themes and the active theme of each preview instance.
+    #[cfg(feature = "preview")]
+    pub fn list_preview_themes(&mut self, _args: Vec<JsonValue>) -> AnySchedulableResponse {
+        self.preview.list_themes()
+    }
+
+    /// Get the number of pages rendered by a preview instance and the page in
+    /// its viewport.
+    #[cfg(feature = "preview")]
+    pub fn preview_status(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let task_id = get_arg!(args[0] as String);
+
+        self.preview.status(task_id)
+    }
+
     /// Initialize a new template.
     #[cfg(feature = "system")]
     pub fn init_template(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
-        use crate::tool::package::{self, TemplateSource};
-
-        #[derive(Debug, serde::Serialize)]
-        #[serde(rename_all = "camelCase")]
-        struct InitResult {
-            entry_path: PathBuf,
-        }
+        use crate::tool::package::{self, InitMode};
 
         let from_source = get_arg!(args[0] as String);
         let to_path = get_arg!(args[1] as Option<PathBuf>).map(From::from);
+        let mode = get_arg_or_default!(args[2] as InitMode);
 
         let snap = self.snapshot().map_err(internal_error)?;
 
         just_future(async move {
-            // Parse the package specification. If the user didn't specify the version,
-            // we try to figure it out automatically by downloading the package index
-            // or searching the disk.
-            let spec: PackageSpec = from_source
-                .parse()
-                .or_else(|err| {
-                    // Try to parse without version, but prefer the error message of the
-                    // normal package spec parsing if it fails.
-                    let spec: VersionlessPackageSpec = from_source.parse().map_err(|_| err)?;
-                    let version = snap.registry().determine_latest_version(&spec)?;
-                    StrResult::Ok(spec.at(version))
-                })
+            let from_source = parse_template_source(&snap, &from_source)
                 .map_err(map_string_err("failed to parse package spec"))
                 .map_err(internal_error)?;
 
-            let from_source = TemplateSource::Package(spec);
-
-            let entry_path = package::init(
+            let result = package::init(
                 snap.world(),
                 InitTask {
                     tmpl: from_source.clone(),
                     dir: to_path.clone(),
+                    mode,
                 },
             )
             .map_err(map_string_err("failed to initialize template"))
             .map_err(internal_error)?;
 
-            log::info!("template initialized: {from_source:?} to {to_path:?}");
+            log::info!(
+                "template initialized: {from_source:?} to {to_path:?}, skipped {:?}",
+                result.skipped
+            );
 
-            serde_json::to_value(InitResult { entry_path })
-                .map_err(|_| internal_error("Cannot serialize path"))
+            serde_json::to_value(result).map_err(|_| internal_error("Cannot serialize path"))
         })
     }
 
     /// Get the entry of a template.
     #[cfg(feature = "system")]
     pub fn get_template_entry(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
-        use crate::tool::package::{self, TemplateSource};
+        use crate::tool::package;
 
         let from_source = get_arg!(args[0] as String);
 
         let snap = self.snapshot().map_err(internal_error)?;
 
         just_future(async move {
-            // Parse the package specification. If the user didn't specify the version,
-            // we try to figure it out automatically by downloading the package index
-            // or searching the disk.
-            let spec: PackageSpec = from_source
-                .parse()
-                .or_else(|err| {
-                    // Try to parse without version, but prefer the error message of the
-                    // normal package spec parsing if it fails.
-                    let spec: VersionlessPackageSpec = from_source.parse().map_err(|_| err)?;
-                    let version = snap.registry().determine_latest_version(&spec)?;
-                    StrResult::Ok(spec.at(version))
-                })
+            let from_source = parse_template_source(&snap, &from_source)
                 .map_err(map_string_err("failed to parse package spec"))
                 .map_err(internal_error)?;
 
-            let from_source = TemplateSource::Package(spec);
-
             let entry = package::get_entry(snap.world(), from_source)
                 .map_err(map_string_err("failed to get template entry"))
                 .map_err(internal_error)?;
@@ -293,6 +1240,87 @@ impl ServerState {
         })
     }
 
+    /// Get a snippet generated from the entry of a template, with tab stops at
+    /// the likely edit points.
+    #[cfg(feature = "system")]
+    pub fn get_template_snippet(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        use crate::tool::package;
+
+        let from_source = get_arg!(args[0] as String);
+
+        let snap = self.snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let from_source = parse_template_source(&snap, &from_source)
+                .map_err(map_string_err("failed to parse package spec"))
+                .map_err(internal_error)?;
+
+            let entry = package::get_entry(snap.world(), from_source)
+                .map_err(map_string_err("failed to get template entry"))
+                .map_err(internal_error)?;
+
+            let entry = std::str::from_utf8(entry.as_slice())
+                .map_err(|_| invalid_params("template entry is not a valid UTF-8 string"))?;
+
+            Ok(JsonValue::String(package::template_snippet(entry)))
+        })
+    }
+
+    /// Computes the size and the file inventory of a package.
+    #[cfg(feature = "system")]
+    pub fn package_inventory(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        use crate::tool::package::{self, DEFAULT_LARGE_FILE_THRESHOLD};
+
+        let info = get_arg!(args[0] as PackageInfo);
+        let threshold = get_arg_or_default!(args[1] as Option<u64>);
+        let threshold = threshold.unwrap_or(DEFAULT_LARGE_FILE_THRESHOLD);
+
+        let snap = self.snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let inventory = package::package_inventory(snap.world(), &info, threshold)
+                .map_err(map_string_err("failed to compute package inventory"))
+                .map_err(internal_error)?;
+
+            serde_json::to_value(inventory).map_err(internal_error)
+        })
+    }
+
+    /// Reloads the sources of a package from the disk, evicting the cached
+    /// analysis of the package and recompiling the documents.
+    #[cfg(feature = "system")]
+    pub fn reload_package(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        use crate::project::Interrupt;
+        use crate::world::vfs::FilesystemEvent;
+
+        let info = get_arg!(args[0] as PackageInfo);
+
+        let snap = self.snapshot().map_err(internal_error)?;
+        let changes = crate::tool::package::package_changes(snap.world(), &info)
+            .map_err(map_string_err("failed to reload package"))
+            .map_err(internal_error)?;
+
+        log::info!(
+            "reloading package: @{}/{}:{}, {} files",
+            info.namespace,
+            info.name,
+            info.version,
+            changes.inserts.len()
+        );
+        self.project
+            .interrupt(Interrupt::Fs(FilesystemEvent::Update(changes, false)));
+        self.project.analysis.clear_cache_by(|id| {
+            id.package().is_some_and(|spec| {
+                spec.namespace == info.namespace
+                    && spec.name == info.name
+                    && spec.version.to_string() == info.version
+            })
+        });
+
+        self.schedule_async();
+        just_ok(JsonValue::Null)
+    }
+
     /// Interact with the code context at the source file.
     pub fn interact_code_context(&mut self, _arguments: Vec<JsonValue>) -> ScheduleResult {
         let queries = _arguments.into_iter().next().ok_or_else(|| {
@@ -411,6 +1439,14 @@ impl ServerState {
         just_future(async move { task.resp_rx.await.map_err(internal_error)? })
     }
 
+    /// Resolve the definition of the symbol at the position. Returns null if
+    /// nothing is resolved.
+    pub fn resolve_definition(&mut self, mut args: Vec<JsonValue>) -> ScheduleResult {
+        let path = get_arg!(args[0] as PathBuf);
+        let position = get_arg!(args[1] as lsp_types::Position);
+        run_query!(self.ResolveDefinition(path, position))
+    }
+
     /// Get the metrics of the document.
     pub fn get_document_metrics(&mut self, mut args: Vec<JsonValue>) -> ScheduleResult {
         let path = get_arg!(args[0] as PathBuf);
@@ -426,6 +1462,20 @@ impl ServerState {
     pub fn get_server_info(&mut self, _arguments: Vec<JsonValue>) -> ScheduleResult {
         run_query!(self.ServerInfo())
     }
+
+    /// Get the configuration that the server is effectively using, which is
+    /// resolved from the defaults, the workspace, and the client.
+    pub fn get_effective_config(&mut self, _args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let config = serde_json::to_value(self.config.effective()).map_err(internal_error)?;
+        just_ok(config)
+    }
+
+    /// Get the build information of the server, e.g. the linked typst version
+    /// and the enabled features.
+    pub fn get_build_info(&mut self, _arguments: Vec<JsonValue>) -> AnySchedulableResponse {
+        let info = serde_json::to_value(&*crate::BUILD_INFO).map_err(internal_error)?;
+        just_ok(info)
+    }
 }
 
 impl ServerState {
@@ -477,24 +1527,31 @@ impl ServerState {
         })
     }
 
-    /// Get writable directory of packages
+    /// Get the packages in a namespace, optionally grouped by their names.
     #[cfg(feature = "system")]
     pub fn resource_package_by_ns(
         &mut self,
         mut arguments: Vec<JsonValue>,
     ) -> AnySchedulableResponse {
         let ns = get_arg!(arguments[1] as EcoString);
+        let grouped = get_arg_or_default!(arguments[2] as bool);
 
         let snap = self.snapshot().map_err(internal_error)?;
         just_future(async move {
             let packages = tinymist_query::package::list_package(
                 snap.world(),
                 tinymist_query::package::PackageFilter::For(ns),
-            )
-            .into_iter()
-            .map(PackageInfo::from)
-            .collect::<Vec<_>>();
+            );
+
+            if grouped {
+                let groups = tinymist_query::package::group_packages(packages);
+                return serde_json::to_value(groups).map_err(|e| internal_error(e.to_string()));
+            }
 
+            let packages = packages
+                .into_iter()
+                .map(PackageInfo::from)
+                .collect::<Vec<_>>();
             serde_json::to_value(packages).map_err(|e| internal_error(e.to_string()))
         })
     }
@@ -557,13 +1614,15 @@ impl ServerState {
         })
     }
 
-    /// Check package
+    /// Check package, optionally with additional entry files relative to the
+    /// package root.
     pub fn check_package(
         &mut self,
         info: PackageInfo,
-    ) -> LspResult<impl Future<Output = LspResult<()>>> {
+        extra_entries: Vec<EcoString>,
+    ) -> LspResult<impl Future<Output = LspResult<DiagnosticsMap>>> {
         self.within_package(info.clone(), move |a| {
-            tinymist_query::package::check_package(a, &info)
+            tinymist_query::package::check_package(a, &info, &extra_entries)
                 .map_err(map_string_err("failed to check package"))
         })
     }
@@ -1,19 +1,52 @@
 //! Tinymist LSP commands for export
 
+use std::num::{NonZeroU32, NonZeroUsize};
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use serde::Deserialize;
 use serde_json::Value as JsonValue;
 use tinymist_project::{
-    ExportBundleTask, ExportHtmlTask, ExportPdfTask, ExportPngTask, ExportSvgTask, ExportTeXTask,
-    ExportTextTask, Pages, ProjectTask, QueryTask,
+    BuildTargets, ExportAnimatedSvgTask, ExportBundleTask, ExportGifTask, ExportHtmlTask,
+    ExportPdfTask, ExportPngTask, ExportPptxTask, ExportSvgTask, ExportTeXTask, ExportTextTask,
+    Pages, ProjectTask, QueryTask,
 };
 use tinymist_std::error::prelude::*;
-use tinymist_task::{ExportMarkdownTask, PageMerge};
+use tinymist_task::{
+    ContactSheet, ExportMarkdownTask, GifPalette, OneOrMany, PageFill, PageMerge,
+    PageMergeDirection, SplitBy, SvgTextMode,
+};
+use tokio_util::sync::CancellationToken;
+use typst::foundations::Value;
+use typst::utils::LazyHash;
 
 use super::*;
 use crate::lsp::query::run_query;
 
+/// A task listed by `tinymist.listTasks`.
+#[cfg(feature = "export")]
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskListItem {
+    /// The task's ID.
+    id: tinymist_task::Id,
+    /// The task to run.
+    #[serde(flatten)]
+    task: ProjectTask,
+    /// The resolved output path of the task.
+    path: Option<PathBuf>,
+    /// Whether the task needs to run against the current document.
+    needs_run: bool,
+}
+
+/// Derives the ID of the configured task from its type, e.g. `export-pdf`.
+#[cfg(feature = "export")]
+fn configured_task_id(task: &ProjectTask) -> tinymist_task::Id {
+    let value = serde_json::to_value(task).ok();
+    let ty = value.as_ref().and_then(|value| value.get("type")?.as_str());
+    tinymist_task::Id::new(ty.unwrap_or_default().to_owned())
+}
+
 /// Basic export options with no additional fields.
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(default)]
@@ -37,6 +70,18 @@ struct ExportPdfOpts {
     /// circumstances (for example when trying to reduce the size of a document)
     /// it can be desirable to disable tagged PDF.
     pub no_pdf_tags: Option<bool>,
+    /// Whether to omit the dates of export to make the PDF bit-reproducible.
+    reproducible: bool,
+    /// Whether to write the outline embedded in the PDF to a sidecar JSON file.
+    outline_sidecar: bool,
+    /// The ICC profile to embed as the output intent of the PDF.
+    icc_profile: Option<PathBuf>,
+    /// Whether to export only the pages changed since the last export.
+    changed_pages: bool,
+    /// Whether to write a span map next to the output.
+    span_map: bool,
+    /// Whether to export one file per section, e.g. `heading-level-1`.
+    split_by: Option<SplitBy>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -46,6 +91,10 @@ struct ExportSvgOpts {
     pages: Option<Vec<Pages>>,
     page_number_template: Option<String>,
     merge: Option<PageMerge>,
+    /// How to write the text, which is converted to paths by default.
+    text_mode: SvgTextMode,
+    /// Whether to write a span map next to the output.
+    span_map: bool,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -55,7 +104,103 @@ struct ExportPngOpts {
     pages: Option<Vec<Pages>>,
     page_number_template: Option<String>,
     merge: Option<PageMerge>,
-    fill: Option<String>,
+    fill: Option<PageFill>,
+    /// Whether to export with a transparent background, ignoring the page fill
+    /// of the document. It cannot be combined with `fill`.
+    transparent: Option<bool>,
+    ppi: Option<f32>,
+    /// The number of threads to render pages with. Defaults to the number of
+    /// CPUs.
+    jobs: Option<NonZeroUsize>,
+    /// Whether to export only the pages changed since the last export.
+    changed_pages: bool,
+    /// The ICC profile to embed into the images.
+    icc_profile: Option<PathBuf>,
+    /// The maximum size (in bytes) of each image, which the PPI is lowered to
+    /// fit.
+    max_bytes: Option<NonZeroUsize>,
+    /// Whether to write a span map next to the output.
+    span_map: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ExportContactSheetOpts {
+    /// Which pages to put on the sheet. When unspecified, all pages are put.
+    pages: Option<Vec<Pages>>,
+    /// The number of thumbnails in each row.
+    columns: Option<NonZeroUsize>,
+    /// The PPI (pixels per inch) to render the thumbnails at.
+    thumb_ppi: Option<f32>,
+    fill: Option<PageFill>,
+    /// Whether to export with a transparent background, ignoring the page fill
+    /// of the document. It cannot be combined with `fill`.
+    transparent: Option<bool>,
+    /// The number of threads to render thumbnails with. Defaults to the number
+    /// of CPUs.
+    jobs: Option<NonZeroUsize>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ExportImageStripOpts {
+    /// Which pages to put on the strip. When unspecified, all pages are put.
+    pages: Option<Vec<Pages>>,
+    /// The PPI (pixels per inch) to render the pages at.
+    ppi: Option<f32>,
+    /// The gap between pages (in pt).
+    gap: Option<String>,
+    fill: Option<PageFill>,
+    /// The maximum height of the strip (in pixels). Defaults to
+    /// [`DEFAULT_STRIP_MAX_HEIGHT`].
+    max_height: Option<NonZeroU32>,
+}
+
+/// The default maximum height of an image strip (in pixels), beyond which
+/// many image viewers and chat apps fail to show the image.
+const DEFAULT_STRIP_MAX_HEIGHT: u32 = 32767;
+
+/// Checks the fill option, whose page ranges must not overlap.
+fn check_fill(fill: &Option<PageFill>) -> Result<(), ExportError> {
+    match fill {
+        Some(fill) => fill
+            .check()
+            .map_err(|err| ExportError::InvalidArgs(err.to_string())),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ExportAnimatedSvgOpts {
+    /// Which pages to export. When unspecified, all pages are exported.
+    pages: Option<Vec<Pages>>,
+    /// How long each page is shown (in seconds).
+    frame_duration: Option<f32>,
+    /// Whether the animation starts over after the last page.
+    #[serde(rename = "loop")]
+    repeat: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ExportGifOpts {
+    /// Which pages to export. When unspecified, all pages are exported.
+    pages: Option<Vec<Pages>>,
+    /// The PPI (pixels per inch) to use for the frames.
+    ppi: Option<f32>,
+    /// How long each frame is shown (in milliseconds).
+    frame_delay: Option<u32>,
+    /// How many times the animation repeats after it is played once.
+    loop_count: Option<u16>,
+    /// How to quantize the colors of the frames.
+    palette: GifPalette,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ExportPptxOpts {
+    /// The PPI (pixels per inch) to render the slides at.
     ppi: Option<f32>,
 }
 
@@ -94,9 +239,12 @@ struct ExportQueryOpts {
     output_extension: Option<String>,
     strict: Option<bool>,
     pretty: Option<bool>,
-    selector: String,
-    field: Option<String>,
-    one: Option<bool>,
+    selector: OneOrMany<String>,
+    field: Option<OneOrMany<Option<String>>>,
+    one: Option<OneOrMany<bool>>,
+    page: Option<NonZeroUsize>,
+    /// Whether to query the HTML output with CSS-like selectors.
+    html: bool,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -106,20 +254,49 @@ struct ExportActionOpts {
     write: Option<bool>,
     /// Whether to open the exported file(s) after the export is done.
     open: bool,
+    /// Whether to only resolve the output path and whether the task would
+    /// run, without exporting.
+    dry_run: Option<bool>,
+    /// The in-memory content of the document to export, e.g. an unsaved
+    /// buffer. When unspecified, the content known by the server is used.
+    content: Option<String>,
+    /// The root directory for this export only, which must be absolute and
+    /// contain the document. When unspecified, the root of the editing session
+    /// is used.
+    root: Option<PathBuf>,
+    /// The locale for this export only, e.g. `fr-FR`, which overrides the
+    /// default language and region of the text but not the ones set by the
+    /// document.
+    locale: Option<crate::world::Locale>,
+    /// Whether to skip writing the exported files that are unchanged. When
+    /// unspecified, `tinymist.exportSkipUnchanged` is used.
+    skip_unchanged: Option<bool>,
+}
+
+impl ExportActionOpts {
+    /// Applies the options that override the configuration of the task.
+    fn apply(&self, task: &mut ProjectTask) {
+        if let (Some(skip_unchanged), Some(export)) = (self.skip_unchanged, task.as_export_mut()) {
+            export.skip_unchanged = skip_unchanged;
+        }
+    }
 }
 
 /// Here are implemented the handlers for each command.
 impl ServerState {
     /// Export the current document as PDF file(s).
-    pub fn export_pdf(&mut self, mut args: Vec<JsonValue>) -> ScheduleResult {
+    pub fn export_pdf(
+        &mut self,
+        mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
+    ) -> ScheduleResult {
         let path = get_arg!(args[0] as PathBuf);
         let opts = get_arg_or_default!(args[1] as ExportPdfOpts);
 
         let creation_timestamp = if let Some(value) = opts.creation_timestamp {
-            Some(
-                parse_source_date_epoch(&value)
-                    .map_err(|e| invalid_params(format!("Cannot parse creation timestamp: {e}")))?,
-            )
+            Some(parse_source_date_epoch(&value).map_err(|e| {
+                ExportError::InvalidArgs(format!("Cannot parse creation timestamp: {e}"))
+            })?)
         } else {
             self.config.creation_timestamp()
         };
@@ -128,24 +305,34 @@ impl ServerState {
             .pdf_standard
             .or_else(|| self.config.pdf_standards())
             .unwrap_or_default();
-        let export = self.config.export_task();
+        let mut export = self.config.export_task();
+        export.changed_pages = opts.changed_pages;
+        export.span_map = opts.span_map;
+        export.split_by = opts.split_by;
         let task = ProjectTask::ExportPdf(ExportPdfTask {
             export,
             pages: opts.pages,
             pdf_standards,
             no_pdf_tags,
             creation_timestamp,
+            reproducible: opts.reproducible,
+            outline_sidecar: opts.outline_sidecar,
+            icc_profile: opts.icc_profile,
         });
 
         if path.extension().and_then(|ext| ext.to_str()) == Some("md") {
-            self.export_md(path, opts.processor, task, args)
+            self.export_md(path, opts.processor, task, args, cancel)
         } else {
-            self.export(path, task, args)
+            self.export(path, task, args, cancel)
         }
     }
 
     /// Export the current document as HTML file(s).
-    pub fn export_html(&mut self, mut args: Vec<JsonValue>) -> ScheduleResult {
+    pub fn export_html(
+        &mut self,
+        mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
+    ) -> ScheduleResult {
         let path = get_arg!(args[0] as PathBuf);
         let _opts = get_arg_or_default!(args[1] as ExportOpts);
         let export = self.config.export_task();
@@ -153,19 +340,23 @@ impl ServerState {
             path,
             ProjectTask::ExportHtml(ExportHtmlTask { export }),
             args,
+            cancel,
         )
     }
 
     /// Export the current document as bundle file(s).
-    pub fn export_bundle(&mut self, mut args: Vec<JsonValue>) -> ScheduleResult {
+    pub fn export_bundle(
+        &mut self,
+        mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
+    ) -> ScheduleResult {
         let path = get_arg!(args[0] as PathBuf);
         let opts = get_arg_or_default!(args[1] as ExportBundleOpts);
 
         let creation_timestamp = if let Some(value) = opts.creation_timestamp {
-            Some(
-                parse_source_date_epoch(&value)
-                    .map_err(|e| invalid_params(format!("Cannot parse creation timestamp: {e}")))?,
-            )
+            Some(parse_source_date_epoch(&value).map_err(|e| {
+                ExportError::InvalidArgs(format!("Cannot parse creation timestamp: {e}"))
+            })?)
         } else {
             self.config.creation_timestamp()
         };
@@ -177,8 +368,7 @@ impl ServerState {
         let ppi = opts.ppi.or_else(|| self.config.ppi()).unwrap_or(144.);
         let ppi = ppi
             .try_into()
-            .context("cannot convert ppi")
-            .map_err(invalid_params)?;
+            .map_err(|e| ExportError::InvalidArgs(format!("cannot convert ppi: {e}")))?;
 
         let export = self.config.export_task();
         self.export(
@@ -192,11 +382,16 @@ impl ServerState {
                 ppi,
             }),
             args,
+            cancel,
         )
     }
 
     /// Export the current document as Markdown file(s).
-    pub fn export_markdown(&mut self, mut args: Vec<JsonValue>) -> ScheduleResult {
+    pub fn export_markdown(
+        &mut self,
+        mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
+    ) -> ScheduleResult {
         let path = get_arg!(args[0] as PathBuf);
         let opts = get_arg_or_default!(args[1] as ExportTypliteOpts);
         let export = self.config.export_task();
@@ -208,11 +403,16 @@ impl ServerState {
                 export,
             }),
             args,
+            cancel,
         )
     }
 
     /// Export the current document as Tex file(s).
-    pub fn export_tex(&mut self, mut args: Vec<JsonValue>) -> ScheduleResult {
+    pub fn export_tex(
+        &mut self,
+        mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
+    ) -> ScheduleResult {
         let path = get_arg!(args[0] as PathBuf);
         let opts = get_arg_or_default!(args[1] as ExportTypliteOpts);
         let export = self.config.export_task();
@@ -224,11 +424,16 @@ impl ServerState {
                 export,
             }),
             args,
+            cancel,
         )
     }
 
     /// Export the current document as Text file(s).
-    pub fn export_text(&mut self, mut args: Vec<JsonValue>) -> ScheduleResult {
+    pub fn export_text(
+        &mut self,
+        mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
+    ) -> ScheduleResult {
         let path = get_arg!(args[0] as PathBuf);
         let _opts = get_arg_or_default!(args[1] as ExportOpts);
         let export = self.config.export_task();
@@ -236,11 +441,16 @@ impl ServerState {
             path,
             ProjectTask::ExportText(ExportTextTask { export }),
             args,
+            cancel,
         )
     }
 
     /// Query the current document and export the result as JSON file(s).
-    pub fn export_query(&mut self, mut args: Vec<JsonValue>) -> ScheduleResult {
+    pub fn export_query(
+        &mut self,
+        mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
+    ) -> ScheduleResult {
         let path = get_arg!(args[0] as PathBuf);
         let opts = get_arg_or_default!(args[1] as ExportQueryOpts);
         // todo: deprecate it
@@ -258,19 +468,27 @@ impl ServerState {
                 output_extension: opts.output_extension,
                 selector: opts.selector,
                 field: opts.field,
-                one: opts.one.unwrap_or(false),
+                one: opts.one.unwrap_or_default(),
+                page: opts.page,
+                html: opts.html,
                 export,
             }),
             args,
+            cancel,
         )
     }
 
     /// Export the current document as Svg file(s).
-    pub fn export_svg(&mut self, mut args: Vec<JsonValue>) -> ScheduleResult {
+    pub fn export_svg(
+        &mut self,
+        mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
+    ) -> ScheduleResult {
         let path = get_arg!(args[0] as PathBuf);
         let opts = get_arg_or_default!(args[1] as ExportSvgOpts);
 
-        let export = self.config.export_task();
+        let mut export = self.config.export_task();
+        export.span_map = opts.span_map;
         self.export(
             path,
             ProjectTask::ExportSvg(ExportSvgTask {
@@ -278,23 +496,105 @@ impl ServerState {
                 pages: opts.pages,
                 page_number_template: opts.page_number_template,
                 merge: opts.merge,
+                text_mode: opts.text_mode,
+            }),
+            args,
+            cancel,
+        )
+    }
+
+    /// Export the current document as a single animated SVG file, where each
+    /// page is shown one after another.
+    pub fn export_animated_svg(
+        &mut self,
+        mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
+    ) -> ScheduleResult {
+        let path = get_arg!(args[0] as PathBuf);
+        let opts = get_arg_or_default!(args[1] as ExportAnimatedSvgOpts);
+
+        let frame_duration = opts.frame_duration.unwrap_or(1.);
+        if frame_duration <= 0. {
+            return Err(ExportError::InvalidArgs(format!(
+                "frame duration must be positive, got {frame_duration}"
+            ))
+            .into());
+        }
+        let frame_duration = frame_duration
+            .try_into()
+            .map_err(|e| ExportError::InvalidArgs(format!("cannot convert frame duration: {e}")))?;
+
+        let export = self.config.export_task();
+        self.export(
+            path,
+            ProjectTask::ExportAnimatedSvg(ExportAnimatedSvgTask {
+                export,
+                pages: opts.pages,
+                frame_duration,
+                repeat: opts.repeat.unwrap_or(true),
+            }),
+            args,
+            cancel,
+        )
+    }
+
+    /// Export the current document as a single animated GIF file, where each
+    /// page becomes a frame.
+    pub fn export_gif(
+        &mut self,
+        mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
+    ) -> ScheduleResult {
+        let path = get_arg!(args[0] as PathBuf);
+        let opts = get_arg_or_default!(args[1] as ExportGifOpts);
+
+        let ppi = opts.ppi.or_else(|| self.config.ppi()).unwrap_or(72.);
+        let ppi = ppi
+            .try_into()
+            .map_err(|e| ExportError::InvalidArgs(format!("cannot convert ppi: {e}")))?;
+
+        let export = self.config.export_task();
+        self.export(
+            path,
+            ProjectTask::ExportGif(ExportGifTask {
+                export,
+                pages: opts.pages,
+                ppi,
+                frame_delay: opts.frame_delay.unwrap_or(1000),
+                loop_count: opts.loop_count,
+                palette: opts.palette,
             }),
             args,
+            cancel,
         )
     }
 
     /// Export the current document as Png file(s).
-    pub fn export_png(&mut self, mut args: Vec<JsonValue>) -> ScheduleResult {
+    pub fn export_png(
+        &mut self,
+        mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
+    ) -> ScheduleResult {
         let path = get_arg!(args[0] as PathBuf);
         let opts = get_arg_or_default!(args[1] as ExportPngOpts);
 
         let ppi = opts.ppi.or_else(|| self.config.ppi()).unwrap_or(144.);
         let ppi = ppi
             .try_into()
-            .context("cannot convert ppi")
-            .map_err(invalid_params)?;
+            .map_err(|e| ExportError::InvalidArgs(format!("cannot convert ppi: {e}")))?;
 
-        let export = self.config.export_task();
+        let transparent = opts.transparent.unwrap_or_default();
+        if transparent && opts.fill.is_some() {
+            return Err(ExportError::InvalidArgs(
+                "cannot set both transparent and fill".to_owned(),
+            )
+            .into());
+        }
+        check_fill(&opts.fill)?;
+
+        let mut export = self.config.export_task();
+        export.changed_pages = opts.changed_pages;
+        export.span_map = opts.span_map;
         self.export(
             path,
             ProjectTask::ExportPng(ExportPngTask {
@@ -302,26 +602,262 @@ impl ServerState {
                 pages: opts.pages,
                 page_number_template: opts.page_number_template,
                 merge: opts.merge,
+                contact_sheet: None,
+                fill: opts.fill,
+                transparent,
+                ppi,
+                jobs: opts.jobs,
+                max_height: None,
+                icc_profile: opts.icc_profile,
+                max_bytes: opts.max_bytes,
+            }),
+            args,
+            cancel,
+        )
+    }
+
+    /// Export a contact sheet of the current document as a Png file, which
+    /// tiles the thumbnails of the pages in a grid.
+    pub fn export_contact_sheet(
+        &mut self,
+        mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
+    ) -> ScheduleResult {
+        let path = get_arg!(args[0] as PathBuf);
+        let opts = get_arg_or_default!(args[1] as ExportContactSheetOpts);
+
+        let ppi = opts.thumb_ppi.unwrap_or(24.);
+        let ppi = ppi
+            .try_into()
+            .map_err(|e| ExportError::InvalidArgs(format!("cannot convert thumbPpi: {e}")))?;
+
+        let transparent = opts.transparent.unwrap_or_default();
+        if transparent && opts.fill.is_some() {
+            return Err(ExportError::InvalidArgs(
+                "cannot set both transparent and fill".to_owned(),
+            )
+            .into());
+        }
+        check_fill(&opts.fill)?;
+
+        let export = self.config.export_task();
+        self.export(
+            path,
+            ProjectTask::ExportPng(ExportPngTask {
+                export,
+                pages: opts.pages,
+                page_number_template: None,
+                merge: None,
+                contact_sheet: Some(ContactSheet {
+                    columns: opts.columns,
+                }),
                 fill: opts.fill,
+                transparent,
                 ppi,
+                jobs: opts.jobs,
+                max_height: None,
+                icc_profile: None,
+                max_bytes: None,
             }),
             args,
+            cancel,
         )
     }
 
+    /// Export the current document as a single Png image, which stitches the
+    /// pages from top to bottom.
+    pub fn export_image_strip(
+        &mut self,
+        mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
+    ) -> ScheduleResult {
+        let path = get_arg!(args[0] as PathBuf);
+        let opts = get_arg_or_default!(args[1] as ExportImageStripOpts);
+
+        let ppi = opts.ppi.or_else(|| self.config.ppi()).unwrap_or(144.);
+        let ppi = ppi
+            .try_into()
+            .map_err(|e| ExportError::InvalidArgs(format!("cannot convert ppi: {e}")))?;
+        let max_height = opts
+            .max_height
+            .or(NonZeroU32::new(DEFAULT_STRIP_MAX_HEIGHT));
+        check_fill(&opts.fill)?;
+
+        let export = self.config.export_task();
+        self.export(
+            path,
+            ProjectTask::ExportPng(ExportPngTask {
+                export,
+                pages: opts.pages,
+                page_number_template: None,
+                merge: Some(PageMerge {
+                    gap: opts.gap,
+                    direction: PageMergeDirection::Vertical,
+                }),
+                contact_sheet: None,
+                fill: opts.fill,
+                transparent: false,
+                ppi,
+                jobs: None,
+                max_height,
+                icc_profile: None,
+                max_bytes: None,
+            }),
+            args,
+            cancel,
+        )
+    }
+
+    /// Export the current document as a PPTX presentation, where each page
+    /// becomes a slide with its speaker notes.
+    pub fn export_pptx(
+        &mut self,
+        mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
+    ) -> ScheduleResult {
+        let path = get_arg!(args[0] as PathBuf);
+        let opts = get_arg_or_default!(args[1] as ExportPptxOpts);
+
+        let ppi = opts.ppi.or_else(|| self.config.ppi()).unwrap_or(144.);
+        let ppi = ppi
+            .try_into()
+            .map_err(|e| ExportError::InvalidArgs(format!("cannot convert ppi: {e}")))?;
+
+        let export = self.config.export_task();
+        self.export(
+            path,
+            ProjectTask::ExportPptx(ExportPptxTask { export, ppi }),
+            args,
+            cancel,
+        )
+    }
+
+    /// Lists the export tasks configured for a document, with their resolved
+    /// output paths and whether they need to run. The document defaults to the
+    /// main file of the server.
+    #[cfg(feature = "export")]
+    pub fn list_tasks(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let path = get_arg_or_default!(args[0] as Option<PathBuf>);
+        let path: Option<ImmutPath> = path.map(From::from);
+
+        // The task configured by `exportPdf` and `outputPath`, followed by the
+        // tasks of the document recorded in the lock file.
+        let task = self.config.export().task;
+        let mut tasks = vec![(configured_task_id(&task), task)];
+        let main = path.clone().or_else(|| self.entry_resolver().entry.clone());
+        if let Some(main) = &main {
+            let locked = self.locked_tasks(main).into_iter();
+            tasks.extend(locked.map(|task| (task.id, task.task)));
+        }
+
+        let inputs = self.resolve_task_or(path);
+        let snap = self.snapshot().map_err(internal_error)?;
+        just_future(async move {
+            let snap = snap.task(inputs);
+
+            let items = tasks.into_iter().map(|(id, task)| {
+                let (path, needs_run) =
+                    crate::ExportTask::task_status(&task, &snap).map_err(internal_error)?;
+                Ok(TaskListItem {
+                    id,
+                    task,
+                    path,
+                    needs_run,
+                })
+            });
+            let items = items.collect::<LspResult<Vec<_>>>()?;
+
+            serde_json::to_value(items).map_err(internal_error)
+        })
+    }
+
+    /// Runs an export command that can be canceled by `$/cancelRequest` while
+    /// it is running.
+    pub(crate) fn cancellable_export(
+        &mut self,
+        req_id: RequestId,
+        args: Vec<JsonValue>,
+        export: fn(&mut Self, Vec<JsonValue>, CancellationToken) -> ScheduleResult,
+    ) -> ScheduleResult {
+        let cancel = CancellationToken::new();
+        let cancels = self.export_cancels.clone();
+        cancels.lock().insert(req_id.clone(), cancel.clone());
+
+        let res = export(self, args, cancel);
+
+        match res {
+            Ok(futures::future::MaybeDone::Future(fut)) => just_future(async move {
+                let res = fut.await;
+                cancels.lock().remove(&req_id);
+                res
+            }),
+            res => {
+                cancels.lock().remove(&req_id);
+                res
+            }
+        }
+    }
+
     /// Export the current document as some format. The client is responsible
-    /// for passing the correct absolute path of typst document.
+    /// for passing the correct absolute path of typst document. The export is
+    /// aborted once the `cancel` token is canceled.
     pub fn export(
         &mut self,
         path: PathBuf,
-        task: ProjectTask,
+        mut task: ProjectTask,
         mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
     ) -> ScheduleResult {
         let action_opts = get_arg_or_default!(args[2] as ExportActionOpts);
+        action_opts.apply(&mut task);
         let write = action_opts.write.unwrap_or(true);
         let open = action_opts.open;
+        let dry_run = action_opts.dry_run.unwrap_or_default();
+        let content = action_opts.content;
+        let root = action_opts.root;
+        let locale = action_opts.locale;
+        let inputs = None;
+        let cancel = Some(cancel);
+
+        run_query!(
+            self.OnExport(path, task, write, open, dry_run, content, root, locale, inputs, cancel)
+        )
+    }
 
-        run_query!(self.OnExport(path, task, write, open))
+    /// Builds a target declared in the `[tool.tinymist.targets]` table of the
+    /// nearest `typst.toml`, which is searched from the given path or the root.
+    pub fn build_target(&mut self, mut args: Vec<JsonValue>) -> ScheduleResult {
+        let name = get_arg!(args[0] as String);
+        let from = get_arg_or_default!(args[1] as Option<PathBuf>);
+        let Some(from) = from.or_else(|| self.entry_resolver().root(None).map(|p| p.to_path_buf()))
+        else {
+            let msg = "cannot find typst.toml without a path or a root".to_owned();
+            return Err(ExportError::InvalidArgs(msg).into());
+        };
+
+        let targets =
+            BuildTargets::find(&from).map_err(|err| ExportError::InvalidArgs(err.to_string()))?;
+        let target = targets
+            .get(&name)
+            .map_err(|err| ExportError::InvalidArgs(err.to_string()))?
+            .clone();
+
+        // The inputs of the target override the inputs of the editing session.
+        let mut dict = (**self.config.inputs()).clone();
+        for (key, value) in target.inputs.iter() {
+            dict.insert(key.as_str().into(), Value::Str(value.as_str().into()));
+        }
+
+        let path = target.main.to_path_buf();
+        let task = target.task;
+        let (write, open, dry_run) = (true, false, false);
+        let (content, root, locale) = (None, None, None);
+        let inputs = Some(Arc::new(LazyHash::new(dict)));
+        let cancel = None;
+
+        run_query!(
+            self.OnExport(path, task, write, open, dry_run, content, root, locale, inputs, cancel)
+        )
     }
 
     /// Exports the a markdown document using a custom template.
@@ -329,13 +865,16 @@ impl ServerState {
         &mut self,
         path: PathBuf,
         processor: Option<String>,
-        task: ProjectTask,
+        mut task: ProjectTask,
         mut args: Vec<JsonValue>,
+        cancel: CancellationToken,
     ) -> ScheduleResult {
         let action_opts = get_arg_or_default!(args[2] as ExportActionOpts);
+        action_opts.apply(&mut task);
         let write = action_opts.write.unwrap_or(true);
         let open = action_opts.open;
+        let cancel = Some(cancel);
 
-        run_query!(self.OnExportMd(path, processor, task, write, open))
+        run_query!(self.OnExportMd(path, processor, task, write, open, cancel))
     }
 }
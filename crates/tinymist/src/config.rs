@@ -18,7 +18,7 @@ use task::{FormatUserConfig, FormatterConfig};
 use tinymist_l10n::DebugL10n;
 use tinymist_project::{DynAccessModel, LspAccessModel};
 use tinymist_query::analysis::{Modifier, TokenType};
-use tinymist_query::{url_to_path, CompletionFeat, PositionEncoding};
+use tinymist_query::{url_to_path, CompletionFeat, PositionEncoding, TooltipFeat};
 use tinymist_render::PeriscopeArgs;
 use tinymist_std::error::prelude::*;
 use tinymist_task::ExportTarget;
@@ -60,9 +60,15 @@ const CONFIG_ITEMS: &[&str] = &[
     "formatterIndentSize",
     "formatterProseWrap",
     "hoverPeriscope",
+    "maxCacheMb",
     "onEnter",
     "outputPath",
+    "outputPathExpandEnv",
+    "exportRequire",
+    "exportSkipUnchanged",
+    "exportManifest",
     "syntaxOnly",
+    "tooltip",
     "preview",
     "projectResolution",
     "rootPath",
@@ -112,6 +118,10 @@ pub struct Config {
     pub development: bool,
     /// Whether to run the server in syntax-only mode.
     pub syntax_only: bool,
+    /// The size budget of the analysis caches in megabytes. When it is
+    /// exceeded after a compilation, the least recently used cached resources
+    /// are evicted.
+    pub max_cache_mb: Option<usize>,
 
     /// The preferred color theme for rendering.
     pub color_theme: Option<String>,
@@ -128,6 +138,8 @@ pub struct Config {
 
     /// Tinymist's completion features.
     pub completion: CompletionFeat,
+    /// Tinymist's tooltip features.
+    pub tooltip: TooltipFeat,
     /// Tinymist's preview features.
     pub preview: PreviewFeat,
     /// Tinymist's lint features.
@@ -155,6 +167,16 @@ pub struct Config {
     pub export_pdf: TaskWhen,
     /// The output directory for PDF export.
     pub output_path: PathPattern,
+    /// Whether to expand the environment variables in the output path.
+    pub output_path_expand_env: bool,
+    /// The label of a metadata that a document must set to a truthy value to
+    /// be exported.
+    pub export_require: Option<String>,
+    /// Whether to skip writing the exported files that are unchanged.
+    pub export_skip_unchanged: bool,
+    /// Whether to record the exported files in an `export-manifest.json` next
+    /// to them.
+    pub export_manifest: bool,
 
     /// Dynamic configuration for the experimental formatter.
     pub formatter_mode: FormatterMode,
@@ -183,6 +205,55 @@ pub struct RestartScopedClientOptions {
     delegate_fs_requests: bool,
 }
 
+/// The configuration that the server is effectively using, which is resolved
+/// from the defaults, the workspace, and the client. See
+/// [`Config::effective`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveConfig {
+    /// The root of the workspace, if any.
+    pub root_path: Option<PathBuf>,
+    /// The entry file, if any.
+    pub entry: Option<PathBuf>,
+    /// The default export target.
+    pub export_target: ExportTarget,
+    /// When to export the PDF.
+    pub export_pdf: TaskWhen,
+    /// The output path pattern of the exports.
+    pub output_path: PathPattern,
+    /// Whether to expand the environment variables in the output path.
+    pub output_path_expand_env: bool,
+    /// The label of a metadata that a document must set to be exported.
+    pub export_require: Option<String>,
+    /// Whether to skip writing the exported files that are unchanged.
+    pub export_skip_unchanged: bool,
+    /// Whether to record the exported files in an `export-manifest.json` next
+    /// to them.
+    pub export_manifest: bool,
+    /// The PDF standards that the PDF export enforces conformance with.
+    pub pdf_standards: Vec<PdfStandard>,
+    /// Whether to write untagged PDFs.
+    pub no_pdf_tags: bool,
+    /// The creation timestamp for various outputs (in seconds).
+    pub creation_timestamp: Option<i64>,
+    /// The PPI (pixels per inch) to use for PNG export.
+    pub ppi: Option<f32>,
+    /// The directories searched for fonts, which are absolute.
+    pub font_paths: Vec<PathBuf>,
+    /// Whether to search system fonts.
+    pub system_fonts: bool,
+    /// The path to local packages, if customized.
+    pub package_path: Option<PathBuf>,
+    /// The path to the package cache, if customized.
+    pub package_cache_path: Option<PathBuf>,
+    /// The base URL of the package registry, if customized.
+    pub package_registry: Option<String>,
+    /// The arguments for the `tinymist.startDefaultPreview` command.
+    pub preview_args: Option<Vec<String>>,
+    /// The arguments for the background preview, if it is enabled.
+    pub background_preview_args: Option<Vec<String>>,
+}
+
 impl Config {
     /// Creates a new configuration with system defaults.
     pub fn new(
@@ -371,6 +442,7 @@ impl Config {
         assign_config!(color_theme := "colorTheme"?: Option<String>);
         assign_config!(lint := "lint"?: LintFeat);
         assign_config!(completion := "completion"?: CompletionFeat);
+        assign_config!(tooltip := "tooltip"?: TooltipFeat);
         assign_config!(on_enter := "onEnter"?: OnEnterFeat);
         assign_config!(completion.trigger_suggest := "triggerSuggest"?: bool);
         assign_config!(completion.trigger_parameter_hints := "triggerParameterHints"?: bool);
@@ -385,6 +457,10 @@ impl Config {
         assign_config!(formatter_indent_size := "formatterIndentSize"?: Option<u32>);
         assign_config!(formatter_prose_wrap := "formatterProseWrap"?: Option<bool>);
         assign_config!(output_path := "outputPath"?: PathPattern);
+        assign_config!(output_path_expand_env := "outputPathExpandEnv"?: bool);
+        assign_config!(export_require := "exportRequire"?: Option<String>);
+        assign_config!(export_skip_unchanged := "exportSkipUnchanged"?: bool);
+        assign_config!(export_manifest := "exportManifest"?: bool);
         assign_config!(preview := "preview"?: PreviewFeat);
         assign_config!(lint := "lint"?: LintFeat);
         assign_config!(semantic_tokens := "semanticTokens"?: SemanticTokensMode);
@@ -394,6 +470,7 @@ impl Config {
         assign_config!(extended_code_action := "supportExtendedCodeAction"?: bool);
         assign_config!(development := "development"?: bool);
         assign_config!(system_fonts := "systemFonts"?: Option<bool>);
+        assign_config!(max_cache_mb := "maxCacheMb"?: Option<usize>);
 
         self.notify_status = match try_(|| update.get("compileStatus")?.as_str()) {
             Some("enable") => true,
@@ -544,6 +621,10 @@ impl Config {
     /// Validates the configuration.
     pub fn validate(&self) -> Result<()> {
         self.entry_resolver.validate()?;
+        // The standard output of the server is the protocol stream.
+        if self.output_path.is_stdout() {
+            bail!("the output path cannot be `-` in the language server");
+        }
 
         Ok(())
     }
@@ -604,6 +685,13 @@ impl Config {
             when: self.export_pdf.clone(),
             output: Some(self.output_path.clone()),
             transform: vec![],
+            skip_unchanged: self.export_skip_unchanged,
+            changed_pages: false,
+            expand_env: self.output_path_expand_env,
+            manifest: self.export_manifest,
+            span_map: false,
+            split_by: None,
+            require: self.export_require.clone(),
         }
     }
 
@@ -628,6 +716,9 @@ impl Config {
                 pdf_standards: self.pdf_standards().unwrap_or_default(),
                 no_pdf_tags: self.no_pdf_tags(),
                 creation_timestamp: self.creation_timestamp(),
+                reproducible: false,
+                outline_sidecar: false,
+                icc_profile: None,
             }),
             count_words: self.notify_status,
             development: self.development,
@@ -748,6 +839,38 @@ impl Config {
         self.typst_extra_args.as_ref()?.cert.clone()
     }
 
+    /// Resolves the configuration that the server is effectively using.
+    pub fn effective(&self) -> EffectiveConfig {
+        let font_opts = self.font_opts();
+        let package_opts = self.package_opts();
+        let background = &self.preview.background;
+
+        EffectiveConfig {
+            root_path: self.entry_resolver.root(None).map(|p| p.to_path_buf()),
+            entry: self.entry_resolver.entry.as_ref().map(|p| p.to_path_buf()),
+            export_target: self.export_target,
+            export_pdf: self.export_pdf.clone(),
+            output_path: self.output_path.clone(),
+            output_path_expand_env: self.output_path_expand_env,
+            export_require: self.export_require.clone(),
+            export_skip_unchanged: self.export_skip_unchanged,
+            export_manifest: self.export_manifest,
+            pdf_standards: self.pdf_standards().unwrap_or_default(),
+            no_pdf_tags: self.no_pdf_tags(),
+            creation_timestamp: self.creation_timestamp(),
+            ppi: self.ppi(),
+            font_paths: font_opts.font_paths,
+            system_fonts: !font_opts.ignore_system_fonts,
+            package_path: package_opts.package_path,
+            package_cache_path: package_opts.package_cache_path,
+            package_registry: package_opts.package_registry,
+            preview_args: self.preview.browsing.args.clone(),
+            background_preview_args: background
+                .enabled
+                .then(|| background.args.clone().unwrap_or_default()),
+        }
+    }
+
     /// Applies the primary options related to compilation.
     #[allow(clippy::type_complexity)]
     pub fn primary_opts(
@@ -937,6 +1060,8 @@ pub struct ConstConfig {
     pub tokens_overlapping_token_support: bool,
     /// Allow multiline tokens.
     pub tokens_multiline_token_support: bool,
+    /// Allow label offsets of parameters in signature help.
+    pub signature_label_offset_support: bool,
     /// Allow line folding on documents.
     pub doc_line_folding_only: bool,
     /// Allow dynamic registration of document formatting.
@@ -980,6 +1105,7 @@ impl From<&InitializeParams> for ConstConfig {
         let fold = try_(|| doc?.folding_range.as_ref());
         let format = try_(|| doc?.formatting.as_ref());
         let completion_item = try_(|| doc?.completion.as_ref()?.completion_item.as_ref());
+        let sig_info = try_(|| doc?.signature_help.as_ref()?.signature_information.as_ref());
 
         let locale = params
             .initialization_options
@@ -994,6 +1120,15 @@ impl From<&InitializeParams> for ConstConfig {
             tokens_dynamic_registration: try_or(|| sema?.dynamic_registration, false),
             tokens_overlapping_token_support: try_or(|| sema?.overlapping_token_support, false),
             tokens_multiline_token_support: try_or(|| sema?.multiline_token_support, false),
+            signature_label_offset_support: try_or(
+                || {
+                    sig_info?
+                        .parameter_information
+                        .as_ref()?
+                        .label_offset_support
+                },
+                false,
+            ),
             doc_line_folding_only: try_or(|| fold?.line_folding_only, true),
             doc_fmt_dynamic_registration: try_or(|| format?.dynamic_registration, false),
             completion_insert_replace_support: try_or(
@@ -1128,6 +1263,10 @@ pub struct OnEnterFeat {
 pub struct BrowsingPreviewOpts {
     /// The arguments for the `tinymist.startDefaultPreview` command.
     pub args: Option<Vec<String>>,
+    /// Whether to focus the source file of the element clicked in the
+    /// browsing preview.
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub focus_follows_preview: bool,
 }
 
 /// Options for background preview.
@@ -1276,6 +1415,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_effective_config() {
+        let mut config = Config::default();
+
+        let root_path = Path::new(if cfg!(windows) {
+            "C:\\dummy-root"
+        } else {
+            "/dummy-root"
+        });
+
+        let update = json!({
+            "outputPath": "$root/out/$name",
+            "rootPath": root_path,
+            "fontPaths": ["fonts"],
+            "preview": { "background": { "enabled": true } },
+            "typstExtraArgs": [
+                "--creation-timestamp", "1735689600",
+                "--pdf-standard", "a-2b",
+                "--package-path", "packages",
+            ]
+        });
+
+        good_config(&mut config, &update);
+
+        let effective = config.effective();
+        assert_eq!(effective.root_path.as_deref(), Some(root_path));
+        assert_eq!(effective.output_path, PathPattern::new("$root/out/$name"));
+        assert_eq!(effective.creation_timestamp, Some(1735689600));
+        assert_eq!(effective.pdf_standards, vec![PdfStandard::A_2b]);
+        assert_eq!(effective.font_paths, vec![root_path.join("fonts")]);
+        assert_eq!(effective.package_path, Some(PathBuf::from("packages")));
+        assert_eq!(effective.background_preview_args, Some(vec![]));
+        assert_eq!(effective.preview_args, None);
+    }
+
     #[test]
     fn test_namespaced_config() {
         let mut config = Config::default();
@@ -1497,6 +1671,10 @@ mod tests {
         test_good_config("formatterIndentSize");
         test_good_config("formatterProseWrap");
         test_good_config("outputPath");
+        test_good_config("outputPathExpandEnv");
+        test_good_config("exportRequire");
+        test_good_config("exportSkipUnchanged");
+        test_good_config("exportManifest");
         test_good_config("semanticTokens");
         test_good_config("delegateFsRequests");
         test_good_config("supportHtmlInMarkdown");
@@ -1504,6 +1682,7 @@ mod tests {
         test_good_config("supportExtendedCodeAction");
         test_good_config("development");
         test_good_config("systemFonts");
+        test_good_config("maxCacheMb");
 
         test_good_config("completion");
         test_good_config("completion.triggerSuggest");
@@ -1516,6 +1695,11 @@ mod tests {
         test_good_config("completion.postfixUfcsLeft");
         test_good_config("completion.postfixUfcsRight");
         test_good_config("completion.postfixSnippets");
+        test_good_config("tooltip");
+        test_good_config("tooltip.expr");
+        test_good_config("tooltip.font");
+        test_good_config("tooltip.closure");
+        test_good_config("tooltip.baseFontSize");
 
         test_good_config("lint");
         test_good_config("lint.enabled");
@@ -1524,6 +1708,7 @@ mod tests {
         test_good_config("preview");
         test_good_config("preview.browsing");
         test_good_config("preview.browsing.args");
+        test_good_config("preview.browsing.focusFollowsPreview");
         test_good_config("preview.background");
         test_good_config("preview.background.enabled");
         test_good_config("preview.background.args");
@@ -1631,6 +1816,17 @@ mod tests {
         assert!(err.contains("absolute path"), "unexpected error: {err}");
     }
 
+    #[test]
+    fn test_reject_stdout_output_path() {
+        let mut config = Config::default();
+        let update = json!({
+            "outputPath": "-",
+        });
+
+        let err = format!("{}", update_config(&mut config, &update).unwrap_err());
+        assert!(err.contains("cannot be `-`"), "unexpected error: {err}");
+    }
+
     #[test]
     fn test_entry_by_extra_args() {
         let simple_config = {
@@ -133,6 +133,7 @@ impl ServerState {
         let input = TaskInputs {
             entry: Some(entry),
             inputs: input.inputs,
+            locale: None,
         };
 
         let snapshot = self.project.snapshot().unwrap().snap.clone().task(input);
@@ -11,7 +11,7 @@ use typst::{
     syntax::Source,
 };
 
-use crate::project::Interrupt;
+use crate::project::{ApplyProjectTask, Interrupt};
 use crate::world::vfs::{notify::MemoryEvent, FileChangeSet, FilesystemEvent};
 use crate::world::TaskInputs;
 use crate::*;
@@ -339,6 +339,32 @@ impl ServerState {
             .flatten()
             .unwrap_or_else(|| self.resolve_task_without_lock(Some(path)))
     }
+
+    #[cfg(not(feature = "lock"))]
+    pub(crate) fn locked_tasks(&mut self, _path: &ImmutPath) -> Vec<ApplyProjectTask> {
+        vec![]
+    }
+
+    /// Gets the tasks of the document recorded in the lock file.
+    #[cfg(feature = "lock")]
+    pub(crate) fn locked_tasks(&mut self, path: &ImmutPath) -> Vec<ApplyProjectTask> {
+        let uses_lock = matches!(
+            self.entry_resolver().project_resolution,
+            ProjectResolutionKind::LockDatabase
+        );
+        let Some(resolution) = uses_lock.then(|| self.route.resolve(path)).flatten() else {
+            return vec![];
+        };
+        let Some(lock) = self.route.locate(&resolution) else {
+            return vec![];
+        };
+
+        lock.task
+            .iter()
+            .filter(|task| task.doc_id() == &resolution.project_id)
+            .cloned()
+            .collect()
+    }
 }
 
 /// The file system change request.
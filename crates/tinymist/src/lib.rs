@@ -29,10 +29,10 @@ pub use world::{CompileFontArgs, CompileOnceArgs, CompilePackageArgs};
 
 #[cfg(feature = "export")]
 pub use task::export2 as export;
-#[cfg(feature = "export")]
-pub use task::ExportTask;
 #[cfg(feature = "trace")]
 pub use task::UserActionTask;
+#[cfg(feature = "export")]
+pub use task::{ExportFailure, ExportTask};
 
 #[cfg(feature = "dap")]
 pub use dap::RegularInit as DapRegularInit;
@@ -94,3 +94,36 @@ Typst Source:        {}
         env!("TYPST_SOURCE"),
     )
 });
+
+/// The build information of the library, which is computed once and cached.
+pub static BUILD_INFO: LazyLock<tinymist_query::BuildInfo> = LazyLock::new(|| {
+    let features = [
+        ("battery", cfg!(feature = "battery")),
+        ("dap", cfg!(feature = "dap")),
+        ("export", cfg!(feature = "export")),
+        ("lock", cfg!(feature = "lock")),
+        ("no-content-hint", cfg!(feature = "no-content-hint")),
+        ("open", cfg!(feature = "open")),
+        ("preview", cfg!(feature = "preview")),
+        ("system", cfg!(feature = "system")),
+        ("trace", cfg!(feature = "trace")),
+        ("web", cfg!(feature = "web")),
+    ];
+
+    let mut info = tinymist_query::BuildInfo {
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+        git_describe: Some(env!("VERGEN_GIT_DESCRIBE").to_owned()),
+        commit_sha: option_env!("VERGEN_GIT_SHA").map(ToOwned::to_owned),
+        typst_version: env!("TYPST_VERSION").to_owned(),
+        typst_source: env!("TYPST_SOURCE").to_owned(),
+        target: env!("VERGEN_CARGO_TARGET_TRIPLE").to_owned(),
+        features: features
+            .into_iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| name.to_owned())
+            .collect(),
+        report: String::new(),
+    };
+    info.report = info.format_report();
+    info
+});
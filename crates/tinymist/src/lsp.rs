@@ -1,3 +1,4 @@
+use std::sync::atomic::Ordering;
 use std::sync::OnceLock;
 
 use lsp_types::request::*;
@@ -130,6 +131,25 @@ impl ServerState {
     }
 }
 
+/// LSP Request Cancellation
+impl ServerState {
+    /// Cancels the export run by the request, if any. The export is aborted at
+    /// its next phase and responds with a cancellation error. Other requests
+    /// are not cancelable yet and run to completion.
+    pub(crate) fn cancel_request(&mut self, params: CancelParams) -> LspResult<()> {
+        let id = match params.id {
+            NumberOrString::Number(id) => RequestId::from(id),
+            NumberOrString::String(id) => RequestId::from(id),
+        };
+
+        if let Some(cancel) = self.export_cancels.lock().remove(&id) {
+            log::info!("cancelling the export of request {id:?}");
+            cancel.cancel();
+        }
+        Ok(())
+    }
+}
+
 /// LSP Configuration Synchronization
 impl ServerState {
     pub(crate) fn on_changed_configuration(
@@ -166,6 +186,13 @@ impl ServerState {
                 .log_error("could not change editor actor configuration");
         }
 
+        if old_config.max_cache_mb != self.config.max_cache_mb {
+            let max_cache_mb = self.config.max_cache_mb.unwrap_or_default();
+            self.project
+                .max_cache_mb
+                .store(max_cache_mb, Ordering::Relaxed);
+        }
+
         let primary_opts_changed = old_config.primary_opts() != self.config.primary_opts();
         let restart_scoped_client_opts_changed =
             old_config.restart_scoped_client_opts() != self.config.restart_scoped_client_opts();
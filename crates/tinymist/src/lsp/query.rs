@@ -293,6 +293,7 @@ impl ServerState {
                 Hover(req) => snap.run_semantic(req, R::Hover),
                 GotoDefinition(req) => snap.run_semantic(req, R::GotoDefinition),
                 GotoDeclaration(req) => snap.run_semantic(req, R::GotoDeclaration),
+                ResolveDefinition(req) => snap.run_semantic(req, R::ResolveDefinition),
                 References(req) => snap.run_semantic(req, R::References),
                 InlayHint(req) => snap.run_semantic(req, R::InlayHint),
                 DocumentHighlight(req) => snap.run_semantic(req, R::DocumentHighlight),
@@ -21,7 +21,7 @@ use reflexo_typst::TypstDocument;
 use serde::{Deserialize, Serialize};
 pub use tinymist_project::*;
 
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::{num::NonZeroUsize, sync::Arc};
 
 use parking_lot::Mutex;
@@ -175,10 +175,12 @@ impl ServerState {
                 position_encoding: const_config.position_encoding,
                 allow_overlapping_token: const_config.tokens_overlapping_token_support,
                 allow_multiline_token: const_config.tokens_multiline_token_support,
+                signature_label_offsets: const_config.signature_label_offset_support,
                 remove_html: !config.support_html_in_markdown,
                 support_client_codelens: config.support_client_codelens,
                 extended_code_action: config.extended_code_action,
                 completion_feat,
+                tooltip_feat: config.tooltip.clone(),
                 color_theme: match config.color_theme.as_deref() {
                     Some("dark") => tinymist_query::ColorTheme::Dark,
                     _ => tinymist_query::ColorTheme::Light,
@@ -196,6 +198,7 @@ impl ServerState {
                 stats: Arc::default(),
             }),
 
+            max_cache_mb: Arc::new(AtomicUsize::new(config.max_cache_mb.unwrap_or_default())),
             status_revision: Mutex::default(),
             notified_revision: Mutex::default(),
         });
@@ -249,6 +252,7 @@ impl ServerState {
             #[cfg(feature = "preview")]
             preview: handle.preview.clone(),
             analysis: handle.analysis.clone(),
+            max_cache_mb: handle.max_cache_mb.clone(),
             stats: CompilerQueryStats::default(),
             #[cfg(feature = "export")]
             export: handle.export.clone(),
@@ -329,6 +333,9 @@ pub struct ProjectState {
     pub compiler: LspProjectCompiler,
     /// The analysis data.
     pub analysis: Arc<Analysis>,
+    /// The size budget of the analysis caches in megabytes, zero for
+    /// unlimited.
+    pub max_cache_mb: Arc<AtomicUsize>,
     /// The query statistics.
     pub stats: CompilerQueryStats,
     /// The preview state.
@@ -450,6 +457,10 @@ impl ProjectPreviewState {
     }
 }
 
+/// The maximum age of the memoized results kept when the caches exceed the
+/// budget, while they are kept for 10 compilations otherwise.
+const OVER_BUDGET_COMEMO_MAX_AGE: usize = 2;
+
 /// The implementation of the compile handler.
 pub struct CompileHandlerImpl {
     /// The analysis data.
@@ -467,6 +478,9 @@ pub struct CompileHandlerImpl {
     pub(crate) editor_tx: EditorSender,
     /// The client used to send events back to the server itself or the clients.
     pub(crate) client: Arc<dyn ProjectClient>,
+    /// The size budget of the analysis caches in megabytes, zero for
+    /// unlimited.
+    pub(crate) max_cache_mb: Arc<AtomicUsize>,
     /// The status revision map, used to track the status of the projects.
     pub(crate) status_revision: Mutex<FxHashMap<ProjectInsId, usize>>,
     /// The notified revision map, used to track the notified revisions of the
@@ -581,6 +595,35 @@ impl CompileHandlerImpl {
             });
         }
     }
+
+    /// Evicts the least recently used analysis caches if their estimated size
+    /// exceeds the budget, along with the memoized results of the compiler
+    /// that are unused in the last few compilations.
+    fn evict_caches_if_needed(&self) {
+        let max_cache_mb = self.max_cache_mb.load(Ordering::Relaxed);
+        if max_cache_mb == 0 {
+            return;
+        }
+
+        let budget = max_cache_mb.saturating_mul(1024 * 1024);
+        if self.analysis.cache_size() <= budget {
+            return;
+        }
+
+        let analysis = self.analysis.clone();
+        spawn_cpu(move || {
+            let before = analysis.cache_size();
+            let evicted = analysis.evict_cache_to(budget);
+            // The memoized results are not sized, so they are evicted by their age
+            // only, but more eagerly than by the eviction after each compilation.
+            comemo::evict(OVER_BUDGET_COMEMO_MAX_AGE);
+            log::debug!(
+                "Project: evicted {evicted} cache entries, size: {}MB -> {}MB (max {max_cache_mb}MB)",
+                before / 1024 / 1024,
+                analysis.cache_size() / 1024 / 1024,
+            );
+        });
+    }
 }
 
 impl CompileHandler<LspCompilerFeat, ProjectInsStateExt> for CompileHandlerImpl {
@@ -784,6 +827,7 @@ impl CompileHandler<LspCompilerFeat, ProjectInsStateExt> for CompileHandlerImpl
         }
 
         self.notify_diagnostics(art);
+        self.evict_caches_if_needed();
     }
 }
 
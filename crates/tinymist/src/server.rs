@@ -87,6 +87,10 @@ pub struct ServerState {
     /// The running server trace.
     #[cfg(feature = "trace")]
     pub server_trace: Option<ServerTraceTask>,
+    /// The cancellation token of the running cache warm-up.
+    pub(crate) warm_cache: tokio_util::sync::CancellationToken,
+    /// The cancellation tokens of the running exports, by their requests.
+    pub(crate) export_cancels: crate::cmd::ExportCancels,
 
     // Configurations
     /// User configuration from the editor.
@@ -178,6 +182,8 @@ impl ServerState {
             focusing: None,
             implicit_position: None,
             formatter,
+            warm_cache: tokio_util::sync::CancellationToken::new(),
+            export_cancels: Default::default(),
             editor_actor: None,
             dep_tx,
             dep_rx,
@@ -256,11 +262,17 @@ impl ServerState {
             // User commands
             .with_command("tinymist.startDefaultPreview", State::default_preview)
             .with_command("tinymist.scrollPreview", State::scroll_preview)
+            .with_command("tinymist.setPreviewTheme", State::set_preview_theme)
+            .with_command("tinymist.listPreviewThemes", State::list_preview_themes)
+            .with_command("tinymist.previewStatus", State::preview_status)
             // Internal commands
             .with_command("tinymist.doStartPreview", State::do_start_preview)
             .with_command("tinymist.doStartBrowsingPreview", State::browse_preview)
             .with_command("tinymist.doKillPreview", State::kill_preview);
 
+        #[cfg(feature = "export")]
+        let provider = provider.with_command("tinymist.listTasks", State::list_tasks);
+
         #[cfg(feature = "trace")]
         let provider = provider
             .with_command("tinymist.getDocumentTrace", State::get_document_trace)
@@ -271,11 +283,13 @@ impl ServerState {
         let provider = provider
             .with_command("tinymist.doInitTemplate", State::init_template)
             .with_command("tinymist.doGetTemplateEntry", State::get_template_entry)
+            .with_command("tinymist.getTemplateSnippet", State::get_template_snippet)
+            .with_command("tinymist.packageInventory", State::package_inventory)
+            .with_command("tinymist.reloadPackage", State::reload_package)
             .with_resource("/package/by-namespace", State::resource_package_by_ns)
             .with_resource("/dir/package", State::resource_package_dirs)
             .with_resource("/dir/package/local", State::resource_local_package_dir);
 
-        // todo: .on_sync_mut::<notifs::Cancel>(handlers::handle_cancel)?
         let mut provider = provider
             .with_request::<Shutdown>(State::shutdown)
             // customized event
@@ -323,26 +337,96 @@ impl ServerState {
             .with_notification::<DidChangeTextDocument>(State::did_change)
             .with_notification::<DidSaveTextDocument>(State::did_save)
             .with_notification::<DidChangeConfiguration>(State::did_change_configuration)
+            .with_notification::<Cancel>(State::cancel_request)
             // commands
-            .with_command_("tinymist.exportPdf", State::export_pdf)
-            .with_command_("tinymist.exportSvg", State::export_svg)
+            .with_command_id_("tinymist.exportPdf", |s, id, args| {
+                s.cancellable_export(id, args, State::export_pdf)
+            })
+            .with_command_id_("tinymist.exportSvg", |s, id, args| {
+                s.cancellable_export(id, args, State::export_svg)
+            })
             // .with_command_("tinymist.exportSvgHtml", State::export_html)
-            .with_command_("tinymist.exportPng", State::export_png)
-            .with_command_("tinymist.exportText", State::export_text)
-            .with_command_("tinymist.exportHtml", State::export_html)
-            .with_command_("tinymist.exportBundle", State::export_bundle)
-            .with_command_("tinymist.exportMarkdown", State::export_markdown)
-            .with_command_("tinymist.exportTeX", State::export_tex)
-            .with_command_("tinymist.exportQuery", State::export_query)
+            .with_command_id_("tinymist.exportPng", |s, id, args| {
+                s.cancellable_export(id, args, State::export_png)
+            })
+            .with_command_id_("tinymist.exportContactSheet", |s, id, args| {
+                s.cancellable_export(id, args, State::export_contact_sheet)
+            })
+            .with_command_id_("tinymist.exportImageStrip", |s, id, args| {
+                s.cancellable_export(id, args, State::export_image_strip)
+            })
+            .with_command_id_("tinymist.exportText", |s, id, args| {
+                s.cancellable_export(id, args, State::export_text)
+            })
+            .with_command_id_("tinymist.exportPptx", |s, id, args| {
+                s.cancellable_export(id, args, State::export_pptx)
+            })
+            .with_command_id_("tinymist.exportAnimatedSvg", |s, id, args| {
+                s.cancellable_export(id, args, State::export_animated_svg)
+            })
+            .with_command_id_("tinymist.exportGif", |s, id, args| {
+                s.cancellable_export(id, args, State::export_gif)
+            })
+            .with_command_id_("tinymist.exportHtml", |s, id, args| {
+                s.cancellable_export(id, args, State::export_html)
+            })
+            .with_command_id_("tinymist.exportBundle", |s, id, args| {
+                s.cancellable_export(id, args, State::export_bundle)
+            })
+            .with_command_id_("tinymist.exportMarkdown", |s, id, args| {
+                s.cancellable_export(id, args, State::export_markdown)
+            })
+            .with_command_id_("tinymist.exportTeX", |s, id, args| {
+                s.cancellable_export(id, args, State::export_tex)
+            })
+            .with_command_id_("tinymist.exportQuery", |s, id, args| {
+                s.cancellable_export(id, args, State::export_query)
+            })
+            .with_command("tinymist.buildTarget", State::build_target)
+            .with_command("tinymist.exportSpeakerNotes", State::export_speaker_notes)
+            .with_command("tinymist.getCounts", State::get_counts)
+            .with_command("tinymist.extractPages", State::extract_pages)
+            .with_command("tinymist.diffRender", State::diff_render)
+            .with_command("tinymist.checkPdfStandards", State::check_pdf_standards)
+            .with_command("tinymist.exportDiagnostics", State::export_diagnostics)
+            .with_command("tinymist.exportEquations", State::export_equations)
+            .with_command("tinymist.exportLayout", State::export_layout)
             .with_command("tinymist.exportAnsiHighlight", State::export_ansi_hl)
+            .with_command("tinymist.formatRange", State::format_range)
             .with_command("tinymist.exportAst", State::export_ast)
             .with_command("tinymist.doClearCache", State::clear_cache)
+            .with_command("tinymist.getCacheStats", State::get_cache_stats)
+            .with_command("tinymist.getOutline", State::get_outline)
             .with_command("tinymist.pinMain", State::pin_document)
             .with_command("tinymist.focusMain", State::focus_document)
+            .with_command("tinymist.resolveEntry", State::resolve_entry)
+            .with_command("tinymist.resolveAsset", State::resolve_asset)
+            .with_command(
+                "tinymist.resolveMathShorthand",
+                State::resolve_math_shorthand,
+            )
             .with_command_("tinymist.interactCodeContext", State::interact_code_context)
             .with_command_("tinymist.getDocumentMetrics", State::get_document_metrics)
+            .with_command_("tinymist.resolveDefinition", State::resolve_definition)
             .with_command_("tinymist.getWorkspaceLabels", State::get_workspace_labels)
             .with_command_("tinymist.getServerInfo", State::get_server_info)
+            .with_command("tinymist.getBuildInfo", State::get_build_info)
+            .with_command("tinymist.getEffectiveConfig", State::get_effective_config)
+            .with_command("tinymist.renameLabel", State::rename_label)
+            .with_command("tinymist.convertToFigure", State::convert_to_figure)
+            .with_command("tinymist.inferType", State::infer_type)
+            .with_command("tinymist.eval", State::eval_expr)
+            .with_command("tinymist.getImports", State::get_imports)
+            .with_command(
+                "tinymist.getCompletionContext",
+                State::get_completion_context,
+            )
+            .with_command("tinymist.getDefinitions", State::get_definitions)
+            .with_command("tinymist.exportLabelGraph", State::export_label_graph)
+            .with_command("tinymist.findDuplicateLabels", State::find_duplicate_labels)
+            .with_command("tinymist.compileWorkspace", State::compile_workspace)
+            .with_command("tinymist.warmCache", State::warm_cache)
+            .with_command("tinymist.cancelWarmCache", State::cancel_warm_cache)
             // resources
             .with_resource("/fonts", State::resource_fonts)
             .with_resource("/symbols", State::resource_symbols)
@@ -441,6 +525,20 @@ impl ServerState {
             ServerEvent::UnpinPrimaryByPreview => {
                 ready.set_pin_by_preview(false, false);
             }
+            ServerEvent::FocusByPreview(path) => {
+                // It is opt-in and only follows browsing previews, which
+                // don't pin the main file, so that it doesn't fight focusing
+                // by the editor.
+                if ready.config.preview.browsing.focus_follows_preview
+                    && ready.pinning_by_browsing_preview
+                {
+                    match ready.focus_main_file(Some(path.clone())) {
+                        Ok(true) => log::info!("file focused by preview: {path:?}"),
+                        Ok(false) => {}
+                        Err(err) => log::warn!("could not focus file by preview: {err}"),
+                    }
+                }
+            }
         }
 
         Ok(())
@@ -471,6 +569,8 @@ impl ServerState {
 pub enum ServerEvent {
     /// Updates the `pinning_by_preview` status to false.
     UnpinPrimaryByPreview,
+    /// Focuses the source file of the element clicked in the primary preview.
+    FocusByPreview(ImmutPath),
 }
 
 impl ServerState {
@@ -506,14 +606,28 @@ impl ServerState {
         let query_stats = self.project.analysis.report_query_stats();
         let global_stats = GLOBAL_STATS.report();
         let alloc_stats = self.project.analysis.report_alloc_stats();
+        let system_fonts = !self.config.font_opts().ignore_system_fonts;
 
         let snap = self.snapshot().map_err(internal_error)?;
         just_future(async move {
             let w = snap.world();
 
+            #[cfg(feature = "system")]
+            let (package_paths, local_package_path) = {
+                let registry = snap.registry();
+                let paths = registry.paths().iter().map(|p| p.to_path_buf()).collect();
+                (paths, registry.local_path().map(|p| p.to_path_buf()))
+            };
+            #[cfg(not(feature = "system"))]
+            let (package_paths, local_package_path) = (vec![], None);
+
             let info = ServerInfoResponse {
                 root: w.entry_state().root().map(|e| e.as_ref().to_owned()),
                 font_paths: w.font_resolver.font_paths().to_owned(),
+                font_count: w.font_resolver.len(),
+                system_fonts,
+                package_paths,
+                local_package_path,
                 inputs: w.inputs().as_ref().deref().clone(),
                 stats: HashMap::from_iter([
                     ("api".to_owned(), api_stats),
@@ -521,6 +635,7 @@ impl ServerState {
                     ("query".to_owned(), query_stats),
                     ("alloc".to_owned(), alloc_stats),
                 ]),
+                build: Some(crate::BUILD_INFO.clone()),
             };
 
             let info = Some(HashMap::from_iter([(dg, info)]));
@@ -1,8 +1,10 @@
 //! The actor that handles various document export, like PDF and SVG export.
 
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicUsize;
-use std::sync::{Arc, OnceLock};
+use std::sync::{Arc, LazyLock, OnceLock};
 use std::{ops::DerefMut, pin::Pin};
 
 use reflexo::ImmutPath;
@@ -14,36 +16,45 @@ use tinymist_project::diag::print_diagnostics_to_string;
 use tinymist_project::LspWorld;
 use tinymist_query::{
     CompilerQueryResponse, OnExportMdRequest, OnExportRequest, OnExportResponse,
-    PagedExportResponse, GLOBAL_STATS,
+    PagedExportResponse, SectionExportResponse, GLOBAL_STATS,
 };
 use tinymist_std::error::prelude::*;
 use tinymist_std::fs::paths::write_atomic;
+use tinymist_std::hash::hash128;
 use tinymist_std::path::PathClean;
-use tinymist_std::typst::TypstDocument;
+use tinymist_std::typst::{TypstDocument, TypstPagedDocument};
+use tinymist_std::Error;
 use tinymist_task::{
-    output_template, pdf_options, DocumentQuery, ExportBundleTask, ExportMarkdownTask,
-    ExportPngTask, ExportSvgTask, ExportTarget, ImageOutput, PathPattern, PdfExport, PngExport,
-    SvgExport, TextExport,
+    exported_page_ranges, output_template, pdf_options, select_pages, AnimatedSvgExport,
+    DocumentQuery, ExportBundleTask, ExportMarkdownTask, ExportPngTask, ExportSvgTask,
+    ExportTarget, ExportTimings, GifExport, HtmlQuery, ImageOutput, Pages, PathPattern, PdfExport,
+    PngExport, PptxExport, SplitBy, SvgExport, TextExport,
 };
 use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 use typlite::{Format, Typlite};
 use typst::diag::Warned;
 use typst::ecow::EcoString;
 use typst::foundations::Repr;
+use typst::syntax::VirtualPath;
 use typst_bundle::{Bundle, BundleOptions, VirtualFs};
 
 use futures::Future;
 use parking_lot::Mutex;
 use rayon::Scope;
 
+use super::export2::{write_export_manifest, write_output};
 use super::SyncTaskFactory;
+use crate::cmd::ExportError;
 use crate::lsp::query::QueryFuture;
 use crate::project::{
     update_lock, ApplyProjectTask, CompiledArtifact, DevEvent, DevExportEvent, EntryReader,
-    ExportHtmlTask, ExportPdfTask, ExportTask as ProjectExportTask, ExportTeXTask, ExportTextTask,
-    LspCompiledArtifact, LspComputeGraph, ProjectClient, ProjectTask, TaskWhen,
+    EntryState, ExportHtmlTask, ExportPdfTask, ExportTask as ProjectExportTask, ExportTeXTask,
+    ExportTextTask, LspCompiledArtifact, LspComputeGraph, ProjectClient, ProjectTask, TaskWhen,
     PROJECT_ROUTE_USER_ACTION_PRIORITY,
 };
+use crate::tool::span_map::PageSpans;
+use crate::tool::split::{split_sections, Section};
 use crate::world::TaskInputs;
 use crate::ServerState;
 use crate::{actor::editor::EditorRequest, tool::word_count};
@@ -56,10 +67,28 @@ impl ServerState {
             task,
             open,
             write,
+            dry_run,
+            content,
+            root,
+            locale,
+            inputs,
+            cancel,
         } = req;
-        let entry = self.entry_resolver().resolve(Some(path.as_path().into()));
+        let entry = match &root {
+            Some(root) => Self::entry_with_root(&path, root)?,
+            None => self.entry_resolver().resolve(Some(path.as_path().into())),
+        };
+        if entry.main().is_none() {
+            let msg = format!("cannot resolve the entry of {path:?}");
+            return Err(ExportError::RootUnresolved(msg).into());
+        }
 
-        let lock_dir = self.entry_resolver().resolve_lock(&entry);
+        // The lock records the dependencies of the session, so exports with an
+        // overridden root don't update it.
+        let lock_dir = root
+            .is_none()
+            .then(|| self.entry_resolver().resolve_lock(&entry))
+            .flatten();
         let update_dep = lock_dir.clone().map(|lock_dir| {
             |snap: LspComputeGraph| {
                 tokio::spawn(async move {
@@ -81,18 +110,50 @@ impl ServerState {
 
         let snap = self.snapshot().map_err(internal_error)?;
         just_future(async move {
-            let snap = snap.task(TaskInputs {
+            let inputs = TaskInputs {
                 entry: Some(entry),
-                ..TaskInputs::default()
-            });
+                inputs,
+                locale,
+            };
+            let snap = match content {
+                // Overlays the given content on the document for this export only.
+                Some(content) => {
+                    let mut world = snap.world().task(inputs);
+                    world
+                        .map_shadow(&path, Bytes::from_string(content))
+                        .map_err(internal_error)?;
+                    WorldComputeGraph::new(CompileSnapshot::from_world(world))
+                }
+                None => snap.task(inputs),
+            };
 
             let id = snap.world().main_id();
             let _guard = GLOBAL_STATS.stat(id, "export");
 
-            Self::on_export_typ(task, snap, write, open, update_dep).await
+            if dry_run {
+                return Self::on_export_dry_run(task, snap);
+            }
+
+            Self::on_export_typ(task, snap, write, open, update_dep, cancel).await
         })
     }
 
+    /// Resolves the entry of the document with an overridden root, which must
+    /// contain the document.
+    fn entry_with_root(path: &Path, root: &Path) -> LspResult<EntryState> {
+        if !root.is_absolute() {
+            let msg = format!("the root must be an absolute path, got {root:?}");
+            return Err(ExportError::InvalidArgs(msg).into());
+        }
+
+        let root = root.clean();
+        let main = VirtualPath::virtualize(&root, path).map_err(|err| {
+            let msg = format!("{path:?} is not in the root {root:?}: {err:?}");
+            ExportError::RootUnresolved(msg)
+        })?;
+        Ok(EntryState::new_rooted(root.as_path().into(), Some(main)))
+    }
+
     /// Exports the current document using a custom template.
     pub fn on_export_md(&mut self, req: OnExportMdRequest) -> QueryFuture {
         let OnExportMdRequest {
@@ -101,16 +162,20 @@ impl ServerState {
             mut task,
             open,
             write,
+            cancel,
         } = req;
 
         // Pre-substitute the output path
         let origin_entry = self.entry_resolver().resolve(Some(path.as_path().into()));
-        let subst = task
-            .as_export()
-            .and_then(|e| e.output.as_ref().and_then(|o| o.substitute(&origin_entry)));
+        let output = match task.as_export() {
+            Some(export) => export.output_pattern().map_err(internal_error)?,
+            None => None,
+        };
+        let subst = output.and_then(|o| o.substitute(&origin_entry));
         if let Some(export) = task.as_export_mut() {
             // todo: to string lossy?
             export.output = subst.map(|s| PathPattern::new(&s.as_os_str().to_string_lossy()));
+            export.expand_env = false;
         }
 
         let entry = origin_entry.select_in_workspace(Path::new("/__md_main.typ"));
@@ -124,9 +189,9 @@ impl ServerState {
 
         let snap = self.snapshot().map_err(internal_error)?;
         just_future(async move {
-            let id = entry
-                .main()
-                .ok_or_else(|| internal_error("failed to get entry main file for md export"))?;
+            let id = entry.main().ok_or_else(|| {
+                ExportError::RootUnresolved("failed to get entry main file for md export".into())
+            })?;
             let _guard = GLOBAL_STATS.stat(Some(id), "export");
 
             let mut world = snap.world().task(TaskInputs {
@@ -149,17 +214,34 @@ impl ServerState {
 
             let snap = WorldComputeGraph::new(CompileSnapshot::from_world(world));
 
-            Self::on_export_typ(task, snap, write, open, None::<fn(LspComputeGraph)>).await
+            let update_dep = None::<fn(LspComputeGraph)>;
+            Self::on_export_typ(task, snap, write, open, update_dep, cancel).await
         })
     }
 
+    /// Resolves the output path and whether the task would run, without
+    /// exporting.
+    fn on_export_dry_run(
+        task: ProjectTask,
+        snap: LspComputeGraph,
+    ) -> LspResult<CompilerQueryResponse> {
+        let (path, needs_run) = ExportTask::task_status(&task, &snap)
+            .map_err(|err| ExportError::RootUnresolved(err.to_string()))?;
+
+        Ok(CompilerQueryResponse::OnExport(Some(
+            OnExportResponse::DryRun { path, needs_run },
+        )))
+    }
+
     async fn on_export_typ(
         task: ProjectTask,
         snap: LspComputeGraph,
         write: bool,
         open: bool,
         update_dep: Option<impl FnOnce(LspComputeGraph)>,
+        cancel: Option<CancellationToken>,
     ) -> LspResult<CompilerQueryResponse> {
+        let cancel = cancel.as_ref();
         let is_html = matches!(task, ProjectTask::ExportHtml { .. });
         // todo: we may get some file missing errors here
         let artifact = if matches!(task, ProjectTask::ExportBundle { .. }) {
@@ -169,16 +251,24 @@ impl ServerState {
         };
         let id = artifact.world().main_id();
 
+        // Classifies the failure by the stage where it happens.
+        ExportTask::prepare_output_path(&task, &snap)
+            .map_err(|err| ExportError::RootUnresolved(err.to_string()))?;
+        let compiled = artifact.doc.is_some() || matches!(task, ProjectTask::ExportBundle { .. });
+        let classify = |err: ExportFailure| err.classify(compiled);
+
+        check_cancelled(cancel).map_err(classify)?;
+
         let res = if write {
             // Export to file and return path
-            ExportTask::do_export(task, artifact, None)
+            ExportTask::do_export(task, artifact, None, cancel)
                 .await
-                .map_err(internal_error)?
+                .map_err(classify)?
         } else {
             // Export to memory and return base64-encoded data
-            ExportTask::do_export_to_memory(task, artifact)
+            ExportTask::do_export_to_memory(task, artifact, cancel)
                 .await
-                .map_err(internal_error)?
+                .map_err(|err| classify(ExportFailure::new(err, cancel)))?
         };
 
         if let Some(update_dep) = update_dep {
@@ -200,6 +290,11 @@ impl ServerState {
                         }
                     }
                 }
+                Some(OnExportResponse::Sections { sections }) => {
+                    if let Some(first_section) = sections.first() {
+                        open_external(&first_section.path);
+                    }
+                }
                 None => {
                     log::warn!("CompileActor: on export end: no export result to open");
                 }
@@ -212,6 +307,77 @@ impl ServerState {
     }
 }
 
+/// The failure of an export, by the stage where it happens.
+#[derive(Debug)]
+pub enum ExportFailure {
+    /// The export was canceled by its cancellation token.
+    Cancelled,
+    /// The artifact failed to be generated.
+    Export(Error),
+    /// The artifact failed to be written to the file system.
+    Write(Error),
+}
+
+impl ExportFailure {
+    /// Classifies the error of generating an artifact, which is caused by the
+    /// cancellation if the `cancel` token is canceled.
+    fn new(err: Error, cancel: Option<&CancellationToken>) -> Self {
+        if cancel.is_some_and(CancellationToken::is_cancelled) {
+            Self::Cancelled
+        } else {
+            Self::Export(err)
+        }
+    }
+
+    /// Converts the failure to the error of the export commands. Failures of
+    /// documents that are not `compiled` are compile failures.
+    fn classify(self, compiled: bool) -> ExportError {
+        let msg = self.to_string();
+        match self {
+            Self::Cancelled => ExportError::Cancelled(msg),
+            _ if !compiled => ExportError::CompileFailed(msg),
+            Self::Export(..) => ExportError::ExportFailed(msg),
+            Self::Write(..) => ExportError::WriteFailed(msg),
+        }
+    }
+}
+
+impl From<Error> for ExportFailure {
+    fn from(err: Error) -> Self {
+        Self::Export(err)
+    }
+}
+
+impl From<ExportFailure> for Error {
+    fn from(err: ExportFailure) -> Self {
+        match err {
+            ExportFailure::Cancelled => error_once!("export is cancelled"),
+            ExportFailure::Export(err) | ExportFailure::Write(err) => err,
+        }
+    }
+}
+
+impl std::fmt::Display for ExportFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cancelled => f.write_str("export is cancelled"),
+            Self::Export(err) | Self::Write(err) => std::fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+/// Checks whether the export is canceled. It is checked between the phases of
+/// an export, and always before writing, so a canceled export writes nothing.
+fn check_cancelled(cancel: Option<&CancellationToken>) -> Result<(), ExportFailure> {
+    if cancel.is_some_and(CancellationToken::is_cancelled) {
+        return Err(ExportFailure::Cancelled);
+    }
+    Ok(())
+}
+
+/// The placeholder in the output path replaced by the slug of each section.
+const SECTION_TEMPLATE: &str = "{section}";
+
 /// Runs a export document task.
 #[derive(Clone)]
 pub struct ExportTask {
@@ -274,6 +440,9 @@ impl ExportTask {
             TaskWhen::OnSave => s.by_fs_events,
             TaskWhen::OnDocumentHasTitle => s.by_fs_events && doc.info().title.is_some(),
         };
+        let require = config.task.as_export().and_then(|t| t.require.as_deref());
+        let need_export = need_export
+            && require.is_none_or(|label| ExportTimings::is_required_by_dyn(doc, label));
 
         let export_hook = config.development.then_some({
             let client = client.clone();
@@ -308,7 +477,11 @@ impl ExportTask {
             let task = config.task.clone();
             let artifact = artifact.clone();
             Box::pin(async move {
-                log_err(Self::do_export(task, artifact, None).await);
+                log_err(
+                    Self::do_export(task, artifact, None, None)
+                        .await
+                        .map_err(Error::from),
+                );
                 if let Some(f) = export_hook {
                     f()
                 }
@@ -354,10 +527,35 @@ impl ExportTask {
         Some(())
     }
 
+    /// Resolves the output path of the task and checks whether the task needs
+    /// to run against the document compiled by the graph.
+    pub(crate) fn task_status(
+        task: &ProjectTask,
+        graph: &LspComputeGraph,
+    ) -> Result<(Option<PathBuf>, bool)> {
+        let is_html = matches!(task, ProjectTask::ExportHtml { .. });
+        let artifact = CompiledArtifact::from_graph(graph.clone(), is_html);
+
+        let path = Self::prepare_output_path(task, graph)?;
+        let when = task.when().unwrap_or(&TaskWhen::Never);
+        let require = task.as_export().and_then(|t| t.require.as_deref());
+        let needs_run = artifact
+            .snap
+            .signal
+            .should_run_task_dyn(when, artifact.doc.as_ref())
+            .unwrap_or(true);
+        let needs_run = match (require, artifact.doc.as_ref()) {
+            (Some(label), Some(doc)) if needs_run => ExportTimings::is_required_by_dyn(doc, label),
+            _ => needs_run,
+        };
+
+        Ok((path, needs_run))
+    }
+
     fn prepare_output_path(task: &ProjectTask, graph: &LspComputeGraph) -> Result<Option<PathBuf>> {
         let entry = graph.snap.world.entry_state();
         let config = task.as_export().unwrap();
-        let output = config.output.clone().unwrap_or_default();
+        let output = config.output_pattern()?.unwrap_or_default();
         let Some(write_to) = output.substitute(&entry) else {
             return Ok(None);
         };
@@ -392,8 +590,14 @@ impl ExportTask {
         if matches!(task, ProjectTask::ExportBundle { .. }) {
             return Ok(Some(write_to));
         }
-        if !write_to.add_extension(task.extension()) {
-            write_to = write_to.with_file_name(format!("main.{}", task.extension()));
+        // The changed pages are written into a separate diff set.
+        let extension = if config.changed_pages {
+            format!("diff.{}", task.extension())
+        } else {
+            task.extension().to_owned()
+        };
+        if !write_to.add_extension(&extension) {
+            write_to = write_to.with_file_name(format!("main.{extension}"));
         }
 
         Ok(Some(write_to))
@@ -403,6 +607,7 @@ impl ExportTask {
     pub async fn do_export_to_memory(
         task: ProjectTask,
         artifact: LspCompiledArtifact,
+        cancel: Option<&CancellationToken>,
     ) -> Result<Option<OnExportResponse>> {
         use base64::prelude::*;
 
@@ -410,12 +615,13 @@ impl ExportTask {
 
         let write_to = Self::prepare_output_path(&task, graph)?;
 
-        let artifact = Self::do_export_bytes(task, artifact, 0).await?;
+        let (artifact, ppi) = Self::do_export_bytes(task, artifact, 0, cancel).await?;
 
         let res = match artifact {
             ExportArtifact::Single(data) => OnExportResponse::Single {
                 path: write_to.clone(),
                 data: Some(BASE64_STANDARD.encode(data.as_slice())),
+                ppi,
             },
             ExportArtifact::Paged { total_pages, items } => {
                 let can_handle_multiple = write_to.as_ref().is_some_and(|write_to| {
@@ -447,6 +653,7 @@ impl ExportTask {
                             }
                         })
                         .collect(),
+                    ppi,
                 }
             }
             ExportArtifact::Bundle { .. } => {
@@ -457,12 +664,57 @@ impl ExportTask {
         Ok(Some(res))
     }
 
-    /// Exports a document.
+    /// Exports a document to the standard output. It is only used by the CLI,
+    /// since the standard output of the language server is the protocol
+    /// stream. Nothing is written if the export is canceled by the `cancel`
+    /// token.
+    pub async fn do_export_to_stdout(
+        task: ProjectTask,
+        artifact: LspCompiledArtifact,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Option<OnExportResponse>> {
+        let data = match Self::do_export_bytes(task, artifact, 0, cancel).await?.0 {
+            ExportArtifact::Single(data) => data,
+            ExportArtifact::Paged { mut items, .. } if items.len() == 1 => items.remove(0).1,
+            ExportArtifact::Paged { .. } => bail!("cannot export multiple pages to stdout"),
+            ExportArtifact::Bundle { .. } => bail!("cannot export bundle to stdout"),
+        };
+        if cancel.is_some_and(CancellationToken::is_cancelled) {
+            bail!("export is cancelled");
+        }
+
+        tokio::task::spawn_blocking(move || super::export2::write_stdout(&data))
+            .await
+            .context_ut("failed to export")??;
+
+        Ok(Some(OnExportResponse::Single {
+            path: None,
+            data: None,
+            ppi: None,
+        }))
+    }
+
+    /// Whether the task exports to the standard output, i.e. its output is
+    /// `-`.
+    pub fn is_stdout(task: &ProjectTask) -> bool {
+        task.as_export()
+            .is_some_and(|config| config.output.as_ref().is_some_and(PathPattern::is_stdout))
+    }
+
+    /// Exports a document. The export is aborted without writing anything if
+    /// it is canceled by the `cancel` token.
     pub async fn do_export(
         task: ProjectTask,
         artifact: LspCompiledArtifact,
         lock_dir: Option<ImmutPath>,
-    ) -> Result<Option<OnExportResponse>> {
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Option<OnExportResponse>, ExportFailure> {
+        if Self::is_stdout(&task) {
+            return Err(ExportFailure::Export(error_once!(
+                "cannot export to the standard output, which is only supported by the CLI"
+            )));
+        }
+
         let CompiledArtifact { graph, .. } = &artifact;
 
         let Some(write_to) = Self::prepare_output_path(&task, graph)? else {
@@ -476,12 +728,6 @@ impl ExportTask {
             "ExportTask({export_id},lock={lock_dir:?}): exporting {entry:?} to {write_to:?}",
             entry = graph.snap.world.entry_state()
         );
-        if let Some(e) = write_to.parent() {
-            if !e.exists() {
-                std::fs::create_dir_all(e).context("failed to create directory")?;
-            }
-        }
-
         let _: Option<()> = lock_dir.and_then(|lock_dir| {
             let mut updater = crate::project::update_lock(lock_dir.clone());
             let root = graph.world().entry_state().root()?;
@@ -500,87 +746,342 @@ impl ExportTask {
             Some(())
         });
 
-        // Generate the data using common logic
-        let artifact = Self::do_export_bytes(task.clone(), artifact, export_id).await?;
+        if let Some(split_by) = task.as_export().and_then(|config| config.split_by) {
+            return Self::do_export_sections(task, artifact, split_by, cancel).await;
+        }
 
-        let res = match artifact {
-            ExportArtifact::Single(data) => {
-                let res = OnExportResponse::Single {
-                    path: Some(write_to.clone()),
-                    data: None,
-                };
+        if let Some(e) = write_to.parent() {
+            if !e.exists() {
+                std::fs::create_dir_all(e)
+                    .context("failed to create directory")
+                    .map_err(ExportFailure::Write)?;
+            }
+        }
 
-                let to = write_to.clone();
-                tokio::task::spawn_blocking(move || write_atomic(to, data))
-                    .await
-                    .context_ut("failed to export")??;
+        // Extract the outline before the artifact is consumed by the export.
+        let outline = match (&task, &artifact.doc) {
+            (
+                ProjectTask::ExportPdf(ExportPdfTask {
+                    outline_sidecar: true,
+                    ..
+                }),
+                Some(TypstDocument::Paged(doc)),
+            ) => Some(crate::tool::outline::bookmark_tree(doc)),
+            _ => None,
+        };
 
-                res
+        let changed_pages = ChangedPages::compute(&task, artifact.doc.as_ref(), &write_to);
+        let task = match &changed_pages {
+            Some(changed_pages) if changed_pages.changed.is_empty() => {
+                log::info!("ExportTask({export_id}): no pages changed since the last export");
+                return Ok(None);
             }
-            ExportArtifact::Paged { total_pages, items } => {
-                let can_handle_multiple =
-                    output_template::has_indexable_template(write_to.to_str().unwrap_or_default());
+            Some(changed_pages) => changed_pages.restrict(task),
+            None => task,
+        };
 
-                if !can_handle_multiple && items.len() > 1 {
-                    bail!("cannot export multiple images without a page number template ({{p}}, {{0p}}) in the output path");
-                }
+        // Collect the span map of the exported pages before the artifact is
+        // consumed by the export.
+        let span_map = match (&task, &artifact.doc) {
+            (
+                ProjectTask::ExportPdf(ExportPdfTask { export, pages, .. })
+                | ProjectTask::ExportPng(ExportPngTask { export, pages, .. })
+                | ProjectTask::ExportSvg(ExportSvgTask { export, pages, .. }),
+                Some(TypstDocument::Paged(doc)),
+            ) if export.span_map => Some(crate::tool::span_map::span_map(
+                artifact.graph.world(),
+                doc,
+                pages.as_deref(),
+            )),
+            _ => None,
+        };
 
-                let mut res_items = Vec::new();
-                let mut write_futures = Vec::new();
-                for (page_idx, bytes) in items {
-                    let to = if can_handle_multiple {
-                        let storage = output_template::format(
-                            write_to.to_str().unwrap_or_default(),
-                            page_idx + 1,
-                            total_pages,
-                        );
-                        PathBuf::from(storage)
-                    } else {
-                        write_to.clone()
-                    };
+        let skip_unchanged = task.as_export().is_some_and(|config| config.skip_unchanged);
+        check_cancelled(cancel)?;
+
+        // The pages of svg are written as soon as they are rendered, so that the
+        // pages of a large document are not held in memory together.
+        let res = if let (
+            ProjectTask::ExportSvg(config @ ExportSvgTask { merge: None, .. }),
+            Some(TypstDocument::Paged(doc)),
+        ) = (&task, &artifact.doc)
+        {
+            let total_pages = doc.pages().len();
+            let items = Self::write_svg_pages(
+                config.clone(),
+                doc.clone(),
+                &write_to,
+                skip_unchanged,
+                cancel,
+            )
+            .await?;
+            Self::paged_response(total_pages, items, span_map, None).await?
+        } else {
+            // Generate the data using common logic
+            let (artifact, ppi) = Self::do_export_bytes(task.clone(), artifact, export_id, cancel)
+                .await
+                .map_err(|err| ExportFailure::new(err, cancel))?;
+            check_cancelled(cancel)?;
 
-                    res_items.push(PagedExportResponse {
-                        page: page_idx,
-                        path: Some(to.clone()),
+            match artifact {
+                ExportArtifact::Single(data) => {
+                    let res = OnExportResponse::Single {
+                        path: Some(write_to.clone()),
                         data: None,
-                    });
+                        ppi,
+                    };
+
+                    let to = write_to.clone();
+                    tokio::task::spawn_blocking(move || write_output(&to, &data, skip_unchanged))
+                        .await
+                        .context_ut("failed to export")?
+                        .map_err(ExportFailure::Write)?;
+
+                    if let Some(outline) = outline {
+                        write_sidecar(&write_to, ".outline.json", &outline).await?;
+                    }
+                    if let Some(span_map) = span_map {
+                        write_sidecar(&write_to, ".spans.json", &span_map).await?;
+                    }
 
-                    let fut = tokio::task::spawn_blocking(move || write_atomic(to, bytes));
-                    write_futures.push(fut);
+                    res
                 }
+                ExportArtifact::Paged { total_pages, items } => {
+                    let can_handle_multiple = output_template::has_indexable_template(
+                        write_to.to_str().unwrap_or_default(),
+                    );
 
-                // Await all writes in parallel
-                for result in futures::future::join_all(write_futures).await {
-                    result.context_ut("failed to export")??;
+                    if !can_handle_multiple && items.len() > 1 {
+                        return Err(error_once!("cannot export multiple images without a page number template ({p}, {0p}) in the output path").into());
+                    }
+
+                    let mut res_items = Vec::new();
+                    let mut write_futures = Vec::new();
+                    for (page_idx, bytes) in items {
+                        let to = if can_handle_multiple {
+                            let storage = output_template::format(
+                                write_to.to_str().unwrap_or_default(),
+                                page_idx + 1,
+                                total_pages,
+                            );
+                            PathBuf::from(storage)
+                        } else {
+                            write_to.clone()
+                        };
+
+                        res_items.push(PagedExportResponse {
+                            page: page_idx,
+                            path: Some(to.clone()),
+                            data: None,
+                        });
+
+                        let fut = tokio::task::spawn_blocking(move || {
+                            write_output(&to, &bytes, skip_unchanged)
+                        });
+                        write_futures.push(fut);
+                    }
+
+                    // Await all writes in parallel
+                    for result in futures::future::join_all(write_futures).await {
+                        result
+                            .context_ut("failed to export")?
+                            .map_err(ExportFailure::Write)?;
+                    }
+
+                    Self::paged_response(total_pages, res_items, span_map, ppi).await?
                 }
+                ExportArtifact::Bundle { items } => {
+                    let root = write_to.clone();
+                    let fut = tokio::task::spawn_blocking(move || {
+                        write_bundle_files(&root, &items, skip_unchanged)
+                    });
+                    fut.await
+                        .context_ut("failed to export")?
+                        .map_err(ExportFailure::Write)?;
 
-                OnExportResponse::Paged {
-                    total_pages,
-                    items: res_items,
+                    OnExportResponse::Single {
+                        path: Some(write_to),
+                        data: None,
+                        ppi: None,
+                    }
                 }
             }
-            ExportArtifact::Bundle { items } => {
-                let root = write_to.clone();
-                let fut = tokio::task::spawn_blocking(move || write_bundle_files(&root, &items));
-                fut.await.context_ut("failed to export")??;
+        };
+
+        if task.as_export().is_some_and(|config| config.manifest) {
+            let format = task.extension().to_owned();
+            let outputs = written_paths(&res);
+            tokio::task::spawn_blocking(move || write_export_manifest(&format, &outputs))
+                .await
+                .context_ut("failed to export")?
+                .map_err(ExportFailure::Write)?;
+        }
+
+        if let Some(changed_pages) = changed_pages {
+            changed_pages.commit();
+        }
+
+        log::debug!("ExportTask({export_id}): export complete");
+        Ok(Some(res))
+    }
+
+    /// Writes the pages of a svg export one by one as soon as they are
+    /// rendered, returning the written pages.
+    async fn write_svg_pages(
+        config: ExportSvgTask,
+        doc: Arc<TypstPagedDocument>,
+        write_to: &Path,
+        skip_unchanged: bool,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Vec<PagedExportResponse>, ExportFailure> {
+        let template = write_to.to_str().unwrap_or_default().to_owned();
+        let can_handle_multiple = output_template::has_indexable_template(&template);
+        if !can_handle_multiple && select_pages(&doc, &config.pages).len() > 1 {
+            return Err(error_once!("cannot export multiple images without a page number template ({p}, {0p}) in the output path").into());
+        }
 
-                OnExportResponse::Single {
-                    path: Some(write_to),
+        let write_to = write_to.to_owned();
+        let cancel = cancel.cloned().unwrap_or_default();
+        tokio::task::spawn_blocking(move || {
+            let total_pages = doc.pages().len();
+            let mut items = vec![];
+            let mut write_err = None;
+            let res = SvgExport::run_paged(&doc, &config, &cancel, |page, svg| {
+                let to = if can_handle_multiple {
+                    PathBuf::from(output_template::format(&template, page + 1, total_pages))
+                } else {
+                    write_to.clone()
+                };
+                let output = Bytes::from_string(svg.to_owned());
+                write_output(&to, &output, skip_unchanged)
+                    .inspect_err(|err| write_err = Some(err.clone()))?;
+
+                items.push(PagedExportResponse {
+                    page,
+                    path: Some(to),
                     data: None,
+                });
+                Ok(())
+            });
+
+            match (res, write_err) {
+                (Ok(()), _) => Ok(items),
+                (Err(_), Some(err)) => Err(ExportFailure::Write(err)),
+                (Err(err), None) => Err(ExportFailure::new(err, Some(&cancel))),
+            }
+        })
+        .await
+        .context_ut("failed to export")?
+    }
+
+    /// Writes the span maps of the written pages next to them, returning the
+    /// response of a paged export.
+    async fn paged_response(
+        total_pages: usize,
+        items: Vec<PagedExportResponse>,
+        span_map: Option<Vec<PageSpans>>,
+        ppi: Option<f32>,
+    ) -> Result<OnExportResponse, ExportFailure> {
+        // Each page gets its own span map next to it.
+        if let Some(span_map) = span_map {
+            for page in span_map {
+                let item = items.iter().find(|item| item.page + 1 == page.page);
+                if let Some(to) = item.and_then(|item| item.path.as_ref()) {
+                    write_sidecar(to, ".spans.json", &[page]).await?;
+                }
+            }
+        }
+
+        Ok(OnExportResponse::Paged {
+            total_pages,
+            items,
+            ppi,
+        })
+    }
+
+    /// Exports each section of the document to its own file, whose path is
+    /// the output path with the `{section}` placeholder replaced by the slug
+    /// of the section. If the export is canceled, the files of the sections
+    /// already exported are removed, so no partial set of sections is left.
+    async fn do_export_sections(
+        task: ProjectTask,
+        artifact: LspCompiledArtifact,
+        split_by: SplitBy,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Option<OnExportResponse>, ExportFailure> {
+        let (config, sections) = Self::sections_to_export(task, &artifact, split_by)?;
+        let template = config.export.output.clone().unwrap_or_default().to_string();
+
+        let mut items = vec![];
+        for section in sections {
+            let mut config = config.clone();
+            let output = template.replace(SECTION_TEMPLATE, &section.slug);
+            config.export.output = Some(PathPattern::new(&output));
+            config.export.split_by = None;
+            config.pages = Some(vec![section.pages.clone()]);
+
+            // The section is skipped if none of its pages are changed.
+            let task = ProjectTask::ExportPdf(config);
+            let res = Box::pin(Self::do_export(task, artifact.clone(), None, cancel)).await;
+            let res = match res {
+                Ok(res) => res,
+                Err(ExportFailure::Cancelled) => {
+                    remove_section_files(&items).await;
+                    return Err(ExportFailure::Cancelled);
                 }
+                Err(err) => return Err(err),
+            };
+            if let Some(OnExportResponse::Single {
+                path: Some(path), ..
+            }) = res
+            {
+                items.push(SectionExportResponse {
+                    title: section.title,
+                    pages: section.pages.to_string(),
+                    path,
+                });
             }
+        }
+
+        Ok(Some(OnExportResponse::Sections { sections: items }))
+    }
+
+    /// Splits the document into the sections to export by the PDF task.
+    fn sections_to_export(
+        task: ProjectTask,
+        artifact: &LspCompiledArtifact,
+        split_by: SplitBy,
+    ) -> Result<(ExportPdfTask, Vec<Section>)> {
+        let ProjectTask::ExportPdf(config) = task else {
+            bail!("only PDF exports can be split by sections");
+        };
+        if config.pages.is_some() {
+            bail!("cannot split the selected pages by sections");
+        }
+        let template = config.export.output.clone().unwrap_or_default().to_string();
+        if !template.contains(SECTION_TEMPLATE) {
+            bail!("cannot export sections without a section template ({SECTION_TEMPLATE}) in the output path");
+        }
+        let Some(TypstDocument::Paged(doc)) = &artifact.doc else {
+            bail!("document is not available for export");
         };
 
-        log::debug!("ExportTask({export_id}): export complete");
-        Ok(Some(res))
+        let sections = split_sections(doc, split_by);
+        if sections.is_empty() {
+            bail!("no headings to split the document by ({split_by})");
+        }
+
+        Ok((config, sections))
     }
 
-    /// Export a document into bytes.
+    /// Export a document into bytes, with the PPI that the images are fitted
+    /// at if they are exported in a maximum size.
     async fn do_export_bytes(
         task: ProjectTask,
         artifact: LspCompiledArtifact,
         export_id: usize,
-    ) -> Result<ExportArtifact> {
+        cancel: Option<&CancellationToken>,
+    ) -> Result<(ExportArtifact, Option<f32>)> {
         use reflexo_vec2svg::DefaultExportFeature;
         use ProjectTask::*;
 
@@ -589,7 +1090,9 @@ impl ExportTask {
         } = artifact;
 
         if let ExportBundle(config) = task {
-            return FutureFolder::compute(move |_| export_bundle_artifact(&graph, &config)).await?;
+            return FutureFolder::compute(move |_| export_bundle_artifact(&graph, &config))
+                .await?
+                .map(|artifact| (artifact, None));
         }
 
         // Prepare the document.
@@ -608,7 +1111,8 @@ impl ExportTask {
         };
 
         // Prepare data.
-        let data = FutureFolder::compute(move |_| -> Result<ExportArtifact> {
+        let cancel = cancel.cloned().unwrap_or_default();
+        let data = FutureFolder::compute(move |_| -> Result<(ExportArtifact, Option<f32>)> {
             let doc = &doc;
 
             // static BLANK: Lazy<Page> = Lazy::new(Page::default);
@@ -639,12 +1143,21 @@ impl ExportTask {
             };
             let total_pages = || paged_doc().map(|d| d.pages().len()).unwrap_or_default();
 
-            Ok(match task {
+            let mut fitted_ppi = None;
+            let artifact = match task {
                 Preview(..) => Bytes::new([]).into(),
                 // todo: more pdf flags
-                ExportPdf(config) => PdfExport::run(&graph, paged_doc()?, &config)?.into(),
-                ExportSvg(config) => SvgExport::run(&graph, paged_doc()?, &config)?.with_pages(total_pages()),
-                ExportPng(config) => PngExport::run(&graph, paged_doc()?,& config)?.with_pages(total_pages()),
+                ExportPdf(config) => PdfExport::run_cancellable(&graph, paged_doc()?, &config, &cancel)?.into(),
+                ExportSvg(config) => SvgExport::run_cancellable(paged_doc()?, &config, &cancel)?.with_pages(total_pages()),
+                ExportAnimatedSvg(config) => AnimatedSvgExport::run_cancellable(paged_doc()?, &config, &cancel)?.into(),
+                ExportGif(config) => GifExport::run_cancellable(paged_doc()?, &config, &cancel)?.into(),
+                ExportPng(config) if config.max_bytes.is_some() => {
+                    let (output, ppi) = PngExport::run_fitted(&graph, paged_doc()?, &config, &cancel)?;
+                    fitted_ppi = Some(ppi.to_f32());
+                    output.with_pages(total_pages())
+                }
+                ExportPng(config) => PngExport::run_cancellable(&graph, paged_doc()?, &config, &cancel)?.with_pages(total_pages()),
+                Query(config) if config.html => HtmlQuery::run(&graph, html_doc()?, &config)?.into(),
                 Query(config) => DocumentQuery::run(&graph, paged_doc()?, &config)??.into(),
                 ExportHtml(ExportHtmlTask { export: _ }) =>
                     typst_html::html(html_doc()?, &typst_html::HtmlOptions::default())
@@ -654,6 +1167,7 @@ impl ExportTask {
                 ExportSvgHtml(ExportHtmlTask { export: _ }) =>
                     reflexo_vec2svg::render_svg_html::<DefaultExportFeature>(paged_doc()?).into(),
                 ExportText(ExportTextTask { export: _ }) => TextExport::run_on_doc(doc)?.into(),
+                ExportPptx(config) => PptxExport::run_cancellable(paged_doc()?, &config, &cancel)?.into(),
                 ExportMd(ExportMarkdownTask {
                     processor,
                     assets_path,
@@ -687,7 +1201,8 @@ impl ExportTask {
                         .convert()
                         .map_err(|e| anyhow::anyhow!("failed to convert to latex: {e}"))?;
                     conv.into()
-                }})
+                }};
+            Ok((artifact, fitted_ppi))
         })
         .await??;
 
@@ -695,6 +1210,88 @@ impl ExportTask {
     }
 }
 
+/// The content hashes of the pages last exported to each output path, which are
+/// compared by the exports of the changed pages. They are not persisted, so the
+/// changed pages are only detected within one session of the server.
+static PAGE_HASHES: LazyLock<Mutex<HashMap<PathBuf, Vec<u128>>>> = LazyLock::new(Mutex::default);
+
+/// The pages changed since the last export to an output path, see
+/// [`ProjectExportTask::changed_pages`].
+struct ChangedPages {
+    /// The output path the hashes are recorded for.
+    write_to: PathBuf,
+    /// The content hashes of all pages of the document.
+    hashes: Vec<u128>,
+    /// The 0-based indices of the changed pages to export.
+    changed: Vec<usize>,
+}
+
+impl ChangedPages {
+    /// Compares the pages of the document with the last export, or returns
+    /// `None` if the task doesn't export the changed pages only.
+    fn compute(task: &ProjectTask, doc: Option<&TypstDocument>, write_to: &Path) -> Option<Self> {
+        if !task.as_export().is_some_and(|config| config.changed_pages) {
+            return None;
+        }
+        let pages = match task {
+            ProjectTask::ExportPdf(config) => &config.pages,
+            ProjectTask::ExportPng(config) => &config.pages,
+            _ => return None,
+        };
+        let Some(TypstDocument::Paged(doc)) = doc else {
+            return None;
+        };
+
+        let hashes = doc.pages().iter().map(hash128).collect::<Vec<_>>();
+        let selected = pages.as_deref().map(exported_page_ranges);
+        let last = PAGE_HASHES
+            .lock()
+            .get(write_to)
+            .cloned()
+            .unwrap_or_default();
+        let changed = hashes
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| {
+                selected
+                    .as_ref()
+                    .is_none_or(|ranges| ranges.includes_page_index(*idx))
+            })
+            .filter(|(idx, hash)| last.get(*idx) != Some(*hash))
+            .map(|(idx, _)| idx)
+            .collect();
+
+        Some(Self {
+            write_to: write_to.to_owned(),
+            hashes,
+            changed,
+        })
+    }
+
+    /// Restricts the task to export the changed pages.
+    fn restrict(&self, mut task: ProjectTask) -> ProjectTask {
+        let pages = self
+            .changed
+            .iter()
+            .map(|idx| {
+                let page = NonZeroUsize::new(idx + 1);
+                Pages(page..=page)
+            })
+            .collect();
+        match &mut task {
+            ProjectTask::ExportPdf(config) => config.pages = Some(pages),
+            ProjectTask::ExportPng(config) => config.pages = Some(pages),
+            _ => {}
+        }
+        task
+    }
+
+    /// Records the hashes of the pages after they are exported.
+    fn commit(self) {
+        PAGE_HASHES.lock().insert(self.write_to, self.hashes);
+    }
+}
+
 enum ExportArtifact {
     Single(Bytes),
     Paged {
@@ -781,6 +1378,7 @@ fn export_bundle_artifact(
             &config.pdf_standards,
             config.no_pdf_tags,
             config.creation_timestamp,
+            false,
         )?,
         png: typst_render::RenderOptions {
             pixel_per_pt: f64::from(config.ppi.to_f32() / 72.0).into(),
@@ -817,14 +1415,62 @@ fn collect_bundle_files(fs: &VirtualFs) -> Result<Vec<(PathBuf, Bytes)>> {
         .collect()
 }
 
-fn write_bundle_files(root: &Path, items: &[(PathBuf, Bytes)]) -> Result<()> {
+/// Removes the files of the sections written by a canceled export.
+async fn remove_section_files(sections: &[SectionExportResponse]) {
+    let paths = sections
+        .iter()
+        .map(|section| section.path.clone())
+        .collect::<Vec<_>>();
+    let removed = tokio::task::spawn_blocking(move || {
+        for path in paths {
+            if let Err(err) = std::fs::remove_file(&path) {
+                log::warn!("failed to remove the section file {path:?}: {err}");
+            }
+        }
+    });
+    if let Err(err) = removed.await {
+        log::warn!("failed to remove the section files: {err}");
+    }
+}
+
+/// Collects the paths of the files written by an export.
+fn written_paths(res: &OnExportResponse) -> Vec<PathBuf> {
+    match res {
+        OnExportResponse::DryRun { .. } => vec![],
+        OnExportResponse::Single { path, .. } => path.iter().cloned().collect(),
+        OnExportResponse::Paged { items, .. } => {
+            items.iter().filter_map(|item| item.path.clone()).collect()
+        }
+        OnExportResponse::Sections { sections } => sections
+            .iter()
+            .map(|section| section.path.clone())
+            .collect(),
+    }
+}
+
+/// Writes a JSON sidecar file next to the output, i.e. `<output><suffix>`.
+async fn write_sidecar(
+    output: &Path,
+    suffix: &str,
+    value: &impl serde::Serialize,
+) -> Result<(), ExportFailure> {
+    let mut to = output.to_owned().into_os_string();
+    to.push(suffix);
+    let data = serde_json::to_vec_pretty(value).context("failed to serialize sidecar")?;
+    tokio::task::spawn_blocking(move || write_atomic(to, data))
+        .await
+        .context_ut("failed to export sidecar")?
+        .map_err(ExportFailure::Write)
+}
+
+fn write_bundle_files(root: &Path, items: &[(PathBuf, Bytes)], skip_unchanged: bool) -> Result<()> {
     std::fs::create_dir_all(root).context("failed to create output directory")?;
     for (path, data) in items {
         let realized = root.join(path);
         if let Some(parent) = realized.parent() {
             std::fs::create_dir_all(parent).context("failed to create directory")?;
         }
-        write_atomic(realized, data.clone()).context("failed to write file")?;
+        write_output(&realized, data, skip_unchanged)?;
     }
     Ok(())
 }
@@ -849,11 +1495,21 @@ impl Default for ExportUserConfig {
                     when: TaskWhen::Never,
                     output: None,
                     transform: vec![],
+                    skip_unchanged: false,
+                    changed_pages: false,
+                    expand_env: false,
+                    manifest: false,
+                    span_map: false,
+                    split_by: None,
+                    require: None,
                 },
                 pages: None,
                 pdf_standards: vec![],
                 no_pdf_tags: false,
                 creation_timestamp: None,
+                reproducible: false,
+                outline_sidecar: false,
+                icc_profile: None,
             }),
             count_words: false,
             development: false,
@@ -991,6 +1647,7 @@ mod tests {
     use super::*;
     use crate::export::ProjectCompilation;
     use crate::project::{CompileOnceArgs, CompileSignal, WorldProvider};
+    use crate::task::export2::EXPORT_MANIFEST_NAME;
     use crate::world::base::{CompileSnapshot, WorldComputeGraph};
 
     #[test]
@@ -1017,6 +1674,27 @@ mod tests {
         assert!(!needs_run);
     }
 
+    #[test]
+    fn test_entry_with_root() {
+        use typst_shim::syntax::VirtualPathExt;
+
+        let root = if cfg!(windows) { "C:\\site" } else { "/site" };
+        let root = Path::new(root);
+
+        let entry = ServerState::entry_with_root(&root.join("docs/main.typ"), root)
+            .expect("the document is in the root");
+        assert_eq!(entry.root().as_deref(), Some(root));
+        let main = entry.main().expect("the entry has a main file");
+        assert_eq!(
+            main.vpath().as_rooted_path_compat(),
+            Path::new("/docs/main.typ")
+        );
+
+        let escaped = root.join("../main.typ");
+        assert!(ServerState::entry_with_root(&escaped, root).is_err());
+        assert!(ServerState::entry_with_root(&escaped, Path::new("site")).is_err());
+    }
+
     // todo: on demand compilation
     #[test]
     fn compilation_run_paged_diagnostics() {
@@ -1160,6 +1838,13 @@ mod tests {
                 when: TaskWhen::Never,
                 output: output.map(PathPattern::new),
                 transform: vec![],
+                skip_unchanged: false,
+                changed_pages: false,
+                expand_env: false,
+                manifest: false,
+                span_map: false,
+                split_by: None,
+                require: None,
             },
             ..Default::default()
         })
@@ -1217,4 +1902,218 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_prepare_output_path_changed_pages() {
+        let workspace = TestWorkspace::new(&[("main.typ", "")]);
+        let mut task = pdf_task(None);
+        task.as_export_mut().unwrap().changed_pages = true;
+
+        let graph = workspace.graph("main.typ");
+        assert_eq!(
+            ExportTask::prepare_output_path(&task, &graph).unwrap(),
+            Some(workspace.root.join("main.diff.pdf"))
+        );
+    }
+
+    #[test]
+    fn test_cancelled_export_writes_nothing() {
+        let workspace = TestWorkspace::new(&[("main.typ", "Hello")]);
+        let graph = workspace.graph("main.typ");
+        let artifact = CompiledArtifact::from_graph(graph, false);
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let res = ExportTask::do_export(pdf_task(None), artifact, None, Some(&cancel));
+        let err = futures::executor::block_on(res).expect_err("the export is cancelled");
+
+        assert!(matches!(err, ExportFailure::Cancelled));
+        assert!(!workspace.root.join("main.pdf").exists());
+    }
+
+    /// Runs the export task, returning the code of the error if it fails.
+    fn export_error_code(task: ProjectTask, graph: LspComputeGraph) -> Option<String> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build runtime");
+        let res = runtime.block_on(ServerState::on_export_typ(
+            task,
+            graph,
+            true,
+            false,
+            None::<fn(LspComputeGraph)>,
+            None,
+        ));
+
+        let data = res.err()?.data?;
+        Some(data["code"].as_str()?.to_owned())
+    }
+
+    #[test]
+    fn test_export_error_codes() {
+        let err = ServerState::entry_with_root(Path::new("main.typ"), Path::new("site"))
+            .expect_err("the root is relative");
+        let data = err.data.expect("the error has a code");
+        assert_eq!(data["code"], "INVALID_ARGS");
+
+        let workspace = TestWorkspace::new(&[
+            ("main.typ", "= Heading\nHello"),
+            ("broken.typ", "#panic()"),
+            ("blocker", ""),
+        ]);
+
+        let code = export_error_code(pdf_task(None), workspace.graph("broken.typ"));
+        assert_eq!(code.as_deref(), Some("COMPILE_FAILED"));
+
+        let mut task = pdf_task(None);
+        task.as_export_mut().unwrap().split_by = Some(SplitBy::HeadingLevel(NonZeroUsize::MIN));
+        let code = export_error_code(task, workspace.graph("main.typ"));
+        assert_eq!(code.as_deref(), Some("EXPORT_FAILED"));
+
+        let code = export_error_code(pdf_task(Some("blocker/out")), workspace.graph("main.typ"));
+        assert_eq!(code.as_deref(), Some("WRITE_FAILED"));
+
+        let code = export_error_code(pdf_task(None), workspace.graph("main.typ"));
+        assert_eq!(code, None);
+        assert!(workspace.root.join("main.pdf").exists());
+    }
+
+    #[test]
+    fn test_skip_unchanged_export() {
+        let workspace = TestWorkspace::new(&[("main.typ", "Hello")]);
+        let output = workspace.root.join("main.pdf");
+        let old = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1 << 30);
+
+        let export = |skip_unchanged: bool| {
+            let ProjectTask::ExportPdf(mut config) = pdf_task(None) else {
+                unreachable!()
+            };
+            config.reproducible = true;
+            config.export.skip_unchanged = skip_unchanged;
+
+            let graph = workspace.graph("main.typ");
+            let artifact = CompiledArtifact::from_graph(graph, false);
+            let task = ProjectTask::ExportPdf(config);
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("failed to build runtime");
+            runtime
+                .block_on(ExportTask::do_export(task, artifact, None, None))
+                .expect("failed to export");
+
+            let file = fs::File::options().write(true).open(&output).unwrap();
+            let modified = file.metadata().unwrap().modified().unwrap();
+            file.set_modified(old).unwrap();
+            modified
+        };
+
+        assert_ne!(export(true), old);
+        assert_eq!(export(true), old);
+        assert_ne!(export(false), old);
+    }
+
+    #[test]
+    fn test_export_manifest() {
+        let workspace = TestWorkspace::new(&[("main.typ", "Hello")]);
+        let ProjectTask::ExportPdf(mut config) = pdf_task(None) else {
+            unreachable!()
+        };
+        config.export.manifest = true;
+
+        let graph = workspace.graph("main.typ");
+        let artifact = CompiledArtifact::from_graph(graph, false);
+        let task = ProjectTask::ExportPdf(config);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build runtime");
+        runtime
+            .block_on(ExportTask::do_export(task, artifact, None, None))
+            .expect("failed to export");
+
+        let output = workspace.root.join("main.pdf");
+        let manifest =
+            fs::read(workspace.root.join(EXPORT_MANIFEST_NAME)).expect("failed to read manifest");
+        let manifest: serde_json::Value = serde_json::from_slice(&manifest).unwrap();
+        let [entry] = manifest.as_array().unwrap().as_slice() else {
+            panic!("expected a single entry: {manifest}");
+        };
+        assert_eq!(entry["format"], "pdf");
+        assert_eq!(entry["path"], output.to_str().unwrap());
+        assert_eq!(entry["size"], fs::metadata(&output).unwrap().len());
+    }
+
+    #[test]
+    fn test_reproducible_pdf() {
+        use tinymist_std::typst::TypstPagedDocument;
+
+        let workspace = TestWorkspace::new(&[("main.typ", "#set document(title: [Notes])\nHello")]);
+        let ProjectTask::ExportPdf(mut config) = pdf_task(None) else {
+            unreachable!()
+        };
+        config.reproducible = true;
+
+        let export = || {
+            let graph = workspace.graph("main.typ");
+            let doc = typst::compile::<TypstPagedDocument>(graph.world())
+                .output
+                .expect("failed to compile");
+            PdfExport::run(&graph, &Arc::new(doc), &config).expect("failed to export")
+        };
+
+        let first = export();
+        // Crosses a second so that embedding the time of export would differ.
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        let second = export();
+
+        assert_eq!(first.as_slice(), second.as_slice());
+        assert!(!first
+            .as_slice()
+            .windows(b"/CreationDate".len())
+            .any(|window| window == b"/CreationDate"));
+    }
+
+    #[test]
+    fn test_changed_pages() {
+        use tinymist_std::typst::TypstPagedDocument;
+
+        let compile = |second: &str| {
+            let source = format!("first\n#pagebreak()\n{second}\n#pagebreak()\nthird");
+            let workspace = TestWorkspace::new(&[("main.typ", source.as_str())]);
+            let graph = workspace.graph("main.typ");
+            let doc = typst::compile::<TypstPagedDocument>(graph.world())
+                .output
+                .expect("failed to compile");
+            TypstDocument::Paged(Arc::new(doc))
+        };
+
+        let mut task = pdf_task(None);
+        task.as_export_mut().unwrap().changed_pages = true;
+        let write_to = std::env::temp_dir().join(format!(
+            "tinymist-changed-pages-test-{}.diff.pdf",
+            std::process::id()
+        ));
+        let changed = |doc: &TypstDocument| {
+            ChangedPages::compute(&task, Some(doc), &write_to).expect("changed pages enabled")
+        };
+
+        // The first export writes all pages.
+        let doc = compile("second");
+        let first = changed(&doc);
+        assert_eq!(first.changed, vec![0, 1, 2]);
+        first.commit();
+
+        assert!(changed(&doc).changed.is_empty());
+        assert_eq!(changed(&compile("2nd")).changed, vec![1]);
+
+        // Unselected pages are never exported.
+        let ProjectTask::ExportPdf(config) = &mut task else {
+            unreachable!()
+        };
+        config.pages = Some(vec![Pages::FIRST]);
+        let selected = ChangedPages::compute(&task, Some(&compile("2nd")), &write_to).unwrap();
+        assert!(selected.changed.is_empty());
+    }
 }
@@ -1,18 +1,23 @@
 //! Next generation of the export task. Not used because it is still
 //! complicated.
 
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use reflexo_typst::{Bytes, CompilerFeat, EntryReader, ExportWebSvgHtmlTask, WebSvgHtmlExport};
 use reflexo_vec2svg::DefaultExportFeature;
+use serde::{Deserialize, Serialize};
 use tinymist_std::error::prelude::*;
+use tinymist_std::fs::paths::write_atomic;
+use tinymist_std::hash::hash128;
 use tinymist_std::typst::TypstPagedDocument;
-use tinymist_task::{ExportTimings, TextExport};
+use tinymist_task::{AnimatedSvgExport, ExportTimings, GifExport, PptxExport, TextExport};
 use typlite::{Format, Typlite};
 
 use crate::project::{
-    ExportTeXTask, HtmlExport, LspCompilerFeat, PdfExport, PngExport, ProjectTask, SvgExport,
-    TaskWhen,
+    ExportPptxTask, ExportTeXTask, HtmlExport, LspCompilerFeat, PdfExport, PngExport, ProjectTask,
+    SvgExport, TaskWhen,
 };
 use crate::world::base::{
     BundleCompilationTask, ConfigTask, DiagnosticsTask, ExportComputation, FlagTask,
@@ -52,6 +57,10 @@ impl ProjectCompilation {
             .get::<ConfigTask<ExportTeXTask>>()
             .transpose()?
             .map(|config| config.export.when.clone());
+        let pptx: Option<TaskWhen> = graph
+            .get::<ConfigTask<ExportPptxTask>>()
+            .transpose()?
+            .map(|config| config.export.when.clone());
         let text: Option<TaskWhen> = graph
             .get::<ConfigTask<<TextExport as ExportComputation<LspCompilerFeat, _>>::Config>>()
             .transpose()?
@@ -59,10 +68,10 @@ impl ProjectCompilation {
 
         let doc = None::<TypstPagedDocument>.as_ref();
         let check = |timing: Option<TaskWhen>| {
-            ExportTimings::needs_run(&graph.snap, timing.as_ref(), doc).unwrap_or(true)
+            ExportTimings::needs_run(&graph.snap, timing.as_ref(), None, doc).unwrap_or(true)
         };
 
-        let compile_paged = [paged_diag, paged_diag2, pdf, svg, png, text, md]
+        let compile_paged = [paged_diag, paged_diag2, pdf, svg, png, text, md, pptx]
             .into_iter()
             .any(check);
         let compile_html = [html_diag, html].into_iter().any(check);
@@ -96,11 +105,13 @@ impl ProjectExport {
     >(
         graph: &Arc<WorldComputeGraph<LspCompilerFeat>>,
         when: Option<&TaskWhen>,
+        require: Option<&str>,
         config: &T::Config,
     ) -> Result<Option<Bytes>> {
         let doc = graph.compute::<OptionDocumentTask<D>>()?;
         let doc = doc.as_ref();
-        let n = ExportTimings::needs_run(&graph.snap, when, doc.as_deref()).unwrap_or(true);
+        let n =
+            ExportTimings::needs_run(&graph.snap, when, require, doc.as_deref()).unwrap_or(true);
         if !n {
             return Ok(None);
         }
@@ -116,11 +127,13 @@ impl ProjectExport {
     >(
         graph: &Arc<WorldComputeGraph<LspCompilerFeat>>,
         when: Option<&TaskWhen>,
+        require: Option<&str>,
         config: &T::Config,
     ) -> Result<Option<Bytes>> {
         let doc = graph.compute::<OptionDocumentTask<D>>()?;
         let doc = doc.as_ref();
-        let n = ExportTimings::needs_run(&graph.snap, when, doc.as_deref()).unwrap_or(true);
+        let n =
+            ExportTimings::needs_run(&graph.snap, when, require, doc.as_deref()).unwrap_or(true);
         if !n {
             return Ok(None);
         }
@@ -136,34 +149,44 @@ impl WorldComputable<LspCompilerFeat> for ProjectExport {
 
     fn compute(graph: &Arc<WorldComputeGraph<LspCompilerFeat>>) -> Result<Self> {
         let config = graph.must_get::<ConfigTask<ProjectTask>>()?;
-        let output_path = config.as_export().and_then(|e| {
-            e.output
-                .as_ref()
-                .and_then(|o| o.substitute(&graph.snap.world.entry_state()))
-        });
+        let output = match config.as_export() {
+            Some(export) => export.output_pattern()?,
+            None => None,
+        };
+        let output_path = output.and_then(|o| o.substitute(&graph.snap.world.entry_state()));
         let when = config.when();
+        let require = config.as_export().and_then(|e| e.require.as_deref());
+        let skip_unchanged = config.as_export().is_some_and(|e| e.skip_unchanged);
+        let manifest = config.as_export().is_some_and(|e| e.manifest);
 
         let output = || -> Result<Option<Bytes>> {
             use ProjectTask::*;
             match config.as_ref() {
                 Preview(..) => todo!(),
-                ExportPdf(config) => Self::export_bytes::<_, PdfExport>(graph, when, config),
+                ExportPdf(config) => {
+                    Self::export_bytes::<_, PdfExport>(graph, when, require, config)
+                }
                 ExportPng(_config) => todo!(),
                 ExportSvg(_config) => todo!(),
-                ExportHtml(config) => Self::export_string::<_, HtmlExport>(graph, when, config),
+                ExportAnimatedSvg(config) => {
+                    Self::export_string::<_, AnimatedSvgExport>(graph, when, require, config)
+                }
+                ExportHtml(config) => {
+                    Self::export_string::<_, HtmlExport>(graph, when, require, config)
+                }
                 ExportBundle(..) => unreachable!(),
                 // todo: configuration
                 ExportSvgHtml(_config) => Self::export_string::<
                     _,
                     WebSvgHtmlExport<DefaultExportFeature>,
                 >(
-                    graph, when, &ExportWebSvgHtmlTask::default()
+                    graph, when, require, &ExportWebSvgHtmlTask::default()
                 ),
                 ExportMd(..) => {
                     let doc = graph.compute::<OptionDocumentTask<TypstPagedDocument>>()?;
                     let doc = doc.as_ref();
-                    let n =
-                        ExportTimings::needs_run(&graph.snap, when, doc.as_deref()).unwrap_or(true);
+                    let n = ExportTimings::needs_run(&graph.snap, when, require, doc.as_deref())
+                        .unwrap_or(true);
                     if !n {
                         return Ok(None);
                     }
@@ -173,15 +196,23 @@ impl WorldComputable<LspCompilerFeat> for ProjectExport {
                 ExportTeX(..) => {
                     let doc = graph.compute::<OptionDocumentTask<TypstPagedDocument>>()?;
                     let doc = doc.as_ref();
-                    let n =
-                        ExportTimings::needs_run(&graph.snap, when, doc.as_deref()).unwrap_or(true);
+                    let n = ExportTimings::needs_run(&graph.snap, when, require, doc.as_deref())
+                        .unwrap_or(true);
                     if !n {
                         return Ok(None);
                     }
 
                     Ok(TypliteTeXExport::run(graph)?.map(Bytes::from_string))
                 }
-                ExportText(config) => Self::export_string::<_, TextExport>(graph, when, config),
+                ExportText(config) => {
+                    Self::export_string::<_, TextExport>(graph, when, require, config)
+                }
+                ExportPptx(config) => {
+                    Self::export_bytes::<_, PptxExport>(graph, when, require, config)
+                }
+                ExportGif(config) => {
+                    Self::export_bytes::<_, GifExport>(graph, when, require, config)
+                }
                 Query(..) => todo!(),
             }
         };
@@ -190,7 +221,16 @@ impl WorldComputable<LspCompilerFeat> for ProjectExport {
             let output = output()?;
             // todo: don't ignore export source diagnostics
             if let Some(output) = output {
-                std::fs::write(path, output).context("failed to write output")?;
+                // The logs are written to stderr, so they don't corrupt the output.
+                if path.as_ref() == Path::new("-") {
+                    write_stdout(&output)?;
+                    return Ok(Self {});
+                }
+
+                write_output(&path, &output, skip_unchanged)?;
+                if manifest {
+                    write_export_manifest(config.extension(), &[path.to_path_buf()])?;
+                }
             }
         }
 
@@ -198,6 +238,105 @@ impl WorldComputable<LspCompilerFeat> for ProjectExport {
     }
 }
 
+/// The name of the export manifest, which is written next to the outputs.
+pub const EXPORT_MANIFEST_NAME: &str = "export-manifest.json";
+
+/// An entry of the export manifest, describing a written output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportManifestEntry {
+    /// The format of the output, e.g. `pdf`.
+    pub format: String,
+    /// The path to the output.
+    pub path: PathBuf,
+    /// The size of the output in bytes.
+    pub size: u64,
+    /// The SHA-256 checksum of the output, in hex.
+    pub sha256: String,
+    /// The time when the output was exported, in RFC 3339 format.
+    pub timestamp: String,
+}
+
+impl ExportManifestEntry {
+    /// Describes the output written to the path.
+    fn read(format: &str, path: &Path) -> Result<Self> {
+        use sha2::{Digest, Sha256};
+
+        let output = std::fs::read(path).context("failed to read output")?;
+        let timestamp = tinymist_std::time::utc_now().format(&tinymist_std::time::Rfc3339);
+        Ok(Self {
+            format: format.to_owned(),
+            path: path.to_owned(),
+            size: output.len() as u64,
+            sha256: hex::encode(Sha256::digest(&output)),
+            timestamp: timestamp.unwrap_or_default(),
+        })
+    }
+}
+
+/// Records the written outputs in the export manifests next to them. The
+/// entries of other outputs in a manifest are kept, so that a manifest can be
+/// shared by multiple tasks. Directories, e.g. the root of a bundle, are not
+/// recorded.
+pub(crate) fn write_export_manifest(format: &str, outputs: &[PathBuf]) -> Result<()> {
+    let mut manifests = BTreeMap::<&Path, Vec<ExportManifestEntry>>::new();
+    for output in outputs.iter().filter(|output| output.is_file()) {
+        let Some(dir) = output.parent() else {
+            continue;
+        };
+        let entry = ExportManifestEntry::read(format, output)?;
+        manifests.entry(dir).or_default().push(entry);
+    }
+
+    for (dir, entries) in manifests {
+        let manifest_path = dir.join(EXPORT_MANIFEST_NAME);
+
+        let mut manifest: Vec<ExportManifestEntry> = std::fs::read(&manifest_path)
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+        manifest.retain(|entry| entries.iter().all(|e| e.path != entry.path));
+        manifest.extend(entries);
+        manifest.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let content =
+            serde_json::to_vec_pretty(&manifest).context("failed to serialize manifest")?;
+        write_atomic(manifest_path, content).context("failed to write export manifest")?;
+    }
+
+    Ok(())
+}
+
+/// Writes the output to the standard output.
+pub(crate) fn write_stdout(output: &Bytes) -> Result<()> {
+    use std::io::Write;
+
+    let mut stdout = std::io::stdout().lock();
+    stdout
+        .write_all(output.as_slice())
+        .and_then(|_| stdout.flush())
+        .context("failed to write output to stdout")
+}
+
+/// Writes the output to the path, which is skipped if `skip_unchanged` is set
+/// and the existing file has the same content.
+pub(crate) fn write_output(path: &Path, output: &Bytes, skip_unchanged: bool) -> Result<()> {
+    if skip_unchanged && is_unchanged(path, output) {
+        log::debug!("skip writing unchanged output to {path:?}");
+        return Ok(());
+    }
+    // The rename replaces the existing file on Windows as well, so readers never
+    // see a partially written file.
+    write_atomic(path, output).context("failed to write output")
+}
+
+/// Checks whether the existing file has the same content as the output by
+/// comparing their hashes.
+fn is_unchanged(path: &Path, output: &Bytes) -> bool {
+    std::fs::read(path)
+        .is_ok_and(|existing| hash128(&existing.as_slice()) == hash128(&output.as_slice()))
+}
+
 /// A task that exports the document to a specific format by typlite.
 pub struct TypliteExport<const FORMAT: char>;
 
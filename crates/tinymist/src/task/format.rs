@@ -5,7 +5,7 @@ use std::iter::zip;
 use lsp_types::{Range, TextEdit};
 use sync_ls::{just_future, SchedulableResponse};
 use tinymist_query::{to_lsp_range, to_typst_range, PositionEncoding};
-use typst::syntax::Source;
+use typst::syntax::{LinkedNode, Source};
 
 use super::SyncTaskFactory;
 
@@ -63,23 +63,74 @@ impl FormatTask {
     ) -> SchedulableResponse<Option<Vec<TextEdit>>> {
         fn format_impl(src: Source, range: Range, c: &FormatUserConfig) -> Option<Vec<TextEdit>> {
             let typst_range = to_typst_range(range, c.position_encoding, &src)?;
+            let (source_range, content) = format_source_range(&src, typst_range, c)?;
 
-            match &c.config {
-                FormatterConfig::Typstyle(config) => {
-                    let format_result = typstyle_core::Typstyle::new(config.as_ref().clone())
-                        .format_source_range(src.clone(), typst_range)
-                        .ok()?;
-                    let mut new_full_text = src.text().to_owned();
-                    new_full_text.replace_range(format_result.source_range, &format_result.content);
-                    calc_diff(src, new_full_text, c.position_encoding)
-                }
-                _ => None,
-            }
+            let mut new_full_text = src.text().to_owned();
+            new_full_text.replace_range(source_range, &content);
+            calc_diff(src, new_full_text, c.position_encoding)
         }
 
         let c = self.factory.task();
         just_future(async move { Ok(format_impl(src, range, &c)) })
     }
+
+    /// Formats the syntax nodes selected by the range, see [`select_nodes`].
+    /// Returns the range that is reformatted and its formatted text, which
+    /// keeps the indentation of the surrounding code.
+    pub fn format_range(
+        &self,
+        src: Source,
+        range: std::ops::Range<usize>,
+    ) -> Option<(Range, String)> {
+        let c = self.factory.task();
+        let (source_range, content) = format_source_range(&src, select_nodes(&src, range), &c)?;
+        Some((
+            to_lsp_range(source_range, &src, c.position_encoding),
+            content,
+        ))
+    }
+}
+
+/// Formats a range of the source by the configured formatter, returning the
+/// range that is reformatted and its formatted text.
+fn format_source_range(
+    src: &Source,
+    range: std::ops::Range<usize>,
+    c: &FormatUserConfig,
+) -> Option<(std::ops::Range<usize>, String)> {
+    match &c.config {
+        FormatterConfig::Typstyle(config) => {
+            let format_result = typstyle_core::Typstyle::new(config.as_ref().clone())
+                .format_source_range(src.clone(), range)
+                .ok()?;
+            Some((format_result.source_range, format_result.content))
+        }
+        _ => None,
+    }
+}
+
+/// Expands the range to the syntax nodes it selects, i.e. the children of the
+/// innermost node containing the range that overlap it, so that no node is
+/// formatted partially.
+fn select_nodes(src: &Source, range: std::ops::Range<usize>) -> std::ops::Range<usize> {
+    let mut node = LinkedNode::new(src.root());
+    loop {
+        let covering = node.children().find(|child| {
+            let r = child.range();
+            !r.is_empty() && r.start <= range.start && range.end <= r.end
+        });
+        match covering {
+            Some(child) if child.children().len() > 0 => node = child,
+            Some(child) => return child.range(),
+            None => break,
+        }
+    }
+
+    node.children()
+        .map(|child| child.range())
+        .filter(|r| r.start < range.end && range.start < r.end)
+        .reduce(|a, b| a.start.min(b.start)..a.end.max(b.end))
+        .unwrap_or(range)
 }
 
 /// A simple implementation of the diffing algorithm, borrowed from
@@ -118,3 +169,26 @@ fn calc_diff(prev: Source, next: String, encoding: PositionEncoding) -> Option<V
         range,
     }])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_nodes() {
+        let src = Source::detached("#let a = (1,  2)\n#f(x,  y)\n");
+        let text = src.text();
+        let select = |from: &str, to: &str| {
+            let start = text.find(from).unwrap();
+            let end = text.find(to).unwrap() + to.len();
+            &text[select_nodes(&src, start..end)]
+        };
+
+        // A range inside a node selects the whole node.
+        assert_eq!(select("1", "1"), "1");
+        assert_eq!(select("(1", "2)"), "(1,  2)");
+        // A range across nodes selects the overlapping siblings.
+        assert_eq!(select("1,", "2"), "1,  2");
+        assert_eq!(select("x,", "y"), "x,  y");
+    }
+}
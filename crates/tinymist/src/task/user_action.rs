@@ -1,5 +1,6 @@
 //! The actor that runs user actions.
 
+use std::collections::{BTreeMap, HashMap};
 use std::future::Future;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicU64;
@@ -85,8 +86,15 @@ impl UserActionTask {
 
             typst_timing::clear();
 
+            let compile_spans = serde_json::from_slice(&timings)
+                .map(|events: Vec<TimingEvent>| CompileSpanStats::summarize(&events))
+                .unwrap_or_else(|err| {
+                    log::warn!("failed to summarize the spans of compilations: {err}");
+                    CompileSpanStats::default()
+                });
+
             resp_tx
-                .send(Ok(json!({})))
+                .send(Ok(json!({ "compileSpans": compile_spans })))
                 .ok()
                 .log_error("failed to send response");
 
@@ -299,6 +307,96 @@ async fn trace_main(
     std::process::exit(0);
 }
 
+/// An event in the exported timings, in the Chrome trace event format.
+#[derive(Debug, Clone, Deserialize)]
+struct TimingEvent {
+    name: String,
+    ph: String,
+    ts: f64,
+    /// The thread that the span runs on.
+    #[serde(default)]
+    tid: u64,
+}
+
+/// The timed spans executed during a compilation in the server trace. Only
+/// the executed work is timed, so a memoized call that is reused from the
+/// cache is not counted, and fewer executions mean more reused work.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompileSpans {
+    /// The duration of the compilation in milliseconds.
+    pub duration_ms: f64,
+    /// The number of executions of each timed span during the compilation.
+    pub span_executions: BTreeMap<String, u64>,
+    /// The total number of span executions.
+    pub total_span_executions: u64,
+}
+
+/// The summary of the timed spans executed by the compilations in the server
+/// trace.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompileSpanStats {
+    /// The compilations in the order they start.
+    pub compilations: Vec<CompileSpans>,
+    /// The total number of span executions in all compilations.
+    pub total_span_executions: u64,
+}
+
+impl CompileSpanStats {
+    /// Summarizes the compilations from the timing events. A span is
+    /// attributed to the innermost compilation running on its thread, or to
+    /// the latest started one if none runs on its thread, e.g. for the spans
+    /// on worker threads.
+    fn summarize(events: &[TimingEvent]) -> Self {
+        use crate::world::COMPILE_SCOPE;
+
+        let mut events = events.iter().collect::<Vec<_>>();
+        events.sort_by(|a, b| a.ts.total_cmp(&b.ts));
+
+        let mut cycles = vec![];
+        // The running compilations of each thread, by the indices of their
+        // cycles and their start times.
+        let mut active = HashMap::<u64, Vec<(usize, f64)>>::new();
+        for event in events {
+            match (event.name.as_str(), event.ph.as_str()) {
+                (COMPILE_SCOPE, "B") => {
+                    let stack = active.entry(event.tid).or_default();
+                    stack.push((cycles.len(), event.ts));
+                    cycles.push(CompileSpans::default());
+                }
+                (COMPILE_SCOPE, "E") => {
+                    let stack = active.entry(event.tid).or_default();
+                    if let Some((idx, start)) = stack.pop() {
+                        cycles[idx].duration_ms = (event.ts - start) / 1000.0;
+                    }
+                }
+                (name, "B") => {
+                    let running = active.get(&event.tid).and_then(|stack| stack.last());
+                    let latest = || {
+                        active
+                            .values()
+                            .filter_map(|stack| stack.last())
+                            .max_by_key(|(idx, _)| *idx)
+                    };
+                    if let Some((idx, _)) = running.or_else(latest) {
+                        let cycle = &mut cycles[*idx];
+                        *cycle.span_executions.entry(name.to_owned()).or_default() += 1;
+                        cycle.total_span_executions += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let total_span_executions = cycles.iter().map(|cycle| cycle.total_span_executions).sum();
+        Self {
+            compilations: cycles,
+            total_span_executions,
+        }
+    }
+}
+
 /// The server trace task.
 pub struct ServerTraceTask {
     /// The sender to stop the trace.
@@ -481,3 +579,82 @@ fn resolve_span(world: &LspWorld, span: Span) -> Option<(String, u32)> {
     let line = source.lines().byte_to_line(range.start)?;
     Some((format!("{id:?}"), line as u32 + 1))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(name: &str, ph: &str, ts: f64) -> TimingEvent {
+        TimingEvent {
+            name: name.to_owned(),
+            ph: ph.to_owned(),
+            ts,
+            tid: 0,
+        }
+    }
+
+    #[test]
+    fn test_compile_span_stats() {
+        let events = [
+            event("server_trace", "B", 0.),
+            event("compile", "B", 1000.),
+            event("eval", "B", 1100.),
+            event("eval", "E", 1200.),
+            event("layout", "B", 1300.),
+            event("layout", "E", 1400.),
+            event("layout", "B", 1500.),
+            event("layout", "E", 1600.),
+            event("compile", "E", 3000.),
+            event("edit_source", "B", 4000.),
+            event("edit_source", "E", 4100.),
+            event("compile", "B", 5000.),
+            event("layout", "B", 5100.),
+            event("layout", "E", 5200.),
+            event("compile", "E", 6000.),
+            event("server_trace", "E", 7000.),
+        ];
+
+        let stats = CompileSpanStats::summarize(&events);
+        assert_eq!(stats.compilations.len(), 2);
+        assert_eq!(stats.compilations[0].duration_ms, 2.);
+        assert_eq!(stats.compilations[0].total_span_executions, 3);
+
+        let warm = &stats.compilations[1];
+        assert_eq!(warm.span_executions.get("layout"), Some(&1));
+        assert_eq!(warm.span_executions.get("eval"), None);
+        assert_eq!(stats.total_span_executions, 4);
+    }
+
+    #[test]
+    fn test_compile_span_stats_concurrent() {
+        let on = |tid, event: TimingEvent| TimingEvent { tid, ..event };
+        // The compilation on the thread 1 ends after the one on the thread 2
+        // starts.
+        let events = [
+            on(1, event("compile", "B", 1000.)),
+            on(1, event("eval", "B", 1100.)),
+            on(1, event("eval", "E", 1200.)),
+            on(2, event("compile", "B", 1500.)),
+            on(1, event("layout", "B", 1600.)),
+            on(1, event("layout", "E", 1700.)),
+            on(2, event("eval", "B", 1800.)),
+            on(2, event("eval", "E", 1900.)),
+            on(3, event("layout", "B", 1900.)),
+            on(3, event("layout", "E", 1950.)),
+            on(1, event("compile", "E", 2000.)),
+            on(2, event("compile", "E", 4500.)),
+        ];
+
+        let stats = CompileSpanStats::summarize(&events);
+        assert_eq!(stats.compilations.len(), 2);
+        assert_eq!(stats.compilations[0].duration_ms, 1.);
+        assert_eq!(stats.compilations[0].total_span_executions, 2);
+        assert_eq!(stats.compilations[1].duration_ms, 3.);
+        // The span on the worker thread goes to the latest started one.
+        assert_eq!(
+            stats.compilations[1].span_executions.get("layout"),
+            Some(&1)
+        );
+        assert_eq!(stats.compilations[1].total_span_executions, 2);
+    }
+}
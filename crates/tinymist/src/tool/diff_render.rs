@@ -0,0 +1,234 @@
+//! Compares the rendered pages of two compilations of a document.
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tinymist_std::hash::hash128;
+use tinymist_std::typst::TypstPagedDocument;
+use typst::diag::{eco_format, StrResult};
+
+/// The options of comparing two compilations.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DiffRenderOpts {
+    /// Whether to render a visual diff of each changed page.
+    pub visual: bool,
+    /// The PPI (pixels per inch) to render the visual diffs at. Defaults to
+    /// 72.
+    pub ppi: Option<f32>,
+}
+
+/// How a page changed between two compilations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PageChange {
+    /// The page is rendered the same.
+    Unchanged,
+    /// The page is rendered differently.
+    Changed,
+    /// The page only exists in the new compilation.
+    Added,
+    /// The page only exists in the old compilation.
+    Removed,
+}
+
+/// The change of a page between two compilations.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageDiff {
+    /// The page number, starting from 1.
+    pub page: usize,
+    /// How the page changed.
+    pub change: PageChange,
+    /// The new page as a base64-encoded PNG, where the changed regions are
+    /// highlighted in red and the others are faded. It is only rendered for
+    /// the changed and added pages when requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+/// Compares the pages of the old and new compilations. The pages are compared
+/// by their content hashes, so the unchanged pages are cheap to skip.
+pub fn diff_render(
+    old: &TypstPagedDocument,
+    new: &TypstPagedDocument,
+    opts: &DiffRenderOpts,
+) -> StrResult<Vec<PageDiff>> {
+    let ppi = opts.ppi.unwrap_or(72.);
+    if ppi <= 1e-6 {
+        return Err(eco_format!("invalid ppi: {ppi}"));
+    }
+    let options = typst_render::RenderOptions {
+        pixel_per_pt: f64::from(ppi / 72.).into(),
+        ..Default::default()
+    };
+
+    let (old_pages, new_pages) = (old.pages(), new.pages());
+    let count = old_pages.len().max(new_pages.len());
+    (0..count)
+        .map(|idx| {
+            let (old_page, new_page) = (old_pages.get(idx), new_pages.get(idx));
+            let change = match (old_page, new_page) {
+                (Some(old_page), Some(new_page)) if hash128(old_page) == hash128(new_page) => {
+                    PageChange::Unchanged
+                }
+                (Some(_), Some(_)) => PageChange::Changed,
+                (None, _) => PageChange::Added,
+                (_, None) => PageChange::Removed,
+            };
+
+            let image = match change {
+                PageChange::Changed | PageChange::Added if opts.visual => {
+                    Some(visual_diff(old, new, idx, &options)?)
+                }
+                _ => None,
+            };
+
+            Ok(PageDiff {
+                page: idx + 1,
+                change,
+                image,
+            })
+        })
+        .collect()
+}
+
+/// Renders the page at the index of the new compilation, highlighting the
+/// pixels that differ from the page of the old compilation.
+fn visual_diff(
+    old: &TypstPagedDocument,
+    new: &TypstPagedDocument,
+    idx: usize,
+    options: &typst_render::RenderOptions,
+) -> StrResult<String> {
+    let old = old
+        .pages()
+        .get(idx)
+        .map(|page| typst_render::render(page, options));
+    let mut diff = typst_render::render(&new.pages()[idx], options);
+
+    let old = old.as_ref().map(|old| (old.data(), old.width() as usize));
+    let width = diff.width() as usize;
+    highlight_changes(diff.data_mut(), width, old);
+
+    let png = diff
+        .encode_png()
+        .map_err(|err| eco_format!("failed to encode PNG: {err}"))?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(png))
+}
+
+/// Blends the pixels of the new page, which is `width` pixels wide, with red
+/// where they differ from the pixels of the old page, and fades the others
+/// with white. The old page is given with its width, if it exists.
+fn highlight_changes(data: &mut [u8], width: usize, old: Option<(&[u8], usize)>) {
+    let (old_data, old_width) = old.unwrap_or((&[][..], 0));
+    for (idx, pixel) in data.chunks_exact_mut(4).enumerate() {
+        let (x, y) = (idx % width, idx / width);
+        let old_pixel = (x < old_width)
+            .then(|| old_data.get((y * old_width + x) * 4..)?.get(..4))
+            .flatten();
+
+        // The pixels are premultiplied, so they are mixed with opaque colors
+        // channel by channel.
+        let (color, weight): ([u8; 4], u16) = if old_pixel == Some(&*pixel) {
+            ([255, 255, 255, 255], 3)
+        } else {
+            ([255, 0, 0, 255], 1)
+        };
+        for (channel, target) in pixel.iter_mut().zip(color) {
+            *channel = ((u16::from(*channel) + u16::from(target) * weight) / (weight + 1)) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool::tests::compile;
+
+    const THREE_PAGES: &str = "first\n#pagebreak()\nsecond\n#pagebreak()\nthird";
+    const TWO_PAGES: &str = "first\n#pagebreak()\n2nd";
+
+    fn changes(diffs: &[PageDiff]) -> Vec<PageChange> {
+        diffs.iter().map(|diff| diff.change).collect()
+    }
+
+    #[test]
+    fn test_diff_render() {
+        let (three, two) = (compile(THREE_PAGES).doc, compile(TWO_PAGES).doc);
+        let opts = DiffRenderOpts::default();
+
+        let diffs = diff_render(&three, &two, &opts).unwrap();
+        assert_eq!(
+            changes(&diffs),
+            [
+                PageChange::Unchanged,
+                PageChange::Changed,
+                PageChange::Removed
+            ]
+        );
+        assert_eq!(
+            diffs.iter().map(|diff| diff.page).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        assert!(diffs.iter().all(|diff| diff.image.is_none()));
+
+        let diffs = diff_render(&two, &three, &opts).unwrap();
+        assert_eq!(
+            changes(&diffs),
+            [
+                PageChange::Unchanged,
+                PageChange::Changed,
+                PageChange::Added
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_render_visual() {
+        let (three, two) = (compile(THREE_PAGES).doc, compile(TWO_PAGES).doc);
+        let opts = DiffRenderOpts {
+            visual: true,
+            ppi: Some(36.),
+        };
+
+        // Only the changed and added pages are rendered.
+        let diffs = diff_render(&two, &three, &opts).unwrap();
+        let rendered = diffs
+            .iter()
+            .map(|diff| diff.image.is_some())
+            .collect::<Vec<_>>();
+        assert_eq!(rendered, [false, true, true]);
+        for image in diffs.iter().filter_map(|diff| diff.image.as_ref()) {
+            let png = base64::engine::general_purpose::STANDARD
+                .decode(image)
+                .unwrap();
+            assert!(png.starts_with(b"\x89PNG"));
+        }
+
+        let opts = DiffRenderOpts {
+            visual: true,
+            ppi: Some(0.),
+        };
+        assert!(diff_render(&two, &three, &opts).is_err());
+    }
+
+    #[test]
+    fn test_highlight_changes() {
+        let old = [0, 0, 0, 255, 0, 0, 0, 255];
+
+        // The same pixels are faded, and the different ones are tinted red.
+        let mut data = [0, 0, 0, 255, 10, 20, 30, 255];
+        highlight_changes(&mut data, 2, Some((&old, 2)));
+        assert_eq!(data, [191, 191, 191, 255, 132, 10, 15, 255]);
+
+        // The pixels beyond the old page are changed.
+        let mut data = [0, 0, 0, 255, 0, 0, 0, 255];
+        highlight_changes(&mut data, 2, Some((&old[..4], 1)));
+        assert_eq!(data, [191, 191, 191, 255, 127, 0, 0, 255]);
+
+        // All pixels of an added page are changed.
+        let mut data = [0, 0, 0, 0];
+        highlight_changes(&mut data, 1, None);
+        assert_eq!(data, [127, 0, 0, 127]);
+    }
+}
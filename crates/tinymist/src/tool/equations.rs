@@ -0,0 +1,229 @@
+//! Extracts the equations of a compiled document as standalone svgs.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tinymist_std::typst::TypstPagedDocument;
+use typst::foundations::{NativeElement, Smart, StyleChain};
+use typst::introspection::{Introspector, Location, Tag};
+use typst::layout::{Abs, Frame, FrameItem, GroupItem, Point, Size, Transform};
+use typst::math::EquationElem;
+use typst::visualize::Geometry;
+
+/// An equation rendered as a standalone svg.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EquationSvg {
+    /// The label attached to the equation, or the index of the equation if it
+    /// has no label.
+    pub label: String,
+    /// The index of the equation in the document order, starting from 0.
+    pub index: usize,
+    /// Whether the equation is a display (block) equation rather than an
+    /// inline one.
+    pub display: bool,
+    /// The page on which the equation starts, starting from 1.
+    pub page: usize,
+    /// The svg cropped to the bounding box of the equation.
+    pub svg: String,
+}
+
+/// The items of an equation, each placed by the transform of its parent frame
+/// and its position in the frame.
+#[derive(Default)]
+struct EquationItems {
+    page: usize,
+    items: Vec<(Transform, Point, FrameItem)>,
+}
+
+/// Renders the equations of the document in the document order. The items of
+/// an equation are found between the start and end tags of the element in the
+/// laid out frames.
+pub fn document_equations(doc: &TypstPagedDocument) -> Vec<EquationSvg> {
+    let introspector: &dyn Introspector = doc.introspector().as_ref();
+    let elements = introspector.query(&EquationElem::ELEM.select());
+    let locations = elements
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, elem)| Some((elem.location()?, idx)))
+        .collect::<HashMap<_, _>>();
+
+    let mut collected = HashMap::<usize, EquationItems>::new();
+    for (page_idx, page) in doc.pages().iter().enumerate() {
+        let mut walker = FrameWalker {
+            locations: &locations,
+            collected: &mut collected,
+            page: page_idx,
+            active: vec![],
+        };
+        walker.walk(&page.frame, Transform::identity());
+    }
+
+    elements
+        .iter()
+        .enumerate()
+        .filter_map(|(index, elem)| {
+            let equation = elem.to_packed::<EquationElem>()?;
+            let items = collected.remove(&index)?;
+            let frame = crop_items(&items.items)?;
+
+            let mut page = doc.pages()[items.page].clone();
+            page.frame = frame;
+            page.fill = Smart::Custom(None);
+            let svg = typst_svg::svg(&page, &typst_svg::SvgOptions::default());
+
+            Some(EquationSvg {
+                label: elem
+                    .label()
+                    .map_or_else(|| index.to_string(), |label| label.resolve().to_string()),
+                index,
+                display: equation.block.get(StyleChain::default()),
+                page: items.page + 1,
+                svg,
+            })
+        })
+        .collect()
+}
+
+/// Walks the frames of a page and collects the items of the equations.
+struct FrameWalker<'a> {
+    locations: &'a HashMap<Location, usize>,
+    collected: &'a mut HashMap<usize, EquationItems>,
+    page: usize,
+    /// The indices of the equations whose start tags have been visited but
+    /// not their end tags.
+    active: Vec<usize>,
+}
+
+impl FrameWalker<'_> {
+    fn walk(&mut self, frame: &Frame, ts: Transform) {
+        for (pos, item) in frame.items() {
+            match item {
+                FrameItem::Tag(Tag::Start(elem, ..)) => {
+                    let Some(&idx) = elem.location().and_then(|loc| self.locations.get(&loc))
+                    else {
+                        continue;
+                    };
+                    self.active.push(idx);
+                    self.collected.entry(idx).or_default().page = self.page;
+                }
+                FrameItem::Tag(Tag::End(loc, ..)) => {
+                    if let Some(&idx) = self.locations.get(loc) {
+                        self.active.retain(|&active| active != idx);
+                    }
+                }
+                FrameItem::Tag(..) => {}
+                FrameItem::Group(group) => {
+                    let group_ts = ts
+                        .pre_concat(Transform::translate(pos.x, pos.y))
+                        .pre_concat(group.transform);
+                    if self.active.is_empty() {
+                        self.walk(&group.frame, group_ts);
+                    } else {
+                        // The group belongs to the active equations as a
+                        // whole, but may contain other equations.
+                        self.push(ts, *pos, item);
+                        let active = std::mem::take(&mut self.active);
+                        self.walk(&group.frame, group_ts);
+                        self.active = active;
+                    }
+                }
+                _ => self.push(ts, *pos, item),
+            }
+        }
+    }
+
+    fn push(&mut self, ts: Transform, pos: Point, item: &FrameItem) {
+        for idx in &self.active {
+            let items = &mut self.collected.entry(*idx).or_default().items;
+            items.push((ts, pos, item.clone()));
+        }
+    }
+}
+
+/// Places the items into a frame cropped to their bounding box.
+fn crop_items(items: &[(Transform, Point, FrameItem)]) -> Option<Frame> {
+    let mut min = Point::splat(Abs::inf());
+    let mut max = Point::splat(-Abs::inf());
+    for (ts, pos, item) in items {
+        let Some((top_left, bottom_right)) = item_bounds(item) else {
+            continue;
+        };
+        let corners = [
+            top_left,
+            Point::new(bottom_right.x, top_left.y),
+            Point::new(top_left.x, bottom_right.y),
+            bottom_right,
+        ];
+        for corner in corners {
+            let corner = (*pos + corner).transform(*ts);
+            min = Point::new(min.x.min(corner.x), min.y.min(corner.y));
+            max = Point::new(max.x.max(corner.x), max.y.max(corner.y));
+        }
+    }
+    if min.x > max.x || min.y > max.y {
+        return None;
+    }
+
+    let mut frame = Frame::soft(Size::new(max.x - min.x, max.y - min.y));
+    let origin = Transform::translate(-min.x, -min.y);
+    for (ts, pos, item) in items {
+        let mut inner = Frame::soft(Size::zero());
+        inner.push(*pos, item.clone());
+        let mut group = GroupItem::new(inner);
+        group.transform = origin.pre_concat(*ts);
+        frame.push(Point::zero(), FrameItem::Group(group));
+    }
+    Some(frame)
+}
+
+/// Gets the top-left and bottom-right corners of an item relative to its
+/// position, or `None` if the item isn't visible.
+fn item_bounds(item: &FrameItem) -> Option<(Point, Point)> {
+    match item {
+        FrameItem::Text(text) => {
+            let metrics = text.font.font().metrics();
+            let ascender = metrics.ascender.at(text.size);
+            let descender = metrics.descender.at(text.size);
+            Some((
+                Point::with_y(-ascender),
+                Point::new(text.width(), -descender),
+            ))
+        }
+        FrameItem::Group(group) => {
+            let size = group.frame.size();
+            let corners = [
+                Point::zero(),
+                Point::with_x(size.x),
+                Point::with_y(size.y),
+                size.to_point(),
+            ]
+            .map(|corner| corner.transform(group.transform));
+            let min_x = corners.iter().map(|p| p.x).fold(Abs::inf(), Abs::min);
+            let min_y = corners.iter().map(|p| p.y).fold(Abs::inf(), Abs::min);
+            let max_x = corners.iter().map(|p| p.x).fold(-Abs::inf(), Abs::max);
+            let max_y = corners.iter().map(|p| p.y).fold(-Abs::inf(), Abs::max);
+            Some((Point::new(min_x, min_y), Point::new(max_x, max_y)))
+        }
+        FrameItem::Shape(shape, _) => {
+            let (top_left, bottom_right) = match &shape.geometry {
+                Geometry::Line(end) => (
+                    Point::new(end.x.min(Abs::zero()), end.y.min(Abs::zero())),
+                    Point::new(end.x.max(Abs::zero()), end.y.max(Abs::zero())),
+                ),
+                geometry => (Point::zero(), geometry.bbox_size().to_point()),
+            };
+            // The stroke is centered on the outline.
+            let half = shape
+                .stroke
+                .as_ref()
+                .map_or(Abs::zero(), |stroke| stroke.thickness / 2.0);
+            Some((
+                top_left - Point::splat(half),
+                bottom_right + Point::splat(half),
+            ))
+        }
+        FrameItem::Image(_, size, _) => Some((Point::zero(), size.to_point())),
+        FrameItem::Link(..) | FrameItem::Tag(..) => None,
+    }
+}
@@ -0,0 +1,205 @@
+//! Extracts the sections of a document on some pages into a new document.
+
+use std::ops::Range;
+
+use serde::Serialize;
+use tinymist_std::typst::TypstPagedDocument;
+use tinymist_task::{exported_page_ranges, Pages};
+use typst::diag::{bail, StrResult};
+use typst::foundations::NativeElement;
+use typst::introspection::Introspector;
+use typst::layout::PageRanges;
+use typst::model::HeadingElem;
+use typst::syntax::{LinkedNode, Source, SyntaxKind};
+
+use super::outline::heading_page;
+use super::split::section_pages;
+
+/// A section of the source extracted into the new document.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractedSection {
+    /// The plain text of the heading starting the section, which is `None`
+    /// for the content before the first heading.
+    pub title: Option<String>,
+    /// The page on which the section starts, starting from 1.
+    pub start_page: usize,
+    /// The page on which the section ends, starting from 1.
+    pub end_page: usize,
+}
+
+/// The new document extracted from the pages.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractedPages {
+    /// The source of the new document.
+    pub text: String,
+    /// The extracted sections in the source order.
+    pub sections: Vec<ExtractedSection>,
+}
+
+/// A section of the source, which starts at a top-level heading or the start
+/// of the source.
+struct Section {
+    info: ExtractedSection,
+    range: Range<usize>,
+}
+
+/// Extracts the sections of the main source rendered on the pages into a new
+/// document.
+///
+/// Since the content cannot be mapped to the pages precisely, the source is
+/// split at the top-level headings, and every section sharing a page with the
+/// ranges is extracted as a whole. The set, show, and let rules and the imports
+/// before the first heading are kept even if the content there is not
+/// extracted, while the rules in the other sections are dropped with them.
+pub fn extract_pages(
+    doc: &TypstPagedDocument,
+    source: &Source,
+    ranges: &[Pages],
+) -> StrResult<ExtractedPages> {
+    if ranges.is_empty() {
+        bail!("no pages to extract");
+    }
+    let ranges = exported_page_ranges(ranges);
+
+    let sections = split_sections(doc, source);
+    let selected = |section: &Section| is_selected(&ranges, &section.info);
+
+    let mut text = String::new();
+    let mut extracted = vec![];
+    for (idx, section) in sections.iter().enumerate() {
+        if selected(section) {
+            push_line(&mut text, &source.text()[section.range.clone()]);
+            extracted.push(section.info.clone());
+        } else if idx == 0 {
+            push_line(&mut text, &setup_rules(source, section.range.clone()));
+        }
+    }
+
+    if extracted.is_empty() {
+        bail!("no content is on the pages");
+    }
+
+    Ok(ExtractedPages {
+        text,
+        sections: extracted,
+    })
+}
+
+/// Splits the source at the top-level headings, with the pages on which the
+/// sections are rendered.
+fn split_sections(doc: &TypstPagedDocument, source: &Source) -> Vec<Section> {
+    let introspector: &dyn Introspector = doc.introspector().as_ref();
+    let root = LinkedNode::new(source.root());
+
+    let mut starts = vec![];
+    for elem in introspector.query(&HeadingElem::ELEM.select()).iter() {
+        // Only the headings written at the top level of the main source split
+        // the source, i.e. not those in blocks, functions, or other files.
+        if elem.span().id() != Some(source.id()) {
+            continue;
+        }
+        let Some(node) = root.find(elem.span()) else {
+            continue;
+        };
+        if node.kind() != SyntaxKind::Heading
+            || node.parent().is_none_or(|parent| parent.parent().is_some())
+        {
+            continue;
+        }
+        let Some(heading) = elem.to_packed::<HeadingElem>() else {
+            continue;
+        };
+        let page = heading_page(introspector, elem);
+        let title = heading.body.plain_text().trim().to_owned();
+        starts.push((node.offset(), page, Some(title)));
+    }
+    starts.sort_by_key(|(offset, ..)| *offset);
+    starts.dedup_by_key(|(offset, ..)| *offset);
+    starts.insert(0, (0, 1, None));
+
+    // A section may end in the middle of the page where the next one starts.
+    let pages = starts.iter().map(|(_, page, _)| *page).collect::<Vec<_>>();
+    let spans = section_pages(&pages, doc.pages().len(), true);
+    let ends = starts
+        .iter()
+        .skip(1)
+        .map(|(offset, ..)| *offset)
+        .chain(std::iter::once(source.text().len()))
+        .collect::<Vec<_>>();
+
+    let mut sections: Vec<Section> = vec![];
+    for (((offset, _, title), (start_page, end_page)), end) in
+        starts.into_iter().zip(spans).zip(ends)
+    {
+        if offset == end {
+            continue;
+        }
+        sections.push(Section {
+            info: ExtractedSection {
+                title,
+                start_page,
+                end_page,
+            },
+            range: offset..end,
+        });
+    }
+
+    sections
+}
+
+/// Whether the section shares a page with the ranges.
+fn is_selected(ranges: &PageRanges, section: &ExtractedSection) -> bool {
+    (section.start_page..=section.end_page).any(|page| ranges.includes_page_index(page - 1))
+}
+
+/// Collects the set, show, and let rules and the imports in the range, which
+/// set up the document rather than adding content to it.
+fn setup_rules(source: &Source, range: Range<usize>) -> String {
+    let mut rules = String::new();
+    for child in LinkedNode::new(source.root()).children() {
+        if !range.contains(&child.offset()) {
+            continue;
+        }
+        if matches!(
+            child.kind(),
+            SyntaxKind::SetRule
+                | SyntaxKind::ShowRule
+                | SyntaxKind::LetBinding
+                | SyntaxKind::ModuleImport
+        ) {
+            rules.push('#');
+            push_line(&mut rules, child.get().clone().into_text().as_str());
+        }
+    }
+    rules
+}
+
+/// Pushes the text and ensures that the result ends with a newline.
+fn push_line(text: &mut String, line: &str) {
+    text.push_str(line);
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_selected() {
+        let section = |start_page, end_page| ExtractedSection {
+            title: None,
+            start_page,
+            end_page,
+        };
+        let ranges = exported_page_ranges(&["3-4".parse().unwrap()]);
+
+        assert!(!is_selected(&ranges, &section(1, 2)));
+        assert!(is_selected(&ranges, &section(2, 3)));
+        assert!(is_selected(&ranges, &section(4, 6)));
+        assert!(!is_selected(&ranges, &section(5, 5)));
+    }
+}
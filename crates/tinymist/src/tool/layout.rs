@@ -0,0 +1,298 @@
+//! Serializes the frame tree of a compiled document for layout introspection.
+
+use std::ops::Range;
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tinymist_project::LspWorld;
+use tinymist_std::typst::TypstPagedDocument;
+use tinymist_task::{exported_page_ranges, Pages};
+use typst::layout::{Abs, Frame, GroupItem, Point, Size, Transform};
+use typst::syntax::Span;
+use typst::text::TextItem;
+use typst::visualize::Shape;
+
+use super::span_map::{bounding_box, source_location, walk_frame, FrameVisitor};
+
+/// The frame tree of a page.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageLayout {
+    /// The page number, starting from 1.
+    pub page: usize,
+    /// The width of the page (in pt).
+    pub width: f64,
+    /// The height of the page (in pt).
+    pub height: f64,
+    /// The items of the page in the painting order.
+    pub items: Vec<LayoutItem>,
+}
+
+/// The kind of an item in a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LayoutItemKind {
+    /// A group of items, which may be transformed and clipped.
+    Group,
+    /// A run of shaped text.
+    Text,
+    /// A geometric shape.
+    Shape,
+    /// An image.
+    Image,
+    /// A link area.
+    Link,
+}
+
+/// An item in a frame with the box bounding it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutItem {
+    /// The kind of the item.
+    #[serde(rename = "type")]
+    pub kind: LayoutItemKind,
+    /// The left of the box, relative to the top-left of the page (in pt).
+    pub x: f64,
+    /// The top of the box, relative to the top-left of the page (in pt).
+    pub y: f64,
+    /// The width of the box (in pt).
+    pub width: f64,
+    /// The height of the box (in pt).
+    pub height: f64,
+    /// The source that the item originates from, if it is attached to the
+    /// source.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<LayoutSource>,
+    /// The text of a text item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<LayoutText>,
+    /// Whether the children of a group are clipped to its box.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub clip: bool,
+    /// The items of a group in the painting order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<LayoutItem>,
+}
+
+/// The source that an item originates from.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutSource {
+    /// The path of the source file.
+    pub path: PathBuf,
+    /// The byte range in the source file.
+    pub range: Range<usize>,
+}
+
+/// The text of a text item.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutText {
+    /// The text.
+    pub text: String,
+    /// The family of the font.
+    pub font: String,
+    /// The font size (in pt).
+    pub size: f64,
+}
+
+/// Collects the frame trees of the selected pages, or of all pages if `pages`
+/// is `None`. The introspection tags are skipped.
+pub fn document_layout(
+    world: &LspWorld,
+    doc: &TypstPagedDocument,
+    pages: Option<&[Pages]>,
+) -> Vec<PageLayout> {
+    let selected = pages.map(exported_page_ranges);
+    doc.pages()
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| {
+            selected
+                .as_ref()
+                .is_none_or(|ranges| ranges.includes_page_index(*idx))
+        })
+        .map(|(idx, page)| {
+            let size = page.frame.size();
+            PageLayout {
+                page: idx + 1,
+                width: size.x.to_pt(),
+                height: size.y.to_pt(),
+                items: frame_items(world, &page.frame, Transform::identity()),
+            }
+        })
+        .collect()
+}
+
+/// Converts the items of a frame, whose origin is transformed by `ts`.
+fn frame_items(world: &LspWorld, frame: &Frame, ts: Transform) -> Vec<LayoutItem> {
+    let mut walker = LayoutWalker {
+        world,
+        items: vec![],
+        parents: vec![],
+    };
+    walk_frame(&mut walker, frame, ts);
+    walker.items
+}
+
+/// Builds the items of the frames, keeping the items of the enclosing groups
+/// while walking a group.
+struct LayoutWalker<'a> {
+    world: &'a LspWorld,
+    items: Vec<LayoutItem>,
+    parents: Vec<Vec<LayoutItem>>,
+}
+
+impl FrameVisitor for LayoutWalker<'_> {
+    fn enter_group(&mut self, _group: &GroupItem, _ts: Transform) {
+        self.parents.push(std::mem::take(&mut self.items));
+    }
+
+    fn leave_group(&mut self, group: &GroupItem, ts: Transform) {
+        let children = std::mem::replace(&mut self.items, self.parents.pop().unwrap_or_default());
+        let size = group.frame.size().to_point();
+        self.items.push(LayoutItem {
+            clip: group.clip.is_some(),
+            children,
+            ..layout_item(LayoutItemKind::Group, ts, Point::zero(), size)
+        });
+    }
+
+    fn text(&mut self, text: &TextItem, ts: Transform) {
+        let item = text_item(self.world, text, ts);
+        self.items.push(item);
+    }
+
+    fn shape(&mut self, shape: &Shape, span: Span, ts: Transform) {
+        let size = shape.geometry.bbox_size().to_point();
+        self.items.push(LayoutItem {
+            source: layout_source(self.world, span, None),
+            ..layout_item(LayoutItemKind::Shape, ts, Point::zero(), size)
+        });
+    }
+
+    fn image(&mut self, size: Size, span: Span, ts: Transform) {
+        self.items.push(LayoutItem {
+            source: layout_source(self.world, span, None),
+            ..layout_item(LayoutItemKind::Image, ts, Point::zero(), size.to_point())
+        });
+    }
+
+    fn link(&mut self, size: Size, ts: Transform) {
+        let item = layout_item(LayoutItemKind::Link, ts, Point::zero(), size.to_point());
+        self.items.push(item);
+    }
+}
+
+/// Converts a text item, which spans from the ascender to the descender of
+/// the font. Its source is the span of the first glyph, narrowed to the
+/// glyphs originating from the same span.
+fn text_item(world: &LspWorld, text: &TextItem, ts: Transform) -> LayoutItem {
+    let metrics = text.font.font().metrics();
+    let top_left = Point::new(Abs::zero(), -metrics.ascender.at(text.size));
+    let bottom_right = Point::new(text.width(), -metrics.descender.at(text.size));
+
+    let source = text.glyphs.first().and_then(|first| {
+        let span = first.span.0;
+        let offsets = text
+            .glyphs
+            .iter()
+            .filter(|glyph| glyph.span.0 == span)
+            .map(|glyph| {
+                let offset = usize::from(glyph.span.1);
+                offset..offset + glyph.range().len()
+            })
+            .reduce(|a, b| a.start.min(b.start)..a.end.max(b.end));
+        layout_source(world, span, offsets)
+    });
+
+    LayoutItem {
+        source,
+        text: Some(LayoutText {
+            text: text.text.to_string(),
+            font: text.font.font().info().family.clone(),
+            size: text.size.to_pt(),
+        }),
+        ..layout_item(LayoutItemKind::Text, ts, top_left, bottom_right)
+    }
+}
+
+/// Creates an item bounding the transformed box.
+fn layout_item(
+    kind: LayoutItemKind,
+    ts: Transform,
+    top_left: Point,
+    bottom_right: Point,
+) -> LayoutItem {
+    let (min, max) = bounding_box(ts, top_left, bottom_right);
+    LayoutItem {
+        kind,
+        x: min.x.to_pt(),
+        y: min.y.to_pt(),
+        width: (max.x - min.x).to_pt(),
+        height: (max.y - min.y).to_pt(),
+        source: None,
+        text: None,
+        clip: false,
+        children: vec![],
+    }
+}
+
+fn layout_source(
+    world: &LspWorld,
+    span: Span,
+    offsets: Option<Range<usize>>,
+) -> Option<LayoutSource> {
+    let (path, range) = source_location(world, span, offsets)?;
+    Some(LayoutSource { path, range })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool::tests::compile;
+
+    fn flatten(items: &[LayoutItem]) -> Vec<&LayoutItem> {
+        items
+            .iter()
+            .flat_map(|item| std::iter::once(item).chain(flatten(&item.children)))
+            .collect()
+    }
+
+    #[test]
+    fn test_document_layout() {
+        let source = "#rotate(90deg, rect(width: 20pt, height: 10pt))\n\nHello";
+        let test = compile(source);
+
+        let pages = document_layout(&test.world, &test.doc, None);
+        assert_eq!(pages.len(), 1);
+        let items = flatten(&pages[0].items);
+
+        let text = items
+            .iter()
+            .find(|item| item.kind == LayoutItemKind::Text)
+            .expect("a text item");
+        assert_eq!(text.text.as_ref().unwrap().text, "Hello");
+        let range = text.source.as_ref().unwrap().range.clone();
+        assert_eq!(&source[range], "Hello");
+
+        // The shape is nested in the rotated group, which transforms its box.
+        let group = pages[0]
+            .items
+            .iter()
+            .find(|item| {
+                flatten(&item.children)
+                    .iter()
+                    .any(|c| c.kind == LayoutItemKind::Shape)
+            })
+            .expect("a group of the shape");
+        let shape = flatten(&group.children)
+            .into_iter()
+            .find(|item| item.kind == LayoutItemKind::Shape)
+            .unwrap();
+        assert!((shape.width - 10.0).abs() < 1e-6);
+        assert!((shape.height - 20.0).abs() < 1e-6);
+        let range = shape.source.as_ref().unwrap().range.clone();
+        assert!(source[range].starts_with("rect("));
+    }
+}
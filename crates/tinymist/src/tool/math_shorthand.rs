@@ -0,0 +1,98 @@
+//! Resolves the symbols that math shorthands become, e.g. `→` for `->`.
+
+use serde::Serialize;
+use typst::foundations::Value;
+use typst::syntax::ast::MathShorthand;
+use typst::Library;
+
+/// The symbol that a math shorthand becomes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedShorthand {
+    /// The shorthand, e.g. `->`.
+    pub shorthand: String,
+    /// The resulting symbol, e.g. `→`.
+    pub symbol: String,
+    /// The codepoints of the symbol, e.g. `U+2192`.
+    pub codepoint: String,
+    /// The name of the symbol in the `sym` module, e.g. `arrow.r`.
+    pub name: Option<String>,
+    /// The equivalent expression, e.g. `sym.arrow.r`.
+    pub sym: Option<String>,
+}
+
+/// Resolves the symbol that the math shorthand becomes, using the shorthand
+/// table of the parser. Returns `None` if it is not a math shorthand.
+pub fn resolve_math_shorthand(library: &Library, input: &str) -> Option<ResolvedShorthand> {
+    let input = input.trim();
+    let (shorthand, symbol) = MathShorthand::LIST.iter().find(|(s, _)| *s == input)?;
+    let symbol = symbol.to_string();
+
+    let name = symbol_name(library, &symbol);
+    Some(ResolvedShorthand {
+        shorthand: shorthand.to_string(),
+        codepoint: codepoints(&symbol),
+        sym: name.as_ref().map(|name| format!("sym.{name}")),
+        name,
+        symbol,
+    })
+}
+
+/// Finds the name of the symbol in the `sym` module, preferring the shortest
+/// one if there are several, e.g. `arrow.r` over `arrow.r.long`.
+fn symbol_name(library: &Library, symbol: &str) -> Option<String> {
+    let std = library.std.read().scope()?;
+    let sym = std.get("sym")?.read().scope()?;
+
+    let mut names = vec![];
+    for (sym_name, binding) in sym.iter() {
+        let Value::Symbol(sym) = binding.read() else {
+            continue;
+        };
+        for (modifier_name, ch, _) in sym.variants() {
+            if ch.to_string() != symbol {
+                continue;
+            }
+            names.push(if modifier_name.is_empty() {
+                sym_name.to_string()
+            } else {
+                format!("{sym_name}.{}", modifier_name.as_str())
+            });
+        }
+    }
+
+    names
+        .into_iter()
+        .min_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)))
+}
+
+/// Formats the codepoints of the symbol, e.g. `U+2192`.
+fn codepoints(symbol: &str) -> String {
+    let codepoints = symbol.chars().map(|c| format!("U+{:04X}", c as u32));
+    codepoints.collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use typst::LibraryExt;
+
+    use super::*;
+
+    #[test]
+    fn test_resolve_math_shorthand() {
+        let library = Library::builder().build();
+
+        let arrow = resolve_math_shorthand(&library, "->").unwrap();
+        assert_eq!(arrow.symbol, "→");
+        assert_eq!(arrow.codepoint, "U+2192");
+        assert_eq!(arrow.name.as_deref(), Some("arrow.r"));
+        assert_eq!(arrow.sym.as_deref(), Some("sym.arrow.r"));
+
+        let neq = resolve_math_shorthand(&library, " != ").unwrap();
+        assert_eq!(neq.shorthand, "!=");
+        assert_eq!(neq.symbol, "≠");
+
+        assert_eq!(resolve_math_shorthand(&library, "=>>"), None);
+        assert_eq!(resolve_math_shorthand(&library, "alpha"), None);
+    }
+}
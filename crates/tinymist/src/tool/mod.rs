@@ -1,9 +1,70 @@
 //! All the language tools provided by the `tinymist` crate.
 
 pub mod ast;
+pub mod diff_render;
+pub mod equations;
+pub mod extract_pages;
+pub mod layout;
+pub mod math_shorthand;
+pub mod outline;
 pub mod package;
 pub mod project;
+pub mod sarif;
+pub mod span_map;
+pub mod split;
 pub mod word_count;
+pub mod workspace;
 
 #[cfg(feature = "preview")]
 pub mod preview;
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use clap::Parser;
+    use tinymist_project::LspWorld;
+    use tinymist_std::typst::TypstPagedDocument;
+
+    use crate::project::{CompileOnceArgs, WorldProvider};
+
+    /// A single-file document compiled for the tests of the tools.
+    pub(crate) struct TestDoc {
+        /// The world that the document is compiled in.
+        pub world: LspWorld,
+        /// The compiled document.
+        pub doc: Arc<TypstPagedDocument>,
+        /// The path of the source file.
+        pub path: PathBuf,
+        _root: tempfile::TempDir,
+    }
+
+    /// Compiles a single-file document for the tests of the tools.
+    pub(crate) fn compile(source: &str) -> TestDoc {
+        let root = tempfile::tempdir().expect("failed to create test workspace");
+        let path = root.path().join("main.typ");
+        std::fs::write(&path, source).expect("failed to write test source");
+
+        let args = CompileOnceArgs::parse_from([
+            "tinymist".to_owned(),
+            path.to_string_lossy().into_owned(),
+            "--root".to_owned(),
+            root.path().to_string_lossy().into_owned(),
+        ]);
+        let world = args
+            .resolve()
+            .expect("failed to resolve lsp universe")
+            .snapshot();
+        let doc = typst::compile::<TypstPagedDocument>(&world)
+            .output
+            .expect("failed to compile");
+
+        TestDoc {
+            world,
+            doc: Arc::new(doc),
+            path,
+            _root: root,
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Extracts the outline of a compiled document.
+
+use serde::Serialize;
+use tinymist_std::typst::TypstPagedDocument;
+use typst::foundations::{Content, NativeElement, Packed, StyleChain};
+use typst::introspection::Introspector;
+use typst::model::HeadingElem;
+
+/// A heading in the document outline.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlineHeading {
+    /// The level of the heading, starting from 1.
+    pub level: usize,
+    /// The plain text of the heading.
+    pub text: String,
+    /// The label attached to the heading, if any.
+    pub label: Option<String>,
+    /// The page on which the heading starts, starting from 1.
+    pub page: usize,
+    /// Whether the heading is numbered.
+    pub numbered: bool,
+}
+
+/// A node in the bookmark tree, i.e. the outline embedded in the exported PDF.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookmarkNode {
+    /// The plain text of the heading.
+    pub title: String,
+    /// The level of the heading, starting from 1.
+    pub level: usize,
+    /// The page on which the heading starts, starting from 1.
+    pub page: usize,
+    /// The nested bookmarks.
+    pub children: Vec<BookmarkNode>,
+}
+
+/// Collects the headings of the document in the document order.
+pub fn document_outline(doc: &TypstPagedDocument) -> Vec<OutlineHeading> {
+    let introspector: &dyn Introspector = doc.introspector().as_ref();
+    let elements = introspector.query(&HeadingElem::ELEM.select());
+
+    elements
+        .iter()
+        .filter_map(|elem| {
+            let heading = elem.to_packed::<HeadingElem>()?;
+
+            Some(OutlineHeading {
+                level: heading.resolve_level(StyleChain::default()).get(),
+                text: heading.body.plain_text().trim().to_owned(),
+                label: elem.label().map(|label| label.resolve().to_string()),
+                page: heading_page(introspector, elem),
+                numbered: heading.numbering.get_ref(StyleChain::default()).is_some(),
+            })
+        })
+        .collect()
+}
+
+/// Builds the bookmark tree of the document in the same way as the PDF export.
+///
+/// Headings that are not bookmarked are skipped, and their descendants are
+/// placed at most as deep as the topmost skipped ancestor.
+pub fn bookmark_tree(doc: &TypstPagedDocument) -> Vec<BookmarkNode> {
+    let introspector: &dyn Introspector = doc.introspector().as_ref();
+    let elements = introspector.query(&HeadingElem::ELEM.select());
+
+    let mut tree: Vec<BookmarkNode> = vec![];
+    let mut last_skipped_level = None;
+    for elem in elements.iter() {
+        let Some(heading) = elem.to_packed::<HeadingElem>() else {
+            continue;
+        };
+        let level = heading.resolve_level(StyleChain::default()).get();
+
+        if !is_bookmarked(heading) {
+            // Only the topmost skipped heading limits the depth of the next
+            // bookmarked heading.
+            if last_skipped_level.is_none_or(|l| level < l) {
+                last_skipped_level = Some(level);
+            }
+            continue;
+        }
+
+        let mut children = &mut tree;
+        while children.last().is_some_and(|last| {
+            last_skipped_level.is_none_or(|l| last.level < l) && last.level < level
+        }) {
+            children = &mut children.last_mut().unwrap().children;
+        }
+
+        last_skipped_level = None;
+        children.push(BookmarkNode {
+            title: heading.body.plain_text().trim().to_owned(),
+            level,
+            page: heading_page(introspector, elem),
+            children: vec![],
+        });
+    }
+
+    tree
+}
+
+/// Gets the page on which the heading starts, starting from 1.
+pub(crate) fn heading_page(introspector: &dyn Introspector, elem: &Content) -> usize {
+    elem.location()
+        .and_then(|loc| introspector.position(loc))
+        .map_or(1, |pos| pos.as_paged_or_default().page.get())
+}
+
+/// Whether the heading is added to the PDF outline. The `auto` value falls
+/// back to the value of `outlined`.
+fn is_bookmarked(heading: &Packed<HeadingElem>) -> bool {
+    heading
+        .bookmarked
+        .get(StyleChain::default())
+        .unwrap_or_else(|| heading.outlined.get(StyleChain::default()))
+}
@@ -1,12 +1,13 @@
 //! Actions for initializing a new project from a template.
 
 use std::io::Write;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 use reflexo_typst::{Bytes, ImmutPath, TypstFileId};
+use serde::{Deserialize, Serialize};
 use tinymist_query::package::get_manifest;
-use typst::diag::{bail, eco_format, FileError, FileResult, StrResult};
-use typst::syntax::package::{PackageSpec, TemplateInfo};
+use typst::diag::{bail, eco_format, EcoString, FileError, FileResult, StrResult};
+use typst::syntax::package::{PackageManifest, PackageSpec, TemplateInfo};
 use typst::syntax::{RootedPath, VirtualPath, VirtualRoot};
 use typst::World;
 use typst_shim::syntax::{RootedPathExt, VirtualPathExt};
@@ -18,6 +19,37 @@ use crate::project::LspWorld;
 pub enum TemplateSource {
     /// A package from the registry.
     Package(PackageSpec),
+    /// A git repository containing a template package, e.g.
+    /// `git+https://github.com/user/template`. The `git+` prefix is stripped.
+    Git(EcoString),
+    /// A local directory containing a template package.
+    Dir(PathBuf),
+}
+
+impl TemplateSource {
+    /// Parses a non-package template source, i.e. a git URL starting with
+    /// `git+` or an existing local directory.
+    pub fn from_non_package(source: &str) -> Option<Self> {
+        if let Some(url) = source.strip_prefix("git+") {
+            return Some(Self::Git(url.into()));
+        }
+
+        let path = Path::new(source);
+        path.is_dir().then(|| Self::Dir(path.to_owned()))
+    }
+}
+
+/// How to initialize a project into an existing, non-empty directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InitMode {
+    /// Refuses to initialize into a non-empty directory.
+    #[default]
+    Fresh,
+    /// Merges the template into the directory, skipping colliding files.
+    Merge,
+    /// Merges the template into the directory, replacing colliding files.
+    Overwrite,
 }
 
 /// The task to initialize a new project.
@@ -26,11 +58,29 @@ pub struct InitTask {
     pub tmpl: TemplateSource,
     /// The directory at which to create the project.
     pub dir: Option<ImmutPath>,
+    /// How to handle an existing, non-empty directory.
+    pub mode: InitMode,
+}
+
+/// The result of initializing a project.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitResult {
+    /// The path to the entry file, relative to the project directory.
+    pub entry_path: PathBuf,
+    /// The files written into the project directory.
+    pub written: Vec<PathBuf>,
+    /// The files skipped because they already exist.
+    pub skipped: Vec<PathBuf>,
 }
 
 /// Get content of the entry file of a template.
 pub fn get_entry(world: &LspWorld, tmpl: TemplateSource) -> StrResult<Bytes> {
-    let TemplateSource::Package(spec) = tmpl;
+    let spec = match tmpl {
+        TemplateSource::Package(spec) => spec,
+        TemplateSource::Git(url) => return with_git_checkout(&url, get_dir_entry),
+        TemplateSource::Dir(dir) => return get_dir_entry(&dir),
+    };
 
     let toml_id = TypstFileId::new(RootedPath::new(
         VirtualRoot::Package(spec.clone()),
@@ -54,8 +104,14 @@ pub fn get_entry(world: &LspWorld, tmpl: TemplateSource) -> StrResult<Bytes> {
 }
 
 /// Execute an initialization command.
-pub fn init(world: &LspWorld, task: InitTask) -> StrResult<PathBuf> {
-    let TemplateSource::Package(spec) = task.tmpl;
+pub fn init(world: &LspWorld, task: InitTask) -> StrResult<InitResult> {
+    let spec = match task.tmpl {
+        TemplateSource::Package(spec) => spec,
+        TemplateSource::Git(url) => {
+            return with_git_checkout(&url, |dir| init_dir(dir, task.dir, task.mode));
+        }
+        TemplateSource::Dir(dir) => return init_dir(&dir, task.dir, task.mode),
+    };
     let project_dir = task
         .dir
         .unwrap_or_else(|| Path::new(spec.name.as_str()).into());
@@ -78,37 +134,34 @@ pub fn init(world: &LspWorld, task: InitTask) -> StrResult<PathBuf> {
     // Path::new(command.dir.as_deref().unwrap_or(&manifest.package.name));
 
     // Set up the project.
-    scaffold_project(world, template, toml_id, &project_dir)?;
+    let mut result = InitResult {
+        entry_path: entry_point,
+        ..InitResult::default()
+    };
+    scaffold_project(
+        world,
+        template,
+        toml_id,
+        &project_dir,
+        task.mode,
+        &mut result,
+    )?;
 
-    Ok(entry_point)
+    Ok(result)
 }
 
-/// Creates the project directory with the template's contents and returns the
-/// path at which it was created.
+/// Creates the project directory with the template's contents. If the directory
+/// is not empty, colliding files are skipped or replaced according to `mode`,
+/// and recorded in `result`.
 fn scaffold_project(
     world: &LspWorld,
     tmpl_info: &TemplateInfo,
     toml_id: TypstFileId,
     project_dir: &Path,
+    mode: InitMode,
+    result: &mut InitResult,
 ) -> StrResult<()> {
-    if project_dir.exists() {
-        if !project_dir.is_dir() {
-            bail!(
-                "project directory already exists as a file (at {})",
-                project_dir.display()
-            );
-        }
-        // empty_dir(project_dir)?;
-        let mut entries = std::fs::read_dir(project_dir)
-            .map_err(|e| FileError::from_io(e, project_dir))?
-            .peekable();
-        if entries.peek().is_some() {
-            bail!(
-                "project directory already exists and is not empty (at {})",
-                project_dir.display()
-            );
-        }
-    }
+    check_project_dir(project_dir, mode)?;
 
     let package_root = world.path_for_id(toml_id)?.as_path().to_owned();
     let package_root = package_root
@@ -117,7 +170,8 @@ fn scaffold_project(
 
     let template_dir = TypstFileId::new(RootedPath::new(
         toml_id.root().clone(),
-        VirtualPath::new(tmpl_info.path.as_str()).expect("valid template path"),
+        VirtualPath::new(tmpl_info.path.as_str())
+            .map_err(|err| eco_format!("invalid template path: {err}"))?,
     ));
     // todo: template in memory
     let real_template_dir = world.path_for_id(template_dir)?.to_err()?;
@@ -141,18 +195,213 @@ fn scaffold_project(
                 "failed to strip prefix, path: {file_path:?}, root: {template_dir:?}: {err}"
             )
         })?;
-        let file_path = project_dir.join(relative_path);
-        let file_dir = file_path.parent().unwrap();
-        std::fs::create_dir_all(file_dir).map_err(|e| FileError::from_io(e, file_dir))?;
-        let mut file =
-            std::fs::File::create(&file_path).map_err(|e| FileError::from_io(e, &file_path))?;
-        file.write_all(f.as_slice())
-            .map_err(|e| FileError::from_io(e, &file_path))?
+        write_project_file(project_dir, relative_path, f.as_slice(), mode, result)?;
     }
 
     Ok(())
 }
 
+/// Initializes a project from a template package located in a local
+/// directory.
+fn init_dir(
+    source_dir: &Path,
+    project_dir: Option<ImmutPath>,
+    mode: InitMode,
+) -> StrResult<InitResult> {
+    let manifest = read_dir_manifest(source_dir)?;
+    let Some(template) = &manifest.template else {
+        bail!("{} is not a template", source_dir.display());
+    };
+
+    let project_dir =
+        project_dir.unwrap_or_else(|| Path::new(manifest.package.name.as_str()).into());
+    check_project_dir(&project_dir, mode)?;
+
+    let template_dir = source_dir.join(relative_to_package(template.path.as_str())?);
+    if !template_dir.is_dir() {
+        bail!(
+            "template directory does not exist (at {})",
+            template_dir.display()
+        );
+    }
+
+    let mut result = InitResult {
+        entry_path: Path::new(template.entrypoint.as_str()).to_owned(),
+        ..InitResult::default()
+    };
+    let files = walkdir::WalkDir::new(&template_dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|de| de.file_name() != ".git");
+    for de in files {
+        let Ok(de) = de else {
+            continue;
+        };
+        if !de.file_type().is_file() {
+            continue;
+        }
+
+        let path = de.path();
+        let Ok(relative_path) = path.strip_prefix(&template_dir) else {
+            continue;
+        };
+        let data = std::fs::read(path).map_err(|e| FileError::from_io(e, path))?;
+        write_project_file(&project_dir, relative_path, &data, mode, &mut result)?;
+    }
+
+    Ok(result)
+}
+
+/// Gets content of the entry file of a template package located in a local
+/// directory.
+fn get_dir_entry(source_dir: &Path) -> StrResult<Bytes> {
+    let manifest = read_dir_manifest(source_dir)?;
+    let Some(template) = &manifest.template else {
+        bail!("{} is not a template", source_dir.display());
+    };
+
+    let entry_path = source_dir
+        .join(relative_to_package(template.path.as_str())?)
+        .join(relative_to_package(template.entrypoint.as_str())?);
+    let entry = std::fs::read(&entry_path).map_err(|e| FileError::from_io(e, &entry_path))?;
+    Ok(Bytes::new(entry))
+}
+
+/// Checks that a path in the manifest of a template package located in a local
+/// directory stays inside the package, i.e. it is relative and has no `..`
+/// components, since the manifest may come from an untrusted repository.
+fn relative_to_package(path: &str) -> StrResult<&Path> {
+    let relative = Path::new(path);
+    let inside = relative
+        .components()
+        .all(|comp| matches!(comp, Component::Normal(..) | Component::CurDir));
+    if !inside {
+        bail!("template path {path:?} must be relative to the package and stay inside it");
+    }
+
+    Ok(relative)
+}
+
+/// Reads the manifest of a template package located in a local directory.
+fn read_dir_manifest(source_dir: &Path) -> StrResult<PackageManifest> {
+    let toml_path = source_dir.join("typst.toml");
+    let toml_data =
+        std::fs::read_to_string(&toml_path).map_err(|e| FileError::from_io(e, &toml_path))?;
+
+    toml::from_str(&toml_data)
+        .map_err(|err| eco_format!("package manifest is malformed ({})", err.message()))
+}
+
+/// Clones a git repository into a temporary directory and calls the given
+/// function with the checkout.
+#[cfg(feature = "system")]
+fn with_git_checkout<T>(url: &str, f: impl FnOnce(&Path) -> StrResult<T>) -> StrResult<T> {
+    check_git_url(url)?;
+    let temp_dir = std::env::temp_dir().join("tinymist/template-git");
+
+    tinymist_std::fs::paths::temp_dir_in(temp_dir, |temp_dir| {
+        let checkout = temp_dir.join("template");
+        let status = std::process::Command::new("git")
+            .arg("clone")
+            .arg("--depth=1")
+            .arg("--")
+            .arg(url)
+            .arg(&checkout)
+            .status()?;
+        if !status.success() {
+            anyhow::bail!("git clone failed: {status}");
+        }
+
+        Ok(f(&checkout))
+    })
+    .map_err(|err| eco_format!("failed to clone template from {url}: {err}"))?
+}
+
+/// The URL schemes of git repositories that templates can be cloned from.
+/// Other transports, e.g. `ext::`, are rejected since they can run commands.
+const GIT_URL_SCHEMES: &[&str] = &["https://", "ssh://", "git@", "file://"];
+
+/// Checks that the git URL uses an allowed scheme.
+fn check_git_url(url: &str) -> StrResult<()> {
+    if !GIT_URL_SCHEMES.iter().any(|scheme| url.starts_with(scheme)) {
+        bail!(
+            "cannot clone template from {url}: the URL must start with one of {}",
+            GIT_URL_SCHEMES.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "system"))]
+fn with_git_checkout<T>(url: &str, _f: impl FnOnce(&Path) -> StrResult<T>) -> StrResult<T> {
+    check_git_url(url)?;
+    bail!("cannot clone template from {url}: git is not supported in this build")
+}
+
+/// Checks whether the project can be initialized into the directory.
+fn check_project_dir(project_dir: &Path, mode: InitMode) -> StrResult<()> {
+    if !project_dir.exists() {
+        return Ok(());
+    }
+    if !project_dir.is_dir() {
+        bail!(
+            "project directory already exists as a file (at {})",
+            project_dir.display()
+        );
+    }
+
+    if mode == InitMode::Fresh {
+        // empty_dir(project_dir)?;
+        let mut entries = std::fs::read_dir(project_dir)
+            .map_err(|e| FileError::from_io(e, project_dir))?
+            .peekable();
+        if entries.peek().is_some() {
+            bail!(
+                "project directory already exists and is not empty (at {}), \
+                 merge or overwrite it explicitly",
+                project_dir.display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a template file into the project directory, skipping or replacing
+/// the existing file according to `mode`.
+fn write_project_file(
+    project_dir: &Path,
+    relative_path: &Path,
+    data: &[u8],
+    mode: InitMode,
+    result: &mut InitResult,
+) -> StrResult<()> {
+    let file_path = project_dir.join(relative_path);
+    if file_path.exists() {
+        if file_path.is_dir() {
+            bail!(
+                "cannot write template file over a directory (at {})",
+                file_path.display()
+            );
+        }
+        if mode != InitMode::Overwrite {
+            result.skipped.push(relative_path.to_owned());
+            return Ok(());
+        }
+    }
+
+    let file_dir = file_path.parent().unwrap();
+    std::fs::create_dir_all(file_dir).map_err(|e| FileError::from_io(e, file_dir))?;
+    let mut file =
+        std::fs::File::create(&file_path).map_err(|e| FileError::from_io(e, &file_path))?;
+    file.write_all(data)
+        .map_err(|e| FileError::from_io(e, &file_path))?;
+    result.written.push(relative_path.to_owned());
+
+    Ok(())
+}
+
 fn scan_package_files(
     package: Option<PackageSpec>,
     root: &Path,
@@ -188,3 +437,55 @@ fn scan_package_files(
 
     Ok(res)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_git_url() {
+        assert!(check_git_url("https://github.com/user/template").is_ok());
+        assert!(check_git_url("ssh://git@github.com/user/template").is_ok());
+        assert!(check_git_url("git@github.com:user/template.git").is_ok());
+        assert!(check_git_url("file:///home/user/template").is_ok());
+
+        assert!(check_git_url("--upload-pack=touch /tmp/pwned").is_err());
+        assert!(check_git_url("ext::sh -c touch% /tmp/pwned").is_err());
+        assert!(check_git_url("http://github.com/user/template").is_err());
+    }
+
+    #[test]
+    fn test_relative_to_package() {
+        assert!(relative_to_package("template").is_ok());
+        assert!(relative_to_package("./template/main.typ").is_ok());
+        assert!(relative_to_package("").is_ok());
+
+        assert!(relative_to_package("/etc").is_err());
+        assert!(relative_to_package("../secrets").is_err());
+        assert!(relative_to_package("template/../../secrets").is_err());
+    }
+
+    #[test]
+    fn test_init_dir_rejects_escaping_template() {
+        let source = tempfile::tempdir().unwrap();
+        let manifest = r#"
+[package]
+name = "escape"
+version = "0.1.0"
+entrypoint = "lib.typ"
+
+[template]
+path = ".."
+entrypoint = "main.typ"
+"#;
+        std::fs::write(source.path().join("typst.toml"), manifest).unwrap();
+
+        let project = tempfile::tempdir().unwrap();
+        let project_dir: ImmutPath = project.path().join("project").into();
+        let err = init_dir(source.path(), Some(project_dir.clone()), InitMode::Fresh)
+            .expect_err("the template path escapes the package");
+        assert!(err.contains("must be relative"), "{err}");
+        assert!(get_dir_entry(source.path()).is_err());
+        assert!(!project_dir.exists());
+    }
+}
@@ -0,0 +1,221 @@
+//! Inventory of the files in a package, which is useful before publishing.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tinymist_query::package::{get_manifest, get_manifest_id, PackageInfo};
+use tinymist_std::path::unix_slash;
+use typst::diag::{eco_format, EcoString, StrResult};
+
+use crate::project::LspWorld;
+
+/// The default size (1 MiB) above which a file in a package is warned about.
+pub const DEFAULT_LARGE_FILE_THRESHOLD: u64 = 1024 * 1024;
+
+/// A file in the package directory.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageFileEntry {
+    /// The path relative to the package root, with unix slashes.
+    pub path: String,
+    /// The size of the file in bytes. For symlinks, this is the size of the
+    /// link itself.
+    pub size: u64,
+    /// Whether the file is excluded by the `exclude` field of the manifest.
+    pub excluded: bool,
+    /// Whether the file is a symlink, which is reported but not followed.
+    pub symlink: bool,
+}
+
+/// The inventory of a package.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageInventory {
+    /// The root directory of the package.
+    pub root: PathBuf,
+    /// The files in the package directory.
+    pub files: Vec<PackageFileEntry>,
+    /// The total size of the files that are not excluded, in bytes.
+    pub total_bytes: u64,
+    /// The warnings, e.g. files exceeding the size threshold.
+    pub warnings: Vec<EcoString>,
+}
+
+/// Gets the root directory of a package, i.e. the directory containing its
+/// `typst.toml`.
+pub fn package_root(world: &LspWorld, info: &PackageInfo) -> StrResult<PathBuf> {
+    let toml_id = get_manifest_id(info)?;
+    let toml_path = world.path_for_id(toml_id)?.as_path().to_owned();
+    let root = toml_path
+        .parent()
+        .ok_or_else(|| eco_format!("cannot get package root (parent of {toml_path:?})"))?
+        .to_owned();
+    Ok(root)
+}
+
+/// Walks the package directory and lists its files.
+pub fn package_inventory(
+    world: &LspWorld,
+    info: &PackageInfo,
+    threshold: u64,
+) -> StrResult<PackageInventory> {
+    let toml_id = get_manifest_id(info)?;
+    let root = package_root(world, info)?;
+
+    let manifest = get_manifest(world, toml_id)?;
+    let exclude = &manifest.package.exclude;
+
+    let mut files = vec![];
+    let mut warnings = vec![];
+    let mut total_bytes = 0;
+    for entry in walkdir::WalkDir::new(&root).follow_links(false) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                warnings.push(eco_format!("failed to read package entry: {err}"));
+                continue;
+            }
+        };
+
+        let file_type = entry.file_type();
+        if file_type.is_dir() {
+            continue;
+        }
+
+        let path = entry.path();
+        let Ok(relative_path) = path.strip_prefix(&root) else {
+            continue;
+        };
+        let relative_path = unix_slash(relative_path);
+
+        let size = match entry.metadata() {
+            Ok(meta) => meta.len(),
+            Err(err) => {
+                warnings.push(eco_format!(
+                    "failed to read metadata of {relative_path}: {err}"
+                ));
+                0
+            }
+        };
+
+        let symlink = file_type.is_symlink();
+        let excluded = is_excluded(exclude, &relative_path);
+        if !excluded {
+            total_bytes += size;
+            if size > threshold {
+                warnings.push(eco_format!(
+                    "file {relative_path} is {size} bytes, which exceeds {threshold} bytes"
+                ));
+            }
+        }
+        if symlink {
+            warnings.push(eco_format!(
+                "file {relative_path} is a symlink, which is not followed"
+            ));
+        }
+
+        files.push(PackageFileEntry {
+            path: relative_path,
+            size,
+            excluded,
+            symlink,
+        });
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(PackageInventory {
+        root,
+        files,
+        total_bytes,
+        warnings,
+    })
+}
+
+/// Checks whether a file is excluded by the glob patterns in the manifest.
+fn is_excluded(patterns: &[EcoString], path: &str) -> bool {
+    patterns.iter().any(|pattern| path_matches(pattern, path))
+}
+
+/// Matches a path relative to the root against a gitignore-style pattern. A
+/// pattern without a `/`, e.g. `*.pdf`, matches the name of a file or directory
+/// at any depth, while other patterns are anchored at the root. A pattern also
+/// matches the files under a matched directory.
+pub(crate) fn path_matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    let pattern = match pattern.strip_prefix('/') {
+        Some(anchored) => anchored.to_owned(),
+        None if pattern.contains('/') => pattern.to_owned(),
+        None => format!("**/{pattern}"),
+    };
+
+    glob_match(&pattern, path)
+        || path
+            .match_indices('/')
+            .any(|(idx, _)| glob_match(&pattern, &path[..idx]))
+}
+
+/// Matches a path against a glob pattern, supporting `*`, `**`, and `?`.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    fn matches(pattern: &[u8], path: &[u8]) -> bool {
+        match pattern {
+            [] => path.is_empty(),
+            [b'*', b'*', b'/', rest @ ..] => {
+                // `**/` matches zero or more directories.
+                matches(rest, path)
+                    || (0..path.len()).any(|i| path[i] == b'/' && matches(rest, &path[i + 1..]))
+            }
+            [b'*', b'*', rest @ ..] => (0..=path.len()).any(|i| matches(rest, &path[i..])),
+            [b'*', rest @ ..] => (0..=path.len())
+                .take_while(|&i| i == 0 || path[i - 1] != b'/')
+                .any(|i| matches(rest, &path[i..])),
+            [b'?', rest @ ..] => {
+                path.first().is_some_and(|&c| c != b'/') && matches(rest, &path[1..])
+            }
+            [c, rest @ ..] => path.first() == Some(c) && matches(rest, &path[1..]),
+        }
+    }
+
+    matches(pattern.as_bytes(), path.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.png", "logo.png"));
+        assert!(!glob_match("*.png", "assets/logo.png"));
+        assert!(glob_match("**/*.png", "assets/logo.png"));
+        assert!(glob_match("assets/**", "assets/img/logo.png"));
+        assert!(glob_match("test?.typ", "test1.typ"));
+        assert!(!glob_match("test?.typ", "test12.typ"));
+    }
+
+    #[test]
+    fn test_excluded_dir() {
+        let patterns = [EcoString::from("tests"), EcoString::from("*.pdf")];
+        assert!(is_excluded(&patterns, "tests/main.typ"));
+        assert!(is_excluded(&patterns, "manual.pdf"));
+        assert!(!is_excluded(&patterns, "lib.typ"));
+    }
+
+    #[test]
+    fn test_excluded_nested() {
+        let patterns = [EcoString::from("*.pdf"), EcoString::from("tests")];
+        assert!(is_excluded(&patterns, "docs/manual.pdf"));
+        assert!(is_excluded(&patterns, "docs/img/figure.pdf"));
+        assert!(is_excluded(&patterns, "src/tests/main.typ"));
+        assert!(!is_excluded(&patterns, "docs/manual.typ"));
+    }
+
+    #[test]
+    fn test_excluded_anchored() {
+        let patterns = [EcoString::from("/build"), EcoString::from("docs/*.pdf")];
+        assert!(is_excluded(&patterns, "build/main.pdf"));
+        assert!(!is_excluded(&patterns, "src/build/main.typ"));
+        assert!(is_excluded(&patterns, "docs/manual.pdf"));
+        assert!(!is_excluded(&patterns, "src/docs/manual.pdf"));
+    }
+}
@@ -2,3 +2,9 @@
 
 mod init;
 pub use init::*;
+mod inventory;
+pub use inventory::*;
+mod reload;
+pub use reload::*;
+mod snippet;
+pub use snippet::*;
@@ -0,0 +1,34 @@
+//! Reloading the sources of a package, which is useful when editing a local
+//! package.
+
+use tinymist_query::package::PackageInfo;
+use tinymist_std::ImmutPath;
+use typst::diag::StrResult;
+use typst::foundations::Bytes;
+
+use super::package_root;
+use crate::project::LspWorld;
+use crate::world::vfs::FileChangeSet;
+
+/// Reads the files of a package from the disk, which can be sent to the
+/// compiler to replace its stale sources.
+pub fn package_changes(world: &LspWorld, info: &PackageInfo) -> StrResult<FileChangeSet> {
+    let root = package_root(world, info)?;
+
+    let mut inserts = vec![];
+    for entry in walkdir::WalkDir::new(&root).follow_links(false) {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let path: ImmutPath = entry.path().into();
+        let content = std::fs::read(&path).map(Bytes::new);
+        let content = content.map_err(|err| typst::diag::FileError::from_io(err, &path));
+        inserts.push((path, content.into()));
+    }
+
+    Ok(FileChangeSet::new_inserts(inserts))
+}
@@ -0,0 +1,202 @@
+//! Generating a ready-to-insert snippet from the entry file of a template.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::ops::Range;
+
+use typst::syntax::{ast, LinkedNode, SyntaxKind};
+
+/// The argument names or dictionary keys whose values are typically edited
+/// after instantiating a template.
+const EDIT_KEYS: &[&str] = &[
+    "title", "subtitle", "author", "authors", "abstract", "date", "keywords",
+];
+
+/// A range of the entry file that becomes a placeholder of the snippet.
+struct EditPoint {
+    /// The range of the default text of the placeholder.
+    range: Range<usize>,
+    /// The key of the edited value, if any. Edit points sharing the key and
+    /// the default text share the tab stop, so that they are edited together.
+    key: Option<&'static str>,
+}
+
+/// Generates an LSP snippet from the entry file of a template, with tab stops
+/// at the likely edit points, i.e. the title, the authors, and the body of the
+/// document. Falls back to the whole entry as a single placeholder if no edit
+/// point is found.
+pub fn template_snippet(entry: &str) -> String {
+    let root = typst::syntax::parse(entry);
+    let root = LinkedNode::new(&root);
+
+    let body = document_body(&root, entry);
+    let limit = body.as_ref().map_or(entry.len(), |body| body.range.start);
+
+    let mut edits = vec![];
+    collect_edit_points(&root, limit, &mut edits);
+    edits.extend(body);
+
+    if edits.is_empty() {
+        return format!("${{1:{}}}", escape_snippet(entry));
+    }
+
+    let mut snippet = String::new();
+    let mut tab_stops = HashMap::new();
+    let mut count = 0;
+    let mut cursor = 0;
+    for edit in edits {
+        snippet.push_str(&escape_snippet(&entry[cursor..edit.range.start]));
+
+        let text = &entry[edit.range.clone()];
+        let tab_stop = match edit.key {
+            Some(key) => *tab_stops.entry((key, text)).or_insert_with(|| {
+                count += 1;
+                count
+            }),
+            None => {
+                count += 1;
+                count
+            }
+        };
+        if text.is_empty() {
+            let _ = write!(snippet, "${tab_stop}");
+        } else {
+            let _ = write!(snippet, "${{{tab_stop}:{}}}", escape_snippet(text));
+        }
+
+        cursor = edit.range.end;
+    }
+    snippet.push_str(&escape_snippet(&entry[cursor..]));
+
+    snippet
+}
+
+/// Finds the body of the document, i.e. the markup following the last
+/// top-level show rule without a selector, like `#show: template.with(..)`.
+fn document_body(root: &LinkedNode, entry: &str) -> Option<EditPoint> {
+    let show = root.children().rev().find(|child| {
+        child
+            .cast::<ast::ShowRule>()
+            .is_some_and(|show| show.selector().is_none())
+    })?;
+
+    let start = show.range().end;
+    let rest = &entry[start..];
+    let start = start + (rest.len() - rest.trim_start().len());
+    // An empty body leaves a tab stop at the end of the entry.
+    let end = entry.trim_end().len().max(start);
+
+    Some(EditPoint {
+        range: start..end,
+        key: None,
+    })
+}
+
+/// Collects the values of the named arguments and dictionary pairs that are
+/// likely to be edited, in the order of appearance. The outermost value wins
+/// if edit points are nested.
+fn collect_edit_points(node: &LinkedNode, limit: usize, edits: &mut Vec<EditPoint>) {
+    if node.range().start >= limit {
+        return;
+    }
+
+    if let Some(named) = node.cast::<ast::Named>() {
+        let key = EDIT_KEYS
+            .iter()
+            .copied()
+            .find(|key| *key == named.name().get().as_str());
+        let value = node
+            .children()
+            .rev()
+            .find(|child| child.cast::<ast::Expr>().is_some());
+        if let Some((key, value)) = key.zip(value) {
+            if value.range().end <= limit {
+                edits.push(EditPoint {
+                    range: value_range(&value),
+                    key: Some(key),
+                });
+                return;
+            }
+        }
+    }
+
+    for child in node.children() {
+        collect_edit_points(&child, limit, edits);
+    }
+}
+
+/// Gets the range to replace for a value, which is the text inside the quotes
+/// or brackets for strings and content blocks.
+fn value_range(value: &LinkedNode) -> Range<usize> {
+    let range = value.range();
+    match value.kind() {
+        SyntaxKind::Str if range.len() >= 2 => range.start + 1..range.end - 1,
+        SyntaxKind::ContentBlock => value
+            .children()
+            .find(|child| child.kind() == SyntaxKind::Markup)
+            .map_or(range, |markup| markup.range()),
+        _ => range,
+    }
+}
+
+/// Escapes the characters that have special meanings in LSP snippets.
+fn escape_snippet(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '$' | '}' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_snippet() {
+        let entry = r#"#import "@preview/paper:0.1.0": paper
+#show: paper.with(
+  title: [A Fancy Title],
+  authors: ("Jane Doe",),
+)
+
+= Introduction
+Costs $5$.
+"#;
+        let snippet = template_snippet(entry);
+        assert_eq!(
+            snippet,
+            r#"#import "@preview/paper:0.1.0": paper
+#show: paper.with(
+  title: [${1:A Fancy Title}],
+  authors: ${2:("Jane Doe",)},
+)
+
+${3:= Introduction
+Costs \$5\$.}
+"#
+        );
+    }
+
+    #[test]
+    fn test_template_snippet_mirrored() {
+        let entry = r#"#set document(title: "Notes")
+#show: doc => conf(title: "Notes", doc)
+"#;
+        let snippet = template_snippet(entry);
+        assert_eq!(
+            snippet,
+            r#"#set document(title: "${1:Notes}")
+#show: doc => conf(title: "${1:Notes}", doc)
+$2"#
+        );
+    }
+
+    #[test]
+    fn test_template_snippet_fallback() {
+        assert_eq!(template_snippet("Hello $x$"), "${1:Hello \\$x\\$}");
+    }
+}
@@ -20,7 +20,8 @@ use sync_ls::just_ok;
 use tinymist_assets::TYPST_PREVIEW_HTML;
 use tinymist_preview::{
     frontend_html, ControlPlaneMessage, ControlPlaneRx, ControlPlaneTx, DocToSrcJumpInfo,
-    PreviewBuilder, PreviewConfig, PreviewMode, Previewer, ViewerWindowState, WsMessage,
+    PreviewBuilder, PreviewConfig, PreviewMode, PreviewStatus, PreviewTheme, Previewer,
+    ViewerWindowState, WsMessage,
 };
 use tinymist_query::{LspPosition, LspRange};
 use tinymist_std::error::IgnoreLogging;
@@ -461,6 +462,8 @@ impl PreviewState {
         let tid = task_id.clone();
         let client = self.client.clone();
         let customized_show_document = self.customized_show_document;
+        let status = Arc::new(parking_lot::Mutex::new(PreviewStatus::default()));
+        let tab_status = status.clone();
         self.client.handle.spawn(async move {
             let mut resp_rx = resp_rx;
             while let Some(resp) = resp_rx.recv().await {
@@ -473,6 +476,11 @@ impl PreviewState {
                         log::warn!("PreviewTask({tid}): is sending SyncEditorChanges in lsp mode");
                     }
                     EditorScrollTo(s) => {
+                        // Lets the server decide whether to focus the clicked source.
+                        if is_primary {
+                            let path = Path::new(&s.filepath).into();
+                            client.send_event(ServerEvent::FocusByPreview(path));
+                        }
                         if customized_show_document {
                             client.send_notification::<ScrollSource>(&s)
                         } else {
@@ -487,6 +495,7 @@ impl PreviewState {
                             window: s.window,
                         },
                     ),
+                    PreviewStatus(s) => *status.lock() = s,
                 }
             }
 
@@ -558,6 +567,8 @@ impl PreviewState {
                 compile_handler,
                 is_primary,
                 is_background,
+                theme: PreviewTheme::default(),
+                status: tab_status,
             }));
             sent.map_err(|_| internal_error("failed to register preview tab"))?;
 
@@ -593,6 +604,39 @@ impl PreviewState {
         just_ok(JsonValue::Null)
     }
 
+    /// Switches the rendering theme of a preview task.
+    pub fn set_theme(&self, task_id: String, theme: PreviewTheme) -> AnySchedulableResponse {
+        let (tx, rx) = oneshot::channel();
+
+        let sent = self
+            .preview_tx
+            .send(PreviewRequest::SetTheme(task_id, theme, tx));
+        sent.map_err(|_| internal_error("failed to send theme request"))?;
+
+        just_future(async move { rx.await.map_err(|_| internal_error("cancelled"))? })
+    }
+
+    /// Lists the kinds of themes and the active theme of each preview task.
+    pub fn list_themes(&self) -> AnySchedulableResponse {
+        let (tx, rx) = oneshot::channel();
+
+        let sent = self.preview_tx.send(PreviewRequest::ListThemes(tx));
+        sent.map_err(|_| internal_error("failed to send theme request"))?;
+
+        just_future(async move { rx.await.map_err(|_| internal_error("cancelled"))? })
+    }
+
+    /// Gets the number of pages rendered by a preview task and the page in its
+    /// viewport.
+    pub fn status(&self, task_id: String) -> AnySchedulableResponse {
+        let (tx, rx) = oneshot::channel();
+
+        let sent = self.preview_tx.send(PreviewRequest::Status(task_id, tx));
+        sent.map_err(|_| internal_error("failed to send status request"))?;
+
+        just_future(async move { rx.await.map_err(|_| internal_error("cancelled"))? })
+    }
+
     /// Scroll all preview panels to a given position.
     pub fn scroll_all(&self, req: ControlPlaneMessage) -> AnySchedulableResponse {
         let sent = self.preview_tx.send(PreviewRequest::ScrollAll(req));
@@ -1,6 +1,6 @@
 //! Project management tools.
 
-use std::sync::Arc;
+use std::sync::{atomic::AtomicUsize, Arc};
 
 use parking_lot::Mutex;
 use tinymist_query::analysis::Analysis;
@@ -87,6 +87,9 @@ where
         client: Arc::new(intr_tx.clone()),
 
         analysis: opts.analysis,
+        max_cache_mb: Arc::new(AtomicUsize::new(
+            opts.config.max_cache_mb.unwrap_or_default(),
+        )),
         status_revision: Mutex::default(),
         notified_revision: Mutex::default(),
     });
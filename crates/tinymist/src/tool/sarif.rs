@@ -0,0 +1,252 @@
+//! Converts the diagnostics of a document to a SARIF log, which is consumed by
+//! code scanning tools.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Url};
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+use tinymist_query::DiagnosticsMap;
+
+/// The version of the SARIF format.
+const SARIF_VERSION: &str = "2.1.0";
+/// The schema of the SARIF format.
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+/// The base id of the files under the root.
+const SRCROOT: &str = "%SRCROOT%";
+
+/// The format of the exported diagnostics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiagnosticsFormat {
+    /// A SARIF 2.1.0 log.
+    #[default]
+    Sarif,
+    /// The LSP diagnostics keyed by the file URIs.
+    Json,
+}
+
+/// Converts the diagnostics to a SARIF 2.1.0 log with a single run.
+///
+/// The diagnostics must be converted with the UTF-16 position encoding, since
+/// the columns are counted in UTF-16 code units. The files under the root are
+/// referred to relatively to the `%SRCROOT%` base.
+pub fn to_sarif(diagnostics: &DiagnosticsMap, root: Option<&Path>) -> JsonValue {
+    let root_uri = root.and_then(|root| Url::from_directory_path(root).ok());
+
+    // Sorts the files and rules so that the log is stable.
+    let files = diagnostics.iter().collect::<BTreeMap<_, _>>();
+    let rules = files
+        .values()
+        .flat_map(|diags| diags.iter())
+        .map(|diag| (rule_id(diag), diag))
+        .collect::<BTreeMap<_, _>>();
+    let rule_index = |id: &str| rules.keys().position(|rule| rule == id);
+
+    let mut results = vec![];
+    for (uri, diags) in files {
+        let location = artifact_location(uri, root_uri.as_ref());
+        for diag in diags.iter() {
+            let id = rule_id(diag);
+            let related = diag.related_information.iter().flatten().map(|info| {
+                json!({
+                    "message": { "text": info.message },
+                    "physicalLocation": {
+                        "artifactLocation": artifact_location(&info.location.uri, root_uri.as_ref()),
+                        "region": region(&info.location.range),
+                    },
+                })
+            });
+
+            let mut result = json!({
+                "ruleId": id,
+                "ruleIndex": rule_index(&id),
+                "level": level(diag.severity),
+                "message": { "text": diag.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": location,
+                        "region": region(&diag.range),
+                    },
+                }],
+            });
+            let related = related.collect::<Vec<_>>();
+            if !related.is_empty() {
+                result["relatedLocations"] = related.into();
+            }
+            results.push(result);
+        }
+    }
+
+    let rules = rules
+        .iter()
+        .map(|(id, diag)| {
+            json!({
+                "id": id,
+                "shortDescription": { "text": rule_description(diag) },
+                "defaultConfiguration": { "level": level(diag.severity) },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut run = json!({
+        "tool": {
+            "driver": {
+                "name": "tinymist",
+                "version": env!("CARGO_PKG_VERSION"),
+                "informationUri": "https://github.com/Myriad-Dreamin/tinymist",
+                "rules": rules,
+            },
+        },
+        "columnKind": "utf16CodeUnits",
+        "results": results,
+    });
+    if let Some(root_uri) = root_uri {
+        run["originalUriBaseIds"] = json!({ SRCROOT: { "uri": root_uri } });
+    }
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [run],
+    })
+}
+
+/// Identifies the rule of a diagnostic by its source and code, or by its source
+/// and severity if it has no code, e.g. `typst/error`.
+fn rule_id(diag: &Diagnostic) -> String {
+    let source = diag.source.as_deref().unwrap_or("typst");
+    match &diag.code {
+        Some(NumberOrString::String(code)) => format!("{source}/{code}"),
+        Some(NumberOrString::Number(code)) => format!("{source}/{code}"),
+        None => format!("{source}/{}", severity_name(diag.severity)),
+    }
+}
+
+/// Describes the rule of a diagnostic.
+fn rule_description(diag: &Diagnostic) -> String {
+    let source = diag.source.as_deref().unwrap_or("typst");
+    match &diag.code {
+        Some(_) => format!("A {} reported by {source}", severity_name(diag.severity)),
+        None => format!("The {}s reported by {source}", severity_name(diag.severity)),
+    }
+}
+
+/// Names the severity of a diagnostic.
+fn severity_name(severity: Option<DiagnosticSeverity>) -> &'static str {
+    match severity {
+        Some(DiagnosticSeverity::ERROR) => "error",
+        Some(DiagnosticSeverity::INFORMATION) => "information",
+        Some(DiagnosticSeverity::HINT) => "hint",
+        _ => "warning",
+    }
+}
+
+/// Maps the severity of a diagnostic to a SARIF level.
+fn level(severity: Option<DiagnosticSeverity>) -> &'static str {
+    match severity {
+        Some(DiagnosticSeverity::ERROR) => "error",
+        Some(DiagnosticSeverity::INFORMATION | DiagnosticSeverity::HINT) => "note",
+        _ => "warning",
+    }
+}
+
+/// Refers to a file relatively to the root if it is under the root, or by its
+/// absolute URI otherwise.
+fn artifact_location(uri: &Url, root_uri: Option<&Url>) -> JsonValue {
+    let relative = root_uri.and_then(|root| uri.as_str().strip_prefix(root.as_str()));
+    match relative {
+        Some(relative) => json!({ "uri": relative, "uriBaseId": SRCROOT }),
+        None => json!({ "uri": uri }),
+    }
+}
+
+/// Converts a zero-based LSP range to a one-based SARIF region.
+fn region(range: &lsp_types::Range) -> JsonValue {
+    json!({
+        "startLine": range.start.line + 1,
+        "startColumn": range.start.character + 1,
+        "endLine": range.end.line + 1,
+        "endColumn": range.end.character + 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{Position, Range};
+
+    fn diagnostic(severity: DiagnosticSeverity, source: &str, message: &str) -> Diagnostic {
+        Diagnostic {
+            range: Range::new(Position::new(1, 2), Position::new(1, 5)),
+            severity: Some(severity),
+            source: Some(source.to_owned()),
+            message: message.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_to_sarif() {
+        let root = if cfg!(windows) { "C:\\root" } else { "/root" };
+        let root_uri = Url::from_directory_path(root).unwrap();
+        let main = root_uri.join("main.typ").unwrap();
+        let other = Url::parse("file:///other/lib.typ").unwrap();
+
+        let mut diagnostics = DiagnosticsMap::default();
+        diagnostics.insert(
+            main,
+            [
+                diagnostic(DiagnosticSeverity::ERROR, "typst", "unknown variable"),
+                diagnostic(DiagnosticSeverity::HINT, "tinymist-lint", "unused"),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        diagnostics.insert(
+            other.clone(),
+            [diagnostic(DiagnosticSeverity::WARNING, "typst", "unused")]
+                .into_iter()
+                .collect(),
+        );
+
+        let log = to_sarif(&diagnostics, Some(Path::new(root)));
+        assert_eq!(log["version"], "2.1.0");
+
+        let run = &log["runs"][0];
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        let rules = rules.iter().map(|rule| &rule["id"]).collect::<Vec<_>>();
+        assert_eq!(
+            rules,
+            ["tinymist-lint/hint", "typst/error", "typst/warning"]
+        );
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        let error = results
+            .iter()
+            .find(|result| result["ruleId"] == "typst/error")
+            .unwrap();
+        assert_eq!(error["level"], "error");
+        assert_eq!(error["ruleIndex"], 1);
+        let location = &error["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "main.typ");
+        assert_eq!(location["artifactLocation"]["uriBaseId"], SRCROOT);
+        assert_eq!(location["region"]["startLine"], 2);
+        assert_eq!(location["region"]["startColumn"], 3);
+
+        let hint = results
+            .iter()
+            .find(|result| result["ruleId"] == "tinymist-lint/hint")
+            .unwrap();
+        assert_eq!(hint["level"], "note");
+
+        let warning = results
+            .iter()
+            .find(|result| result["ruleId"] == "typst/warning")
+            .unwrap();
+        let location = &warning["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], other.as_str());
+    }
+}
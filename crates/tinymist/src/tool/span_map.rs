@@ -0,0 +1,268 @@
+//! Maps the rendered elements of a compiled document to their source ranges.
+
+use std::ops::Range;
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tinymist_project::LspWorld;
+use tinymist_std::typst::TypstPagedDocument;
+use tinymist_task::{exported_page_ranges, Pages};
+use typst::layout::{Abs, Frame, FrameItem, GroupItem, Point, Size, Transform};
+use typst::syntax::Span;
+use typst::text::TextItem;
+use typst::visualize::Shape;
+use typst::WorldExt;
+
+/// The rendered elements of a page with their originating source ranges.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageSpans {
+    /// The page number, starting from 1.
+    pub page: usize,
+    /// The width of the page (in pt).
+    pub width: f64,
+    /// The height of the page (in pt).
+    pub height: f64,
+    /// The rendered elements in the painting order.
+    pub spans: Vec<SpanBox>,
+}
+
+/// The bounding box of a rendered element and the source range it originates
+/// from.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpanBox {
+    /// The left of the box, relative to the top-left of the page (in pt).
+    pub x: f64,
+    /// The top of the box, relative to the top-left of the page (in pt).
+    pub y: f64,
+    /// The width of the box (in pt).
+    pub width: f64,
+    /// The height of the box (in pt).
+    pub height: f64,
+    /// The path of the source file.
+    pub path: PathBuf,
+    /// The byte range in the source file.
+    pub range: Range<usize>,
+}
+
+/// Collects the span map of the selected pages, or of all pages if `pages`
+/// is `None`. A text is split into runs of glyphs originating from the same
+/// span, and elements that are detached from the source are skipped.
+pub fn span_map(
+    world: &LspWorld,
+    doc: &TypstPagedDocument,
+    pages: Option<&[Pages]>,
+) -> Vec<PageSpans> {
+    let selected = pages.map(exported_page_ranges);
+    doc.pages()
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| {
+            selected
+                .as_ref()
+                .is_none_or(|ranges| ranges.includes_page_index(*idx))
+        })
+        .map(|(idx, page)| {
+            let mut walker = SpanWalker {
+                world,
+                spans: vec![],
+            };
+            walk_frame(&mut walker, &page.frame, Transform::identity());
+
+            let size = page.frame.size();
+            PageSpans {
+                page: idx + 1,
+                width: size.x.to_pt(),
+                height: size.y.to_pt(),
+                spans: walker.spans,
+            }
+        })
+        .collect()
+}
+
+/// Visits the items of the frames of a page in the painting order, with the
+/// transforms from the origins of the items to the page.
+pub(crate) trait FrameVisitor {
+    /// Visits a group before its items.
+    fn enter_group(&mut self, _group: &GroupItem, _ts: Transform) {}
+    /// Visits a group after its items.
+    fn leave_group(&mut self, _group: &GroupItem, _ts: Transform) {}
+    /// Visits a text item.
+    fn text(&mut self, text: &TextItem, ts: Transform);
+    /// Visits a shape.
+    fn shape(&mut self, shape: &Shape, span: Span, ts: Transform);
+    /// Visits an image.
+    fn image(&mut self, size: Size, span: Span, ts: Transform);
+    /// Visits a link area.
+    fn link(&mut self, _size: Size, _ts: Transform) {}
+}
+
+/// Walks the items of a frame, whose origin is transformed by `ts`. The
+/// introspection tags are skipped.
+pub(crate) fn walk_frame(visitor: &mut impl FrameVisitor, frame: &Frame, ts: Transform) {
+    for (pos, item) in frame.items() {
+        let ts = ts.pre_concat(Transform::translate(pos.x, pos.y));
+        match item {
+            FrameItem::Group(group) => {
+                let ts = ts.pre_concat(group.transform);
+                visitor.enter_group(group, ts);
+                walk_frame(visitor, &group.frame, ts);
+                visitor.leave_group(group, ts);
+            }
+            FrameItem::Text(text) => visitor.text(text, ts),
+            FrameItem::Shape(shape, span) => visitor.shape(shape, *span, ts),
+            FrameItem::Image(_, size, span) => visitor.image(*size, *span, ts),
+            FrameItem::Link(_, size) => visitor.link(*size, ts),
+            FrameItem::Tag(..) => {}
+        }
+    }
+}
+
+/// Collects the boxes of the elements of a page.
+struct SpanWalker<'a> {
+    world: &'a LspWorld,
+    spans: Vec<SpanBox>,
+}
+
+impl FrameVisitor for SpanWalker<'_> {
+    /// Pushes the runs of glyphs that originate from the same span.
+    fn text(&mut self, text: &TextItem, ts: Transform) {
+        let metrics = text.font.font().metrics();
+        let top = -metrics.ascender.at(text.size);
+        let bottom = -metrics.descender.at(text.size);
+
+        let mut x = Abs::zero();
+        let mut glyphs = text.glyphs.iter().peekable();
+        while let Some(first) = glyphs.next() {
+            let (span, offset) = first.span;
+            let start = x;
+            let mut offsets = usize::from(offset)..usize::from(offset) + first.range().len();
+            x += first.x_advance.at(text.size);
+            while let Some(glyph) = glyphs.next_if(|glyph| glyph.span.0 == span) {
+                let offset = usize::from(glyph.span.1);
+                offsets.start = offsets.start.min(offset);
+                offsets.end = offsets.end.max(offset + glyph.range().len());
+                x += glyph.x_advance.at(text.size);
+            }
+
+            self.push(
+                span,
+                Some(offsets),
+                ts,
+                Point::new(start, top),
+                Point::new(x, bottom),
+            );
+        }
+    }
+
+    fn shape(&mut self, shape: &Shape, span: Span, ts: Transform) {
+        let size = shape.geometry.bbox_size();
+        self.push(span, None, ts, Point::zero(), size.to_point());
+    }
+
+    fn image(&mut self, size: Size, span: Span, ts: Transform) {
+        self.push(span, None, ts, Point::zero(), size.to_point());
+    }
+}
+
+impl SpanWalker<'_> {
+    /// Pushes the box of an element, narrowing the source range of the span
+    /// to the offsets within it if any.
+    fn push(
+        &mut self,
+        span: Span,
+        offsets: Option<Range<usize>>,
+        ts: Transform,
+        top_left: Point,
+        bottom_right: Point,
+    ) {
+        let Some((path, range)) = source_location(self.world, span, offsets) else {
+            return;
+        };
+        let (min, max) = bounding_box(ts, top_left, bottom_right);
+
+        self.spans.push(SpanBox {
+            x: min.x.to_pt(),
+            y: min.y.to_pt(),
+            width: (max.x - min.x).to_pt(),
+            height: (max.y - min.y).to_pt(),
+            path,
+            range,
+        });
+    }
+}
+
+/// Gets the path and the byte range of the source that a span originates
+/// from, narrowed to the offsets within the span if any.
+pub(crate) fn source_location(
+    world: &LspWorld,
+    span: Span,
+    offsets: Option<Range<usize>>,
+) -> Option<(PathBuf, Range<usize>)> {
+    let id = span.id()?;
+    let (Some(range), Ok(path)) = (world.range(span), world.path_for_id(id)) else {
+        return None;
+    };
+    let range = match offsets {
+        Some(offsets) => {
+            let start = (range.start + offsets.start).min(range.end);
+            let end = (range.start + offsets.end).min(range.end);
+            start..end
+        }
+        None => range,
+    };
+
+    Some((path.as_path().to_owned(), range))
+}
+
+/// Transforms a box, returning the top-left and bottom-right corners of the
+/// axis-aligned box bounding it.
+pub(crate) fn bounding_box(ts: Transform, top_left: Point, bottom_right: Point) -> (Point, Point) {
+    let corners = [
+        top_left,
+        Point::new(bottom_right.x, top_left.y),
+        Point::new(top_left.x, bottom_right.y),
+        bottom_right,
+    ]
+    .map(|corner| corner.transform(ts));
+    let min_x = corners.iter().map(|p| p.x).fold(Abs::inf(), Abs::min);
+    let min_y = corners.iter().map(|p| p.y).fold(Abs::inf(), Abs::min);
+    let max_x = corners.iter().map(|p| p.x).fold(-Abs::inf(), Abs::max);
+    let max_y = corners.iter().map(|p| p.y).fold(-Abs::inf(), Abs::max);
+
+    (Point::new(min_x, min_y), Point::new(max_x, max_y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool::tests::compile;
+
+    #[test]
+    fn test_span_map() {
+        let source = "#rect(width: 20pt, height: 10pt)\n\nHello";
+        let test = compile(source);
+
+        let pages = span_map(&test.world, &test.doc, None);
+        assert_eq!(pages.len(), 1);
+        let spans = &pages[0].spans;
+        assert!(spans.iter().all(|span| span.path == test.path));
+
+        let text = spans
+            .iter()
+            .find(|span| &source[span.range.clone()] == "Hello")
+            .expect("a box of the text run");
+        assert!(text.width > 0.0 && text.height > 0.0);
+
+        let shape = spans
+            .iter()
+            .find(|span| source[span.range.clone()].starts_with("rect("))
+            .expect("a box of the shape");
+        assert!((shape.width - 20.0).abs() < 1e-6);
+        assert!((shape.height - 10.0).abs() < 1e-6);
+        assert!(shape.y + shape.height <= text.y);
+
+        assert!(span_map(&test.world, &test.doc, Some(&["2".parse().unwrap()])).is_empty());
+    }
+}
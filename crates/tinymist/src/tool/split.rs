@@ -0,0 +1,180 @@
+//! Splits a paged document into sections at its headings, which are exported
+//! to separate files.
+
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
+
+use tinymist_std::typst::TypstPagedDocument;
+use tinymist_task::{Pages, SplitBy};
+
+use super::outline::document_outline;
+
+/// A section of the document, which starts at a heading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// The plain text of the heading starting the section.
+    pub title: String,
+    /// The slug of the title, which is unique among the sections.
+    pub slug: String,
+    /// The pages of the section, starting from 1.
+    pub pages: Pages,
+}
+
+/// Splits the document into sections at the headings specified by `split_by`.
+///
+/// A section spans from the page of its heading to the page before the next
+/// section, so sections should start on new pages. The pages before the first
+/// heading, e.g. a title page, belong to the first section.
+pub fn split_sections(doc: &TypstPagedDocument, split_by: SplitBy) -> Vec<Section> {
+    let SplitBy::HeadingLevel(level) = split_by;
+    let starts = document_outline(doc)
+        .into_iter()
+        .filter(|heading| heading.level == level.get())
+        .map(|heading| (heading.text, heading.page))
+        .collect::<Vec<_>>();
+
+    partition(starts, doc.pages().len())
+}
+
+/// Partitions the pages into sections at the pages where they start.
+fn partition(starts: Vec<(String, usize)>, total_pages: usize) -> Vec<Section> {
+    let pages = starts.iter().map(|(_, page)| *page).collect::<Vec<_>>();
+    let spans = section_pages(&pages, total_pages, false);
+
+    let mut slugs = HashSet::new();
+    starts
+        .into_iter()
+        .zip(spans)
+        .map(|((title, _), (start, end))| Section {
+            slug: unique_slug(&mut slugs, &title),
+            title,
+            pages: Pages(NonZeroUsize::new(start)..=NonZeroUsize::new(end)),
+        })
+        .collect()
+}
+
+/// Computes the pages spanned by consecutive sections from the pages on which
+/// they start, as pairs of the first and last page, starting from 1.
+///
+/// The first section starts on the first page, and the last one ends on the
+/// last page. A section ends on the page before the next one starts, or on the
+/// same page if the sections are `shared_pages`, i.e. the next section may
+/// start in the middle of a page.
+pub(crate) fn section_pages(
+    starts: &[usize],
+    total_pages: usize,
+    shared_pages: bool,
+) -> Vec<(usize, usize)> {
+    let total_pages = total_pages.max(1);
+    let next_pages = starts.iter().skip(1).map(Some).chain(std::iter::once(None));
+
+    starts
+        .iter()
+        .zip(next_pages)
+        .enumerate()
+        .map(|(idx, (&page, next_page))| {
+            let start = if idx == 0 { 1 } else { page };
+            let end = match next_page {
+                Some(&next_page) if shared_pages => next_page.max(start),
+                Some(&next_page) => next_page.saturating_sub(1).max(start),
+                None => total_pages.max(start),
+            };
+            (start, end)
+        })
+        .collect()
+}
+
+/// Slugifies the title, and appends a numeric suffix to it if it is already
+/// taken, e.g. `intro-2`.
+fn unique_slug(slugs: &mut HashSet<String>, title: &str) -> String {
+    let base = slugify(title);
+    let mut slug = base.clone();
+    let mut suffix = 2;
+    while !slugs.insert(slug.clone()) {
+        slug = format!("{base}-{suffix}");
+        suffix += 1;
+    }
+    slug
+}
+
+/// Converts the title to a lowercase slug, in which the runs of characters
+/// other than letters and digits are replaced with single hyphens.
+fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "section".to_owned()
+    } else {
+        slug.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sections(starts: &[(&str, usize)], total_pages: usize) -> Vec<(String, String)> {
+        let starts = starts
+            .iter()
+            .map(|(title, page)| (title.to_string(), *page))
+            .collect();
+        partition(starts, total_pages)
+            .into_iter()
+            .map(|section| (section.slug, section.pages.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Getting Started"), "getting-started");
+        assert_eq!(slugify("  1. What's New?  "), "1-what-s-new");
+        assert_eq!(slugify("Über Typst"), "über-typst");
+        assert_eq!(slugify("???"), "section");
+    }
+
+    #[test]
+    fn test_partition() {
+        assert_eq!(
+            sections(&[("Intro", 2), ("Usage", 4), ("Reference", 7)], 9),
+            [
+                ("intro".to_owned(), "1-3".to_owned()),
+                ("usage".to_owned(), "4-6".to_owned()),
+                ("reference".to_owned(), "7-9".to_owned()),
+            ]
+        );
+        assert!(sections(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn test_partition_shared_pages() {
+        assert_eq!(
+            sections(&[("Notes", 1), ("Notes", 1), ("Notes", 2)], 2),
+            [
+                ("notes".to_owned(), "1-1".to_owned()),
+                ("notes-2".to_owned(), "1-1".to_owned()),
+                ("notes-3".to_owned(), "2-2".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_section_pages() {
+        assert_eq!(
+            section_pages(&[2, 4, 4, 7], 9, false),
+            [(1, 3), (4, 4), (4, 6), (7, 9)]
+        );
+        assert_eq!(
+            section_pages(&[2, 4, 4, 7], 9, true),
+            [(1, 4), (4, 4), (4, 7), (7, 9)]
+        );
+        assert_eq!(section_pages(&[1], 0, true), [(1, 1)]);
+    }
+}
@@ -5,8 +5,12 @@ use std::sync::Arc;
 
 use reflexo_typst::debug_loc::SourceSpanOffset;
 use serde::{Deserialize, Serialize};
-use tinymist_std::typst::TypstDocument;
+use tinymist_std::typst::{TypstDocument, TypstPagedDocument};
 use tinymist_task::TextExport;
+use typst::diag::{eco_format, StrResult};
+use typst::foundations::Label;
+use typst::introspection::{Introspector, Location};
+use typst::utils::PicoStr;
 use typst::{syntax::Span, text::TextItem};
 use unicode_script::{Script, UnicodeScript};
 
@@ -30,14 +34,18 @@ pub fn word_count(doc: &TypstDocument) -> WordsCount {
     // the mapping is still not use, so we prevent the warning here
     let _ = TextContent::map_back_spans;
 
+    // First, get text representation of the document.
+    let content = TextExport::run_on_doc(doc).unwrap_or_default();
+    count_words(&content)
+}
+
+/// Count words in a text.
+fn count_words(content: &str) -> WordsCount {
     let mut words = 0;
     let mut chars = 0;
     let mut cjk_chars = 0;
     let mut spaces = 0;
 
-    // First, get text representation of the document.
-    let content = TextExport::run_on_doc(doc).unwrap_or_default();
-
     /// A automaton to count words.
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     enum CountState {
@@ -91,6 +99,76 @@ pub fn word_count(doc: &TypstDocument) -> WordsCount {
     }
 }
 
+/// Word, character, and page counts for a document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentCounts {
+    /// Number of words.
+    pub words: usize,
+    /// Number of characters.
+    pub chars: usize,
+    /// Number of characters, excluding whitespace.
+    pub chars_without_spaces: usize,
+    /// Number of pages, from the page of the start label to the page before
+    /// the end label.
+    pub pages: usize,
+}
+
+/// The region of a document to count, which starts at the element with the
+/// `start` label and ends before the element with the `end` label. A missing
+/// label extends the region to the start or the end of the document.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CountRegion {
+    /// The label of the element starting the region.
+    pub start: Option<String>,
+    /// The label of the element ending the region.
+    pub end: Option<String>,
+}
+
+/// Count words, characters, and pages in a region of a paged document.
+pub fn document_counts(
+    doc: &Arc<TypstPagedDocument>,
+    region: &CountRegion,
+) -> StrResult<DocumentCounts> {
+    let introspector: &dyn Introspector = doc.introspector().as_ref();
+    let locate = |label: &Option<String>| -> StrResult<Option<Location>> {
+        let Some(label) = label else {
+            return Ok(None);
+        };
+        let label = Label::new(PicoStr::intern(label)).ok_or("label must not be empty")?;
+        let elem = doc
+            .introspector()
+            .query_label(label)
+            .map_err(|err| eco_format!("{err}"))?;
+        Ok(elem.location())
+    };
+    let start = locate(&region.start)?;
+    let end = locate(&region.end)?;
+
+    let content = if start.is_none() && end.is_none() {
+        TextExport::run_on_doc(&TypstDocument::Paged(doc.clone()))
+    } else {
+        TextExport::run_on_region(doc, start, end)
+    }
+    .map_err(|err| eco_format!("failed to extract text: {err}"))?;
+    let counts = count_words(&content);
+
+    let page_of = |loc: Option<Location>| {
+        let pos = introspector.position(loc?)?;
+        Some(pos.as_paged_or_default().page.get())
+    };
+    // The page of the end label is excluded, like the end label itself.
+    let first_page = page_of(start).unwrap_or(1);
+    let end_page = page_of(end).unwrap_or(doc.pages().len() + 1);
+
+    Ok(DocumentCounts {
+        words: counts.words,
+        chars: counts.chars,
+        chars_without_spaces: content.chars().filter(|c| !c.is_whitespace()).count(),
+        pages: end_page.saturating_sub(first_page),
+    })
+}
+
 /// Given a text range, map it back to the original document.
 #[derive(Debug, Clone)]
 pub struct MappedSpan {
@@ -256,3 +334,61 @@ impl SpanMapper {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool::tests::compile;
+
+    const DOC: &str = "Alpha beta
+#pagebreak()
+#metadata(none) <start>
+Gamma delta epsilon
+#pagebreak()
+Zeta
+#pagebreak()
+#metadata(none) <end>
+Eta";
+
+    fn region(start: Option<&str>, end: Option<&str>) -> CountRegion {
+        CountRegion {
+            start: start.map(str::to_owned),
+            end: end.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn test_document_counts() {
+        let doc = compile(DOC).doc;
+
+        let counts = document_counts(&doc, &CountRegion::default()).unwrap();
+        assert_eq!(counts.chars_without_spaces, 33);
+        assert_eq!(counts.pages, 4);
+    }
+
+    #[test]
+    fn test_document_counts_region() {
+        let doc = compile(DOC).doc;
+
+        // The region and its pages end before the end label.
+        let counts = document_counts(&doc, &region(Some("start"), Some("end"))).unwrap();
+        assert_eq!(counts.chars_without_spaces, 21);
+        assert_eq!(counts.pages, 2);
+
+        let counts = document_counts(&doc, &region(Some("start"), None)).unwrap();
+        assert_eq!(counts.chars_without_spaces, 24);
+        assert_eq!(counts.pages, 3);
+
+        let counts = document_counts(&doc, &region(None, Some("end"))).unwrap();
+        assert_eq!(counts.chars_without_spaces, 30);
+        assert_eq!(counts.pages, 3);
+    }
+
+    #[test]
+    fn test_document_counts_missing_label() {
+        let doc = compile(DOC).doc;
+
+        assert!(document_counts(&doc, &region(Some("missing"), None)).is_err());
+        assert!(document_counts(&doc, &region(None, Some("missing"))).is_err());
+    }
+}
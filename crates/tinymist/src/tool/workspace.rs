@@ -0,0 +1,178 @@
+//! Discovers the documents in a workspace, which are compiled together to
+//! check the whole workspace.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use tinymist_std::path::{unix_slash, PathClean};
+use typst::syntax::ast;
+use typst::syntax::SyntaxNode;
+
+use crate::tool::package::path_matches;
+
+/// Options for discovering the documents in a workspace.
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceFilter {
+    /// The gitignore-style patterns of the files to include, relative to the
+    /// root. All files are included if empty.
+    pub include: Vec<String>,
+    /// The gitignore-style patterns of the files to exclude, relative to the
+    /// root.
+    pub exclude: Vec<String>,
+}
+
+impl WorkspaceFilter {
+    /// Whether the file at the path relative to the root is selected.
+    pub fn is_selected(&self, rel: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| path_matches(p, rel)))
+            && !self.exclude.iter().any(|p| path_matches(p, rel))
+    }
+
+    /// Whether the file at the path is selected, matching the patterns against
+    /// the path relative to the first root containing it. Files outside the
+    /// roots are matched by their whole path.
+    pub fn is_path_selected(&self, roots: &[PathBuf], path: &Path) -> bool {
+        let rel = roots
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path);
+        self.is_selected(&unix_slash(rel))
+    }
+}
+
+/// Discovers the entrypoints under the root, i.e. the typst files that are not
+/// imported or included by other files in the workspace. Hidden directories
+/// are skipped. The result is sorted by path.
+pub fn discover_entries(root: &Path, filter: &WorkspaceFilter) -> Vec<PathBuf> {
+    let mut files = vec![];
+    let walker = walkdir::WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'));
+    for entry in walker.flatten() {
+        let path = entry.path();
+        if !entry.file_type().is_file() || path.extension().is_none_or(|ext| ext != "typ") {
+            continue;
+        }
+
+        let Ok(text) = std::fs::read_to_string(path) else {
+            log::warn!("failed to read workspace file {path:?}");
+            continue;
+        };
+        files.push((path.to_owned(), text));
+    }
+
+    let roots = [root.to_owned()];
+    let entries = find_entries(root, &files);
+    entries
+        .into_iter()
+        .filter(|path| filter.is_path_selected(&roots, path))
+        .collect()
+}
+
+/// Finds the files that are not imported or included by any other file.
+fn find_entries(root: &Path, files: &[(PathBuf, String)]) -> Vec<PathBuf> {
+    let imported = files
+        .iter()
+        .flat_map(|(path, text)| imported_paths(root, path, text))
+        .collect::<HashSet<_>>();
+
+    let mut entries = files
+        .iter()
+        .map(|(path, _)| path.clean())
+        .filter(|path| !imported.contains(path))
+        .collect::<Vec<_>>();
+    entries.sort();
+    entries
+}
+
+/// Collects the local paths that are imported or included by a file. Package
+/// imports and dynamic paths are ignored.
+fn imported_paths(root: &Path, path: &Path, text: &str) -> Vec<PathBuf> {
+    let node = typst::syntax::parse(text);
+    let mut specs = vec![];
+    collect_specs(&node, &mut specs);
+
+    let dir = path.parent().unwrap_or(root);
+    specs
+        .into_iter()
+        .filter(|spec| !spec.starts_with('@'))
+        .map(|spec| match spec.strip_prefix('/') {
+            Some(spec) => root.join(spec).clean(),
+            None => dir.join(spec).clean(),
+        })
+        .collect()
+}
+
+/// Collects the string sources of the `import` and `include` expressions.
+fn collect_specs(node: &SyntaxNode, specs: &mut Vec<String>) {
+    let source = if let Some(import) = node.cast::<ast::ModuleImport>() {
+        Some(import.source())
+    } else {
+        node.cast::<ast::ModuleInclude>()
+            .map(|include| include.source())
+    };
+    if let Some(ast::Expr::Str(s)) = source {
+        specs.push(s.get().to_string());
+    }
+
+    for child in node.children() {
+        collect_specs(child, specs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_entries() {
+        let root = Path::new("/ws");
+        let files = [
+            (
+                root.join("main.typ"),
+                "#import \"lib.typ\": *\n#include \"chapters/intro.typ\"".to_owned(),
+            ),
+            (root.join("lib.typ"), "#let x = 1".to_owned()),
+            (
+                root.join("chapters/intro.typ"),
+                "#import \"/lib.typ\": x\n#import \"@preview/example:0.1.0\"".to_owned(),
+            ),
+            (
+                root.join("slides.typ"),
+                "#import \"lib.typ\"\n#include \"../outside.typ\"".to_owned(),
+            ),
+        ];
+
+        let entries = find_entries(root, &files);
+        assert_eq!(
+            entries,
+            vec![root.join("main.typ"), root.join("slides.typ")]
+        );
+    }
+
+    #[test]
+    fn test_workspace_filter() {
+        let filter = WorkspaceFilter {
+            include: vec!["docs/**".to_owned()],
+            exclude: vec!["**/draft-*.typ".to_owned()],
+        };
+        assert!(filter.is_selected("docs/main.typ"));
+        assert!(!filter.is_selected("docs/notes/draft-1.typ"));
+        assert!(!filter.is_selected("main.typ"));
+        assert!(WorkspaceFilter::default().is_selected("main.typ"));
+
+        let filter = WorkspaceFilter {
+            include: vec![],
+            exclude: vec!["draft-*.typ".to_owned(), "/archive".to_owned()],
+        };
+        assert!(!filter.is_selected("chapters/notes/draft-1.typ"));
+        assert!(!filter.is_selected("archive/main.typ"));
+        assert!(filter.is_selected("chapters/archive/main.typ"));
+
+        let roots = [PathBuf::from("/ws")];
+        assert!(filter.is_path_selected(&roots, Path::new("/ws/docs/main.typ")));
+        assert!(!filter.is_path_selected(&roots, Path::new("/ws/main.typ")));
+        assert!(!filter.is_path_selected(&roots, Path::new("/ws/docs/draft-2.typ")));
+    }
+}
@@ -368,6 +368,7 @@ impl TypliteFeat {
         let task_inputs = TaskInputs {
             entry: Some(entry.select_in_workspace(main_id.vpath().as_rooted_path_compat())),
             inputs: Some(Arc::new(LazyHash::new(dict))),
+            locale: None,
         };
 
         let mut world = world.task(task_inputs).html_task().into_owned();
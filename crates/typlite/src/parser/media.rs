@@ -273,6 +273,7 @@ impl HtmlToAstParser {
                 Some(ColorTheme::Dark) => Some(DARK_THEME_INPUT.clone()),
                 None | Some(ColorTheme::Light) => None,
             },
+            locale: None,
         });
         // todo: cost some performance.
         world.take_db();
@@ -11,7 +11,8 @@ use crate::debug_loc::{InternQuery, SpanInterner};
 use crate::outline::Outline;
 use crate::{
     ChangeCursorPositionRequest, DocToSrcJumpInfo, EditorServer, MemoryFiles, MemoryFilesShort,
-    ResolveSourceLocRequest, ViewerWindowStateMessage,
+    PreviewStatus, PreviewTheme, PreviewThemeState, ResolveSourceLocRequest,
+    ViewerWindowStateMessage,
 };
 
 use super::webview::WebviewActorRequest;
@@ -42,6 +43,10 @@ pub enum EditorActorRequest {
     ViewerWindowState(ViewerWindowStateMessage),
     Outline(Outline),
     CompileStatus(CompileStatus),
+    /// The number of pages of the rendered document.
+    PageCount(u32),
+    /// The page in the viewport of a viewer.
+    ViewportPage(u32),
 }
 
 pub struct ControlPlaneTx {
@@ -115,6 +120,8 @@ pub struct EditorActor<T> {
     webview_sender: broadcast::Sender<WebviewActorRequest>,
 
     span_interner: SpanInterner,
+    theme: Arc<PreviewThemeState>,
+    status: PreviewStatus,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -134,6 +141,16 @@ pub enum ControlPlaneMessage {
     UpdateMemoryFiles(MemoryFiles),
     #[serde(rename = "removeMemoryFiles")]
     RemoveMemoryFiles(MemoryFilesShort),
+    #[serde(rename = "setTheme")]
+    SetTheme(SetThemeRequest),
+    /// Requests a `previewStatus` response with the current status.
+    #[serde(rename = "queryStatus")]
+    QueryStatus,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetThemeRequest {
+    pub theme: PreviewTheme,
 }
 
 #[derive(Debug, Serialize)]
@@ -149,6 +166,9 @@ pub enum ControlPlaneResponse {
     Outline(Outline),
     #[serde(rename = "viewerWindowState")]
     ViewerWindowState(ViewerWindowStateMessage),
+    /// Sent when the status changes or is queried by `queryStatus`.
+    #[serde(rename = "previewStatus")]
+    PreviewStatus(PreviewStatus),
 }
 
 impl<T: EditorServer> EditorActor<T> {
@@ -159,6 +179,7 @@ impl<T: EditorServer> EditorActor<T> {
         renderer_sender: broadcast::Sender<RenderActorRequest>,
         webview_sender: broadcast::Sender<WebviewActorRequest>,
         span_interner: SpanInterner,
+        theme: Arc<PreviewThemeState>,
     ) -> Self {
         Self {
             server,
@@ -168,6 +189,8 @@ impl<T: EditorServer> EditorActor<T> {
             webview_sender,
 
             span_interner,
+            theme,
+            status: PreviewStatus::default(),
         }
     }
 
@@ -203,6 +226,12 @@ impl<T: EditorServer> EditorActor<T> {
                         EditorActorRequest::Outline(outline) => {
                             self.editor_conn.resp_ctl_plane("Outline", ControlPlaneResponse::Outline(outline)).await
                         }
+                        EditorActorRequest::PageCount(page_count) => {
+                            self.update_status(PreviewStatus { page_count: Some(page_count), ..self.status }).await
+                        }
+                        EditorActorRequest::ViewportPage(page) => {
+                            self.update_status(PreviewStatus { current_page: Some(page), ..self.status }).await
+                        }
                     };
 
                     if !sent {
@@ -260,6 +289,14 @@ impl<T: EditorServer> EditorActor<T> {
                                 self.server.remove_memory_files(req).await,
                             );
                         }
+                        ControlPlaneMessage::SetTheme(req) => {
+                            log::debug!("EditorActor: processing set theme: {:?}", req.theme);
+                            *self.theme.active.write() = req.theme;
+                            self.webview_sender.send(WebviewActorRequest::Theme(self.theme.message())).log_error("EditorActor");
+                        }
+                        ControlPlaneMessage::QueryStatus => {
+                            self.editor_conn.resp_ctl_plane("PreviewStatus", ControlPlaneResponse::PreviewStatus(self.status)).await;
+                        }
                     };
                 }
             }
@@ -273,6 +310,17 @@ impl<T: EditorServer> EditorActor<T> {
         }
     }
 
+    /// Updates the status and sends it if it has changed.
+    async fn update_status(&mut self, status: PreviewStatus) -> bool {
+        if self.status == status {
+            return true;
+        }
+        self.status = status;
+        self.editor_conn
+            .resp_ctl_plane("PreviewStatus", ControlPlaneResponse::PreviewStatus(status))
+            .await
+    }
+
     async fn source_scroll_by_span(&mut self, span: String) {
         let jump_info = {
             match self.span_interner.span_by_str(&span).await {
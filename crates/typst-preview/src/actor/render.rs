@@ -322,6 +322,11 @@ impl OutlineRenderActor {
                 log::info!("OutlineRenderActor: document is not ready");
                 continue;
             };
+            let page_count = EditorActorRequest::PageCount(document.num_of_pages());
+            let Ok(_) = self.editor_tx.send(page_count) else {
+                log::info!("OutlineRenderActor: outline_sender is dropped");
+                break;
+            };
             let data = self.outline(&document).await;
             log::debug!("OutlineRenderActor: sending outline");
             let Ok(_) = self.editor_tx.send(EditorActorRequest::Outline(data)) else {
@@ -13,6 +13,8 @@ pub type SrcToDocJumpInfo = DocumentPosition;
 pub enum WebviewActorRequest {
     ViewportPosition(DocumentPosition),
     SrcToDocJump(Vec<SrcToDocJumpInfo>),
+    /// The message to apply a theme, see `PreviewThemeState::message`.
+    Theme(String),
     // CursorPosition(CursorPosition),
 }
 
@@ -93,6 +95,10 @@ where
                             self.webview_websocket_conn.send(WsMessage::Binary(msg.into()))
                               .await.log_error("WebViewActor");
                         }
+                        WebviewActorRequest::Theme(msg) => {
+                            self.webview_websocket_conn.send(WsMessage::Binary(msg.into()))
+                              .await.log_error("WebViewActor");
+                        }
                     }
                 }
                 Some(svg) = self.svg_receiver.recv() => {
@@ -147,6 +153,10 @@ where
                         if let Ok(path) = path {
                             self.render_sender.send(RenderActorRequest::WebviewResolveFrameLoc(path)).log_error("WebViewActor");
                         };
+                    } else if let Some(page) = msg.strip_prefix("viewport-page ") {
+                        if let Ok(page) = page.trim().parse() {
+                            self.editor_sender.send(EditorActorRequest::ViewportPage(page)).log_error("WebViewActor");
+                        };
                     } else if let Some(state) = msg.strip_prefix("viewer-window-state ") {
                         if let Ok(state) = serde_json::from_str::<ViewerWindowStateMessage>(state) {
                             self.editor_sender.send(EditorActorRequest::ViewerWindowState(state)).log_error("WebViewActor");
@@ -8,7 +8,7 @@ pub mod protocol;
 
 pub use crate::actor::editor::{
     CompileStatus, ControlPlaneMessage, ControlPlaneResponse, ControlPlaneRx, ControlPlaneTx,
-    PanelScrollByPositionRequest,
+    PanelScrollByPositionRequest, SetThemeRequest,
 };
 pub use crate::outline::Outline;
 
@@ -57,6 +57,63 @@ pub struct PreviewConfig {
     pub invert_colors: String,
 }
 
+/// The theme to render the preview in, which can be switched at runtime.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum PreviewTheme {
+    /// Inverts colors by the configured [`PreviewConfig::invert_colors`].
+    #[default]
+    Default,
+    /// Never inverts colors.
+    Light,
+    /// Always inverts colors.
+    Dark,
+    /// Inverts colors like [`PreviewTheme::Default`], and injects the CSS
+    /// snippet into the frontend page.
+    Custom {
+        /// The CSS snippet to inject.
+        css: String,
+    },
+}
+
+impl PreviewTheme {
+    /// The kinds of the themes.
+    pub const KINDS: &[&str] = &["default", "light", "dark", "custom"];
+}
+
+/// The active theme of a previewer, which is shared by the control plane and
+/// the data plane, so that the webviews connected later also take the theme.
+#[derive(Debug, Default)]
+struct PreviewThemeState {
+    /// The configured invert colors setting.
+    invert_colors: String,
+    /// The active theme.
+    active: parking_lot::RwLock<PreviewTheme>,
+}
+
+impl PreviewThemeState {
+    /// The message to send to the webviews to apply the active theme.
+    fn message(&self) -> String {
+        let invert_colors = match &*self.active.read() {
+            PreviewTheme::Light => serde_json::Value::from("never"),
+            PreviewTheme::Dark => serde_json::Value::from("always"),
+            PreviewTheme::Default | PreviewTheme::Custom { .. } => {
+                match self.invert_colors.as_str() {
+                    "" => serde_json::Value::from("never"),
+                    // The setting is either serialized or given as a plain strategy.
+                    s => serde_json::from_str(s).unwrap_or_else(|_| s.into()),
+                }
+            }
+        };
+        let css = match &*self.active.read() {
+            PreviewTheme::Custom { css } => Some(css.clone()),
+            _ => None,
+        };
+        let theme = serde_json::json!({ "invertColors": invert_colors, "css": css });
+        format!("theme,{theme}")
+    }
+}
+
 /// Gets the HTML for the frontend by a given preview mode and server to connect
 pub fn frontend_html(html: &str, mode: PreviewMode, to: &str, page_title: &str) -> String {
     let mode = match mode {
@@ -168,9 +225,13 @@ impl Previewer {
                         .await
                         .log_error("SendPartialRendering");
                 }
-                if !h.invert_colors.is_empty() {
+                if *h.theme.active.read() != PreviewTheme::Default {
+                    conn.send(WsMessage::Binary(h.theme.message().into()))
+                        .await
+                        .log_error("SendTheme");
+                } else if !h.theme.invert_colors.is_empty() {
                     conn.send(WsMessage::Binary(
-                        format!("invert-colors,{}", h.invert_colors).into(),
+                        format!("invert-colors,{}", h.theme.invert_colors).into(),
                     ))
                     .await
                     .log_error("SendInvertColor");
@@ -308,6 +369,11 @@ impl PreviewBuilder {
         let span_interner = SpanInterner::new();
         let (shutdown_data_plane_tx, shutdown_data_plane_rx) = mpsc::channel(1);
 
+        let theme = Arc::new(PreviewThemeState {
+            invert_colors: config.invert_colors,
+            active: Default::default(),
+        });
+
         // Spawns the editor actor
         let editor_actor = EditorActor::new(
             server,
@@ -316,6 +382,7 @@ impl PreviewBuilder {
             renderer_mailbox.0.clone(),
             webview_tx.clone(),
             span_interner.clone(),
+            theme.clone(),
         );
         let control_plane_handle = tokio::spawn(editor_actor.run());
         log::info!("Previewer: editor actor spawned");
@@ -325,7 +392,7 @@ impl PreviewBuilder {
             span_interner: span_interner.clone(),
             webview_tx: webview_tx.clone(),
             editor_tx: editor_tx.clone(),
-            invert_colors: config.invert_colors,
+            theme,
             renderer_tx: renderer_mailbox.0.clone(),
             enable_partial_rendering: config.enable_partial_rendering,
             doc_sender,
@@ -366,7 +433,7 @@ mod tests {
     use reflexo_vec2svg::IncrSvgDocServer;
     use tinymist_std::typst::{TypstDocument, TypstPagedDocument};
 
-    use super::{escape_html_text, protocol};
+    use super::{PreviewTheme, PreviewThemeState, escape_html_text, protocol};
 
     #[test]
     fn escapes_html_text_without_breaking_multibyte_code_points() {
@@ -374,6 +441,38 @@ mod tests {
         assert_eq!(escape_html_text("plain text"), "plain text");
     }
 
+    #[test]
+    fn theme_message_keeps_configured_invert_colors_by_default() {
+        let theme = PreviewThemeState {
+            invert_colors: r#""auto""#.to_owned(),
+            active: Default::default(),
+        };
+        let message = |theme: &PreviewThemeState| {
+            let message = theme.message();
+            let payload = message
+                .strip_prefix("theme,")
+                .expect("theme message prefix");
+            serde_json::from_str::<serde_json::Value>(payload).expect("theme message payload")
+        };
+        assert_eq!(
+            message(&theme),
+            serde_json::json!({ "invertColors": "auto", "css": null })
+        );
+
+        *theme.active.write() = PreviewTheme::Dark;
+        assert_eq!(
+            message(&theme),
+            serde_json::json!({ "invertColors": "always", "css": null })
+        );
+
+        *theme.active.write() = serde_json::from_str(r#"{"kind":"custom","css":"body{}"}"#)
+            .expect("custom theme should deserialize");
+        assert_eq!(
+            message(&theme),
+            serde_json::json!({ "invertColors": "auto", "css": "body{}" })
+        );
+    }
+
     #[test]
     fn full_current_event_uses_new_prefix_after_incremental_render() {
         tinymist_tests::run_with_sources(
@@ -487,6 +586,19 @@ pub struct ViewerWindowStateMessage {
     pub window: ViewerWindowState,
 }
 
+/// The pages of the rendered document and the position of the viewer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewStatus {
+    /// The number of pages of the rendered document, which is `None` before
+    /// the first document is rendered.
+    pub page_count: Option<u32>,
+    /// The page in the viewport of the most recently scrolled viewer, i.e. the
+    /// one closest to the center of the viewport or the current slide,
+    /// starting from 1. It is `None` before a viewer reports its position.
+    pub current_page: Option<u32>,
+}
+
 pub trait CompileView: Send + Sync {
     /// Get the compiled document.
     fn doc(&self) -> Option<TypstDocument>;
@@ -582,7 +694,7 @@ struct DataPlane {
     webview_tx: broadcast::Sender<WebviewActorRequest>,
     editor_tx: mpsc::UnboundedSender<EditorActorRequest>,
     enable_partial_rendering: bool,
-    invert_colors: String,
+    theme: Arc<PreviewThemeState>,
     renderer_tx: broadcast::Sender<RenderActorRequest>,
     doc_sender: Arc<parking_lot::RwLock<Option<Arc<dyn CompileView>>>>,
 }
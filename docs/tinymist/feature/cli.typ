@@ -48,6 +48,12 @@ The `tinymist compile` command is compatible with `typst compile`:
 tinymist compile path/to/main.typ
 ```
 
+To write a single-file output to the standard output, e.g. to pipe it into another program, pass `-` as the output path. The output is a PDF unless another format is given by `--format`. The output path `-` is only supported by the CLI, and the language server rejects it, since its standard output is the protocol stream.
+
+```bash
+tinymist compile --format svg path/to/main.typ - > main.svg
+```
+
 To save the compilation command to the lock file:
 
 ```bash
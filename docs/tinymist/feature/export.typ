@@ -94,6 +94,81 @@ To simplify configuration,
 }
 ```
 
+To get the speaker notes of each slide instead, run the LSP command `tinymist.exportSpeakerNotes` with the path of the presentation. It returns a JSON array with the notes attached by `<pdfpc-notes>` metadata on each slide, where a slide without notes has an empty string.
+
+== Equations
+
+To extract the equations of a document, e.g. for use on the web, run the LSP command `tinymist.exportEquations` with the path of the document. It returns a JSON array with an entry for each equation in the document order. Each entry holds the SVG cropped to the bounding box of the equation, the label of the equation (or its index if it has no label), the page it starts on, and whether it is a display equation (`display: true`) or an inline one.
+
+== Layout Tree
+
+For automated layout testing, run the LSP command `tinymist.exportLayout` with the path of the document to get the full frame tree of the compiled document as JSON. It returns an entry for each page with the `page` number, the `width` and `height` of the page, and the `items` on the page in the painting order. Each item has a `type`, i.e. `"group"`, `"text"`, `"shape"`, `"image"`, or `"link"`, and the box bounding it (`x`, `y`, `width`, and `height` in pt, relative to the top-left of the page). Items attached to the source have the `source` with the `path` of the file and the byte `range` in it, text items have the `text` with the `font` family and `size`, and groups have their `children` and whether they `clip` them. Since the output can be large, pass `{ "pages": ["1-3"] }` as the second argument to limit it to some pages. Compared to the span map of the export commands, which lists the boxes of the elements attached to the source, it keeps the nesting of the frames and the items detached from the source.
+
+== Checking PDF Standards
+
+To check whether a document conforms to PDF standards before setting up a PDF/A pipeline, run the LSP command `tinymist.checkPdfStandards` with the path of the document and an array of standards, e.g. `["a-2b"]`. The standards default to the ones passed by `--pdf-standard` in `tinymist.typstExtraArgs`. The PDF is generated in memory and never written, and the issues blocking the conformance, such as fonts that cannot be embedded or transparency under PDF/A-1, are returned as diagnostics grouped by file, with the locations of the offending content.
+
+== Exporting Diagnostics
+
+To feed the compile errors and warnings of a document to code scanning tools, such as GitHub code scanning, run the LSP command `tinymist.exportDiagnostics` with the path of the document. The document is compiled and its diagnostics are returned as a SARIF 2.1.0 log with a single run, in which the rules are named after the source and the severity of the diagnostics, e.g. `typst/error`, errors and warnings keep their levels, and the other diagnostics become notes. The files under the root are referred to relatively to `%SRCROOT%`, and the columns are counted in UTF-16 code units. Pass `"json"` as the second argument to get the diagnostics grouped by file as in the language server instead.
+
+== Querying the HTML Output
+
+To query the generated HTML rather than the elements of the document, set `query.html` to `true`. The selector is then a CSS-like selector over the HTML output, supporting tag names, `*`, `.class`, `#id`, `[attr]`, `[attr=value]`, and the descendant and child (`>`) combinators, with multiple selectors separated by commas. Each result is an object with the `tag`, `attrs`, and `text` of the element, so `query.field` can be, for example, `text` or `attrs.href`. The following task exports the text of all `<h2>` headings:
+
+```json
+{
+  "label": "Query HTML Headings",
+  "type": "typst",
+  "command": "export",
+  "export": {
+    "format": "query",
+    "query.format": "json",
+    "query.selector": "h2",
+    "query.field": "text",
+    "query.html": true
+  }
+}
+```
+
+== Counts
+
+To get the word, character, and page counts of a document, run the LSP command `tinymist.getCounts` with the path of the document. It returns an object with `words`, `chars`, `charsWithoutSpaces`, and `pages`, computed from the latest compilation of the document. To count a region only, pass an object with the labels of the elements starting and ending the region as the second argument. The region ends before the element with the `end` label, so `{ "end": "appendix" }` excludes the appendix labelled `<appendix>` and everything after it. The pages are counted from the page of the `start` label up to the page before the `end` label.
+
+== Extracting Pages
+
+To split the pages of a large report into a new document, run the LSP command `tinymist.extractPages` with the path of the document, a list of page ranges, e.g. `["3-5", "8"]`, and optionally an absolute output path. It returns the `text` of the new `.typ` document, the `path` that it is written to, and the extracted `sections` with their `title`, `startPage`, and `endPage`.
+
+Since the content cannot be mapped to the pages precisely, the document is split at the headings written at the top level of the main file, and every section sharing a page with the ranges is extracted as a whole, so the new document may contain more than the requested pages. The set, show, and let rules and the imports before the first heading are kept, while the rules in the other sections are dropped along with the sections. Content from included files or headings generated by functions doesn't split the document, and relative paths in the new document are resolved against its own location, so write it next to the original document.
+
+== Comparing Unsaved Edits
+
+To see which rendered pages are changed by the unsaved edits, run the LSP command `tinymist.diffRender` with the path of the document. It compiles both the version saved on the disk and the version with the unsaved edits, and returns a list of the pages with their `page` numbers and how they `change`d, i.e. `"unchanged"`, `"changed"`, `"added"`, or `"removed"`. The pages are compared by their content hashes, so UIs can skip the unchanged pages cheaply. Passing `{ "visual": true }` as the second argument also returns a base64-encoded PNG `image` for each changed or added page, where the changed regions are highlighted in red and the rest is faded. The images are rendered at 72 PPI unless a `ppi` is given.
+
+== Building Targets
+
+A project can declare named build targets in the `[tool.tinymist.targets]` table of its `typst.toml`, e.g. to build a print and a web edition from the same sources. Each target specifies the `main` file relative to the `typst.toml`, the `inputs` passed to the document as `sys.inputs`, and the export task in the same way as the tasks in the lock file, with a relative `output` resolved against the directory of the `typst.toml`:
+
+```toml
+[tool.tinymist.targets.print]
+main = "main.typ"
+type = "export-pdf"
+output = "build/print/$name"
+inputs = { edition = "print" }
+
+[tool.tinymist.targets.web]
+main = "main.typ"
+type = "export-html"
+output = "build/web/$name"
+inputs = { edition = "web" }
+```
+
+To build a target, run the LSP command `tinymist.buildTarget` with the name of the target, e.g. `"print"`. The nearest `typst.toml` declaring targets is searched from the root of the workspace, or from the path given as the second argument. The inputs of the target override the inputs of the editing session, and an unknown name fails with a list of the available targets.
+
+== Listing Tasks
+
+To present the export tasks of a document, e.g. in a task runner, run the LSP command `tinymist.listTasks` with the path of the document, which defaults to the main file. It returns the task configured by `tinymist.exportPdf` and `tinymist.outputPath`, whose `id` is its type, i.e. `"export-pdf"`, followed by the tasks of the document recorded in the lock file with their own `id`s. Each entry holds the options of the task, the resolved output `path`, and whether the task `needsRun` against the current document.
+
 = VSCode: Task Configuration
 
 You can configure tasks in your `tasks.json` file to "persist" the arguments for exporting documents.
@@ -192,11 +267,62 @@ After configuring the tasks, you can run them using the command palette.
 You can call the following export commands.
 - `tinymist.exportSvg`
 - `tinymist.exportPng`
+- `tinymist.exportContactSheet`
+- `tinymist.exportImageStrip`
 - `tinymist.exportPdf`
 - `tinymist.exportHtml`
 - `tinymist.exportMarkdown`
 - `tinymist.exportTeX`
 - `tinymist.exportText`
+- `tinymist.exportPptx`
+- `tinymist.exportAnimatedSvg`
+- `tinymist.exportGif`
 - `tinymist.exportQuery`
 
 The first argument is the path to the file you want to export and the second argument is an object containing additional options.
+
+The `fill` option of the PNG exports also accepts a list of fills of page ranges, which is handy for alternating backgrounds such as chapter dividers. The pages not covered by any range keep the fill of the document, and overlapping ranges are rejected as invalid parameters:
+
+```json
+{ "fill": [{ "ranges": ["1", "5-7"], "fill": "#eeeeee" }, { "ranges": ["8-"], "fill": "white" }] }
+```
+
+`tinymist.exportContactSheet` writes a single PNG file tiling the thumbnails of the pages in a grid, each labeled with its page number, which is handy for proofing. It accepts the `columns` (4 by default) and `thumbPpi` (24 by default) options, besides the `pages`, `fill`, `transparent`, and `jobs` options of `tinymist.exportPng`. The thumbnails are rendered row by row, so large documents don't keep all the pages in memory.
+
+`tinymist.exportImageStrip` writes a single tall PNG file stitching the pages from top to bottom, which is handy for sharing on chat apps. It accepts the `pages`, `ppi` (144 by default), `fill`, and `gap` (e.g. `"10pt"`) options. The export fails with an error if the image would be taller than the `maxHeight` option in pixels, which is 32767 by default, so lower the `ppi` or select fewer `pages` in that case.
+
+`tinymist.exportPptx` writes a PPTX presentation, where each page becomes a slide showing the page rendered at `ppi` (144 by default) as a full-bleed image. The speaker notes set by `#metadata(..) <pdfpc-notes>`, e.g. by touying or polylux, become the notes of the slides. The slides take the size of the first page.
+
+`tinymist.exportAnimatedSvg` writes a single SVG file that shows the pages one after another, which is handy for web demos. Each page is shown for `frameDuration` seconds (1 by default), and the animation starts over after the last page unless the `loop` option is `false`, in which case the last page stays. It also accepts the `pages` option to select the frames. The pages are switched by SMIL animations, so viewers that don't play them show the first page.
+
+`tinymist.exportGif` writes a single animated GIF file, where each page becomes a frame rendered at `ppi` (72 by default), which is handy for sharing previews of slides. Each frame is shown for `frameDelay` milliseconds (1000 by default), which is rounded up to 10 milliseconds. The animation repeats forever unless `loopCount` sets how many times it repeats after it is played once. Since a GIF has at most 256 colors in a palette, the `palette` option chooses between a palette for each frame (`"per-frame"`, the default), which preserves the colors better, and a palette shared by all the frames (`"global"`), which makes smaller files. Transparent pages are shown on a white background. It also accepts the `pages` option to select the frames.
+
+`tinymist.exportPdf` and `tinymist.exportPng` also accept the `changedPages` option, which exports only the pages changed since the last export into a separate diff set next to the output, e.g. `main.diff.pdf`. The server keeps the content hashes of the pages exported to each output path in memory, so the changes are only detected within one session of the server: the first export after the server starts writes all the pages, and nothing is written if no page changed.
+
+`tinymist.exportPdf` and `tinymist.exportPng` also accept the `iccProfile` option, which is the path to an ICC color profile to embed for print-accurate output, relative to the root. The profile becomes the output intent of a PDF and is written as an `iCCP` chunk of a PNG image, which takes a gray or RGB profile. An invalid profile fails the export before anything is written, and so does a profile for a PDF/A document, whose output intent is written by Typst. In the CLI, the same is done by the `--pdf-icc-profile` and `--png-icc-profile` flags.
+
+`tinymist.exportPng` also accepts the `maxBytes` option for upload limits, which is the maximum size of each image in bytes. The export starts at `ppi` and lowers the PPI until every image fits, and the response carries the `ppi` that the images are finally rendered at. The export fails with the smallest achievable size if the images don't fit even at 18 PPI. In the CLI, the same is done by the `--png-max-bytes` flag.
+
+`tinymist.exportPdf` also accepts the `reproducible` option, which makes the PDF bit-reproducible, i.e. exporting the same source twice yields identical bytes. The creation and modification dates are omitted instead of using the time of export, unless a creation timestamp is given by the `creationTimestamp` option or `--creation-timestamp`. In the CLI, the same is done by the `--pdf-reproducible` flag.
+
+`tinymist.exportPdf`, `tinymist.exportPng`, and `tinymist.exportSvg` also accept the `spanMap` option, which writes a span map next to the output, e.g. `main.pdf.spans.json`, for building tools that jump from a click in the output to the source. It is a JSON array with an entry for each exported page, holding the `page` number, the `width` and `height` of the page, and the `spans` of the rendered elements in the painting order. Each span has the bounding box of the element (`x`, `y`, `width`, and `height` in pt, relative to the top-left of the page), the `path` of the source file, and the byte `range` in it. Texts are split into runs of glyphs from the same source. When the pages are written into separate files, each file gets a span map with its page only. In the CLI, the same is done by the `--span-map` flag.
+
+`tinymist.exportPdf` also accepts the `splitBy` option, which exports one PDF per section rather than a single file, e.g. a handbook with a file for each chapter. The value `"heading-level-1"` starts a section at each top-level heading, and `"heading-level-2"` at each second-level heading, and so on. The output path, i.e. `tinymist.outputPath` or the `output` of a task, must contain the `{section}` placeholder, which is replaced by the slug of the heading, e.g. `$root/build/{section}` for `= Getting Started` writes `build/getting-started.pdf`. Sections with the same slug get numeric suffixes, e.g. `notes-2`. A section spans from the page of its heading to the page before the next section, and the pages before the first heading belong to the first section, so sections should start on new pages, e.g. with `#show heading.where(level: 1): it => pagebreak(weak: true) + it`. The response lists the exported `sections` with their `title`, `pages`, and `path`. In the CLI, the same is done by the `--split-by` flag.
+
+The third argument is an object containing the action options shared by the export commands. The `root` option overrides the root directory for that single export, so that absolute paths like `/assets/logo.png` in the document resolve under the given root instead of the root of the editing session. The root must be an absolute path containing the document, and paths escaping it are rejected as usual. This allows exporting the same document for both a site and a print build:
+
+```jsonc
+["tinymist.exportPdf", "/repo/site/pages/main.typ", {}, { "root": "/repo/site" }]
+```
+
+The `locale` option sets the default language and region of the text for that single export, e.g. `"fr-FR"` or `"en"`, as if the document started with `#set text(lang: "fr", region: "FR")`. It affects everything that depends on the language, such as hyphenation, quotes, and the supplements of references, as well as transform scripts reading `text.lang`, while the rules in the document still take precedence over it. This allows producing localized variants from one source without editing it:
+
+```jsonc
+["tinymist.exportPdf", "/repo/main.typ", {}, { "locale": "fr-FR" }]
+```
+
+The `skipUnchanged` option skips writing the exported files whose content is unchanged, which keeps their modification times, so that the tools watching them don't rebuild on a no-op export. It defaults to the `tinymist.exportSkipUnchanged` setting, which applies to the exports on save or type as well.
+
+The `tinymist.exportManifest` setting records the exported files in an `export-manifest.json` next to them, which gives build pipelines a checksum artifact to compare against. Each entry holds the `format`, `path`, and `size` of a file, its `sha256` checksum in hex, and the `timestamp` of the export in RFC 3339 format. The entries of other files in the manifest are kept, so a manifest can be shared by multiple tasks. In the CLI, the same is done by the `--manifest` flag.
+
+An export command can be canceled by sending `$/cancelRequest` with the ID of its request, e.g. after accidentally exporting a huge document. The export is aborted at its next phase, i.e. after compiling and after generating the artifact, and nothing is written, so no partial file is left behind. The request then fails with the `RequestCanceled` error (`-32800`) and the `CANCELLED` code in its `data`.
@@ -23,3 +23,13 @@ If enabled, the linter will run on save or on type, depending on your configurat
   - `onType` run linting as you type.
 
 
+
+= Unused Imports and Variables
+
+The linter reports the imported names and let-bindings that are never referenced in a file as hints, which editors usually render as faded text. Wildcard imports (`import "a.typ": *`) are never reported. In files other than the main file, the exported names, including the re-exported imports, are not reported either, since they may be used by other files.
+
+To silence the check for a file, add the following comment on a line of its own:
+
+```typ
+// tinymist: allow(unused)
+```
@@ -90,6 +90,31 @@ value is `["--data-plane-host=127.0.0.1:23635", "--invert-colors=auto"]`. Exampl
 }
 ```
 
+== Switching the Preview Theme
+
+You can switch the theme of a running preview instance without restarting it by the `tinymist.setPreviewTheme` command. The first argument is the task ID of the preview, and the second argument is the theme:
+- `{ "kind": "default" }` inverts colors by the `--invert-colors` argument, which is the initial theme.
+- `{ "kind": "light" }` never inverts colors.
+- `{ "kind": "dark" }` always inverts colors.
+- `{ "kind": "custom", "css": "..." }` inverts colors like the default theme, and injects the CSS snippet into the preview page.
+
+The `tinymist.listPreviewThemes` command returns the kinds of themes and the active theme of each preview instance.
+
+```jsonc
+["tinymist.setPreviewTheme", "my-task", { "kind": "custom", "css": "body { background: #1e1e1e; }" }]
+```
+
+== Querying the Preview Status
+
+To script a preview, the `tinymist.previewStatus` command returns the status of a running preview instance given its task ID, i.e. the `pageCount` of the rendered document and the `currentPage` in the viewport of the most recently scrolled viewer, which is the page closest to the center of the viewport, or the current slide in the slide mode. Pages are numbered from 1, and either field is `null` until the document is rendered or a viewer reports its position.
+
+```jsonc
+["tinymist.previewStatus", "my-task"]
+// => { "pageCount": 12, "currentPage": 3 }
+```
+
+Over the control plane of a standalone preview, the `{ "event": "queryStatus" }` message requests the same status, which is answered by a `{ "event": "previewStatus", "pageCount": .., "currentPage": .. }` response. The response is also sent whenever the status changes.
+
 == CLI Integration
 
 ```bash
@@ -32,6 +32,17 @@ This is the default resolution method and has been used for years. Despite using
 - Pinning a main file manually by commands is possible:
   - Use command `Typst Pin Main` (tinymist.pinMainToCurrent) to set the current file as the main file.
   - Use command `Typst Unpin Main` (tinymist.unpinMain) to unset the main file.
+- To check which root and main file the server would use for a file, run the LSP command `tinymist.resolveEntry` with the path of the file. It also reports whether the root comes from `rootPath`, a workspace folder, a `typst.toml`, or a guess from the file's location.
+- To check which file a path in a document, e.g. `image("fig.png")` or `read("data.csv")`, refers to, run the LSP command `tinymist.resolveAsset` with the path of the document and the path string. It resolves the path in the same way as the compiler does and returns the absolute path, whether the file exists, its size in bytes, and the error of reading it, which helps to debug "file not found" errors caused by a misconfigured root.
+- To check all the documents at once, run the LSP command `tinymist.compileWorkspace`. It compiles the configured main file, or otherwise every file under the workspace roots that is not imported or included by another file, and returns the diagnostics keyed by file. The options `include` and `exclude` filter the entries by gitignore-style glob patterns relative to the root, where a pattern without a `/`, e.g. `draft-*.typ`, matches files at any depth, `entries` lists the files to compile explicitly, which are filtered as well, and `jobs` bounds how many documents are compiled at the same time.
+- To visualize the dependencies of a file, run the LSP command `tinymist.getImports` with its path. It returns every `import` and `include` statement of the file with the imported path or package spec, the URI of the resolved file, the package spec, and the names brought into scope. A statement whose target cannot be resolved is returned with an `error` instead of being omitted.
+- To list the symbols defined in a file, run the LSP command `tinymist.getDefinitions` with its path. Unlike the document outline, which lists the headings, it returns every `let` definition and imported name of the file with its kind (`function`, `variable`, `import`, or `module`), the range of the name, whether it is at the top level, and its documentation comments. Nested definitions and shadowed definitions are listed as well.
+- To reuse the context detection of the completions in an external completion engine, run the LSP command `tinymist.getCompletionContext` with the path of a file and a position. It returns the `kind` of completions offered there, e.g. `markup`, `math`, `code`, `setRule`, `selector`, `field`, `path`, `package`, or `reference`, with the `prefix` being completed and its `range`, which the completions replace. The kind is decided by the same logic as the completions of tinymist.
+- To find labels defined more than once in a multi-file project, run the LSP command `tinymist.findDuplicateLabels`. It scans the syntax of the files in the workspace without compiling the document and returns a group for each duplicated label, with the `name` of the label and the `locations` of all its definitions.
+- To see which symbol a math shorthand turns into, run the LSP command `tinymist.resolveMathShorthand` with the shorthand, e.g. `->` or `!=`. It looks the shorthand up in the same table as the parser and returns the resulting `symbol`, e.g. `→`, its `codepoint`, e.g. `U+2192`, its `name` in the `sym` module, e.g. `arrow.r`, and the equivalent `sym` expression, e.g. `sym.arrow.r`. If several names refer to the symbol, the shortest one is returned. Unknown shorthands return `null`.
+- To try out the definitions of a file like in a REPL, run the LSP command `tinymist.eval` with the path of the file and a code expression, e.g. `answer(2)`. It evaluates the expression in the scope of the file, i.e. with the definitions and imports of the file, and returns the repr of the result. The expression can query the document, e.g. `query(heading).len()`, once the document is compiled. The evaluation is cancelled after the `timeout` option in the third argument, which is 5000 milliseconds by default. A cancelled evaluation stops at its next access to a file or a font, while a pure computation runs to the end within the iteration and call depth limits of Typst.
+- To make the first hovers and completions of a large project fast, run the LSP command `tinymist.warmCache` with an array of paths, e.g. when the project is opened. It compiles the files and runs the common analyses on them in the background, filling the caches without returning any results. The `jobs` option in the second argument bounds how many files are warmed at the same time, which is half of the CPUs by default. A new warm-up cancels the running one, and `tinymist.cancelWarmCache` cancels it explicitly.
+- Unless `rootPath` (or `--root` in the CLI) is set, a directory whose `typst.toml` contains a `[workspace]` table is used as the root of all files under it. The nearest such directory wins, and it takes precedence over the `typst.toml` of a package inside it.
 
 = A Sample Usage of `lockDatabase`
 
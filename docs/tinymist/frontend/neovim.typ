@@ -85,6 +85,8 @@ formatterIndentSize = 4,   -- indentation width
 Note that, since #link("https://github.com/neovim/neovim/pull/19677")[this Neovim PR], neovim uses LSP servers (hence `tinymist` in our case) by default when formatting code. 
 This applies to calls to `vim.lsp.buf.format()` (which formats the whole current buffer) and also to the `gq` command.
 
+To reformat only a part of a file, the `tinymist.formatRange` command takes the path of the file and an LSP range. It expands the range to the syntax nodes it selects, and returns the reformatted `range` and its formatted `text` without editing the file, so the text can be spliced in with the surrounding indentation kept. It requires the `typstyle` formatter.
+
 = Live Preview
 <live-preview>
 
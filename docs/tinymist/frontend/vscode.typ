@@ -147,6 +147,8 @@ If you also need package paths, package cache paths, or certificate settings, re
 + Use `Typst: Open Typst Local Package` command to open a local package.
 + View and manage a list of available local packages in the "PACKAGE" view in the activity bar.
 
+To hide packages or versions from the list, e.g. build artifacts, add a `.gitignore` or `.tinymistignore` file to the namespace or package directory. For example, `my-pkg/0.1.0` in `local/.tinymistignore` hides the version `0.1.0` of `@local/my-pkg`.
+
 == Compiling PDF
 <compiling-pdf>
 This extension compiles to PDF, but it doesn’t have a PDF viewer yet. To view the output as you work, install a PDF viewer extension, such as `vscode-pdf`.
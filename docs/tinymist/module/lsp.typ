@@ -72,10 +72,14 @@ dhat: The data has been saved to dhat-heap.json, and is viewable with dhat/dh_vi
 
 Once you have the `dhat-heap.json`, you can visualize the memory usage with #link("https://nnethercote.github.io/dh_view/dh_view.html")[the DHAT viewer].
 
+To see what the analysis caches hold, run the LSP command `tinymist.getCacheStats`. Its `analysis` entry counts the cached results of the analyzers, such as `signatures` and `typeChecks`, and estimates the size of the interned objects in `internedBytes`. The memoized results of the compiler are not counted, since `comemo` doesn't expose them. The LSP command `tinymist.doClearCache` clears both the analysis caches and the memoized results.
+
 = Server-Level Profiling
 
 In VS Code, you can get the profiling data of the language server by searching and running the "Typst: Profile server" command.
 
+The response of stopping the profiling contains `compileSpans`, which lists the `compilations` in the time window with their `durationMs` and the number of executions of each timed span, e.g. `eval` or `layout`. A memoized call that is reused from the cache is not timed, so fewer executions while typing mean that the incremental compilation reuses more work. The cache hits are not counted.
+
 #note-box[
   To use this feature in other LSP clients, please check `/editors/vscode/src/features/tool.ts`. A client should #link("https://github.com/Myriad-Dreamin/tinymist/blob/4bfb5c22daf78076f24d0b198641730f388e46ed/editors/vscode/src/features/tool.ts#L347-L359")[start] and then #link("https://github.com/Myriad-Dreamin/tinymist/blob/4bfb5c22daf78076f24d0b198641730f388e46ed/editors/vscode/src/features/tool.ts#L286-L295")[stop] profiling to collect performance events inside the time window.
 ]
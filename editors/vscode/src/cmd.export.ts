@@ -30,6 +30,7 @@ export interface ExportSvgOpts {
   pages?: string[];
   pageNumberTemplate?: string;
   merge?: PageMergeOpts;
+  textMode?: "paths" | "embed" | "reference";
 }
 
 export interface ExportTypliteOpts {
@@ -45,6 +46,7 @@ export interface ExportQueryOpts {
   selector: string;
   field?: string;
   one?: boolean;
+  html?: boolean;
 }
 
 // biome-ignore lint/suspicious/noEmptyInterface: no fields yet
@@ -26,6 +26,12 @@ export type ExportFormat =
   | "query"
   | "pdfpc";
 
+/** The fill of the pages in some ranges, e.g. `{ ranges: ["1", "5-7"], fill: "red" }`. */
+export interface PageRangeFill {
+  ranges: string[];
+  fill: string;
+}
+
 export interface ExportArgs {
   format: ExportFormat | ExportFormat[];
   inputPath: string;
@@ -64,8 +70,8 @@ export interface ExportArgs {
 
   "png.ppi"?: number;
 
-  fill?: string;
-  "png.fill"?: string;
+  fill?: string | PageRangeFill[];
+  "png.fill"?: string | PageRangeFill[];
 
   "query.format": string;
   "query.outputExtension"?: string;
@@ -74,6 +80,7 @@ export interface ExportArgs {
   "query.selector": string;
   "query.field"?: string;
   "query.one"?: boolean;
+  "query.html"?: boolean;
 
   processor?: string;
   "markdown.processor"?: string;
@@ -274,6 +281,7 @@ export const provideFormats = (exportArgs: ExportArgs, ops = exportOps(exportArg
         selector: exportArgs["query.selector"],
         field: exportArgs["query.field"],
         one: exportArgs["query.one"],
+        html: exportArgs["query.html"],
       };
     },
     export: tinymist.exportQuery,
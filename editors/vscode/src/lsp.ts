@@ -359,6 +359,7 @@ export class LanguageState {
   exportPdf = exportCommand("tinymist.exportPdf");
   exportSvg = exportCommand("tinymist.exportSvg");
   exportPng = exportCommand("tinymist.exportPng");
+  exportContactSheet = exportCommand("tinymist.exportContactSheet");
   exportHtml = exportCommand("tinymist.exportHtml");
   exportBundle = exportCommand("tinymist.exportBundle");
   exportMarkdown = exportCommand("tinymist.exportMarkdown");
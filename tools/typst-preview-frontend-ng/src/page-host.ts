@@ -27,8 +27,8 @@ import {
 } from "./navigation";
 import { renderOutline } from "./outline";
 import { createPageRecord } from "./page-record";
-import { parseInvertColorStrategy, parsePreviewPositions } from "./protocol";
-import { InvertColorController } from "./theme";
+import { parseInvertColorStrategy, parsePreviewPositions, parsePreviewTheme } from "./protocol";
+import { InvertColorController, applyThemeCss } from "./theme";
 import type {
   PageRecord,
   PageSpec,
@@ -55,6 +55,7 @@ export class PageHost {
   private dragging = false;
   private scrolling = false;
   private lastViewportPostKey = "";
+  private lastViewportPage = 0;
   private lastViewportTimer = 0;
   private scrollIdleTimer = 0;
   private sourceSyncEchoIgnoreUntil = 0;
@@ -309,6 +310,7 @@ export class PageHost {
       this.applyAllPageLayouts(metrics);
     }
     const layouts = this.collectPageLayouts();
+    this.reportViewportPage(layouts);
     const viewport = this.readViewportSnapshot(metrics);
     if (options.renderDuringDrag !== undefined) {
       viewport.renderDuringDrag = options.renderDuringDrag;
@@ -328,6 +330,22 @@ export class PageHost {
     }
   }
 
+  /** Tells the server the page in the viewport when it changes. */
+  private reportViewportPage(layouts: PageLayoutRecord[]) {
+    if (layouts.length === 0) {
+      return;
+    }
+    const page =
+      this.previewMode === "Slide"
+        ? this.currentSlidePage
+        : currentPageFromLayouts(this.elements.viewport, layouts);
+    if (page === this.lastViewportPage) {
+      return;
+    }
+    this.lastViewportPage = page;
+    this.postWorker({ type: "send", text: `viewport-page ${page}` });
+  }
+
   handlePreviewProtocolMessage(kind: string, text: string) {
     switch (kind) {
       case "jump":
@@ -347,6 +365,12 @@ export class PageHost {
       case "invert-colors":
         this.invertColors.apply(this.elements.root, parseInvertColorStrategy(text));
         break;
+      case "theme": {
+        const theme = parsePreviewTheme(text);
+        this.invertColors.apply(this.elements.root, theme.invertColors);
+        applyThemeCss(theme.css);
+        break;
+      }
       case "outline":
         try {
           this.setOutlineData(JSON.parse(text));
@@ -1,4 +1,9 @@
-import type { InvertColorStrategy, InvertColorStrategyMap, PreviewPosition } from "./types";
+import type {
+  InvertColorStrategy,
+  InvertColorStrategyMap,
+  PreviewPosition,
+  PreviewTheme,
+} from "./types";
 
 export function parsePreviewPositions(text: string): PreviewPosition[] {
   return text
@@ -27,3 +32,15 @@ export function parseInvertColorStrategy(
     return "never";
   }
 }
+
+export function parsePreviewTheme(raw: string): PreviewTheme {
+  try {
+    const theme = JSON.parse(raw);
+    return {
+      invertColors: theme.invertColors ?? "never",
+      css: typeof theme.css === "string" ? theme.css : null,
+    };
+  } catch (_error) {
+    return { invertColors: "never", css: null };
+  }
+}
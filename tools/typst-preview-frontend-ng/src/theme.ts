@@ -28,3 +28,19 @@ function determineInvertColor() {
     !cls.contains("vscode-light")
   );
 }
+
+/** Injects the CSS snippet of a custom theme, or removes it if there is none. */
+export function applyThemeCss(css: string | null | undefined) {
+  const id = "typst-preview-theme";
+  let style = document.getElementById(id);
+  if (!css) {
+    style?.remove();
+    return;
+  }
+  if (!style) {
+    style = document.createElement("style");
+    style.id = id;
+    document.head.appendChild(style);
+  }
+  style.textContent = css;
+}
@@ -4,6 +4,11 @@ export type PreviewMode = "Doc" | "Slide";
 export type InvertColorStrategy = "never" | "auto" | "always";
 export type InvertColorStrategyMap = Partial<Record<"rest" | "image", InvertColorStrategy>>;
 
+export interface PreviewTheme {
+  invertColors: InvertColorStrategy | InvertColorStrategyMap;
+  css: string | null;
+}
+
 export interface PreviewArgs {
   ws: string;
   mode: string;
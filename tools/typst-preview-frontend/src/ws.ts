@@ -442,6 +442,12 @@ export async function wsMain({ url, previewMode, isContentPreview }: WsArgs) {
         console.log("Experimental feature: invert colors strategy taken:", strategy);
         ensureInvertColors(document.getElementById("typst-app"), strategy);
         return;
+      } else if (message[0] === "theme") {
+        const theme = JSON.parse(dec.decode((message[1] as any).buffer)) as PreviewTheme;
+        console.log("preview theme taken:", theme);
+        ensureInvertColors(document.getElementById("typst-app"), theme.invertColors);
+        ensureThemeCss(theme.css);
+        return;
       } else if (message[0] === "outline") {
         console.log("Experimental feature: outline rendering");
         return;
@@ -475,6 +481,28 @@ export async function wsMain({ url, previewMode, isContentPreview }: WsArgs) {
   );
 }
 
+/** The theme switched at runtime by `tinymist.setPreviewTheme` */
+interface PreviewTheme {
+  invertColors: StrategyKey | StrategyMap;
+  css: string | null;
+}
+
+/** Injects the CSS snippet of a custom theme, or removes it if there is none */
+function ensureThemeCss(css: string | null) {
+  const id = "typst-preview-theme";
+  let style = document.getElementById(id);
+  if (!css) {
+    style?.remove();
+    return;
+  }
+  if (!style) {
+    style = document.createElement("style");
+    style.id = id;
+    document.head.appendChild(style);
+  }
+  style.textContent = css;
+}
+
 /** The strategy to set invert colors, see editors/vscode/package.json for enum descriptions */
 const INVERT_COLORS_STRATEGY = ["never", "auto", "always"] as const;
 /** The value of strategy constant */